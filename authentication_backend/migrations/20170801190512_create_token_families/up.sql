@@ -0,0 +1,8 @@
+CREATE TABLE token_families (
+    id SERIAL PRIMARY KEY,
+    family VARCHAR(100) NOT NULL UNIQUE,
+    user_id INTEGER NOT NULL,
+    current_jti VARCHAR(100) NOT NULL,
+    revoked BOOLEAN NOT NULL DEFAULT 'f',
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+)
@@ -0,0 +1,9 @@
+CREATE TABLE failed_logins (
+    id SERIAL PRIMARY KEY,
+    subject VARCHAR(64) NOT NULL UNIQUE,
+    user_id INTEGER,
+    failures INTEGER NOT NULL,
+    last_failed_at TIMESTAMP NOT NULL,
+    locked_until TIMESTAMP,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+)
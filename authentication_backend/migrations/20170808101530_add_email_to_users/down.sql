@@ -0,0 +1,2 @@
+ALTER TABLE verification_codes DROP COLUMN email;
+ALTER TABLE users DROP COLUMN email
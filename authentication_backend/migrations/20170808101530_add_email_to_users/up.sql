@@ -0,0 +1,2 @@
+ALTER TABLE users ADD COLUMN email VARCHAR(254) UNIQUE;
+ALTER TABLE verification_codes ADD COLUMN email VARCHAR(254)
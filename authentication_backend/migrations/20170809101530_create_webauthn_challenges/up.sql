@@ -0,0 +1,5 @@
+CREATE TABLE webauthn_challenges (
+    id SERIAL PRIMARY KEY,
+    challenge VARCHAR(64) NOT NULL UNIQUE,
+    expires_at TIMESTAMP NOT NULL
+)
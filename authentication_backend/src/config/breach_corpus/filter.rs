@@ -0,0 +1,256 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::cmp;
+use std::f64::consts::LN_2;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::Path;
+use ring::digest;
+use error::{Error, Result};
+
+const MAGIC: &[u8] = b"BREACHF1";
+const HEADER_LEN: usize = 20;
+const PREFIX_LEN: usize = 5;
+
+pub struct BreachFilter {
+    hashes: u32,
+    bits: Vec<u8>,
+}
+
+impl BreachFilter {
+    pub fn new(expected: u64, false_positive_rate: f64) -> Self {
+        let expected = cmp::max(expected, 1) as f64;
+        let bit_len = (-expected * false_positive_rate.ln() / (LN_2 * LN_2)).ceil();
+        let hashes = (bit_len / expected * LN_2).round();
+
+        BreachFilter {
+            hashes: if hashes < 1.0 { 1 } else { hashes as u32 },
+            bits: vec![0; cmp::max((bit_len as usize + 7) / 8, 1)],
+        }
+    }
+
+    pub fn build(path: &Path, false_positive_rate: f64) -> Result<Self> {
+        let mut expected = 0;
+
+        for_each_hash(path, |_| expected += 1)?;
+
+        let mut filter = BreachFilter::new(expected, false_positive_rate);
+
+        for_each_hash(path, |hash| filter.insert_hash(hash))?;
+
+        Ok(filter)
+    }
+
+    pub fn read_from<R>(reader: &mut R) -> Result<Self>
+    where
+        R: Read,
+    {
+        let mut contents = Vec::new();
+        reader.read_to_end(&mut contents)?;
+
+        if contents.len() <= HEADER_LEN || &contents[..MAGIC.len()] != MAGIC {
+            return Err(Error::ParseError);
+        }
+
+        let hashes = le_u64(&contents[8..12]) as u32;
+        let byte_len = le_u64(&contents[12..HEADER_LEN]) as usize;
+
+        if hashes == 0 || byte_len != contents.len() - HEADER_LEN {
+            return Err(Error::ParseError);
+        }
+
+        Ok(BreachFilter {
+            hashes: hashes,
+            bits: contents.split_off(HEADER_LEN),
+        })
+    }
+
+    pub fn write_to<W>(&self, writer: &mut W) -> Result<()>
+    where
+        W: Write,
+    {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&to_le(self.hashes as u64, 4))?;
+        writer.write_all(&to_le(self.bits.len() as u64, 8))?;
+        writer.write_all(&self.bits)?;
+
+        Ok(())
+    }
+
+    pub fn insert_hash(&mut self, hash: &[u8]) {
+        for position in self.positions(hash) {
+            self.bits[(position / 8) as usize] |= 1 << (position % 8);
+        }
+    }
+
+    pub fn contains_hash(&self, hash: &[u8]) -> bool {
+        self.positions(hash).into_iter().all(|position| {
+            self.bits[(position / 8) as usize] & (1 << (position % 8)) != 0
+        })
+    }
+
+    pub fn contains(&self, password: &str) -> bool {
+        self.contains_hash(digest::digest(&digest::SHA1, password.as_bytes()).as_ref())
+    }
+
+    fn positions(&self, hash: &[u8]) -> Vec<u64> {
+        let bit_len = self.bits.len() as u64 * 8;
+        let first = le_u64(&hash[0..8]);
+        let step = le_u64(&hash[8..16]) | 1;
+
+        (0..self.hashes as u64)
+            .map(|i| first.wrapping_add(i.wrapping_mul(step)) % bit_len)
+            .collect()
+    }
+}
+
+pub fn parse_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 || !is_hex(hex) {
+        return None;
+    }
+
+    (0..hex.len() / 2)
+        .map(|i| u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok())
+        .collect()
+}
+
+fn is_hex(hex: &str) -> bool {
+    hex.chars().all(|c| c.is_digit(16))
+}
+
+fn for_each_hash<F>(path: &Path, mut f: F) -> Result<()>
+where
+    F: FnMut(&[u8]),
+{
+    if !path.is_dir() {
+        return read_hashes(path, "", &mut f);
+    }
+
+    let mut paths: Vec<_> = fs::read_dir(path)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .collect();
+    paths.sort();
+
+    for path in paths {
+        let prefix = match path.file_stem().and_then(|stem| stem.to_str()) {
+            Some(prefix) if prefix.len() == PREFIX_LEN && is_hex(prefix) => prefix.to_owned(),
+            _ => continue,
+        };
+
+        read_hashes(&path, &prefix, &mut f)?;
+    }
+
+    Ok(())
+}
+
+// Range files pad some responses with entries that have a count of 0, which aren't breached
+fn read_hashes<F>(path: &Path, prefix: &str, f: &mut F) -> Result<()>
+where
+    F: FnMut(&[u8]),
+{
+    let reader = BufReader::new(File::open(path)?);
+
+    for line in reader.lines() {
+        let line = line?;
+        let mut parts = line.trim().splitn(2, ':');
+
+        let (suffix, count) = match (parts.next(), parts.next()) {
+            (Some(suffix), Some(count)) => (suffix, count.trim()),
+            (Some(""), None) => continue,
+            _ => return Err(Error::ParseError),
+        };
+
+        if count == "0" {
+            continue;
+        }
+
+        match parse_hex(&format!("{}{}", prefix, suffix)) {
+            Some(ref hash) if hash.len() == 20 => f(&hash[..]),
+            _ => return Err(Error::ParseError),
+        }
+    }
+
+    Ok(())
+}
+
+fn le_u64(bytes: &[u8]) -> u64 {
+    bytes.iter().rev().fold(0, |acc, byte| (acc << 8) | *byte as u64)
+}
+
+fn to_le(value: u64, len: usize) -> Vec<u8> {
+    (0..len).map(|i| (value >> (i * 8)) as u8).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use super::*;
+    use test_helper::generate_string;
+
+    const PASSWORD_HASH: &str = "5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8";
+
+    #[test]
+    fn contains_finds_inserted_passwords() {
+        let mut filter = BreachFilter::new(10, 0.001);
+
+        filter.insert_hash(&parse_hex(PASSWORD_HASH).unwrap());
+
+        assert!(filter.contains("password"), "Did not find inserted password");
+        assert!(!filter.contains("vT9#qLmz2!Rx"), "Found password that wasn't inserted");
+    }
+
+    #[test]
+    fn read_from_restores_written_filter() {
+        let mut filter = BreachFilter::new(10, 0.001);
+        filter.insert_hash(&parse_hex(PASSWORD_HASH).unwrap());
+
+        let mut bytes = Vec::new();
+        filter.write_to(&mut bytes).expect("Failed to write filter");
+
+        let filter = BreachFilter::read_from(&mut bytes.as_slice()).expect("Failed to read filter");
+
+        assert!(filter.contains("password"), "Lost inserted password");
+    }
+
+    #[test]
+    fn read_from_rejects_other_files() {
+        let result = BreachFilter::read_from(&mut &b"5BAA61E4C9B93F3F0682250B6CF8331B:3"[..]);
+
+        assert!(result.is_err(), "Read filter from hash list");
+    }
+
+    #[test]
+    fn build_reads_range_files() {
+        let dir = env::temp_dir().join(generate_string());
+        fs::create_dir(&dir).expect("Failed to create directory");
+
+        File::create(dir.join("5BAA6.txt"))
+            .and_then(|mut f| f.write_all(b"1E4C9B93F3F0682250B6CF8331B7EE68FD8:9545824\r\n"))
+            .expect("Failed to write range file");
+
+        let filter = BreachFilter::build(&dir, 0.001);
+        let _ = fs::remove_dir_all(&dir);
+
+        assert!(
+            filter.expect("Failed to build filter").contains("password"),
+            "Did not add range file to filter"
+        );
+    }
+}
@@ -0,0 +1,155 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+mod filter;
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, ErrorKind};
+use std::path::{Path, PathBuf};
+use ring::digest;
+use error::{ConfigErrorKind, Error, Result};
+
+pub use self::filter::BreachFilter;
+
+const PASSWORD_BREACH_CORPUS: &str = "password_breach_corpus";
+
+pub const SETTINGS: &[&str] = &[PASSWORD_BREACH_CORPUS];
+
+pub enum BreachCorpus {
+    Ranges(PathBuf),
+    Filter(BreachFilter),
+}
+
+impl BreachCorpus {
+    pub fn open(path: &Path) -> Result<Self> {
+        if path.is_dir() {
+            return Ok(BreachCorpus::Ranges(path.to_owned()));
+        }
+
+        let filter = BreachFilter::read_from(&mut File::open(path)?)?;
+
+        Ok(BreachCorpus::Filter(filter))
+    }
+
+    pub fn from_settings(
+        values: &HashMap<String, String>,
+        errors: &mut Vec<ConfigErrorKind>,
+    ) -> Option<Self> {
+        let path = match values.get(PASSWORD_BREACH_CORPUS) {
+            Some(path) => path,
+            None => return None,
+        };
+
+        match BreachCorpus::open(Path::new(path)) {
+            Ok(corpus) => Some(corpus),
+            Err(Error::IOError) => {
+                errors.push(ConfigErrorKind::UnreadableFile(path.to_owned()));
+                None
+            }
+            Err(_) => {
+                errors.push(ConfigErrorKind::Malformed(
+                    PASSWORD_BREACH_CORPUS.to_owned(),
+                    "must be a directory of range files or a filter built by build_breach_filter"
+                        .to_owned(),
+                ));
+                None
+            }
+        }
+    }
+
+    pub fn contains(&self, password: &str) -> Result<bool> {
+        match *self {
+            BreachCorpus::Ranges(ref dir) => range_contains(dir, password),
+            BreachCorpus::Filter(ref filter) => Ok(filter.contains(password)),
+        }
+    }
+}
+
+fn range_contains(dir: &Path, password: &str) -> Result<bool> {
+    let hash: String = digest::digest(&digest::SHA1, password.as_bytes())
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{:02X}", byte))
+        .collect();
+
+    let (prefix, suffix) = hash.split_at(5);
+
+    // A corpus missing some ranges is still checked against the ones it has
+    let file = match File::open(dir.join(format!("{}.txt", prefix))) {
+        Ok(file) => file,
+        Err(ref err) if err.kind() == ErrorKind::NotFound => return Ok(false),
+        Err(err) => return Err(err.into()),
+    };
+    let reader = BufReader::new(file);
+
+    for line in reader.lines() {
+        let line = line?;
+        let mut parts = line.trim().splitn(2, ':');
+
+        if let (Some(entry), Some(count)) = (parts.next(), parts.next()) {
+            if entry.to_uppercase() == suffix && count.trim() != "0" {
+                return Ok(true);
+            }
+        }
+    }
+
+    Ok(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::io::Write;
+    use super::*;
+    use test_helper::generate_string;
+
+    #[test]
+    fn contains_reads_range_files() {
+        let dir = env::temp_dir().join(generate_string());
+        fs::create_dir(&dir).expect("Failed to create directory");
+
+        File::create(dir.join("5BAA6.txt"))
+            .and_then(|mut f| {
+                f.write_all(b"1E4C9B93F3F0682250B6CF8331B7EE68FD7:0\r\n")?;
+                f.write_all(b"1e4c9b93f3f0682250b6cf8331b7ee68fd8:9545824\r\n")
+            })
+            .expect("Failed to write range file");
+
+        let corpus = BreachCorpus::open(&dir).expect("Failed to open corpus");
+
+        assert!(corpus.contains("password").unwrap(), "Did not find breached password");
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn contains_passes_passwords_in_missing_ranges() {
+        let dir = env::temp_dir().join(generate_string());
+        fs::create_dir(&dir).expect("Failed to create directory");
+
+        let corpus = BreachCorpus::open(&dir).expect("Failed to open corpus");
+        let result = corpus.contains("password");
+
+        let _ = fs::remove_dir_all(&dir);
+
+        assert!(!result.expect("Failed to check password"), "Found password in missing range");
+    }
+}
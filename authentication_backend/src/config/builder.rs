@@ -0,0 +1,613 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::collections::HashMap;
+use std::env;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use dotenv::dotenv;
+use serde_json::Value;
+use serde_yaml;
+use toml;
+use error::{ConfigErrorKind, Error, Result};
+use super::Config;
+use super::breach_corpus::{self, BreachCorpus};
+use super::clients::{self, Clients};
+use super::jwt_secret::{self, JWTSecret};
+use super::lockout::{self, Lockout};
+use super::password_hasher::{self, PasswordHasher};
+use super::pepper;
+use super::password_policy::{self, PasswordPolicy};
+use super::rate_limits::{self, RateLimits};
+use super::relying_party::{self, RelyingParty};
+use super::token_lifetimes::{self, TokenLifetimes};
+use super::totp;
+
+const CONFIG_FILE: &str = "config";
+const CONFIG_FILE_ENV: &str = "AUTHENTICATION_CONFIG";
+
+pub const DATABASE_URL: &str = "database_url";
+const AUDIENCES: &str = "audiences";
+const MAX_TOKEN_PERMISSIONS: &str = "max_token_permissions";
+
+const DEFAULT_MAX_TOKEN_PERMISSIONS: usize = 50;
+
+const SETTINGS: &[&[&str]] = &[
+    &[DATABASE_URL, AUDIENCES, MAX_TOKEN_PERMISSIONS],
+    jwt_secret::SETTINGS,
+    clients::SETTINGS,
+    token_lifetimes::SETTINGS,
+    totp::SETTINGS,
+    relying_party::SETTINGS,
+    pepper::SETTINGS,
+    password_hasher::SETTINGS,
+    password_policy::SETTINGS,
+    breach_corpus::SETTINGS,
+    lockout::SETTINGS,
+    rate_limits::SETTINGS,
+];
+
+#[derive(Default)]
+pub struct ConfigBuilder {
+    file: HashMap<String, String>,
+    env: HashMap<String, String>,
+    args: HashMap<String, String>,
+    errors: Vec<ConfigErrorKind>,
+}
+
+impl ConfigBuilder {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn file<P>(mut self, path: P) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let display = path.display().to_string();
+
+        let mut contents = String::new();
+
+        if File::open(path)
+            .and_then(|mut f| f.read_to_string(&mut contents))
+            .is_err()
+        {
+            self.errors.push(ConfigErrorKind::UnreadableFile(display));
+            return self;
+        }
+
+        let parsed: Option<Value> = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => toml::from_str(&contents).ok(),
+            Some("yaml") | Some("yml") => serde_yaml::from_str(&contents).ok(),
+            _ => {
+                self.errors.push(ConfigErrorKind::UnsupportedFormat(display));
+                return self;
+            }
+        };
+
+        match parsed {
+            Some(value) => flatten("", &value, &mut self.file),
+            None => self.errors.push(ConfigErrorKind::InvalidFile(display)),
+        }
+
+        self
+    }
+
+    pub fn env(mut self) -> Self {
+        dotenv().ok();
+
+        if let Ok(path) = env::var(CONFIG_FILE_ENV) {
+            self = self.file(path);
+        }
+
+        for settings in SETTINGS {
+            for setting in *settings {
+                if let Ok(value) = env::var(env_name(setting)) {
+                    self.env.insert((*setting).to_owned(), value);
+                }
+            }
+        }
+
+        self
+    }
+
+    pub fn args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        for arg in args {
+            let arg = arg.as_ref();
+
+            if !arg.starts_with("--") {
+                continue;
+            }
+
+            let mut parts = arg[2..].splitn(2, '=');
+
+            let setting = match parts.next() {
+                Some(setting) => setting.replace('-', "_"),
+                None => continue,
+            };
+
+            if setting != CONFIG_FILE && !is_setting(&setting) {
+                continue;
+            }
+
+            match parts.next() {
+                Some(value) if setting == CONFIG_FILE => self = self.file(value),
+                Some(value) => {
+                    self.args.insert(setting, value.to_owned());
+                }
+                None => {
+                    self.errors.push(ConfigErrorKind::Malformed(
+                        setting,
+                        "must be given as --setting=value".to_owned(),
+                    ))
+                }
+            }
+        }
+
+        self
+    }
+
+    pub fn build(self) -> Result<Config> {
+        let values = self.values();
+        let mut errors = self.errors;
+
+        for setting in values.keys() {
+            if !is_setting(setting) {
+                errors.push(ConfigErrorKind::UnknownSetting(setting.to_owned()));
+            }
+        }
+
+        let database_url = values.get(DATABASE_URL).cloned();
+        let audiences = audiences(&values, &mut errors);
+        let jwt_secret = JWTSecret::from_settings(&values, &mut errors);
+        let password_policy = PasswordPolicy::from_settings(&values, &mut errors);
+        let breach_corpus = BreachCorpus::from_settings(&values, &mut errors);
+        let password_hasher = PasswordHasher::from_settings(&values, &mut errors);
+        let clients = Clients::from_settings(&values, &mut errors);
+        let max_token_permissions = max_token_permissions(&values, &mut errors);
+        let token_lifetimes = TokenLifetimes::from_settings(&values, &mut errors);
+        let lockout = Lockout::from_settings(&values, &mut errors);
+        let rate_limits = RateLimits::from_settings(&values, &mut errors);
+        let totp_secret_box = totp::secret_box(&values, &mut errors);
+        let totp_issuer = totp::issuer(&values, &mut errors);
+        let relying_party = RelyingParty::from_settings(&values, &mut errors);
+
+        if !errors.is_empty() {
+            return Err(Error::ConfigError(errors));
+        }
+
+        let (jwt_secret, password_hasher) = match (jwt_secret, password_hasher) {
+            (Some(jwt_secret), Some(password_hasher)) => (jwt_secret, password_hasher),
+            _ => return Err(Error::ConfigError(errors)),
+        };
+
+        Ok(Config {
+            database_url: database_url,
+            audiences: audiences,
+            jwt_secret: jwt_secret,
+            password_policy: password_policy,
+            breach_corpus: breach_corpus,
+            password_hasher: password_hasher,
+            clients: clients,
+            max_token_permissions: max_token_permissions,
+            token_lifetimes: token_lifetimes,
+            lockout: lockout,
+            rate_limits: rate_limits,
+            totp_secret_box: totp_secret_box,
+            totp_issuer: totp_issuer,
+            relying_party: relying_party,
+        })
+    }
+
+    fn values(&self) -> HashMap<String, String> {
+        let mut values = self.file.clone();
+
+        values.extend(self.env.clone());
+        values.extend(self.args.clone());
+
+        values
+    }
+}
+
+fn is_setting(setting: &str) -> bool {
+    SETTINGS.iter().any(|settings| settings.contains(&setting))
+}
+
+fn env_name(setting: &str) -> String {
+    setting.replace('.', "_").to_uppercase()
+}
+
+fn flatten(prefix: &str, value: &Value, values: &mut HashMap<String, String>) {
+    match *value {
+        Value::Object(ref map) => {
+            for (key, value) in map {
+                let key = if prefix.is_empty() {
+                    key.to_owned()
+                } else {
+                    format!("{}.{}", prefix, key)
+                };
+
+                flatten(&key, value, values);
+            }
+        }
+        Value::Array(ref array) => {
+            let items: Vec<String> = array.iter().map(scalar).collect();
+
+            values.insert(prefix.to_owned(), items.join(","));
+        }
+        Value::Null => (),
+        _ => {
+            values.insert(prefix.to_owned(), scalar(value));
+        }
+    }
+}
+
+fn scalar(value: &Value) -> String {
+    match *value {
+        Value::String(ref s) => s.to_owned(),
+        _ => value.to_string(),
+    }
+}
+
+fn audiences(values: &HashMap<String, String>, errors: &mut Vec<ConfigErrorKind>) -> Vec<String> {
+    let entries = match values.get(AUDIENCES) {
+        Some(entries) => entries,
+        None => return Vec::new(),
+    };
+
+    let mut audiences: Vec<String> = Vec::new();
+
+    for audience in entries.split(',').map(|audience| audience.trim()) {
+        if audience.is_empty() {
+            continue;
+        }
+
+        if audience.contains(':') {
+            errors.push(ConfigErrorKind::Malformed(
+                AUDIENCES.to_owned(),
+                format!("has an audience containing ':', '{}'", audience),
+            ));
+            continue;
+        }
+
+        audiences.push(audience.to_owned());
+    }
+
+    audiences
+}
+
+fn max_token_permissions(
+    values: &HashMap<String, String>,
+    errors: &mut Vec<ConfigErrorKind>,
+) -> usize {
+    let max = match values.get(MAX_TOKEN_PERMISSIONS) {
+        Some(max) => max,
+        None => return DEFAULT_MAX_TOKEN_PERMISSIONS,
+    };
+
+    match max.parse::<usize>() {
+        Ok(max) => max,
+        Err(_) => {
+            errors.push(ConfigErrorKind::Malformed(
+                MAX_TOKEN_PERMISSIONS.to_owned(),
+                "must be a number".to_owned(),
+            ));
+            DEFAULT_MAX_TOKEN_PERMISSIONS
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::io::Write;
+    use super::*;
+    use test_helper::generate_string;
+
+    #[test]
+    fn args_override_file() {
+        with_config_file("toml", "bcrypt_cost = 10\n", |path| {
+            let builder = ConfigBuilder::new().args(vec!["--bcrypt-cost=5"]).file(path);
+
+            let values = builder.values();
+
+            assert_eq!(
+                values.get("bcrypt_cost").map(|cost| cost.as_str()),
+                Some("5"),
+                "File setting overrode command line setting"
+            );
+        });
+    }
+
+    #[test]
+    fn file_reads_yaml() {
+        with_config_file("yaml", "jwt:\n  private: key.der\n", |path| {
+            let values = ConfigBuilder::new().file(path).values();
+
+            assert_eq!(
+                values.get("jwt.private").map(|key| key.as_str()),
+                Some("key.der"),
+                "Failed to read nested YAML setting"
+            );
+        });
+    }
+
+    #[test]
+    fn args_ignores_unrelated_arguments() {
+        let builder = ConfigBuilder::new().args(vec!["username", "--test-threads=1"]);
+
+        assert!(builder.values().is_empty(), "Picked up unrelated arguments");
+        assert!(builder.errors.is_empty(), "Reported unrelated arguments");
+    }
+
+    #[test]
+    fn build_reports_every_missing_setting() {
+        let result = ConfigBuilder::new().build();
+
+        match result {
+            Err(Error::ConfigError(errors)) => {
+                assert_eq!(errors.len(), 2, "Did not report every missing setting")
+            }
+            _ => panic!("Built config without required settings"),
+        }
+    }
+
+    #[test]
+    fn build_reports_malformed_bcrypt_cost() {
+        let result = ConfigBuilder::new().args(vec!["--bcrypt-cost=1"]).build();
+
+        match result {
+            Err(Error::ConfigError(errors)) => {
+                assert_eq!(errors.len(), 3, "Did not report malformed bcrypt_cost")
+            }
+            _ => panic!("Built config with malformed bcrypt_cost"),
+        }
+    }
+
+    #[test]
+    fn build_reports_unknown_password_hasher() {
+        let result = ConfigBuilder::new().args(vec!["--password-hasher=md5"]).build();
+
+        match result {
+            Err(Error::ConfigError(errors)) => {
+                assert_eq!(errors.len(), 3, "Did not report unknown password_hasher")
+            }
+            _ => panic!("Built config with unknown password_hasher"),
+        }
+    }
+
+    #[test]
+    fn build_reports_short_password_pepper() {
+        with_config_file("key", "too short", |path| {
+            let result = ConfigBuilder::new()
+                .args(vec![format!("--password-pepper={}", path.display())])
+                .build();
+
+            match result {
+                Err(Error::ConfigError(errors)) => {
+                    assert_eq!(errors.len(), 3, "Did not report short password_pepper")
+                }
+                _ => panic!("Built config with short password_pepper"),
+            }
+        });
+    }
+
+    #[test]
+    fn build_reports_inverted_password_lengths() {
+        let result = ConfigBuilder::new()
+            .args(vec!["--password-min-length=20", "--password-max-length=10"])
+            .build();
+
+        match result {
+            Err(Error::ConfigError(errors)) => {
+                assert_eq!(errors.len(), 3, "Did not report inverted password lengths")
+            }
+            _ => panic!("Built config with inverted password lengths"),
+        }
+    }
+
+    #[test]
+    fn build_reports_malformed_password_flag() {
+        let result = ConfigBuilder::new()
+            .args(vec!["--password-require-symbol=sometimes"])
+            .build();
+
+        match result {
+            Err(Error::ConfigError(errors)) => {
+                assert_eq!(errors.len(), 3, "Did not report malformed password flag")
+            }
+            _ => panic!("Built config with malformed password flag"),
+        }
+    }
+
+    #[test]
+    fn build_reports_malformed_rate_limit() {
+        let result = ConfigBuilder::new()
+            .args(vec!["--rate-limit-log-in=often"])
+            .build();
+
+        match result {
+            Err(Error::ConfigError(errors)) => {
+                assert_eq!(errors.len(), 3, "Did not report malformed rate limit")
+            }
+            _ => panic!("Built config with malformed rate limit"),
+        }
+    }
+
+    #[test]
+    fn build_reports_hasher_that_cant_hash() {
+        let result = ConfigBuilder::new()
+            .args(vec!["--argon2-memory-cost=8", "--argon2-parallelism=16"])
+            .build();
+
+        match result {
+            Err(Error::ConfigError(errors)) => {
+                assert_eq!(errors.len(), 3, "Did not report hasher that can't hash")
+            }
+            _ => panic!("Built config with hasher that can't hash"),
+        }
+    }
+
+    #[test]
+    fn build_reports_malformed_breach_corpus() {
+        with_config_file("filter", "5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8:3", |path| {
+            let result = ConfigBuilder::new()
+                .args(vec![format!("--password-breach-corpus={}", path.display())])
+                .build();
+
+            match result {
+                Err(Error::ConfigError(errors)) => {
+                    assert_eq!(errors.len(), 3, "Did not report malformed breach corpus")
+                }
+                _ => panic!("Built config with malformed breach corpus"),
+            }
+        });
+    }
+
+    #[test]
+    fn build_reports_malformed_verification_keys() {
+        let result = ConfigBuilder::new()
+            .args(vec!["--jwt-verification-keys=old:,missing-kid"])
+            .build();
+
+        match result {
+            Err(Error::ConfigError(errors)) => {
+                assert_eq!(errors.len(), 4, "Did not report malformed verification keys")
+            }
+            _ => panic!("Built config with malformed verification keys"),
+        }
+    }
+
+    #[test]
+    fn build_reports_unknown_algorithm() {
+        let result = ConfigBuilder::new()
+            .args(vec!["--jwt-algorithm=none", "--jwt-verification-keys=old:XS1:key.der"])
+            .build();
+
+        match result {
+            Err(Error::ConfigError(errors)) => {
+                assert_eq!(errors.len(), 1, "Did not report only the unknown algorithm")
+            }
+            _ => panic!("Built config with unknown algorithm"),
+        }
+    }
+
+    #[test]
+    fn build_does_not_require_public_key_for_hmac() {
+        let result = ConfigBuilder::new().args(vec!["--jwt-algorithm=HS512"]).build();
+
+        match result {
+            Err(Error::ConfigError(errors)) => {
+                assert_eq!(errors.len(), 1, "Required a public key for HMAC")
+            }
+            _ => panic!("Built config without a secret"),
+        }
+    }
+
+    #[test]
+    fn build_reports_malformed_introspection_clients() {
+        let result = ConfigBuilder::new()
+            .args(vec!["--introspection-clients=no-secret,:path"])
+            .build();
+
+        match result {
+            Err(Error::ConfigError(errors)) => {
+                assert_eq!(errors.len(), 4, "Did not report malformed introspection clients")
+            }
+            _ => panic!("Built config with malformed introspection clients"),
+        }
+    }
+
+    #[test]
+    fn build_reports_malformed_audiences() {
+        let result = ConfigBuilder::new()
+            .args(vec!["--audiences=billing,support:admin"])
+            .build();
+
+        match result {
+            Err(Error::ConfigError(errors)) => {
+                assert_eq!(errors.len(), 3, "Did not report malformed audiences")
+            }
+            _ => panic!("Built config with malformed audiences"),
+        }
+    }
+
+    #[test]
+    fn build_reports_short_totp_encryption_key() {
+        with_config_file("key", "too short", |path| {
+            let result = ConfigBuilder::new()
+                .args(vec![format!("--totp-encryption-key={}", path.display())])
+                .build();
+
+            match result {
+                Err(Error::ConfigError(errors)) => {
+                    assert_eq!(errors.len(), 3, "Did not report short TOTP encryption key")
+                }
+                _ => panic!("Built config with short TOTP encryption key"),
+            }
+        });
+    }
+
+    #[test]
+    fn build_reports_mismatched_webauthn_rp_id() {
+        let result = ConfigBuilder::new()
+            .args(vec![
+                "--webauthn-origin=https://login.example.com",
+                "--webauthn-rp-id=example.org",
+            ])
+            .build();
+
+        match result {
+            Err(Error::ConfigError(errors)) => {
+                assert_eq!(errors.len(), 3, "Did not report mismatched relying party id")
+            }
+            _ => panic!("Built config with mismatched relying party id"),
+        }
+    }
+
+    #[test]
+    fn file_rejects_unsupported_format() {
+        with_config_file("ini", "bcrypt_cost = 10\n", |path| {
+            let builder = ConfigBuilder::new().file(path);
+
+            assert_eq!(builder.errors.len(), 1, "Accepted unsupported config format");
+        });
+    }
+
+    fn with_config_file<T>(extension: &str, contents: &str, test: T)
+    where
+        T: FnOnce(&Path),
+    {
+        let path = env::temp_dir().join(format!("{}.{}", generate_string(), extension));
+
+        File::create(&path)
+            .and_then(|mut f| f.write_all(contents.as_bytes()))
+            .expect("Failed to write config file");
+
+        test(&path);
+
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,157 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use ring::{constant_time, digest};
+use error::ConfigErrorKind;
+
+const INTROSPECTION_CLIENTS: &str = "introspection_clients";
+
+pub const SETTINGS: &[&str] = &[INTROSPECTION_CLIENTS];
+
+pub struct Clients {
+    secrets: HashMap<String, Vec<u8>>,
+}
+
+impl Clients {
+    pub fn new(secrets: HashMap<String, Vec<u8>>) -> Self {
+        Clients {
+            secrets: secrets
+                .into_iter()
+                .map(|(client_id, secret)| (client_id, hash(&secret)))
+                .collect(),
+        }
+    }
+
+    pub fn from_settings(
+        values: &HashMap<String, String>,
+        errors: &mut Vec<ConfigErrorKind>,
+    ) -> Self {
+        let mut secrets: HashMap<String, Vec<u8>> = HashMap::new();
+
+        let entries = match values.get(INTROSPECTION_CLIENTS) {
+            Some(entries) => entries,
+            None => return Clients::new(secrets),
+        };
+
+        for entry in entries.split(',').map(|entry| entry.trim()) {
+            if entry.is_empty() {
+                continue;
+            }
+
+            let mut parts = entry.splitn(2, ':');
+
+            let (client_id, path) = match (parts.next(), parts.next()) {
+                (Some(client_id), Some(path)) if !client_id.is_empty() && !path.is_empty() => {
+                    (client_id, path)
+                }
+                _ => {
+                    errors.push(ConfigErrorKind::Malformed(
+                        INTROSPECTION_CLIENTS.to_owned(),
+                        "must be a list of client_id:path pairs".to_owned(),
+                    ));
+                    continue;
+                }
+            };
+
+            if secrets.contains_key(client_id) {
+                errors.push(ConfigErrorKind::Malformed(
+                    INTROSPECTION_CLIENTS.to_owned(),
+                    format!("uses the client id '{}' more than once", client_id),
+                ));
+                continue;
+            }
+
+            let mut contents = String::new();
+
+            if File::open(path)
+                .and_then(|mut f| f.read_to_string(&mut contents))
+                .is_err()
+            {
+                errors.push(ConfigErrorKind::UnreadableFile(path.to_owned()));
+                continue;
+            }
+
+            let secret = contents.trim();
+
+            if secret.is_empty() {
+                errors.push(ConfigErrorKind::Malformed(
+                    INTROSPECTION_CLIENTS.to_owned(),
+                    format!("has an empty secret for '{}'", client_id),
+                ));
+                continue;
+            }
+
+            secrets.insert(client_id.to_owned(), secret.as_bytes().to_vec());
+        }
+
+        Clients::new(secrets)
+    }
+
+    pub fn authenticate(&self, client_id: &str, secret: &[u8]) -> bool {
+        let secret = hash(secret);
+
+        match self.secrets.get(client_id) {
+            Some(expected) => constant_time::verify_slices_are_equal(expected, &secret).is_ok(),
+            None => false,
+        }
+    }
+}
+
+fn hash(secret: &[u8]) -> Vec<u8> {
+    digest::digest(&digest::SHA256, secret).as_ref().to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clients() -> Clients {
+        let mut secrets = HashMap::new();
+        secrets.insert("resource-server".to_owned(), b"secret".to_vec());
+
+        Clients::new(secrets)
+    }
+
+    #[test]
+    fn authenticate_accepts_correct_secret() {
+        assert!(
+            clients().authenticate("resource-server", b"secret"),
+            "Rejected correct client secret"
+        );
+    }
+
+    #[test]
+    fn authenticate_rejects_wrong_secret() {
+        assert!(
+            !clients().authenticate("resource-server", b"wrong"),
+            "Accepted wrong client secret"
+        );
+    }
+
+    #[test]
+    fn authenticate_rejects_unknown_client() {
+        assert!(
+            !clients().authenticate("unknown", b"secret"),
+            "Accepted unknown client"
+        );
+    }
+}
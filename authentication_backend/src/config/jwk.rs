@@ -0,0 +1,320 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use base64;
+use super::algorithm::Algorithm;
+
+const SEQUENCE: u8 = 0x30;
+const INTEGER: u8 = 0x02;
+const BIT_STRING: u8 = 0x03;
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Jwk {
+    kty: String,
+    #[serde(rename = "use")]
+    key_use: String,
+    alg: String,
+    kid: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    crv: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    n: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    e: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    x: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    y: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct JwkSet {
+    keys: Vec<Jwk>,
+}
+
+impl Jwk {
+    pub fn new(kid: &str, algorithm: Algorithm, public_key: &[u8]) -> Option<Self> {
+        let mut jwk = Jwk {
+            kty: String::new(),
+            key_use: "sig".to_owned(),
+            alg: algorithm.name().to_owned(),
+            kid: kid.to_owned(),
+            crv: None,
+            n: None,
+            e: None,
+            x: None,
+            y: None,
+        };
+
+        match algorithm {
+            Algorithm::RS256 | Algorithm::RS384 | Algorithm::RS512 => {
+                let (modulus, exponent) = match rsa_components(public_key) {
+                    Some(components) => components,
+                    None => return None,
+                };
+
+                jwk.kty = "RSA".to_owned();
+                jwk.n = Some(encode(unsigned(modulus)));
+                jwk.e = Some(encode(unsigned(exponent)));
+            }
+            Algorithm::ES256 | Algorithm::ES384 => {
+                let coordinate_len = (public_key.len() - 1) / 2;
+
+                jwk.kty = "EC".to_owned();
+                jwk.crv = Some(
+                    if algorithm == Algorithm::ES256 {
+                        "P-256"
+                    } else {
+                        "P-384"
+                    }.to_owned(),
+                );
+                jwk.x = Some(encode(&public_key[1..1 + coordinate_len]));
+                jwk.y = Some(encode(&public_key[1 + coordinate_len..]));
+            }
+            Algorithm::EdDSA => {
+                jwk.kty = "OKP".to_owned();
+                jwk.crv = Some("Ed25519".to_owned());
+                jwk.x = Some(encode(public_key));
+            }
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512 => return None,
+        }
+
+        Some(jwk)
+    }
+
+    pub fn kid(&self) -> &str {
+        &self.kid
+    }
+}
+
+impl JwkSet {
+    pub fn new(keys: Vec<Jwk>) -> Self {
+        JwkSet { keys: keys }
+    }
+
+    pub fn keys(&self) -> &[Jwk] {
+        &self.keys
+    }
+}
+
+pub fn public_key_bytes(algorithm: Algorithm, der: &[u8]) -> Option<Vec<u8>> {
+    match algorithm {
+        Algorithm::RS256 | Algorithm::RS384 | Algorithm::RS512 => {
+            rsa_components(der).map(|_| der.to_vec())
+        }
+        Algorithm::ES256 => uncompressed_point(spki_key(der, 65)),
+        Algorithm::ES384 => uncompressed_point(spki_key(der, 97)),
+        Algorithm::EdDSA => spki_key(der, 32).map(|key| key.to_vec()),
+        Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512 => None,
+    }
+}
+
+fn encode(bytes: &[u8]) -> String {
+    base64::encode_config(bytes, base64::URL_SAFE_NO_PAD)
+}
+
+fn rsa_components(der: &[u8]) -> Option<(&[u8], &[u8])> {
+    let (sequence, rest) = match der_element(SEQUENCE, der) {
+        Some(element) => element,
+        None => return None,
+    };
+
+    if !rest.is_empty() {
+        return None;
+    }
+
+    let (modulus, rest) = match der_element(INTEGER, sequence) {
+        Some(element) => element,
+        None => return None,
+    };
+
+    let (exponent, rest) = match der_element(INTEGER, rest) {
+        Some(element) => element,
+        None => return None,
+    };
+
+    if !rest.is_empty() {
+        return None;
+    }
+
+    Some((modulus, exponent))
+}
+
+fn spki_key(der: &[u8], len: usize) -> Option<&[u8]> {
+    let (sequence, rest) = match der_element(SEQUENCE, der) {
+        Some(element) => element,
+        None => return None,
+    };
+
+    if !rest.is_empty() {
+        return None;
+    }
+
+    let (_, rest) = match der_element(SEQUENCE, sequence) {
+        Some(element) => element,
+        None => return None,
+    };
+
+    let (bit_string, rest) = match der_element(BIT_STRING, rest) {
+        Some(element) => element,
+        None => return None,
+    };
+
+    if !rest.is_empty() || bit_string.len() != len + 1 || bit_string[0] != 0 {
+        return None;
+    }
+
+    Some(&bit_string[1..])
+}
+
+fn uncompressed_point(key: Option<&[u8]>) -> Option<Vec<u8>> {
+    match key {
+        Some(key) if key[0] == 0x04 => Some(key.to_vec()),
+        _ => None,
+    }
+}
+
+fn der_element(tag: u8, der: &[u8]) -> Option<(&[u8], &[u8])> {
+    if der.len() < 2 || der[0] != tag {
+        return None;
+    }
+
+    let (length, header) = if der[1] & 0x80 == 0 {
+        (der[1] as usize, 2)
+    } else {
+        let octets = (der[1] & 0x7f) as usize;
+
+        if octets == 0 || octets > 4 || der.len() < 2 + octets {
+            return None;
+        }
+
+        let length = der[2..2 + octets].iter().fold(
+            0,
+            |acc, &byte| (acc << 8) | byte as usize,
+        );
+
+        (length, 2 + octets)
+    };
+
+    if der.len() - header < length {
+        return None;
+    }
+
+    let rest = &der[header..];
+
+    Some((&rest[..length], &rest[length..]))
+}
+
+fn unsigned(integer: &[u8]) -> &[u8] {
+    match integer.iter().position(|&byte| byte != 0) {
+        Some(start) => &integer[start..],
+        None => &integer[integer.len().saturating_sub(1)..],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_reads_rsa_modulus_and_exponent() {
+        let der = [0x30, 0x08, 0x02, 0x03, 0x00, 0xff, 0x01, 0x02, 0x01, 0x03];
+
+        let jwk = Jwk::new("key", Algorithm::RS512, &der);
+
+        assert!(jwk.is_some(), "Failed to read RSA public key");
+
+        let jwk = jwk.unwrap();
+
+        assert_eq!(jwk.kty, "RSA", "JWK has the wrong key type");
+        assert_eq!(jwk.n, Some("_wE".to_owned()), "Modulus was encoded incorrectly");
+        assert_eq!(jwk.e, Some("Aw".to_owned()), "Exponent was encoded incorrectly");
+        assert_eq!(jwk.kid(), "key", "JWK has the wrong kid");
+    }
+
+    #[test]
+    fn public_key_bytes_reads_long_lengths() {
+        let mut der = vec![0x30, 0x81, 0x87, 0x02, 0x81, 0x81, 0x00];
+        der.extend(vec![0xab; 128]);
+        der.extend(vec![0x02, 0x01, 0x03]);
+
+        let key = public_key_bytes(Algorithm::RS256, &der);
+
+        assert_eq!(key, Some(der.clone()), "Failed to read RSA public key");
+    }
+
+    #[test]
+    fn public_key_bytes_reads_ec_point() {
+        let der = ec_spki();
+
+        let key = public_key_bytes(Algorithm::ES256, &der).expect("Failed to read EC public key");
+
+        assert_eq!(key.len(), 65, "EC point has the wrong length");
+
+        let jwk = Jwk::new("key", Algorithm::ES256, &key).expect("Failed to create EC JWK");
+
+        assert_eq!(jwk.crv, Some("P-256".to_owned()), "JWK has the wrong curve");
+        assert_eq!(jwk.x, Some(encode(&[0x01; 32])), "X was encoded incorrectly");
+        assert_eq!(jwk.y, Some(encode(&[0x02; 32])), "Y was encoded incorrectly");
+    }
+
+    #[test]
+    fn public_key_bytes_rejects_wrong_curve() {
+        assert!(
+            public_key_bytes(Algorithm::ES384, &ec_spki()).is_none(),
+            "Read P-256 key as P-384 key"
+        );
+    }
+
+    #[test]
+    fn public_key_bytes_rejects_truncated_keys() {
+        let der = [0x30, 0x08, 0x02, 0x03, 0x00, 0xff, 0x01, 0x02];
+
+        assert!(
+            public_key_bytes(Algorithm::RS512, &der).is_none(),
+            "Read truncated RSA public key"
+        );
+    }
+
+    #[test]
+    fn public_key_bytes_rejects_garbage() {
+        assert!(
+            public_key_bytes(Algorithm::EdDSA, b"not a key").is_none(),
+            "Read garbage as Ed25519 public key"
+        );
+    }
+
+    #[test]
+    fn new_skips_hmac_keys() {
+        assert!(
+            Jwk::new("key", Algorithm::HS512, b"secret").is_none(),
+            "Published HMAC secret"
+        );
+    }
+
+    fn ec_spki() -> Vec<u8> {
+        let mut der = vec![0x30, 0x59, 0x30, 0x13];
+        der.extend(vec![0x06, 0x07, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01]);
+        der.extend(vec![0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07]);
+        der.extend(vec![0x03, 0x42, 0x00, 0x04]);
+        der.extend(vec![0x01; 32]);
+        der.extend(vec![0x02; 32]);
+        der
+    }
+}
@@ -0,0 +1,348 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::sync::Arc;
+use ring::{digest, hmac, signature};
+use ring::rand::SystemRandom;
+use untrusted::Input;
+use error::{ConfigErrorKind, Error, JWTErrorKind, Result};
+use super::algorithm::Algorithm;
+use super::jwk::{self, Jwk};
+
+pub const JWT_PRIVATE_KEY: &str = "jwt_private_key";
+pub const JWT_PUBLIC_KEY: &str = "jwt_public_key";
+
+const PROBE_MESSAGE: &[u8] = b"probe";
+
+enum Signer {
+    Hmac(hmac::SigningKey),
+    Rsa(Arc<signature::RSAKeyPair>, &'static signature::RSAEncoding),
+    Ecdsa(signature::ECDSAKeyPair),
+    Ed25519(signature::Ed25519KeyPair),
+}
+
+enum Verifier {
+    Hmac(hmac::SigningKey),
+    Public(&'static signature::VerificationAlgorithm, Vec<u8>),
+}
+
+// Each key is bound to a single algorithm, and only verifies signatures made with it
+pub struct JWTKey {
+    kid: String,
+    algorithm: Algorithm,
+    signer: Option<Signer>,
+    verifier: Verifier,
+    jwk: Option<Jwk>,
+}
+
+impl JWTKey {
+    pub fn verifying(kid: &str, algorithm: Algorithm, key: &[u8]) -> Option<Self> {
+        let (verifier, jwk) = match verifier(algorithm, key) {
+            Some(verifier) => verifier,
+            None => return None,
+        };
+
+        Some(JWTKey {
+            kid: kid.to_owned(),
+            algorithm: algorithm,
+            signer: None,
+            verifier: verifier,
+            jwk: jwk.and_then(|public_key| Jwk::new(kid, algorithm, &public_key)),
+        })
+    }
+
+    pub fn signing(
+        kid: &str,
+        algorithm: Algorithm,
+        private_key: &[u8],
+        public_key: Option<&[u8]>,
+    ) -> ::std::result::Result<Self, ConfigErrorKind> {
+        let malformed = || {
+            ConfigErrorKind::Malformed(JWT_PRIVATE_KEY.to_owned(), key_format(algorithm))
+        };
+
+        let signer = match signer(algorithm, private_key) {
+            Some(signer) => signer,
+            None => return Err(malformed()),
+        };
+
+        let verification_key = if algorithm.is_symmetric() {
+            private_key
+        } else {
+            match public_key {
+                Some(public_key) => public_key,
+                None => return Err(malformed()),
+            }
+        };
+
+        let key = match JWTKey::verifying(kid, algorithm, verification_key) {
+            Some(key) => JWTKey { signer: Some(signer), ..key },
+            None => {
+                return Err(ConfigErrorKind::Malformed(
+                    JWT_PUBLIC_KEY.to_owned(),
+                    key_format(algorithm),
+                ))
+            }
+        };
+
+        match key.sign(PROBE_MESSAGE) {
+            Ok(ref signature) if key.verify(PROBE_MESSAGE, signature) => Ok(key),
+            _ => {
+                Err(ConfigErrorKind::Malformed(
+                    JWT_PUBLIC_KEY.to_owned(),
+                    format!("must belong to the key in '{}'", JWT_PRIVATE_KEY),
+                ))
+            }
+        }
+    }
+
+    pub fn kid(&self) -> &str {
+        &self.kid
+    }
+
+    pub fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+
+    pub fn jwk(&self) -> Option<&Jwk> {
+        self.jwk.as_ref()
+    }
+
+    pub fn sign(&self, message: &[u8]) -> Result<Vec<u8>> {
+        let signing_failed = || Error::JWTError(JWTErrorKind::SigningFailed);
+
+        let signer = match self.signer {
+            Some(ref signer) => signer,
+            None => return Err(signing_failed()),
+        };
+
+        match *signer {
+            Signer::Hmac(ref key) => Ok(hmac::sign(key, message).as_ref().to_vec()),
+            Signer::Rsa(ref key_pair, encoding) => {
+                let mut state = signature::RSASigningState::new(key_pair.clone())
+                    .map_err(|_| signing_failed())?;
+                let mut signature = vec![0; key_pair.public_modulus_len()];
+
+                state
+                    .sign(encoding, &SystemRandom::new(), message, &mut signature)
+                    .map_err(|_| signing_failed())?;
+
+                Ok(signature)
+            }
+            Signer::Ecdsa(ref key_pair) => {
+                key_pair
+                    .sign(Input::from(message), &SystemRandom::new())
+                    .map(|signature| signature.as_ref().to_vec())
+                    .map_err(|_| signing_failed())
+            }
+            Signer::Ed25519(ref key_pair) => Ok(key_pair.sign(message).as_ref().to_vec()),
+        }
+    }
+
+    pub fn verify(&self, message: &[u8], signature: &[u8]) -> bool {
+        match self.verifier {
+            Verifier::Hmac(ref key) => hmac::verify_with_own_key(key, message, signature).is_ok(),
+            Verifier::Public(algorithm, ref public_key) => {
+                signature::verify(
+                    algorithm,
+                    Input::from(public_key),
+                    Input::from(message),
+                    Input::from(signature),
+                ).is_ok()
+            }
+        }
+    }
+}
+
+pub fn key_format(algorithm: Algorithm) -> String {
+    let format = match algorithm {
+        Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512 => "a non-empty secret",
+        Algorithm::RS256 | Algorithm::RS384 | Algorithm::RS512 => "a DER encoded RSA key",
+        Algorithm::ES256 => "a DER encoded P-256 key",
+        Algorithm::ES384 => "a DER encoded P-384 key",
+        Algorithm::EdDSA => "a DER encoded Ed25519 key",
+    };
+
+    format!("must be {} for {}", format, algorithm.name())
+}
+
+fn hmac_digest(algorithm: Algorithm) -> &'static digest::Algorithm {
+    match algorithm {
+        Algorithm::HS384 => &digest::SHA384,
+        Algorithm::HS512 => &digest::SHA512,
+        _ => &digest::SHA256,
+    }
+}
+
+fn signer(algorithm: Algorithm, private_key: &[u8]) -> Option<Signer> {
+    let input = Input::from(private_key);
+
+    match algorithm {
+        Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512 => {
+            if private_key.is_empty() {
+                return None;
+            }
+
+            Some(Signer::Hmac(
+                hmac::SigningKey::new(hmac_digest(algorithm), private_key),
+            ))
+        }
+        Algorithm::RS256 | Algorithm::RS384 | Algorithm::RS512 => {
+            let encoding: &'static signature::RSAEncoding = match algorithm {
+                Algorithm::RS256 => &signature::RSA_PKCS1_SHA256,
+                Algorithm::RS384 => &signature::RSA_PKCS1_SHA384,
+                _ => &signature::RSA_PKCS1_SHA512,
+            };
+
+            signature::RSAKeyPair::from_der(input).ok().map(|key_pair| {
+                Signer::Rsa(Arc::new(key_pair), encoding)
+            })
+        }
+        Algorithm::ES256 => {
+            signature::ECDSAKeyPair::from_pkcs8(&signature::ECDSA_P256_SHA256_FIXED_SIGNING, input)
+                .ok()
+                .map(Signer::Ecdsa)
+        }
+        Algorithm::ES384 => {
+            signature::ECDSAKeyPair::from_pkcs8(&signature::ECDSA_P384_SHA384_FIXED_SIGNING, input)
+                .ok()
+                .map(Signer::Ecdsa)
+        }
+        Algorithm::EdDSA => {
+            signature::Ed25519KeyPair::from_pkcs8_maybe_unchecked(input)
+                .ok()
+                .map(Signer::Ed25519)
+        }
+    }
+}
+
+fn verifier(algorithm: Algorithm, key: &[u8]) -> Option<(Verifier, Option<Vec<u8>>)> {
+    if algorithm.is_symmetric() {
+        if key.is_empty() {
+            return None;
+        }
+
+        let verifier = Verifier::Hmac(hmac::SigningKey::new(hmac_digest(algorithm), key));
+
+        return Some((verifier, None));
+    }
+
+    let verification_algorithm: &'static signature::VerificationAlgorithm = match algorithm {
+        Algorithm::RS256 => &signature::RSA_PKCS1_2048_8192_SHA256,
+        Algorithm::RS384 => &signature::RSA_PKCS1_2048_8192_SHA384,
+        Algorithm::RS512 => &signature::RSA_PKCS1_2048_8192_SHA512,
+        Algorithm::ES256 => &signature::ECDSA_P256_SHA256_FIXED,
+        Algorithm::ES384 => &signature::ECDSA_P384_SHA384_FIXED,
+        _ => &signature::ED25519,
+    };
+
+    jwk::public_key_bytes(algorithm, key).map(|public_key| {
+        (
+            Verifier::Public(verification_algorithm, public_key.clone()),
+            Some(public_key),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hmac_key_verifies_own_signature() {
+        let key = JWTKey::signing("key", Algorithm::HS512, b"secret", None)
+            .ok()
+            .expect("Failed to create HMAC key");
+
+        let signature = key.sign(b"message").expect("Failed to sign message");
+
+        assert!(key.verify(b"message", &signature), "Failed to verify signature");
+        assert!(
+            !key.verify(b"other message", &signature),
+            "Verified signature for a different message"
+        );
+    }
+
+    #[test]
+    fn hmac_key_rejects_other_secret() {
+        let key = JWTKey::signing("key", Algorithm::HS256, b"secret", None)
+            .ok()
+            .expect("Failed to create HMAC key");
+        let other_key = JWTKey::verifying("key", Algorithm::HS256, b"other secret")
+            .expect("Failed to create HMAC key");
+
+        let signature = key.sign(b"message").expect("Failed to sign message");
+
+        assert!(
+            !other_key.verify(b"message", &signature),
+            "Verified signature made with another secret"
+        );
+    }
+
+    #[test]
+    fn verifying_key_cannot_sign() {
+        let key = JWTKey::verifying("key", Algorithm::HS256, b"secret")
+            .expect("Failed to create HMAC key");
+
+        assert!(!key.sign(b"message").is_ok(), "Signed with a verifying key");
+    }
+
+    #[test]
+    fn hmac_key_is_not_published() {
+        let key = JWTKey::verifying("key", Algorithm::HS256, b"secret")
+            .expect("Failed to create HMAC key");
+
+        assert!(key.jwk().is_none(), "Published HMAC secret");
+    }
+
+    #[test]
+    fn signing_rejects_mismatched_private_key() {
+        let key = JWTKey::signing("key", Algorithm::EdDSA, b"not a key", Some(&b"not a key"[..]));
+
+        assert!(key.is_err(), "Accepted garbage Ed25519 key");
+    }
+
+    #[test]
+    fn signing_rejects_public_key_of_another_pair() {
+        let pairs: Vec<(Algorithm, &[u8], &[u8])> = vec![
+            (
+                Algorithm::RS256,
+                &include_bytes!("../../test_keys/rsa_private_1.der")[..],
+                &include_bytes!("../../test_keys/rsa_public_2.der")[..],
+            ),
+            (
+                Algorithm::ES256,
+                &include_bytes!("../../test_keys/ec_private_1.der")[..],
+                &include_bytes!("../../test_keys/ec_public_2.der")[..],
+            ),
+            (
+                Algorithm::EdDSA,
+                &include_bytes!("../../test_keys/ed25519_private_1.der")[..],
+                &include_bytes!("../../test_keys/ed25519_public_2.der")[..],
+            ),
+        ];
+
+        for (algorithm, private_key, public_key) in pairs {
+            match JWTKey::signing("key", algorithm, private_key, Some(public_key)) {
+                Err(ConfigErrorKind::Malformed(ref setting, _)) if setting == JWT_PUBLIC_KEY => (),
+                _ => panic!("Accepted mismatched {} key pair", algorithm.name()),
+            }
+        }
+    }
+}
@@ -17,56 +17,625 @@
  * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
  */
 
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::Read;
-use dotenv::dotenv;
-use jwt;
-use jwt::{Header, Validation};
-use error::Result;
+use std::iter;
+use base64;
+use chrono::Utc;
 use serde::{Serialize, Deserialize};
+use serde_json::{self, Value};
+use error::{ConfigErrorKind, Error, JWTErrorKind, Result};
+use super::algorithm::Algorithm;
+use super::jwk::JwkSet;
+use super::jwt_key::{self, JWTKey, JWT_PRIVATE_KEY, JWT_PUBLIC_KEY};
+use super::settings::read_key;
+
+const JWT_ALGORITHM: &str = "jwt_algorithm";
+const JWT_KEY_ID: &str = "jwt_key_id";
+const JWT_VERIFICATION_KEYS: &str = "jwt_verification_keys";
+
+pub const SETTINGS: &[&str] = &[
+    JWT_ALGORITHM,
+    JWT_PRIVATE_KEY,
+    JWT_PUBLIC_KEY,
+    JWT_KEY_ID,
+    JWT_VERIFICATION_KEYS,
+];
+
+const DEFAULT_KEY_ID: &str = "default";
+const DEFAULT_ALGORITHM: Algorithm = Algorithm::RS512;
+
+#[derive(Serialize, Deserialize)]
+struct Header {
+    alg: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    typ: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    kid: Option<String>,
+}
+
+pub struct Validation {
+    pub leeway: i64,
+    pub validate_exp: bool,
+    pub validate_iat: bool,
+    pub validate_nbf: bool,
+    pub iss: Option<String>,
+    pub sub: Option<String>,
+    pub aud: Option<String>,
+}
+
+impl Default for Validation {
+    fn default() -> Self {
+        Validation {
+            leeway: 0,
+            validate_exp: true,
+            validate_iat: true,
+            validate_nbf: false,
+            iss: None,
+            sub: None,
+            aud: None,
+        }
+    }
+}
 
 pub struct JWTSecret {
-    public_key: Vec<u8>,
-    private_key: Vec<u8>,
+    key: JWTKey,
+    keys: Vec<JWTKey>,
 }
 
 impl JWTSecret {
-    pub fn initialize() -> JWTSecret {
-        dotenv().ok();
-
+    pub fn new(key: JWTKey, keys: Vec<JWTKey>) -> JWTSecret {
         JWTSecret {
-            private_key: JWTSecret::read_file(dotenv!("JWT_PRIVATE_KEY")),
-            public_key: JWTSecret::read_file(dotenv!("JWT_PUBLIC_KEY")),
+            key: key,
+            keys: keys,
         }
     }
 
-    fn read_file(filename: &str) -> Vec<u8> {
-        let mut f = File::open(filename).expect(&format!("File '{}' does not exist", filename));
-        let mut contents: Vec<u8> = Vec::new();
+    pub fn from_settings(
+        values: &HashMap<String, String>,
+        errors: &mut Vec<ConfigErrorKind>,
+    ) -> Option<JWTSecret> {
+        let kid = match values.get(JWT_KEY_ID) {
+            Some(kid) => kid.to_owned(),
+            None => DEFAULT_KEY_ID.to_owned(),
+        };
 
-        f.read_to_end(&mut contents).expect(&format!(
-            "Failed to read file '{}'",
-            filename
-        ));
+        let algorithm = match jwt_algorithm(values, errors) {
+            Some(algorithm) => algorithm,
+            None => return None,
+        };
+
+        let key = signing_key(values, &kid, algorithm, errors);
+        let keys = verification_keys(values, &kid, algorithm, errors);
 
-        contents
+        key.map(|key| JWTSecret::new(key, keys))
     }
 
-    pub fn encode<T>(&self, header: &Header, claims: &T) -> Result<String>
+    pub fn kid(&self) -> &str {
+        self.key.kid()
+    }
+
+    pub fn encode<T>(&self, claims: &T) -> Result<String>
     where
         T: Serialize,
     {
-        let token = jwt::encode(header, claims, &self.private_key)?;
+        let header = Header {
+            alg: self.key.algorithm().name().to_owned(),
+            typ: Some("JWT".to_owned()),
+            kid: Some(self.key.kid().to_owned()),
+        };
+
+        let signing_failed = |_: serde_json::Error| jwt_error(JWTErrorKind::SigningFailed);
 
-        Ok(token)
+        let header = serde_json::to_vec(&header).map_err(&signing_failed)?;
+        let claims = serde_json::to_vec(claims).map_err(&signing_failed)?;
+
+        let message = format!("{}.{}", encode(&header), encode(&claims));
+        let signature = self.key.sign(message.as_bytes())?;
+
+        Ok(format!("{}.{}", message, encode(&signature)))
     }
 
+    // Tokens without a kid were issued before key rotation existed, so they belong to the
+    // active key. The algorithm named in the header must be the one the key is bound to.
     pub fn decode<T>(&self, token: &str, validation: &Validation) -> Result<T>
     where
         for<'a> T: Deserialize<'a>,
     {
-        let token_data = jwt::decode::<T>(token, &self.public_key, validation)?;
+        let parts: Vec<&str> = token.split('.').collect();
+
+        if parts.len() != 3 {
+            return Err(jwt_error(JWTErrorKind::InvalidToken));
+        }
+
+        let header: Header = serde_json::from_slice(&decode(parts[0])?)
+            .map_err(|_| jwt_error(JWTErrorKind::InvalidToken))?;
+
+        let key = {
+            let kid = match header.kid {
+                Some(ref kid) => kid,
+                None => self.key.kid(),
+            };
+
+            match self.keys().find(|key| key.kid() == kid) {
+                Some(key) => key,
+                None => return Err(jwt_error(JWTErrorKind::UnknownKey)),
+            }
+        };
+
+        if header.alg != key.algorithm().name() {
+            return Err(jwt_error(JWTErrorKind::InvalidAlgorithm));
+        }
+
+        let message_length = parts[0].len() + 1 + parts[1].len();
+
+        if !key.verify(token[..message_length].as_bytes(), &decode(parts[2])?) {
+            return Err(jwt_error(JWTErrorKind::InvalidSignature));
+        }
+
+        let claims: Value = serde_json::from_slice(&decode(parts[1])?)
+            .map_err(|_| jwt_error(JWTErrorKind::InvalidToken))?;
+
+        validate(&claims, validation)?;
+
+        serde_json::from_value(claims).map_err(|_| jwt_error(JWTErrorKind::InvalidToken))
+    }
+
+    pub fn jwks(&self) -> JwkSet {
+        JwkSet::new(self.keys().filter_map(|key| key.jwk().cloned()).collect())
+    }
+
+    fn keys<'a>(&'a self) -> Box<Iterator<Item = &'a JWTKey> + 'a> {
+        Box::new(iter::once(&self.key).chain(self.keys.iter()))
+    }
+}
+
+fn jwt_algorithm(
+    values: &HashMap<String, String>,
+    errors: &mut Vec<ConfigErrorKind>,
+) -> Option<Algorithm> {
+    let name = match values.get(JWT_ALGORITHM) {
+        Some(name) => name,
+        None => return Some(DEFAULT_ALGORITHM),
+    };
+
+    let algorithm = Algorithm::from_name(name);
+
+    if algorithm.is_none() {
+        errors.push(ConfigErrorKind::Malformed(
+            JWT_ALGORITHM.to_owned(),
+            "must be one of HS256, HS384, HS512, RS256, RS384, RS512, ES256, ES384 or EdDSA"
+                .to_owned(),
+        ));
+    }
+
+    algorithm
+}
+
+fn signing_key(
+    values: &HashMap<String, String>,
+    kid: &str,
+    algorithm: Algorithm,
+    errors: &mut Vec<ConfigErrorKind>,
+) -> Option<JWTKey> {
+    let private_key = read_key(values, JWT_PRIVATE_KEY, errors);
+
+    let public_key = if algorithm.is_symmetric() {
+        None
+    } else {
+        match read_key(values, JWT_PUBLIC_KEY, errors) {
+            Some(public_key) => Some(public_key),
+            None => return None,
+        }
+    };
+
+    let private_key = match private_key {
+        Some(private_key) => private_key,
+        None => return None,
+    };
+
+    let key = JWTKey::signing(
+        kid,
+        algorithm,
+        &private_key,
+        public_key.as_ref().map(|public_key| public_key.as_slice()),
+    );
+
+    match key {
+        Ok(key) => Some(key),
+        Err(err) => {
+            errors.push(err);
+            None
+        }
+    }
+}
+
+fn verification_keys(
+    values: &HashMap<String, String>,
+    active_kid: &str,
+    active_algorithm: Algorithm,
+    errors: &mut Vec<ConfigErrorKind>,
+) -> Vec<JWTKey> {
+    let entries = match values.get(JWT_VERIFICATION_KEYS) {
+        Some(entries) => entries,
+        None => return Vec::new(),
+    };
+
+    let mut keys: Vec<JWTKey> = Vec::new();
+
+    for entry in entries.split(',').map(|entry| entry.trim()) {
+        if entry.is_empty() {
+            continue;
+        }
+
+        let parts: Vec<&str> = entry.splitn(3, ':').collect();
+
+        let (kid, algorithm, path) = match parts.len() {
+            2 => (parts[0], active_algorithm.name(), parts[1]),
+            3 => (parts[0], parts[1], parts[2]),
+            _ => ("", "", ""),
+        };
+
+        if kid.is_empty() || path.is_empty() {
+            errors.push(ConfigErrorKind::Malformed(
+                JWT_VERIFICATION_KEYS.to_owned(),
+                "must be a list of kid:path or kid:algorithm:path entries".to_owned(),
+            ));
+            continue;
+        }
+
+        let algorithm = match Algorithm::from_name(algorithm) {
+            Some(algorithm) => algorithm,
+            None => {
+                errors.push(ConfigErrorKind::Malformed(
+                    JWT_VERIFICATION_KEYS.to_owned(),
+                    format!("uses the unknown algorithm '{}'", algorithm),
+                ));
+                continue;
+            }
+        };
+
+        if kid == active_kid || keys.iter().any(|key| key.kid() == kid) {
+            errors.push(ConfigErrorKind::Malformed(
+                JWT_VERIFICATION_KEYS.to_owned(),
+                format!("uses the key id '{}' more than once", kid),
+            ));
+            continue;
+        }
+
+        let mut contents: Vec<u8> = Vec::new();
+
+        if File::open(path)
+            .and_then(|mut f| f.read_to_end(&mut contents))
+            .is_err()
+        {
+            errors.push(ConfigErrorKind::UnreadableFile(path.to_owned()));
+            continue;
+        }
+
+        match JWTKey::verifying(kid, algorithm, &contents) {
+            Some(key) => keys.push(key),
+            None => {
+                errors.push(ConfigErrorKind::Malformed(
+                    JWT_VERIFICATION_KEYS.to_owned(),
+                    jwt_key::key_format(algorithm),
+                ))
+            }
+        }
+    }
+
+    keys
+}
+
+fn jwt_error(kind: JWTErrorKind) -> Error {
+    Error::JWTError(kind)
+}
+
+fn encode(bytes: &[u8]) -> String {
+    base64::encode_config(bytes, base64::URL_SAFE_NO_PAD)
+}
+
+fn decode(part: &str) -> Result<Vec<u8>> {
+    base64::decode_config(part, base64::URL_SAFE_NO_PAD)
+        .map_err(|_| jwt_error(JWTErrorKind::InvalidToken))
+}
+
+fn validate(claims: &Value, validation: &Validation) -> Result<()> {
+    let now = Utc::now().timestamp();
+    let timestamp = |name: &str| claims.get(name).and_then(|value| value.as_i64());
+
+    if validation.validate_exp {
+        match timestamp("exp") {
+            Some(exp) if exp >= now - validation.leeway => (),
+            _ => return Err(jwt_error(JWTErrorKind::ExpiredSignature)),
+        }
+    }
+
+    if validation.validate_iat {
+        match timestamp("iat") {
+            Some(iat) if iat > now + validation.leeway => {
+                return Err(jwt_error(JWTErrorKind::InvalidIssuedAt))
+            }
+            _ => (),
+        }
+    }
+
+    if validation.validate_nbf {
+        match timestamp("nbf") {
+            Some(nbf) if nbf > now + validation.leeway => {
+                return Err(jwt_error(JWTErrorKind::ImmatureSignature))
+            }
+            _ => (),
+        }
+    }
+
+    let matches = |name: &str, expected: &str| {
+        claims.get(name).and_then(|value| value.as_str()) == Some(expected)
+    };
+
+    if let Some(ref iss) = validation.iss {
+        if !matches("iss", iss) {
+            return Err(jwt_error(JWTErrorKind::InvalidIssuer));
+        }
+    }
+
+    if let Some(ref sub) = validation.sub {
+        if !matches("sub", sub) {
+            return Err(jwt_error(JWTErrorKind::InvalidSubject));
+        }
+    }
+
+    if let Some(ref aud) = validation.aud {
+        let audience_matches = match claims.get("aud") {
+            Some(&Value::String(ref audience)) => audience == aud,
+            Some(&Value::Array(ref audiences)) => {
+                audiences.iter().any(|audience| audience.as_str() == Some(aud))
+            }
+            _ => false,
+        };
+
+        if !audience_matches {
+            return Err(jwt_error(JWTErrorKind::InvalidAudience));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct TestClaims {
+        sub: String,
+        iat: i64,
+        exp: i64,
+    }
+
+    fn hmac_key(kid: &str, algorithm: Algorithm, secret: &[u8]) -> JWTKey {
+        JWTKey::signing(kid, algorithm, secret, None).ok().expect("Failed to create HMAC key")
+    }
+
+    fn verifying_key(kid: &str, secret: &[u8]) -> JWTKey {
+        JWTKey::verifying(kid, Algorithm::HS512, secret).expect("Failed to create HMAC key")
+    }
+
+    fn secret(kid: &str, keys: Vec<JWTKey>) -> JWTSecret {
+        JWTSecret::new(hmac_key(kid, Algorithm::HS512, kid.as_bytes()), keys)
+    }
+
+    fn key_pair_secret(algorithm: Algorithm, private_key: &[u8], public_key: &[u8]) -> JWTSecret {
+        let key = JWTKey::signing("key", algorithm, private_key, Some(public_key))
+            .ok()
+            .expect("Failed to create key");
+
+        JWTSecret::new(key, vec![])
+    }
+
+    fn assert_rejects_forgeries(secret: &JWTSecret, other_algorithm: &str) {
+        let token = secret.encode(&claims(1)).expect("Failed to encode claims");
+        let parts: Vec<&str> = token.split('.').collect();
+
+        let result = secret.decode::<TestClaims>(&token, &Validation::default());
+
+        assert!(result.is_ok(), "Failed to decode own token");
+
+        let header = encode(format!(r#"{{"alg":"{}","kid":"key"}}"#, other_algorithm).as_bytes());
+        let other_algorithm_token = format!("{}.{}.{}", header, parts[1], parts[2]);
+
+        match secret.decode::<TestClaims>(&other_algorithm_token, &Validation::default()) {
+            Err(Error::JWTError(JWTErrorKind::InvalidAlgorithm)) => (),
+            _ => panic!("Decoded token claiming to use {}", other_algorithm),
+        }
+
+        let header = encode(br#"{"alg":"none","kid":"key"}"#);
+        let unsigned_token = format!("{}.{}.", header, parts[1]);
+
+        let result = secret.decode::<TestClaims>(&unsigned_token, &Validation::default());
+
+        assert!(!result.is_ok(), "Decoded unsigned token");
+
+        let truncated_token = &token[..token.len() - 4];
+
+        let result = secret.decode::<TestClaims>(truncated_token, &Validation::default());
+
+        assert!(!result.is_ok(), "Decoded token with truncated signature");
+    }
+
+    fn claims(days: i64) -> TestClaims {
+        let issued_at = Utc::now();
+
+        TestClaims {
+            sub: "user".to_owned(),
+            iat: issued_at.timestamp(),
+            exp: (issued_at + Duration::days(days)).timestamp(),
+        }
+    }
+
+    fn header(token: &str) -> Header {
+        let part = token.split('.').next().expect("Token has no header");
+
+        serde_json::from_slice(&decode(part).expect("Failed to decode header"))
+            .expect("Failed to parse header")
+    }
+
+    #[test]
+    fn encode_sets_kid_and_algorithm() {
+        let token = secret("key", vec![]).encode(&claims(1)).expect(
+            "Failed to encode claims",
+        );
+
+        let header = header(&token);
+
+        assert_eq!(header.kid, Some("key".to_owned()), "Header has the wrong kid");
+        assert_eq!(header.alg, "HS512", "Header has the wrong algorithm");
+    }
+
+    #[test]
+    fn decode_accepts_own_tokens() {
+        let secret = secret("key", vec![]);
+        let token = secret.encode(&claims(1)).expect("Failed to encode claims");
+
+        let result = secret.decode::<TestClaims>(&token, &Validation::default());
+
+        assert!(result.is_ok(), "Failed to decode own token");
+    }
+
+    #[test]
+    fn decode_accepts_tokens_from_rotated_out_keys() {
+        let token = secret("old", vec![]).encode(&claims(1)).expect(
+            "Failed to encode claims",
+        );
+
+        let new_secret = secret("new", vec![verifying_key("old", b"old")]);
+        let result = new_secret.decode::<TestClaims>(&token, &Validation::default());
+
+        assert!(result.is_ok(), "Failed to decode with rotated out key");
+    }
+
+    #[test]
+    fn decode_rejects_unknown_kid() {
+        let token = secret("unknown", vec![]).encode(&claims(1)).expect(
+            "Failed to encode claims",
+        );
+
+        let result = secret("key", vec![]).decode::<TestClaims>(&token, &Validation::default());
+
+        assert!(!result.is_ok(), "Decoded token with unknown kid");
+    }
+
+    #[test]
+    fn decode_rejects_algorithm_other_than_the_keys() {
+        let other_secret = JWTSecret::new(hmac_key("key", Algorithm::HS256, b"key"), vec![]);
+        let token = other_secret.encode(&claims(1)).expect("Failed to encode claims");
+
+        let result = secret("key", vec![]).decode::<TestClaims>(&token, &Validation::default());
+
+        match result {
+            Err(Error::JWTError(JWTErrorKind::InvalidAlgorithm)) => (),
+            _ => panic!("Decoded token signed with a different algorithm"),
+        }
+    }
+
+    #[test]
+    fn decode_rejects_unsigned_tokens() {
+        let header = encode(br#"{"alg":"none","kid":"key"}"#);
+        let claims = encode(&serde_json::to_vec(&claims(1)).expect("Failed to encode claims"));
+        let token = format!("{}.{}.", header, claims);
+
+        let result = secret("key", vec![]).decode::<TestClaims>(&token, &Validation::default());
+
+        assert!(!result.is_ok(), "Decoded unsigned token");
+    }
+
+    #[test]
+    fn decode_rejects_expired_tokens() {
+        let secret = secret("key", vec![]);
+        let token = secret.encode(&claims(-1)).expect("Failed to encode claims");
+
+        let result = secret.decode::<TestClaims>(&token, &Validation::default());
+
+        assert!(!result.is_ok(), "Decoded expired token");
+    }
+
+    #[test]
+    fn jwks_skips_hmac_keys() {
+        let secret = secret("new", vec![verifying_key("old", b"old")]);
+
+        assert!(secret.jwks().keys().is_empty(), "Published HMAC secrets");
+    }
+
+    #[test]
+    fn decode_checks_audience() {
+        #[derive(Serialize, Deserialize)]
+        struct AudienceClaims {
+            aud: String,
+            exp: i64,
+        }
+
+        let secret = secret("key", vec![]);
+        let claims = AudienceClaims {
+            aud: "billing".to_owned(),
+            exp: (Utc::now() + Duration::days(1)).timestamp(),
+        };
+        let token = secret.encode(&claims).expect("Failed to encode claims");
+
+        let validation = |aud: &str| {
+            Validation {
+                aud: Some(aud.to_owned()),
+                ..Default::default()
+            }
+        };
+
+        let result = secret.decode::<AudienceClaims>(&token, &validation("billing"));
+
+        assert!(result.is_ok(), "Rejected token for expected audience");
+
+        let result = secret.decode::<AudienceClaims>(&token, &validation("support"));
+
+        assert!(!result.is_ok(), "Decoded token for another audience");
+    }
+
+    #[test]
+    fn decode_rejects_forged_rsa_tokens() {
+        let secret = key_pair_secret(
+            Algorithm::RS256,
+            include_bytes!("../../test_keys/rsa_private_1.der"),
+            include_bytes!("../../test_keys/rsa_public_1.der"),
+        );
+
+        assert_rejects_forgeries(&secret, "HS256");
+    }
+
+    #[test]
+    fn decode_rejects_forged_ecdsa_tokens() {
+        let secret = key_pair_secret(
+            Algorithm::ES256,
+            include_bytes!("../../test_keys/ec_private_1.der"),
+            include_bytes!("../../test_keys/ec_public_1.der"),
+        );
+
+        assert_rejects_forgeries(&secret, "ES384");
+    }
+
+    #[test]
+    fn decode_rejects_forged_ed25519_tokens() {
+        let secret = key_pair_secret(
+            Algorithm::EdDSA,
+            include_bytes!("../../test_keys/ed25519_private_1.der"),
+            include_bytes!("../../test_keys/ed25519_public_1.der"),
+        );
+
+        assert_rejects_forgeries(&secret, "HS256");
+    }
+
+    #[test]
+    fn decode_rejects_forged_hmac_tokens() {
+        let secret = JWTSecret::new(hmac_key("key", Algorithm::HS256, b"secret"), vec![]);
 
-        Ok(token_data.claims)
+        assert_rejects_forgeries(&secret, "HS512");
     }
 }
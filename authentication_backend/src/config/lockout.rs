@@ -0,0 +1,125 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::cmp;
+use std::collections::HashMap;
+use chrono::Duration;
+use error::ConfigErrorKind;
+use super::settings::{bounded, duration};
+
+const LOCKOUT_THRESHOLD: &str = "lockout_threshold";
+const LOCKOUT_IP_THRESHOLD: &str = "lockout_ip_threshold";
+const LOCKOUT_DURATION: &str = "lockout_duration";
+const LOGIN_BACKOFF: &str = "login_backoff";
+const LOGIN_MAX_BACKOFF: &str = "login_max_backoff";
+
+pub const SETTINGS: &[&str] = &[
+    LOCKOUT_THRESHOLD,
+    LOCKOUT_IP_THRESHOLD,
+    LOCKOUT_DURATION,
+    LOGIN_BACKOFF,
+    LOGIN_MAX_BACKOFF,
+];
+
+const DEFAULT_LOCKOUT_THRESHOLD: u32 = 10;
+const DEFAULT_LOCKOUT_IP_THRESHOLD: u32 = 100;
+const MAX_DOUBLINGS: i32 = 30;
+
+pub struct Lockout {
+    threshold: u32,
+    ip_threshold: u32,
+    duration: Duration,
+    backoff: Duration,
+    max_backoff: Duration,
+}
+
+impl Lockout {
+    pub fn new(
+        threshold: u32,
+        ip_threshold: u32,
+        duration: Duration,
+        backoff: Duration,
+        max_backoff: Duration,
+    ) -> Self {
+        Lockout {
+            threshold: threshold,
+            ip_threshold: ip_threshold,
+            duration: duration,
+            backoff: backoff,
+            max_backoff: max_backoff,
+        }
+    }
+
+    pub fn from_settings(
+        values: &HashMap<String, String>,
+        errors: &mut Vec<ConfigErrorKind>,
+    ) -> Self {
+        Lockout::new(
+            bounded(values, LOCKOUT_THRESHOLD, 0, 10000, DEFAULT_LOCKOUT_THRESHOLD, errors),
+            bounded(values, LOCKOUT_IP_THRESHOLD, 0, 1000000, DEFAULT_LOCKOUT_IP_THRESHOLD, errors),
+            duration(values, LOCKOUT_DURATION, Duration::minutes(15), errors),
+            duration(values, LOGIN_BACKOFF, Duration::seconds(1), errors),
+            duration(values, LOGIN_MAX_BACKOFF, Duration::minutes(1), errors),
+        )
+    }
+
+    pub fn threshold(&self) -> u32 {
+        self.threshold
+    }
+
+    pub fn ip_threshold(&self) -> u32 {
+        self.ip_threshold
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    pub fn backoff(&self, failures: i32) -> Duration {
+        if failures < 1 {
+            return Duration::zero();
+        }
+
+        let doublings = cmp::min(failures - 1, MAX_DOUBLINGS);
+        let millis = self.backoff.num_milliseconds().saturating_mul(1 << doublings);
+
+        cmp::min(Duration::milliseconds(millis), self.max_backoff)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_up_to_max() {
+        let lockout = Lockout::new(
+            5,
+            50,
+            Duration::minutes(15),
+            Duration::seconds(1),
+            Duration::seconds(6),
+        );
+
+        assert_eq!(lockout.backoff(0), Duration::zero(), "Delayed first attempt");
+        assert_eq!(lockout.backoff(1), Duration::seconds(1), "Wrong first back-off");
+        assert_eq!(lockout.backoff(3), Duration::seconds(4), "Back-off didn't double");
+        assert_eq!(lockout.backoff(40), Duration::seconds(6), "Back-off passed max");
+    }
+}
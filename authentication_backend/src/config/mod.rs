@@ -17,62 +17,117 @@
  * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
  */
 
-use bcrypt::DEFAULT_COST;
-use diesel::pg::PgConnection;
-use r2d2_diesel::ConnectionManager;
-use error::Result;
-use dotenv::dotenv;
-use self::db::DB;
+use self::breach_corpus::BreachCorpus;
+use self::clients::Clients;
 use self::jwt_secret::JWTSecret;
-use self::password_regex::PasswordRegex;
-use self::connection_pool::ConnectionPool;
+use self::lockout::Lockout;
+use self::password_hasher::PasswordHasher;
+use self::password_policy::PasswordPolicy;
+use self::rate_limits::RateLimits;
+use self::relying_party::RelyingParty;
+use self::secret_box::SecretBox;
+use self::token_lifetimes::TokenLifetimes;
 
-pub mod db;
-mod jwt_secret;
-mod password_regex;
-mod connection_pool;
+pub use self::builder::{ConfigBuilder, DATABASE_URL};
+pub use self::clients::Clients;
+pub use self::algorithm::Algorithm;
+pub use self::breach_corpus::BreachFilter;
+pub use self::jwk::{Jwk, JwkSet};
+pub use self::rate_limits::RateLimit;
 
-type ManagedConnection = ConnectionManager<PgConnection>;
+pub mod jwt_secret;
+mod algorithm;
+pub mod breach_corpus;
+mod clients;
+mod jwk;
+mod jwt_key;
+pub mod lockout;
+pub mod password_hasher;
+pub mod password_policy;
+pub mod pepper;
+pub mod rate_limits;
+pub mod relying_party;
+pub mod secret_box;
+mod settings;
+pub mod token_lifetimes;
+mod totp;
+mod builder;
 
 pub struct Config {
+    database_url: Option<String>,
+    audiences: Vec<String>,
     jwt_secret: JWTSecret,
-    db_pool: ConnectionPool,
-    password_regex: PasswordRegex,
-    bcrypt_cost: u32,
+    password_policy: PasswordPolicy,
+    breach_corpus: Option<BreachCorpus>,
+    password_hasher: PasswordHasher,
+    clients: Clients,
+    max_token_permissions: usize,
+    token_lifetimes: TokenLifetimes,
+    lockout: Lockout,
+    rate_limits: RateLimits,
+    totp_secret_box: Option<SecretBox>,
+    totp_issuer: String,
+    relying_party: Option<RelyingParty>,
 }
 
 impl Config {
-    pub fn initialize() -> Self {
-        Config {
-            jwt_secret: JWTSecret::initialize(),
-            db_pool: ConnectionPool::initialize(),
-            password_regex: PasswordRegex::initialize(),
-            bcrypt_cost: bcrypt_cost(),
-        }
+    pub fn builder() -> ConfigBuilder {
+        ConfigBuilder::new()
     }
 
-    pub fn db(&self) -> Result<DB> {
-        Ok(DB(self.db_pool.get()?))
+    pub fn database_url(&self) -> Option<&str> {
+        self.database_url.as_ref().map(|url| url.as_str())
+    }
+
+    pub fn audiences(&self) -> &[String] {
+        &self.audiences
     }
 
     pub fn jwt_secret(&self) -> &JWTSecret {
         &self.jwt_secret
     }
 
-    pub fn password_regex(&self) -> &PasswordRegex {
-        &self.password_regex
+    pub fn password_policy(&self) -> &PasswordPolicy {
+        &self.password_policy
     }
 
-    pub fn bcrypt_cost(&self) -> u32 {
-        self.bcrypt_cost
+    pub fn breach_corpus(&self) -> Option<&BreachCorpus> {
+        self.breach_corpus.as_ref()
+    }
+
+    pub fn password_hasher(&self) -> &PasswordHasher {
+        &self.password_hasher
+    }
+
+    pub fn clients(&self) -> &Clients {
+        &self.clients
+    }
+
+    pub fn max_token_permissions(&self) -> usize {
+        self.max_token_permissions
     }
-}
 
-fn bcrypt_cost() -> u32 {
-    dotenv().ok();
+    pub fn token_lifetimes(&self) -> &TokenLifetimes {
+        &self.token_lifetimes
+    }
+
+    pub fn lockout(&self) -> &Lockout {
+        &self.lockout
+    }
+
+    pub fn rate_limits(&self) -> &RateLimits {
+        &self.rate_limits
+    }
+
+    pub fn totp_secret_box(&self) -> Option<&SecretBox> {
+        self.totp_secret_box.as_ref()
+    }
+
+    pub fn totp_issuer(&self) -> &str {
+        &self.totp_issuer
+    }
 
-    match dotenv!("BCRYPT_COST").parse::<u32>() {
-        Ok(u) => u,
-        Err(_) => DEFAULT_COST,
+    pub fn relying_party(&self) -> Option<&RelyingParty> {
+        self.relying_party.as_ref()
     }
 }
@@ -0,0 +1,542 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::collections::HashMap;
+use argon2::{self, Variant, Version};
+use base64::{self, CharacterSet, LineWrap};
+use bcrypt::{self, DEFAULT_COST};
+use ring::constant_time;
+use scrypt::{self, ScryptParams};
+use error::{ConfigErrorKind, Error, Result};
+use super::pepper::Peppers;
+use super::settings::bounded;
+
+const PASSWORD_HASHER: &str = "password_hasher";
+const ARGON2_MEMORY_COST: &str = "argon2_memory_cost";
+const ARGON2_TIME_COST: &str = "argon2_time_cost";
+const ARGON2_PARALLELISM: &str = "argon2_parallelism";
+const SCRYPT_LOG_N: &str = "scrypt_log_n";
+const BCRYPT_COST: &str = "bcrypt_cost";
+
+pub const SETTINGS: &[&str] = &[
+    PASSWORD_HASHER,
+    ARGON2_MEMORY_COST,
+    ARGON2_TIME_COST,
+    ARGON2_PARALLELISM,
+    SCRYPT_LOG_N,
+    BCRYPT_COST,
+];
+
+const DEFAULT_HASH_ALGORITHM: HashAlgorithm = HashAlgorithm::Argon2id;
+const DEFAULT_ARGON2_MEMORY_COST: u32 = 19456;
+const DEFAULT_ARGON2_TIME_COST: u32 = 2;
+const DEFAULT_ARGON2_PARALLELISM: u32 = 1;
+const DEFAULT_SCRYPT_LOG_N: u32 = 15;
+
+const SALT_LEN: usize = 16;
+const HASH_LEN: usize = 32;
+const SCRYPT_R: u32 = 8;
+const SCRYPT_P: u32 = 1;
+const BCRYPT_PREFIXES: &[&str] = &["$2a$", "$2b$", "$2x$", "$2y$"];
+const PEPPER_PREFIX: &str = "$pepper$";
+const DUMMY_PASSWORD: &str = "dummy password";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HashAlgorithm {
+    Argon2id,
+    Scrypt,
+    Bcrypt,
+}
+
+impl HashAlgorithm {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "argon2id" => Some(HashAlgorithm::Argon2id),
+            "scrypt" => Some(HashAlgorithm::Scrypt),
+            "bcrypt" => Some(HashAlgorithm::Bcrypt),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            HashAlgorithm::Argon2id => "argon2id",
+            HashAlgorithm::Scrypt => "scrypt",
+            HashAlgorithm::Bcrypt => "bcrypt",
+        }
+    }
+
+    fn of_hash(hash: &str) -> Option<Self> {
+        if hash.starts_with("$argon2") {
+            Some(HashAlgorithm::Argon2id)
+        } else if hash.starts_with("$scrypt$") {
+            Some(HashAlgorithm::Scrypt)
+        } else if BCRYPT_PREFIXES.iter().any(|prefix| hash.starts_with(prefix)) {
+            Some(HashAlgorithm::Bcrypt)
+        } else {
+            None
+        }
+    }
+}
+
+pub struct PasswordHasher {
+    algorithm: HashAlgorithm,
+    argon2_memory_cost: u32,
+    argon2_time_cost: u32,
+    argon2_parallelism: u32,
+    scrypt_log_n: u8,
+    bcrypt_cost: u32,
+    peppers: Peppers,
+    dummy_hash: String,
+}
+
+impl PasswordHasher {
+    pub fn new(
+        algorithm: HashAlgorithm,
+        argon2_memory_cost: u32,
+        argon2_time_cost: u32,
+        argon2_parallelism: u32,
+        scrypt_log_n: u8,
+        bcrypt_cost: u32,
+        peppers: Peppers,
+    ) -> Result<Self> {
+        let mut hasher = PasswordHasher {
+            algorithm: algorithm,
+            argon2_memory_cost: argon2_memory_cost,
+            argon2_time_cost: argon2_time_cost,
+            argon2_parallelism: argon2_parallelism,
+            scrypt_log_n: scrypt_log_n,
+            bcrypt_cost: bcrypt_cost,
+            peppers: peppers,
+            dummy_hash: String::new(),
+        };
+
+        hasher.dummy_hash = hasher.hash(DUMMY_PASSWORD)?;
+
+        Ok(hasher)
+    }
+
+    pub fn from_settings(
+        values: &HashMap<String, String>,
+        errors: &mut Vec<ConfigErrorKind>,
+    ) -> Option<Self> {
+        let algorithm = match values.get(PASSWORD_HASHER) {
+            Some(name) => {
+                match HashAlgorithm::from_name(name) {
+                    Some(algorithm) => algorithm,
+                    None => {
+                        errors.push(ConfigErrorKind::Malformed(
+                            PASSWORD_HASHER.to_owned(),
+                            "must be argon2id, scrypt or bcrypt".to_owned(),
+                        ));
+                        DEFAULT_HASH_ALGORITHM
+                    }
+                }
+            }
+            None => DEFAULT_HASH_ALGORITHM,
+        };
+
+        let hasher = PasswordHasher::new(
+            algorithm,
+            bounded(values, ARGON2_MEMORY_COST, 8, 4194304, DEFAULT_ARGON2_MEMORY_COST, errors),
+            bounded(values, ARGON2_TIME_COST, 1, 100, DEFAULT_ARGON2_TIME_COST, errors),
+            bounded(values, ARGON2_PARALLELISM, 1, 16, DEFAULT_ARGON2_PARALLELISM, errors),
+            bounded(values, SCRYPT_LOG_N, 10, 24, DEFAULT_SCRYPT_LOG_N, errors) as u8,
+            bounded(values, BCRYPT_COST, 4, 31, DEFAULT_COST, errors),
+            Peppers::from_settings(values, errors),
+        );
+
+        match hasher {
+            Ok(hasher) => Some(hasher),
+            Err(_) => {
+                errors.push(ConfigErrorKind::Malformed(
+                    PASSWORD_HASHER.to_owned(),
+                    format!(
+                        "could not hash a password with the {} settings given",
+                        algorithm.name()
+                    ),
+                ));
+                None
+            }
+        }
+    }
+
+    pub fn algorithm(&self) -> HashAlgorithm {
+        self.algorithm
+    }
+
+    pub fn hash(&self, password: &str) -> Result<String> {
+        match self.peppers.current() {
+            Some(pepper) => {
+                let hash = self.hash_with_algorithm(&pepper.apply(password))?;
+
+                Ok(format!("{}{}{}", PEPPER_PREFIX, pepper.id(), hash))
+            }
+            None => self.hash_with_algorithm(password),
+        }
+    }
+
+    pub fn verify(&self, password: &str, hash: &str) -> Result<bool> {
+        let (pepper_id, hash) = split_pepper(hash);
+
+        match pepper_id {
+            Some(pepper_id) => {
+                match self.peppers.find(pepper_id) {
+                    Some(pepper) => verify_with_algorithm(&pepper.apply(password), hash),
+                    None => Err(Error::PasswordHashError),
+                }
+            }
+            None => verify_with_algorithm(password, hash),
+        }
+    }
+
+    // Takes as long as checking a real password, for users that don't exist
+    pub fn verify_dummy(&self, password: &str) {
+        let _ = self.verify(password, &self.dummy_hash);
+    }
+
+    pub fn needs_rehash(&self, hash: &str) -> bool {
+        let (pepper_id, hash) = split_pepper(hash);
+
+        pepper_id != self.peppers.current().map(|pepper| pepper.id()) || self.is_outdated(hash)
+    }
+
+    fn hash_with_algorithm(&self, password: &str) -> Result<String> {
+        match self.algorithm {
+            HashAlgorithm::Argon2id => {
+                let config = argon2::Config {
+                    variant: Variant::Argon2id,
+                    version: Version::Version13,
+                    mem_cost: self.argon2_memory_cost,
+                    time_cost: self.argon2_time_cost,
+                    lanes: self.argon2_parallelism,
+                    hash_length: HASH_LEN as u32,
+                    ..argon2::Config::default()
+                };
+
+                argon2::hash_encoded(password.as_bytes(), &salt()?, &config)
+                    .map_err(|_| Error::PasswordHashError)
+            }
+            HashAlgorithm::Scrypt => {
+                let salt = salt()?;
+                let hash = scrypt_hash(
+                    password,
+                    &salt,
+                    self.scrypt_log_n,
+                    SCRYPT_R,
+                    SCRYPT_P,
+                    HASH_LEN,
+                )?;
+
+                Ok(format!(
+                    "{}{}${}",
+                    self.prefix(),
+                    base64::encode_config(&salt, phc_base64()),
+                    base64::encode_config(&hash, phc_base64())
+                ))
+            }
+            HashAlgorithm::Bcrypt => Ok(bcrypt::hash(password, self.bcrypt_cost)?),
+        }
+    }
+
+    fn is_outdated(&self, hash: &str) -> bool {
+        match self.algorithm {
+            HashAlgorithm::Bcrypt => {
+                let cost: String = hash.chars().skip(4).take(2).collect();
+
+                HashAlgorithm::of_hash(hash) != Some(HashAlgorithm::Bcrypt) ||
+                    cost.parse::<u32>().ok() != Some(self.bcrypt_cost)
+            }
+            _ => !hash.starts_with(&self.prefix()),
+        }
+    }
+
+    fn prefix(&self) -> String {
+        match self.algorithm {
+            HashAlgorithm::Argon2id => {
+                format!(
+                    "$argon2id$v=19$m={},t={},p={}$",
+                    self.argon2_memory_cost,
+                    self.argon2_time_cost,
+                    self.argon2_parallelism
+                )
+            }
+            HashAlgorithm::Scrypt => {
+                format!("$scrypt$ln={},r={},p={}$", self.scrypt_log_n, SCRYPT_R, SCRYPT_P)
+            }
+            HashAlgorithm::Bcrypt => format!("$2y${:02}$", self.bcrypt_cost),
+        }
+    }
+}
+
+fn verify_with_algorithm(password: &str, hash: &str) -> Result<bool> {
+    match HashAlgorithm::of_hash(hash) {
+        Some(HashAlgorithm::Argon2id) => {
+            argon2::verify_encoded(hash, password.as_bytes()).map_err(|_| Error::PasswordHashError)
+        }
+        Some(HashAlgorithm::Scrypt) => verify_scrypt(password, hash),
+        Some(HashAlgorithm::Bcrypt) => Ok(bcrypt::verify(password, hash)?),
+        None => Err(Error::PasswordHashError),
+    }
+}
+
+fn split_pepper(hash: &str) -> (Option<&str>, &str) {
+    if !hash.starts_with(PEPPER_PREFIX) {
+        return (None, hash);
+    }
+
+    let rest = &hash[PEPPER_PREFIX.len()..];
+
+    match rest.find('$') {
+        Some(end) => (Some(&rest[..end]), &rest[end..]),
+        None => (None, hash),
+    }
+}
+
+fn verify_scrypt(password: &str, hash: &str) -> Result<bool> {
+    let parts: Vec<&str> = hash.split('$').collect();
+
+    if parts.len() != 5 {
+        return Err(Error::PasswordHashError);
+    }
+
+    let (mut log_n, mut r, mut p) = (None, None, None);
+
+    for param in parts[2].split(',') {
+        let mut pair = param.splitn(2, '=');
+
+        match (pair.next(), pair.next()) {
+            (Some("ln"), Some(value)) => log_n = value.parse::<u8>().ok(),
+            (Some("r"), Some(value)) => r = value.parse::<u32>().ok(),
+            (Some("p"), Some(value)) => p = value.parse::<u32>().ok(),
+            _ => return Err(Error::PasswordHashError),
+        }
+    }
+
+    let (log_n, r, p) = match (log_n, r, p) {
+        (Some(log_n), Some(r), Some(p)) => (log_n, r, p),
+        _ => return Err(Error::PasswordHashError),
+    };
+
+    let salt = base64::decode_config(parts[3], phc_base64())
+        .map_err(|_| Error::PasswordHashError)?;
+    let expected = base64::decode_config(parts[4], phc_base64())
+        .map_err(|_| Error::PasswordHashError)?;
+
+    let actual = scrypt_hash(password, &salt, log_n, r, p, expected.len())?;
+
+    Ok(constant_time::verify_slices_are_equal(&expected, &actual).is_ok())
+}
+
+fn scrypt_hash(
+    password: &str,
+    salt: &[u8],
+    log_n: u8,
+    r: u32,
+    p: u32,
+    len: usize,
+) -> Result<Vec<u8>> {
+    let params = ScryptParams::new(log_n, r, p).map_err(|_| Error::PasswordHashError)?;
+    let mut output = vec![0; len];
+
+    scrypt::scrypt(password.as_bytes(), salt, &params, &mut output)
+        .map_err(|_| Error::PasswordHashError)?;
+
+    Ok(output)
+}
+
+fn salt() -> Result<Vec<u8>> {
+    use rand::Rng;
+    use rand::OsRng;
+
+    let mut salt = vec![0; SALT_LEN];
+    OsRng::new()?.fill_bytes(&mut salt);
+
+    Ok(salt)
+}
+
+fn phc_base64() -> base64::Config {
+    base64::Config::new(CharacterSet::Standard, false, false, LineWrap::NoWrap)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use config::pepper::Pepper;
+
+    const PASSWORD: &str = "Passw0rd$.";
+
+    fn hasher(algorithm: HashAlgorithm) -> PasswordHasher {
+        PasswordHasher::new(algorithm, 256, 1, 1, 4, 4, Peppers::default())
+            .expect("Failed to create PasswordHasher")
+    }
+
+    fn peppered(current: &str, previous: &[&str]) -> PasswordHasher {
+        let peppers = Peppers::new(
+            Pepper::new(current, current.repeat(32).as_bytes()),
+            previous
+                .iter()
+                .map(|id| Pepper::new(id, id.repeat(32).as_bytes()).unwrap())
+                .collect(),
+        );
+
+        PasswordHasher::new(HashAlgorithm::Argon2id, 256, 1, 1, 4, 4, peppers)
+            .expect("Failed to create PasswordHasher")
+    }
+
+    fn algorithms() -> Vec<HashAlgorithm> {
+        vec![
+            HashAlgorithm::Argon2id,
+            HashAlgorithm::Scrypt,
+            HashAlgorithm::Bcrypt,
+        ]
+    }
+
+    #[test]
+    fn from_name_reads_every_name() {
+        for algorithm in algorithms() {
+            assert_eq!(
+                HashAlgorithm::from_name(algorithm.name()),
+                Some(algorithm),
+                "Failed to read algorithm name"
+            );
+        }
+    }
+
+    #[test]
+    fn hash_round_trips_with_every_algorithm() {
+        for algorithm in algorithms() {
+            let hasher = hasher(algorithm);
+            let hash = hasher.hash(PASSWORD).expect("Failed to hash password");
+
+            assert!(
+                hasher.verify(PASSWORD, &hash).unwrap(),
+                "Rejected correct password with {}",
+                algorithm.name()
+            );
+            assert!(
+                !hasher.verify("Wr0ng-password", &hash).unwrap(),
+                "Accepted wrong password with {}",
+                algorithm.name()
+            );
+            assert!(
+                !hasher.needs_rehash(&hash),
+                "Fresh {} hash needs rehash",
+                algorithm.name()
+            );
+        }
+    }
+
+    #[test]
+    fn verify_uses_algorithm_of_stored_hash() {
+        let hash = hasher(HashAlgorithm::Bcrypt).hash(PASSWORD).unwrap();
+        let hasher = hasher(HashAlgorithm::Argon2id);
+
+        assert!(hasher.verify(PASSWORD, &hash).unwrap(), "Rejected bcrypt hash");
+        assert!(hasher.needs_rehash(&hash), "Outdated algorithm doesn't need rehash");
+    }
+
+    #[test]
+    fn needs_rehash_detects_changed_parameters() {
+        let hash = hasher(HashAlgorithm::Argon2id).hash(PASSWORD).unwrap();
+        let stronger = PasswordHasher::new(
+            HashAlgorithm::Argon2id,
+            512,
+            1,
+            1,
+            4,
+            4,
+            Peppers::default(),
+        ).expect("Failed to create PasswordHasher");
+
+        assert!(stronger.needs_rehash(&hash), "Changed memory cost doesn't need rehash");
+
+        let hash = hasher(HashAlgorithm::Bcrypt).hash(PASSWORD).unwrap();
+        let stronger = PasswordHasher::new(
+            HashAlgorithm::Bcrypt,
+            256,
+            1,
+            1,
+            4,
+            5,
+            Peppers::default(),
+        ).expect("Failed to create PasswordHasher");
+
+        assert!(stronger.needs_rehash(&hash), "Changed bcrypt cost doesn't need rehash");
+    }
+
+    #[test]
+    fn verify_fails_with_unknown_format() {
+        let result = hasher(HashAlgorithm::Argon2id).verify(PASSWORD, PASSWORD);
+
+        assert!(!result.is_ok(), "Verified against unknown hash format");
+    }
+
+    #[test]
+    fn peppered_hash_needs_pepper() {
+        let hash = peppered("1", &[]).hash(PASSWORD).unwrap();
+
+        assert!(hash.starts_with("$pepper$1$argon2id$"), "Hash doesn't record pepper");
+        assert!(
+            peppered("1", &[]).verify(PASSWORD, &hash).unwrap(),
+            "Rejected peppered hash"
+        );
+        assert!(
+            !hasher(HashAlgorithm::Argon2id).verify(PASSWORD, &hash).is_ok(),
+            "Verified peppered hash without pepper"
+        );
+    }
+
+    #[test]
+    fn rotated_pepper_still_verifies_and_needs_rehash() {
+        let hash = peppered("1", &[]).hash(PASSWORD).unwrap();
+        let rotated = peppered("2", &["1"]);
+
+        assert!(rotated.verify(PASSWORD, &hash).unwrap(), "Rejected hash with previous pepper");
+        assert!(rotated.needs_rehash(&hash), "Previous pepper doesn't need rehash");
+        assert!(
+            !rotated.needs_rehash(&rotated.hash(PASSWORD).unwrap()),
+            "Current pepper needs rehash"
+        );
+    }
+
+    #[test]
+    fn unpeppered_hash_needs_rehash_once_pepper_is_added() {
+        let hash = hasher(HashAlgorithm::Argon2id).hash(PASSWORD).unwrap();
+        let hasher = peppered("1", &[]);
+
+        assert!(hasher.verify(PASSWORD, &hash).unwrap(), "Rejected unpeppered hash");
+        assert!(hasher.needs_rehash(&hash), "Unpeppered hash doesn't need rehash");
+    }
+
+    #[test]
+    fn new_fails_with_parameters_that_cant_hash() {
+        let result = PasswordHasher::new(
+            HashAlgorithm::Argon2id,
+            8,
+            1,
+            16,
+            4,
+            4,
+            Peppers::default(),
+        );
+
+        assert!(result.is_err(), "Created PasswordHasher that can't hash");
+    }
+}
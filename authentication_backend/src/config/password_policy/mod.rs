@@ -0,0 +1,332 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+mod common_passwords;
+mod strength;
+
+use std::cmp;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use error::{ConfigErrorKind, PasswordErrorKind};
+use self::common_passwords::COMMON_PASSWORDS;
+use super::settings::{bounded, flag};
+
+const PASSWORD_MIN_LENGTH: &str = "password_min_length";
+const PASSWORD_MAX_LENGTH: &str = "password_max_length";
+const PASSWORD_REQUIRE_LOWERCASE: &str = "password_require_lowercase";
+const PASSWORD_REQUIRE_UPPERCASE: &str = "password_require_uppercase";
+const PASSWORD_REQUIRE_NUMBER: &str = "password_require_number";
+const PASSWORD_REQUIRE_SYMBOL: &str = "password_require_symbol";
+const PASSWORD_MIN_STRENGTH: &str = "password_min_strength";
+const PASSWORD_DENY_LIST: &str = "password_deny_list";
+
+pub const SETTINGS: &[&str] = &[
+    PASSWORD_MIN_LENGTH,
+    PASSWORD_MAX_LENGTH,
+    PASSWORD_REQUIRE_LOWERCASE,
+    PASSWORD_REQUIRE_UPPERCASE,
+    PASSWORD_REQUIRE_NUMBER,
+    PASSWORD_REQUIRE_SYMBOL,
+    PASSWORD_MIN_STRENGTH,
+    PASSWORD_DENY_LIST,
+];
+
+const DEFAULT_PASSWORD_MIN_LENGTH: u32 = 8;
+const DEFAULT_PASSWORD_MAX_LENGTH: u32 = 128;
+
+#[derive(Clone, Copy, Debug)]
+pub struct CharacterClasses {
+    lowercase: bool,
+    uppercase: bool,
+    number: bool,
+    symbol: bool,
+}
+
+impl CharacterClasses {
+    pub fn new(lowercase: bool, uppercase: bool, number: bool, symbol: bool) -> Self {
+        CharacterClasses {
+            lowercase: lowercase,
+            uppercase: uppercase,
+            number: number,
+            symbol: symbol,
+        }
+    }
+}
+
+pub struct PasswordPolicy {
+    min_length: usize,
+    max_length: usize,
+    required: CharacterClasses,
+    min_strength: u8,
+    denied: HashMap<String, usize>,
+    max_denied_len: usize,
+}
+
+impl PasswordPolicy {
+    pub fn new(
+        min_length: usize,
+        max_length: usize,
+        required: CharacterClasses,
+        min_strength: u8,
+        denied: &[String],
+    ) -> Self {
+        let mut ranked = HashMap::new();
+
+        let all = COMMON_PASSWORDS.iter().map(|password| *password).chain(
+            denied.iter().map(|password| password.as_str()),
+        );
+
+        for (rank, password) in all.enumerate() {
+            ranked.entry(password.to_lowercase()).or_insert(rank + 1);
+        }
+
+        let max_denied_len = ranked.keys().map(|password| password.chars().count()).max();
+
+        PasswordPolicy {
+            min_length: min_length,
+            max_length: max_length,
+            required: required,
+            min_strength: min_strength,
+            denied: ranked,
+            max_denied_len: max_denied_len.unwrap_or(0),
+        }
+    }
+
+    pub fn from_settings(
+        values: &HashMap<String, String>,
+        errors: &mut Vec<ConfigErrorKind>,
+    ) -> Self {
+        let min_length = bounded(
+            values,
+            PASSWORD_MIN_LENGTH,
+            1,
+            1024,
+            DEFAULT_PASSWORD_MIN_LENGTH,
+            errors,
+        );
+        let max_length = bounded(
+            values,
+            PASSWORD_MAX_LENGTH,
+            1,
+            1024,
+            DEFAULT_PASSWORD_MAX_LENGTH,
+            errors,
+        );
+
+        if min_length > max_length {
+            errors.push(ConfigErrorKind::Malformed(
+                PASSWORD_MAX_LENGTH.to_owned(),
+                format!("must be at least {}", PASSWORD_MIN_LENGTH),
+            ));
+        }
+
+        let required = CharacterClasses::new(
+            flag(values, PASSWORD_REQUIRE_LOWERCASE, true, errors),
+            flag(values, PASSWORD_REQUIRE_UPPERCASE, true, errors),
+            flag(values, PASSWORD_REQUIRE_NUMBER, true, errors),
+            flag(values, PASSWORD_REQUIRE_SYMBOL, true, errors),
+        );
+
+        PasswordPolicy::new(
+            min_length as usize,
+            max_length as usize,
+            required,
+            bounded(values, PASSWORD_MIN_STRENGTH, 0, 4, 0, errors) as u8,
+            &deny_list(values, errors),
+        )
+    }
+
+    pub fn check(&self, username: &str, password: &str) -> Vec<PasswordErrorKind> {
+        let mut errors = Vec::new();
+        let length = password.chars().count();
+
+        if length < self.min_length {
+            errors.push(PasswordErrorKind::TooShort(self.min_length));
+        }
+
+        // Long passwords are expensive to hash and to score, so nothing else is checked
+        if length > self.max_length {
+            errors.push(PasswordErrorKind::TooLong(self.max_length));
+            return errors;
+        }
+
+        if self.required.number && !password.chars().any(|c| c.is_numeric()) {
+            errors.push(PasswordErrorKind::NoNumber);
+        }
+
+        if self.required.symbol && !password.chars().any(is_symbol) {
+            errors.push(PasswordErrorKind::NoSymbol);
+        }
+
+        if self.required.uppercase && !password.chars().any(|c| c.is_uppercase()) {
+            errors.push(PasswordErrorKind::NoUppercase);
+        }
+
+        if self.required.lowercase && !password.chars().any(|c| c.is_lowercase()) {
+            errors.push(PasswordErrorKind::NoLowercase);
+        }
+
+        let lower_password = password.to_lowercase();
+
+        if self.denied.contains_key(&lower_password) {
+            errors.push(PasswordErrorKind::Common);
+        }
+
+        if is_similar(&username.to_lowercase(), &lower_password) {
+            errors.push(PasswordErrorKind::SimilarToUsername);
+        }
+
+        if self.min_strength > 0 && self.strength(username, password) < self.min_strength {
+            errors.push(PasswordErrorKind::TooWeak);
+        }
+
+        errors
+    }
+
+    fn strength(&self, username: &str, password: &str) -> u8 {
+        let username = username.to_lowercase();
+        let max_word_len = cmp::max(self.max_denied_len, username.chars().count());
+
+        strength::score(password, &self.denied, &username, max_word_len)
+    }
+}
+
+fn deny_list(values: &HashMap<String, String>, errors: &mut Vec<ConfigErrorKind>) -> Vec<String> {
+    let path = match values.get(PASSWORD_DENY_LIST) {
+        Some(path) => path,
+        None => return Vec::new(),
+    };
+
+    let mut contents = String::new();
+
+    if File::open(path)
+        .and_then(|mut f| f.read_to_string(&mut contents))
+        .is_err()
+    {
+        errors.push(ConfigErrorKind::UnreadableFile(path.to_owned()));
+        return Vec::new();
+    }
+
+    contents
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .map(|line| line.to_owned())
+        .collect()
+}
+
+fn is_symbol(c: char) -> bool {
+    !c.is_alphanumeric() && !c.is_whitespace()
+}
+
+fn is_similar(username: &str, password: &str) -> bool {
+    if username.chars().count() < 3 {
+        return false;
+    }
+
+    let reversed: String = username.chars().rev().collect();
+
+    password.contains(username) || password.contains(&reversed) || username.contains(password)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(required: CharacterClasses, min_strength: u8) -> PasswordPolicy {
+        PasswordPolicy::new(8, 64, required, min_strength, &["hunter22".to_owned()])
+    }
+
+    fn no_classes() -> CharacterClasses {
+        CharacterClasses::new(false, false, false, false)
+    }
+
+    fn all_classes() -> CharacterClasses {
+        CharacterClasses::new(true, true, true, true)
+    }
+
+    #[test]
+    fn check_reports_every_broken_rule() {
+        let errors = policy(all_classes(), 0).check("username", "short");
+
+        assert_eq!(errors.len(), 4, "Did not report every broken rule");
+    }
+
+    #[test]
+    fn check_accepts_non_ascii_symbols() {
+        let errors = policy(all_classes(), 0).check("username", "Pässwörd1€");
+
+        assert!(errors.is_empty(), "Rejected non-ASCII symbol");
+    }
+
+    #[test]
+    fn check_accepts_passphrase_without_classes() {
+        let errors = policy(no_classes(), 3).check("username", "correct horse battery staple");
+
+        assert!(errors.is_empty(), "Rejected long passphrase");
+    }
+
+    #[test]
+    fn check_rejects_too_long_password() {
+        let password: String = ::std::iter::repeat('x').take(65).collect();
+
+        match policy(no_classes(), 0).check("username", &password).as_slice() {
+            &[PasswordErrorKind::TooLong(64)] => (),
+            _ => panic!("Accepted too long password"),
+        }
+    }
+
+    #[test]
+    fn check_rejects_common_and_denied_passwords() {
+        let errors = policy(no_classes(), 0).check("username", "Password1");
+
+        assert!(
+            errors.iter().any(|err| *err == PasswordErrorKind::Common),
+            "Accepted common password"
+        );
+
+        let errors = policy(no_classes(), 0).check("username", "HUNTER22");
+
+        assert!(
+            errors.iter().any(|err| *err == PasswordErrorKind::Common),
+            "Accepted denied password"
+        );
+    }
+
+    #[test]
+    fn check_rejects_password_containing_username() {
+        let errors = policy(no_classes(), 0).check("Alice", "my-ecila-password");
+
+        assert!(
+            errors.iter().any(|err| *err == PasswordErrorKind::SimilarToUsername),
+            "Accepted password containing reversed username"
+        );
+    }
+
+    #[test]
+    fn check_rejects_weak_password() {
+        let errors = policy(no_classes(), 3).check("username", "dragon123");
+
+        assert!(
+            errors.iter().any(|err| *err == PasswordErrorKind::TooWeak),
+            "Accepted weak password"
+        );
+    }
+}
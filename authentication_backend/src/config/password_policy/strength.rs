@@ -0,0 +1,195 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::collections::HashMap;
+
+const BRUTEFORCE_CARDINALITY: f64 = 10.0;
+const MIN_PATTERN_LEN: usize = 3;
+
+pub fn score(
+    password: &str,
+    words: &HashMap<String, usize>,
+    username: &str,
+    max_word_len: usize,
+) -> u8 {
+    let words = Words {
+        ranked: words,
+        username: username,
+        max_len: max_word_len,
+    };
+    let guesses = log10_guesses(password, &words);
+
+    if guesses < 3.0 {
+        0
+    } else if guesses < 6.0 {
+        1
+    } else if guesses < 8.0 {
+        2
+    } else if guesses < 10.0 {
+        3
+    } else {
+        4
+    }
+}
+
+struct Words<'a> {
+    ranked: &'a HashMap<String, usize>,
+    username: &'a str,
+    max_len: usize,
+}
+
+impl<'a> Words<'a> {
+    fn rank(&self, word: &str) -> Option<usize> {
+        if word == self.username {
+            Some(1)
+        } else {
+            self.ranked.get(word).cloned()
+        }
+    }
+}
+
+fn log10_guesses(password: &str, words: &Words) -> f64 {
+    let chars: Vec<char> = password.chars().collect();
+    let len = chars.len();
+
+    let mut cheapest = vec![0.0; len + 1];
+
+    for end in 1..len + 1 {
+        cheapest[end] = cheapest[end - 1] + BRUTEFORCE_CARDINALITY.log10();
+
+        for start in 0..end {
+            if end - start < MIN_PATTERN_LEN {
+                break;
+            }
+
+            let guesses = match pattern_guesses(&chars[start..end], words) {
+                Some(guesses) => guesses,
+                None => continue,
+            };
+
+            let total = cheapest[start] + guesses.log10();
+
+            if total < cheapest[end] {
+                cheapest[end] = total;
+            }
+        }
+    }
+
+    cheapest[len]
+}
+
+fn pattern_guesses(chars: &[char], words: &Words) -> Option<f64> {
+    let len = chars.len() as f64;
+
+    if chars.iter().all(|c| *c == chars[0]) {
+        return Some(BRUTEFORCE_CARDINALITY * len);
+    }
+
+    if is_sequence(chars) {
+        return Some(4.0 * len);
+    }
+
+    if chars.len() > words.max_len {
+        return None;
+    }
+
+    let lower: String = chars.iter().flat_map(|c| c.to_lowercase()).collect();
+
+    let capitalized = if chars.iter().any(|c| c.is_uppercase()) {
+        2.0
+    } else {
+        1.0
+    };
+
+    if let Some(rank) = words.rank(&lower) {
+        return Some(rank as f64 * capitalized);
+    }
+
+    let unleet: String = lower.chars().map(unleet).collect();
+
+    words.rank(&unleet).map(
+        |rank| rank as f64 * capitalized * 2.0,
+    )
+}
+
+fn is_sequence(chars: &[char]) -> bool {
+    let delta = chars[1] as i64 - chars[0] as i64;
+
+    if delta == 0 || delta.abs() > 2 {
+        return false;
+    }
+
+    chars.windows(2).all(
+        |pair| pair[1] as i64 - pair[0] as i64 == delta,
+    )
+}
+
+fn unleet(c: char) -> char {
+    match c {
+        '0' => 'o',
+        '1' | '!' => 'i',
+        '3' => 'e',
+        '4' | '@' => 'a',
+        '5' | '$' => 's',
+        '7' => 't',
+        _ => c,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words() -> HashMap<String, usize> {
+        let mut words = HashMap::new();
+
+        words.insert("password".to_owned(), 2);
+        words.insert("dragon".to_owned(), 10);
+
+        words
+    }
+
+    #[test]
+    fn score_rates_common_words_low() {
+        assert_eq!(score("password", &words(), "", 8), 0, "Rated 'password' as strong");
+        assert_eq!(score("P4ssw0rd", &words(), "", 8), 0, "Rated l33t 'password' as strong");
+    }
+
+    #[test]
+    fn score_rates_patterns_low() {
+        assert!(score("aaaaaaaaaaaa", &words(), "", 8) < 2, "Rated repeated letter as strong");
+        assert!(score("abcdefghijkl", &words(), "", 8) < 2, "Rated sequence as strong");
+    }
+
+    #[test]
+    fn score_rates_long_random_passwords_high() {
+        assert_eq!(score("vT9#qLmz2!Rx", &words(), "", 8), 4, "Rated random password as weak");
+        assert_eq!(
+            score("correct horse battery staple", &words(), "", 8),
+            4,
+            "Rated long passphrase as weak"
+        );
+    }
+
+    #[test]
+    fn score_rates_username_as_most_common_word() {
+        assert_eq!(score("Zaphod", &words(), "zaphod", 8), 0, "Rated username as strong");
+        assert!(score("Zaphod", &words(), "", 8) > 0, "Rated unknown word as weak");
+    }
+}
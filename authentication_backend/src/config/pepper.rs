@@ -0,0 +1,205 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use base64;
+use ring::{digest, hmac};
+use error::ConfigErrorKind;
+use super::settings::read_key;
+
+const PASSWORD_PEPPER: &str = "password_pepper";
+const PASSWORD_PEPPER_ID: &str = "password_pepper_id";
+const PASSWORD_OLD_PEPPERS: &str = "password_old_peppers";
+
+pub const SETTINGS: &[&str] = &[PASSWORD_PEPPER, PASSWORD_PEPPER_ID, PASSWORD_OLD_PEPPERS];
+
+const DEFAULT_PEPPER_ID: &str = "1";
+pub const MIN_PEPPER_LEN: usize = 32;
+
+pub struct Pepper {
+    id: String,
+    key: hmac::SigningKey,
+}
+
+impl Pepper {
+    pub fn new(id: &str, key: &[u8]) -> Option<Self> {
+        if id.is_empty() || id.contains('$') || key.len() < MIN_PEPPER_LEN {
+            return None;
+        }
+
+        Some(Pepper {
+            id: id.to_owned(),
+            key: hmac::SigningKey::new(&digest::SHA256, key),
+        })
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    // The HMAC is base64 encoded, since bcrypt stops reading at the first NUL byte
+    pub fn apply(&self, password: &str) -> String {
+        base64::encode(hmac::sign(&self.key, password.as_bytes()).as_ref())
+    }
+}
+
+#[derive(Default)]
+pub struct Peppers {
+    current: Option<Pepper>,
+    previous: Vec<Pepper>,
+}
+
+impl Peppers {
+    pub fn new(current: Option<Pepper>, previous: Vec<Pepper>) -> Self {
+        Peppers {
+            current: current,
+            previous: previous,
+        }
+    }
+
+    pub fn from_settings(
+        values: &HashMap<String, String>,
+        errors: &mut Vec<ConfigErrorKind>,
+    ) -> Self {
+        let current_id = match values.get(PASSWORD_PEPPER_ID) {
+            Some(id) => id.trim(),
+            None => DEFAULT_PEPPER_ID,
+        };
+
+        let current = if values.contains_key(PASSWORD_PEPPER) {
+            read_key(values, PASSWORD_PEPPER, errors).and_then(|key| {
+                let pepper = Pepper::new(current_id, &key);
+
+                if pepper.is_none() {
+                    errors.push(ConfigErrorKind::Malformed(
+                        PASSWORD_PEPPER.to_owned(),
+                        pepper_format(),
+                    ));
+                }
+
+                pepper
+            })
+        } else {
+            None
+        };
+
+        let entries = match values.get(PASSWORD_OLD_PEPPERS) {
+            Some(entries) => entries,
+            None => return Peppers::new(current, Vec::new()),
+        };
+
+        let mut previous: Vec<Pepper> = Vec::new();
+
+        for entry in entries.split(',').map(|entry| entry.trim()) {
+            if entry.is_empty() {
+                continue;
+            }
+
+            let mut parts = entry.splitn(2, ':');
+
+            let (id, path) = match (parts.next(), parts.next()) {
+                (Some(id), Some(path)) if !id.is_empty() && !path.is_empty() => (id, path),
+                _ => {
+                    errors.push(ConfigErrorKind::Malformed(
+                        PASSWORD_OLD_PEPPERS.to_owned(),
+                        "must be a list of id:path entries".to_owned(),
+                    ));
+                    continue;
+                }
+            };
+
+            if id == current_id || previous.iter().any(|pepper| pepper.id() == id) {
+                errors.push(ConfigErrorKind::Malformed(
+                    PASSWORD_OLD_PEPPERS.to_owned(),
+                    format!("uses the pepper id '{}' more than once", id),
+                ));
+                continue;
+            }
+
+            let mut contents: Vec<u8> = Vec::new();
+
+            if File::open(path)
+                .and_then(|mut f| f.read_to_end(&mut contents))
+                .is_err()
+            {
+                errors.push(ConfigErrorKind::UnreadableFile(path.to_owned()));
+                continue;
+            }
+
+            match Pepper::new(id, &contents) {
+                Some(pepper) => previous.push(pepper),
+                None => {
+                    errors.push(ConfigErrorKind::Malformed(
+                        PASSWORD_OLD_PEPPERS.to_owned(),
+                        pepper_format(),
+                    ))
+                }
+            }
+        }
+
+        Peppers::new(current, previous)
+    }
+
+    pub fn current(&self) -> Option<&Pepper> {
+        self.current.as_ref()
+    }
+
+    pub fn find(&self, id: &str) -> Option<&Pepper> {
+        self.current
+            .iter()
+            .chain(self.previous.iter())
+            .find(|pepper| pepper.id() == id)
+    }
+}
+
+fn pepper_format() -> String {
+    format!(
+        "must be a file holding at least {} bytes, with an id that doesn't contain '$'",
+        MIN_PEPPER_LEN
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_rejects_short_key() {
+        assert!(Pepper::new("1", &[0; 16]).is_none(), "Accepted short pepper");
+    }
+
+    #[test]
+    fn new_rejects_id_with_dollar_sign() {
+        assert!(Pepper::new("$1", &[0; 32]).is_none(), "Accepted id containing '$'");
+    }
+
+    #[test]
+    fn find_finds_previous_peppers() {
+        let peppers = Peppers::new(
+            Pepper::new("2", &[2; 32]),
+            vec![Pepper::new("1", &[1; 32]).unwrap()],
+        );
+
+        assert!(peppers.find("1").is_some(), "Failed to find previous pepper");
+        assert!(peppers.find("2").is_some(), "Failed to find current pepper");
+        assert!(peppers.find("3").is_none(), "Found unknown pepper");
+    }
+}
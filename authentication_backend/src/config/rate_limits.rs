@@ -0,0 +1,200 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::collections::HashMap;
+use chrono::Duration;
+use error::ConfigErrorKind;
+use super::settings::parse_duration;
+
+const RATE_LIMIT_LOG_IN: &str = "rate_limit_log_in";
+const RATE_LIMIT_SIGN_UP: &str = "rate_limit_sign_up";
+const RATE_LIMIT_RENEW_TOKEN: &str = "rate_limit_renew_token";
+const RATE_LIMIT_VERIFY: &str = "rate_limit_verify";
+
+pub const SETTINGS: &[&str] = &[
+    RATE_LIMIT_LOG_IN,
+    RATE_LIMIT_SIGN_UP,
+    RATE_LIMIT_RENEW_TOKEN,
+    RATE_LIMIT_VERIFY,
+];
+
+const DEFAULT_RATE_LIMIT_REQUESTS: u32 = 10;
+const DEFAULT_RENEW_TOKEN_RATE_LIMIT_REQUESTS: u32 = 30;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RateLimit {
+    name: &'static str,
+    requests: u32,
+    period: Duration,
+}
+
+impl RateLimit {
+    pub fn new(name: &'static str, requests: u32, period: Duration) -> Self {
+        RateLimit {
+            name: name,
+            requests: requests,
+            period: period,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn requests(&self) -> u32 {
+        self.requests
+    }
+
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.requests > 0
+    }
+}
+
+pub struct RateLimits {
+    log_in: RateLimit,
+    sign_up: RateLimit,
+    renew_token: RateLimit,
+    verify: RateLimit,
+}
+
+impl RateLimits {
+    pub fn new(
+        log_in: RateLimit,
+        sign_up: RateLimit,
+        renew_token: RateLimit,
+        verify: RateLimit,
+    ) -> Self {
+        RateLimits {
+            log_in: log_in,
+            sign_up: sign_up,
+            renew_token: renew_token,
+            verify: verify,
+        }
+    }
+
+    pub fn from_settings(
+        values: &HashMap<String, String>,
+        errors: &mut Vec<ConfigErrorKind>,
+    ) -> Self {
+        let minute = Duration::minutes(1);
+        let hour = Duration::hours(1);
+
+        RateLimits::new(
+            rate_limit(
+                values,
+                RATE_LIMIT_LOG_IN,
+                RateLimit::new("log_in", DEFAULT_RATE_LIMIT_REQUESTS, minute),
+                errors,
+            ),
+            rate_limit(
+                values,
+                RATE_LIMIT_SIGN_UP,
+                RateLimit::new("sign_up", DEFAULT_RATE_LIMIT_REQUESTS, hour),
+                errors,
+            ),
+            rate_limit(
+                values,
+                RATE_LIMIT_RENEW_TOKEN,
+                RateLimit::new("renew_token", DEFAULT_RENEW_TOKEN_RATE_LIMIT_REQUESTS, minute),
+                errors,
+            ),
+            rate_limit(
+                values,
+                RATE_LIMIT_VERIFY,
+                RateLimit::new("verify", DEFAULT_RATE_LIMIT_REQUESTS, minute),
+                errors,
+            ),
+        )
+    }
+
+    pub fn log_in(&self) -> &RateLimit {
+        &self.log_in
+    }
+
+    pub fn sign_up(&self) -> &RateLimit {
+        &self.sign_up
+    }
+
+    pub fn renew_token(&self) -> &RateLimit {
+        &self.renew_token
+    }
+
+    pub fn verify(&self) -> &RateLimit {
+        &self.verify
+    }
+}
+
+fn rate_limit(
+    values: &HashMap<String, String>,
+    setting: &str,
+    default: RateLimit,
+    errors: &mut Vec<ConfigErrorKind>,
+) -> RateLimit {
+    let value = match values.get(setting) {
+        Some(value) => value,
+        None => return default,
+    };
+
+    let mut parts = value.splitn(2, '/');
+    let requests = parts.next().and_then(|requests| requests.trim().parse::<u32>().ok());
+    let period = parts.next().and_then(parse_duration);
+
+    match (requests, period) {
+        (Some(requests), Some(period)) if period > Duration::zero() => {
+            RateLimit::new(default.name(), requests, period)
+        }
+        _ => {
+            errors.push(ConfigErrorKind::Malformed(
+                setting.to_owned(),
+                "must be a number of requests and a period, like 10/1m".to_owned(),
+            ));
+            default
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rate_limit_reads_requests_per_period() {
+        let default = RateLimit::new("log_in", 10, Duration::minutes(1));
+        let mut values = HashMap::new();
+        values.insert(RATE_LIMIT_LOG_IN.to_owned(), "5/1h".to_owned());
+        values.insert(RATE_LIMIT_VERIFY.to_owned(), "5/0".to_owned());
+
+        let mut errors = Vec::new();
+
+        assert_eq!(
+            rate_limit(&values, RATE_LIMIT_LOG_IN, default, &mut errors),
+            RateLimit::new("log_in", 5, Duration::hours(1)),
+            "Failed to read rate limit"
+        );
+        assert!(errors.is_empty(), "Reported valid rate limit");
+
+        let _ = rate_limit(&values, RATE_LIMIT_VERIFY, default, &mut errors);
+
+        assert_eq!(errors.len(), 1, "Did not report empty period");
+    }
+}
@@ -0,0 +1,190 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::collections::HashMap;
+use ring::digest;
+use error::ConfigErrorKind;
+
+const WEBAUTHN_ORIGIN: &str = "webauthn_origin";
+const WEBAUTHN_RP_ID: &str = "webauthn_rp_id";
+const WEBAUTHN_RP_NAME: &str = "webauthn_rp_name";
+
+pub const SETTINGS: &[&str] = &[WEBAUTHN_ORIGIN, WEBAUTHN_RP_ID, WEBAUTHN_RP_NAME];
+
+const DEFAULT_WEBAUTHN_RP_NAME: &str = "Authentication";
+
+#[derive(Clone, Debug)]
+pub struct RelyingParty {
+    id: String,
+    name: String,
+    origin: String,
+}
+
+impl RelyingParty {
+    pub fn new(origin: &str, id: Option<&str>, name: &str) -> Option<Self> {
+        let origin = origin.trim_right_matches('/');
+
+        let host = match host(origin) {
+            Some(host) => host,
+            None => return None,
+        };
+
+        let id = match id {
+            Some(id) => id,
+            None => host,
+        };
+
+        if !(host == id || host.ends_with(&format!(".{}", id))) {
+            return None;
+        }
+
+        Some(RelyingParty {
+            id: id.to_owned(),
+            name: name.to_owned(),
+            origin: origin.to_owned(),
+        })
+    }
+
+    pub fn from_settings(
+        values: &HashMap<String, String>,
+        errors: &mut Vec<ConfigErrorKind>,
+    ) -> Option<Self> {
+        let origin = match values.get(WEBAUTHN_ORIGIN) {
+            Some(origin) => origin,
+            None => {
+                if values.contains_key(WEBAUTHN_RP_ID) {
+                    errors.push(ConfigErrorKind::Malformed(
+                        WEBAUTHN_RP_ID.to_owned(),
+                        format!("requires {} to be set", WEBAUTHN_ORIGIN),
+                    ));
+                }
+                return None;
+            }
+        };
+
+        let name = match values.get(WEBAUTHN_RP_NAME).map(|name| name.trim()) {
+            Some(name) if !name.is_empty() => name,
+            Some(_) => {
+                errors.push(ConfigErrorKind::Malformed(
+                    WEBAUTHN_RP_NAME.to_owned(),
+                    "must not be empty".to_owned(),
+                ));
+                return None;
+            }
+            None => DEFAULT_WEBAUTHN_RP_NAME,
+        };
+
+        let id = values.get(WEBAUTHN_RP_ID).map(|id| id.as_str());
+
+        let relying_party = RelyingParty::new(origin, id, name);
+
+        if relying_party.is_none() {
+            errors.push(ConfigErrorKind::Malformed(
+                WEBAUTHN_ORIGIN.to_owned(),
+                format!(
+                    "must be an https origin whose host is {} or one of its subdomains",
+                    WEBAUTHN_RP_ID
+                ),
+            ));
+        }
+
+        relying_party
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn origin(&self) -> &str {
+        &self.origin
+    }
+
+    pub fn id_hash(&self) -> Vec<u8> {
+        digest::digest(&digest::SHA256, self.id.as_bytes()).as_ref().to_vec()
+    }
+}
+
+// WebAuthn only runs in secure contexts, so plain http is only allowed for localhost
+fn host(origin: &str) -> Option<&str> {
+    let (secure, rest) = if origin.starts_with("https://") {
+        (true, &origin["https://".len()..])
+    } else if origin.starts_with("http://") {
+        (false, &origin["http://".len()..])
+    } else {
+        return None;
+    };
+
+    if rest.contains('/') {
+        return None;
+    }
+
+    let host = match rest.rfind(':') {
+        Some(index) => &rest[..index],
+        None => rest,
+    };
+
+    if host.is_empty() || !(secure || host == "localhost") {
+        None
+    } else {
+        Some(host)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_uses_host_as_id() {
+        let relying_party = RelyingParty::new("https://login.example.com:8443/", None, "Example")
+            .expect("Rejected origin");
+
+        assert_eq!(relying_party.id(), "login.example.com", "Wrong relying party id");
+        assert_eq!(
+            relying_party.origin(),
+            "https://login.example.com:8443",
+            "Origin kept trailing slash"
+        );
+    }
+
+    #[test]
+    fn new_accepts_parent_domain_as_id() {
+        let relying_party = RelyingParty::new("https://login.example.com", Some("example.com"), "");
+
+        assert!(relying_party.is_some(), "Rejected parent domain");
+    }
+
+    #[test]
+    fn new_rejects_unrelated_id() {
+        let relying_party = RelyingParty::new("https://login.example.com", Some("ample.com"), "");
+
+        assert!(relying_party.is_none(), "Accepted id for another domain");
+    }
+
+    #[test]
+    fn new_rejects_insecure_origin() {
+        let relying_party = RelyingParty::new("http://example.com", None, "");
+
+        assert!(relying_party.is_none(), "Accepted plain http origin");
+    }
+}
@@ -0,0 +1,127 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use ring::aead::{self, AES_256_GCM, OpeningKey, SealingKey};
+use ring::rand::{SecureRandom, SystemRandom};
+use error::{Error, Result};
+
+pub const KEY_LEN: usize = 32;
+
+// Encrypts secrets that have to be read back, like TOTP seeds, with AES-256-GCM. Each sealed
+// value starts with its own random nonce. The associated data ties a value to the row it was
+// sealed for, so it can't be copied to another one.
+pub struct SecretBox {
+    sealing_key: SealingKey,
+    opening_key: OpeningKey,
+}
+
+impl SecretBox {
+    pub fn new(key: &[u8]) -> Option<Self> {
+        match (
+            SealingKey::new(&AES_256_GCM, key),
+            OpeningKey::new(&AES_256_GCM, key),
+        ) {
+            (Ok(sealing_key), Ok(opening_key)) => Some(SecretBox {
+                sealing_key: sealing_key,
+                opening_key: opening_key,
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn seal(&self, plaintext: &[u8], associated_data: &[u8]) -> Result<Vec<u8>> {
+        let nonce_len = AES_256_GCM.nonce_len();
+        let tag_len = AES_256_GCM.tag_len();
+
+        let mut sealed = vec![0; nonce_len];
+
+        SystemRandom::new().fill(&mut sealed).map_err(
+            |_| Error::EncryptionError,
+        )?;
+
+        sealed.extend_from_slice(plaintext);
+        sealed.extend(vec![0; tag_len]);
+
+        {
+            let (nonce, in_out) = sealed.split_at_mut(nonce_len);
+
+            aead::seal_in_place(
+                &self.sealing_key,
+                nonce,
+                associated_data,
+                in_out,
+                tag_len,
+            ).map_err(|_| Error::EncryptionError)?;
+        }
+
+        Ok(sealed)
+    }
+
+    pub fn open(&self, sealed: &[u8], associated_data: &[u8]) -> Result<Vec<u8>> {
+        let nonce_len = AES_256_GCM.nonce_len();
+
+        if sealed.len() < nonce_len {
+            return Err(Error::EncryptionError);
+        }
+
+        let (nonce, ciphertext) = sealed.split_at(nonce_len);
+        let mut in_out = ciphertext.to_vec();
+
+        let plaintext =
+            aead::open_in_place(&self.opening_key, nonce, associated_data, 0, &mut in_out)
+                .map_err(|_| Error::EncryptionError)?;
+
+        Ok(plaintext.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secret_box() -> SecretBox {
+        SecretBox::new(&[7; KEY_LEN]).expect("Failed to create SecretBox")
+    }
+
+    #[test]
+    fn open_opens_sealed_secret() {
+        let sealed = secret_box().seal(b"secret", b"1").expect("Failed to seal secret");
+
+        assert!(&sealed[..] != b"secret", "Secret was not encrypted");
+
+        let opened = secret_box().open(&sealed, b"1");
+
+        assert_eq!(opened.ok(), Some(b"secret".to_vec()), "Failed to open sealed secret");
+    }
+
+    #[test]
+    fn open_fails_with_other_associated_data() {
+        let sealed = secret_box().seal(b"secret", b"1").expect("Failed to seal secret");
+
+        assert!(
+            secret_box().open(&sealed, b"2").is_err(),
+            "Opened secret sealed for another row"
+        );
+    }
+
+    #[test]
+    fn new_fails_with_short_key() {
+        assert!(SecretBox::new(&[7; 16]).is_none(), "Accepted a 128 bit key");
+    }
+}
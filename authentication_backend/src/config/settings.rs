@@ -0,0 +1,172 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use chrono::Duration;
+use error::ConfigErrorKind;
+
+pub fn required(
+    values: &HashMap<String, String>,
+    setting: &str,
+    errors: &mut Vec<ConfigErrorKind>,
+) -> Option<String> {
+    match values.get(setting) {
+        Some(value) if !value.is_empty() => Some(value.to_owned()),
+        _ => {
+            errors.push(ConfigErrorKind::Missing(setting.to_owned()));
+            None
+        }
+    }
+}
+
+pub fn read_key(
+    values: &HashMap<String, String>,
+    setting: &str,
+    errors: &mut Vec<ConfigErrorKind>,
+) -> Option<Vec<u8>> {
+    let filename = match required(values, setting, errors) {
+        Some(filename) => filename,
+        None => return None,
+    };
+
+    let mut contents: Vec<u8> = Vec::new();
+
+    match File::open(&filename).and_then(|mut f| f.read_to_end(&mut contents)) {
+        Ok(_) => Some(contents),
+        Err(_) => {
+            errors.push(ConfigErrorKind::UnreadableFile(filename));
+            None
+        }
+    }
+}
+
+pub fn flag(
+    values: &HashMap<String, String>,
+    setting: &str,
+    default: bool,
+    errors: &mut Vec<ConfigErrorKind>,
+) -> bool {
+    match values.get(setting).map(|value| value.as_str()) {
+        None => default,
+        Some("true") => true,
+        Some("false") => false,
+        Some(_) => {
+            errors.push(ConfigErrorKind::Malformed(
+                setting.to_owned(),
+                "must be true or false".to_owned(),
+            ));
+            default
+        }
+    }
+}
+
+pub fn bounded(
+    values: &HashMap<String, String>,
+    setting: &str,
+    min: u32,
+    max: u32,
+    default: u32,
+    errors: &mut Vec<ConfigErrorKind>,
+) -> u32 {
+    let value = match values.get(setting) {
+        Some(value) => value,
+        None => return default,
+    };
+
+    match value.parse::<u32>() {
+        Ok(value) if value >= min && value <= max => value,
+        _ => {
+            errors.push(ConfigErrorKind::Malformed(
+                setting.to_owned(),
+                format!("must be a number from {} to {}", min, max),
+            ));
+            default
+        }
+    }
+}
+
+pub fn duration(
+    values: &HashMap<String, String>,
+    setting: &str,
+    default: Duration,
+    errors: &mut Vec<ConfigErrorKind>,
+) -> Duration {
+    let value = match values.get(setting) {
+        Some(value) => value,
+        None => return default,
+    };
+
+    match parse_duration(value) {
+        Some(duration) => duration,
+        None => {
+            errors.push(ConfigErrorKind::Malformed(
+                setting.to_owned(),
+                "must be a number of seconds, or a number followed by s, m, h or d".to_owned(),
+            ));
+            default
+        }
+    }
+}
+
+pub fn parse_duration(value: &str) -> Option<Duration> {
+    let value = value.trim();
+
+    let (number, unit): (&str, fn(i64) -> Duration) = match value.chars().last() {
+        Some('s') => (&value[..value.len() - 1], Duration::seconds),
+        Some('m') => (&value[..value.len() - 1], Duration::minutes),
+        Some('h') => (&value[..value.len() - 1], Duration::hours),
+        Some('d') => (&value[..value.len() - 1], Duration::days),
+        _ => (value, Duration::seconds),
+    };
+
+    number.parse::<u32>().ok().map(|number| unit(number as i64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn duration_reads_units() {
+        let mut values = HashMap::new();
+        values.insert("user_token_ttl".to_owned(), "15m".to_owned());
+        values.insert("renewal_token_ttl".to_owned(), "90".to_owned());
+        values.insert("token_leeway".to_owned(), "-5s".to_owned());
+
+        let mut errors = Vec::new();
+
+        assert_eq!(
+            duration(&values, "user_token_ttl", Duration::zero(), &mut errors),
+            Duration::minutes(15),
+            "Failed to read minutes"
+        );
+        assert_eq!(
+            duration(&values, "renewal_token_ttl", Duration::zero(), &mut errors),
+            Duration::seconds(90),
+            "Failed to read seconds"
+        );
+        assert!(errors.is_empty(), "Reported valid durations");
+
+        let _ = duration(&values, "token_leeway", Duration::zero(), &mut errors);
+
+        assert_eq!(errors.len(), 1, "Did not report negative duration");
+    }
+}
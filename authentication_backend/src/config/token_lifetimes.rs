@@ -0,0 +1,118 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::collections::HashMap;
+use chrono::Duration;
+use error::ConfigErrorKind;
+use super::settings::{duration, flag};
+
+const USER_TOKEN_TTL: &str = "user_token_ttl";
+const RENEWAL_TOKEN_TTL: &str = "renewal_token_ttl";
+const MAX_SESSION_LIFETIME: &str = "max_session_lifetime";
+const TOKEN_LEEWAY: &str = "token_leeway";
+const SLIDING_SESSIONS: &str = "sliding_sessions";
+const MFA_TOKEN_TTL: &str = "mfa_token_ttl";
+const PASSWORD_RESET_TTL: &str = "password_reset_ttl";
+
+pub const SETTINGS: &[&str] = &[
+    USER_TOKEN_TTL,
+    RENEWAL_TOKEN_TTL,
+    MAX_SESSION_LIFETIME,
+    TOKEN_LEEWAY,
+    SLIDING_SESSIONS,
+    MFA_TOKEN_TTL,
+    PASSWORD_RESET_TTL,
+];
+
+pub struct TokenLifetimes {
+    user_token: Duration,
+    renewal_token: Duration,
+    mfa_token: Duration,
+    session: Duration,
+    leeway: Duration,
+    sliding: bool,
+    password_reset: Duration,
+}
+
+impl TokenLifetimes {
+    pub fn new(
+        user_token: Duration,
+        renewal_token: Duration,
+        mfa_token: Duration,
+        session: Duration,
+        leeway: Duration,
+        sliding: bool,
+        password_reset: Duration,
+    ) -> Self {
+        TokenLifetimes {
+            user_token: user_token,
+            renewal_token: renewal_token,
+            mfa_token: mfa_token,
+            session: session,
+            leeway: leeway,
+            sliding: sliding,
+            password_reset: password_reset,
+        }
+    }
+
+    pub fn from_settings(
+        values: &HashMap<String, String>,
+        errors: &mut Vec<ConfigErrorKind>,
+    ) -> Self {
+        let sliding = flag(values, SLIDING_SESSIONS, false, errors);
+
+        TokenLifetimes::new(
+            duration(values, USER_TOKEN_TTL, Duration::days(2), errors),
+            duration(values, RENEWAL_TOKEN_TTL, Duration::days(7), errors),
+            duration(values, MFA_TOKEN_TTL, Duration::minutes(5), errors),
+            duration(values, MAX_SESSION_LIFETIME, Duration::days(30), errors),
+            duration(values, TOKEN_LEEWAY, Duration::seconds(30), errors),
+            sliding,
+            duration(values, PASSWORD_RESET_TTL, Duration::hours(1), errors),
+        )
+    }
+
+    pub fn user_token(&self) -> Duration {
+        self.user_token
+    }
+
+    pub fn renewal_token(&self) -> Duration {
+        self.renewal_token
+    }
+
+    pub fn mfa_token(&self) -> Duration {
+        self.mfa_token
+    }
+
+    pub fn session(&self) -> Duration {
+        self.session
+    }
+
+    pub fn leeway(&self) -> Duration {
+        self.leeway
+    }
+
+    pub fn is_sliding(&self) -> bool {
+        self.sliding
+    }
+
+    pub fn password_reset(&self) -> Duration {
+        self.password_reset
+    }
+}
@@ -0,0 +1,69 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::collections::HashMap;
+use error::ConfigErrorKind;
+use super::secret_box::{self, SecretBox};
+use super::settings::read_key;
+
+const TOTP_ENCRYPTION_KEY: &str = "totp_encryption_key";
+const TOTP_ISSUER: &str = "totp_issuer";
+
+pub const SETTINGS: &[&str] = &[TOTP_ENCRYPTION_KEY, TOTP_ISSUER];
+
+const DEFAULT_TOTP_ISSUER: &str = "Authentication";
+
+pub fn secret_box(
+    values: &HashMap<String, String>,
+    errors: &mut Vec<ConfigErrorKind>,
+) -> Option<SecretBox> {
+    if !values.contains_key(TOTP_ENCRYPTION_KEY) {
+        return None;
+    }
+
+    let key = match read_key(values, TOTP_ENCRYPTION_KEY, errors) {
+        Some(key) => key,
+        None => return None,
+    };
+
+    let secret_box = SecretBox::new(&key);
+
+    if secret_box.is_none() {
+        errors.push(ConfigErrorKind::Malformed(
+            TOTP_ENCRYPTION_KEY.to_owned(),
+            format!("must be a file holding exactly {} bytes", secret_box::KEY_LEN),
+        ));
+    }
+
+    secret_box
+}
+
+pub fn issuer(values: &HashMap<String, String>, errors: &mut Vec<ConfigErrorKind>) -> String {
+    match values.get(TOTP_ISSUER).map(|issuer| issuer.trim()) {
+        Some(issuer) if !issuer.is_empty() => issuer.to_owned(),
+        Some(_) => {
+            errors.push(ConfigErrorKind::Malformed(
+                TOTP_ISSUER.to_owned(),
+                "must not be empty".to_owned(),
+            ));
+            DEFAULT_TOTP_ISSUER.to_owned()
+        }
+        None => DEFAULT_TOTP_ISSUER.to_owned(),
+    }
+}
@@ -21,3 +21,66 @@ pub mod users;
 pub mod webtokens;
 pub mod verification_codes;
 pub mod permissions;
+pub mod totp;
+pub mod webauthn;
+pub mod password_resets;
+
+use authenticatable::{Authenticatable, ToAuth};
+use models::{AuthenticatedThisSession, FailedLogin, TotpCredential, User, UserTrait};
+use service::AuthService;
+use error::{Error, MfaErrorKind, Result};
+
+fn own_session<T>(
+    service: &AuthService,
+    target_user: &str,
+    auth: &T,
+) -> Result<AuthenticatedThisSession>
+where
+    T: ToAuth,
+{
+    let user = User::authenticate_session(service, auth)?;
+
+    if user.username() != target_user {
+        return Err(Error::PermissionError);
+    }
+
+    Ok(user)
+}
+
+// Tokens are only issued once the second factor is given, so only a password needs a code with it
+fn second_factor<T, U>(
+    service: &AuthService,
+    auth: &T,
+    user: &U,
+    code: Option<&str>,
+) -> Result<()>
+where
+    T: ToAuth,
+    U: UserTrait,
+{
+    match auth.to_auth() {
+        Authenticatable::UserAndPass { .. } => (),
+        _ => return Ok(()),
+    }
+
+    if !TotpCredential::is_enabled(service, user)? {
+        return Ok(());
+    }
+
+    let code = match code {
+        Some(code) => code,
+        None => return Err(Error::MfaError(MfaErrorKind::CodeRequired)),
+    };
+
+    match TotpCredential::verify(service, user, code) {
+        Ok(_) => Ok(()),
+        Err(Error::MfaError(MfaErrorKind::InvalidCode)) => {
+            if FailedLogin::record(service, Some(user.username()), Some(user.id()), None)? {
+                return Err(Error::AccountLockedError(user.id()));
+            }
+
+            Err(Error::MfaError(MfaErrorKind::InvalidCode))
+        }
+        Err(err) => Err(err),
+    }
+}
@@ -0,0 +1,85 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use authentication_background::{Message, MsgSender};
+use models::{PasswordReset, User, UserTrait};
+use service::AuthService;
+use error::Result;
+
+// Succeeds whether or not the user exists, so it can't be used to find out who has an account
+pub fn request(service: &AuthService, username: &str, sender: &MsgSender<i32>) -> Result<()> {
+    if let Ok(user) = User::find_by_name(service, username) {
+        match sender.send(Message::new("password_reset", Some(user.id()))) {
+            _ => (),
+        };
+    }
+
+    Ok(())
+}
+
+pub fn confirm(service: &AuthService, token: &str, new_password: &str) -> Result<()> {
+    PasswordReset::confirm(service, token, new_password)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use user_test_helper::with_user;
+    use test_helper::{generate_string, service, with_msg_sender};
+    use super::*;
+
+    #[test]
+    fn request_sends_mail_to_user() {
+        with_user(|user| {
+            with_msg_sender(1, |sender| {
+                let result = request(service(), user.username(), &sender);
+
+                assert!(result.is_ok(), "Failed to request password reset");
+            });
+        });
+    }
+
+    #[test]
+    fn request_succeeds_quietly_for_unknown_user() {
+        with_msg_sender(0, |sender| {
+            let result = request(service(), &generate_string(), &sender);
+
+            assert!(result.is_ok(), "Revealed that user doesn't exist");
+        });
+    }
+
+    #[test]
+    fn confirm_sets_new_password() {
+        with_user(|user| {
+            let token = PasswordReset::create(service(), &user).expect("Failed to create reset");
+
+            let result = confirm(service(), &token, "N3w-Passw0rd");
+
+            assert!(result.is_ok(), "Failed to confirm password reset");
+        });
+    }
+
+    #[test]
+    fn confirm_fails_with_bad_token() {
+        let result = confirm(service(), &generate_string(), "N3w-Passw0rd");
+
+        assert!(!result.is_ok(), "Accepted unknown reset token");
+    }
+}
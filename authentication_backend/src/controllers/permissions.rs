@@ -19,26 +19,27 @@
 
 use authenticatable::ToAuth;
 use models::{Admin, User, Permission};
+use service::AuthService;
 use error::Result;
 
-pub fn create<T>(permission: &str, auth: &T) -> Result<Permission>
+pub fn create<T>(service: &AuthService, permission: &str, auth: &T) -> Result<Permission>
 where
     T: ToAuth,
 {
-    let user = User::authenticate(auth)?;
-    let admin = Admin::from_authenticated(user)?;
+    let user = User::authenticate(service, auth)?;
+    let admin = Admin::from_authenticated(service, user)?;
 
-    admin.create_permission(permission)
+    admin.create_permission(service, permission)
 }
 
-pub fn delete<T>(permission: &str, auth: &T) -> Result<()>
+pub fn delete<T>(service: &AuthService, permission: &str, auth: &T) -> Result<()>
 where
     T: ToAuth,
 {
-    let user = User::authenticate(auth)?;
-    let admin = Admin::from_authenticated(user)?;
+    let user = User::authenticate(service, auth)?;
+    let admin = Admin::from_authenticated(service, user)?;
 
-    admin.delete_permission(permission)?;
+    admin.delete_permission(service, permission)?;
 
     Ok(())
 }
@@ -50,7 +51,7 @@ mod tests {
     use permission_test_helper::{with_permission, teardown_by_name};
     use user_test_helper::{with_admin, with_user};
     use authenticatable::Authenticatable;
-    use test_helper::{generate_string, test_password};
+    use test_helper::{generate_string, service, test_password};
     use models::UserTrait;
 
     #[test]
@@ -62,7 +63,7 @@ mod tests {
                     password: test_password(),
                 };
 
-                let result = create(permission, &auth);
+                let result = create(service(), permission, &auth);
 
                 assert!(result.is_ok(), "Failed to create permission");
             });
@@ -78,7 +79,7 @@ mod tests {
                     password: test_password(),
                 };
 
-                let result = create(permission, &auth);
+                let result = create(service(), permission, &auth);
 
                 assert!(!result.is_ok(), "Failed to create permission");
             });
@@ -94,7 +95,7 @@ mod tests {
                     password: test_password(),
                 };
 
-                let result = delete(permission.name(), &auth);
+                let result = delete(service(), permission.name(), &auth);
 
                 assert!(result.is_ok(), "Failed to delete permission");
             });
@@ -110,7 +111,7 @@ mod tests {
                     password: test_password(),
                 };
 
-                let result = delete(permission.name(), &auth);
+                let result = delete(service(), permission.name(), &auth);
 
                 assert!(!result.is_ok(), "Failed to delete permission");
             });
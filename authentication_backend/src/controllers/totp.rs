@@ -0,0 +1,258 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use authenticatable::ToAuth;
+use models::{RecoveryCodes, TotpCredential, TotpEnrollment, TotpStatus, User, UserTrait};
+use service::AuthService;
+use error::{Error, Result};
+use super::{own_session, second_factor};
+
+pub fn enroll<T>(service: &AuthService, target_user: &str, auth: &T) -> Result<TotpEnrollment>
+where
+    T: ToAuth,
+{
+    let user = own_session(service, target_user, auth)?;
+
+    TotpCredential::enroll(service, &user)
+}
+
+pub fn confirm<T>(service: &AuthService, target_user: &str, code: &str, auth: &T) -> Result<()>
+where
+    T: ToAuth,
+{
+    let user = own_session(service, target_user, auth)?;
+
+    TotpCredential::confirm(service, &user, code)
+}
+
+pub fn disable<T>(
+    service: &AuthService,
+    target_user: &str,
+    code: Option<&str>,
+    auth: &T,
+) -> Result<()>
+where
+    T: ToAuth,
+{
+    let user = own_session(service, target_user, auth)?;
+
+    second_factor(service, auth, &user, code)?;
+
+    TotpCredential::disable(service, &user)
+}
+
+pub fn regenerate_recovery_codes<T>(
+    service: &AuthService,
+    target_user: &str,
+    code: Option<&str>,
+    auth: &T,
+) -> Result<RecoveryCodes>
+where
+    T: ToAuth,
+{
+    let user = own_session(service, target_user, auth)?;
+
+    second_factor(service, auth, &user, code)?;
+
+    TotpCredential::regenerate_recovery_codes(service, &user)
+}
+
+pub fn status<T>(service: &AuthService, target_user: &str, auth: &T) -> Result<TotpStatus>
+where
+    T: ToAuth,
+{
+    let user = User::authenticate(service, auth)?;
+
+    if user.username() != target_user {
+        return Err(Error::PermissionError);
+    }
+
+    TotpCredential::status(service, &user)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use authenticatable::Authenticatable;
+    use totp_credential_test_helper::{current_code, next_code};
+    use user_test_helper::with_user;
+    use test_helper::{service, test_password};
+
+    #[test]
+    fn enroll_and_confirm_enable_two_factor_authentication() {
+        with_user(|user| {
+            let auth = Authenticatable::UserAndPass {
+                username: user.username(),
+                password: test_password(),
+            };
+
+            let enrollment = enroll(service(), user.username(), &auth).expect("Failed to enroll");
+
+            let result = confirm(
+                service(),
+                user.username(),
+                &current_code(enrollment.secret()),
+                &auth,
+            );
+
+            assert!(result.is_ok(), "Failed to confirm enrollment");
+            assert!(
+                TotpCredential::is_enabled(service(), &user).unwrap(),
+                "Two-factor authentication is not enabled"
+            );
+
+            let code = next_code(enrollment.secret());
+            let result = disable(service(), user.username(), Some(&code), &auth);
+
+            assert!(result.is_ok(), "Failed to disable two-factor authentication");
+            assert!(
+                !TotpCredential::is_enabled(service(), &user).unwrap(),
+                "Two-factor authentication is still enabled"
+            );
+        });
+    }
+
+    #[test]
+    fn disable_requires_code_with_password() {
+        with_user(|user| {
+            let auth = Authenticatable::UserAndPass {
+                username: user.username(),
+                password: test_password(),
+            };
+
+            let enrollment = enroll(service(), user.username(), &auth).expect("Failed to enroll");
+            let _ = confirm(
+                service(),
+                user.username(),
+                &current_code(enrollment.secret()),
+                &auth,
+            ).expect("Failed to confirm enrollment");
+
+            let result = disable(service(), user.username(), None, &auth);
+
+            assert!(!result.is_ok(), "Disabled two-factor authentication with only a password");
+
+            let result = disable(service(), user.username(), Some("000000x"), &auth);
+
+            assert!(!result.is_ok(), "Disabled two-factor authentication with a wrong code");
+            assert!(
+                TotpCredential::is_enabled(service(), &user).unwrap(),
+                "Two-factor authentication was disabled"
+            );
+        });
+    }
+
+    #[test]
+    fn enroll_fails_for_other_user() {
+        with_user(|user| {
+            with_user(|user2| {
+                let auth = Authenticatable::UserAndPass {
+                    username: user.username(),
+                    password: test_password(),
+                };
+
+                let result = enroll(service(), user2.username(), &auth);
+
+                assert!(!result.is_ok(), "Enrolled another user");
+            });
+        });
+    }
+
+    #[test]
+    fn regenerate_recovery_codes_restores_count() {
+        with_user(|user| {
+            let auth = Authenticatable::UserAndPass {
+                username: user.username(),
+                password: test_password(),
+            };
+
+            let enrollment = enroll(service(), user.username(), &auth).expect("Failed to enroll");
+            let _ = confirm(
+                service(),
+                user.username(),
+                &current_code(enrollment.secret()),
+                &auth,
+            ).expect("Failed to confirm enrollment");
+            let _ = TotpCredential::verify(service(), &user, &enrollment.recovery_codes()[0])
+                .expect("Failed to use recovery code");
+
+            let remaining = status(service(), user.username(), &auth)
+                .expect("Failed to get status")
+                .recovery_codes_remaining();
+
+            assert_eq!(remaining, enrollment.recovery_codes().len() - 1, "Wrong count");
+
+            let code = next_code(enrollment.secret());
+            let codes = regenerate_recovery_codes(service(), user.username(), Some(&code), &auth)
+                .expect("Failed to regenerate recovery codes");
+
+            let remaining = status(service(), user.username(), &auth)
+                .expect("Failed to get status")
+                .recovery_codes_remaining();
+
+            assert_eq!(remaining, codes.codes().len(), "Wrong count after regenerating");
+        });
+    }
+
+    #[test]
+    fn regenerate_recovery_codes_fails_when_not_enabled() {
+        with_user(|user| {
+            let auth = Authenticatable::UserAndPass {
+                username: user.username(),
+                password: test_password(),
+            };
+
+            let result = regenerate_recovery_codes(service(), user.username(), None, &auth);
+
+            assert!(!result.is_ok(), "Generated recovery codes without two-factor authentication");
+        });
+    }
+
+    #[test]
+    fn regenerate_recovery_codes_requires_code_with_password() {
+        with_user(|user| {
+            let auth = Authenticatable::UserAndPass {
+                username: user.username(),
+                password: test_password(),
+            };
+
+            let enrollment = enroll(service(), user.username(), &auth).expect("Failed to enroll");
+            let _ = confirm(
+                service(),
+                user.username(),
+                &current_code(enrollment.secret()),
+                &auth,
+            ).expect("Failed to confirm enrollment");
+
+            let result = regenerate_recovery_codes(service(), user.username(), None, &auth);
+
+            assert!(!result.is_ok(), "Generated recovery codes with only a password");
+
+            let remaining = status(service(), user.username(), &auth)
+                .expect("Failed to get status")
+                .recovery_codes_remaining();
+
+            assert_eq!(
+                remaining,
+                enrollment.recovery_codes().len(),
+                "Recovery codes were replaced"
+            );
+        });
+    }
+}
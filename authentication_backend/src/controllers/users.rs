@@ -18,50 +18,271 @@
  */
 
 use authentication_background::{Message, MsgSender};
-use authenticatable::ToAuth;
-use webtoken::Webtoken;
-use models::{Admin, Authenticated, User, UserTrait};
-use error::{Result, Error};
-
-pub fn sign_up<T>(auth: &T, sender: &MsgSender<i32>) -> Result<User>
+use authenticatable::{Authenticatable, ToAuth};
+use webtoken::{LogIn, Webtoken};
+use models::{Admin, Authenticated, AuthenticatedThisSession, ClientInfo, SecondFactor, Session,
+             User, UserTrait};
+use service::AuthService;
+use error::{DbError, DbErrorKind, Result, Error};
+
+// Signing up with a taken username looks the same as signing up with a new one. The owner of the
+// username is told about the attempt instead.
+pub fn sign_up<T>(
+    service: &AuthService,
+    auth: &T,
+    email: Option<&str>,
+    sender: &MsgSender<i32>,
+) -> Result<()>
 where
     T: ToAuth,
 {
-    let user = User::create(auth)?;
+    let (message, user_id) = match User::create_with_email(service, auth, email) {
+        Ok(user) => ("mail", user.id()),
+        Err(Error::DbError(DbError::DatabaseError(DbErrorKind::UniqueViolation, _))) => {
+            let user = match auth.to_auth() {
+                Authenticatable::UserAndPass { username, .. } => {
+                    User::find_by_name(service, username)?
+                }
+                _ => return Err(Error::DbError(DbError::NotFound)),
+            };
 
-    match sender.send(Message::new("mail", Some(user.id()))) {
+            ("sign_up_existing", user.id())
+        }
+        Err(err) => return Err(err),
+    };
+
+    match sender.send(Message::new(message, Some(user_id))) {
         _ => (),
     };
 
-    Ok(user)
+    Ok(())
+}
+
+pub fn log_in<T>(
+    service: &AuthService,
+    auth: &T,
+    audience: Option<&str>,
+    client: &ClientInfo,
+) -> Result<LogIn>
+where
+    T: ToAuth,
+{
+    let user = User::authenticate_session_for_client(service, auth, client)?;
+
+    user.log_in(service, audience, client)
+}
+
+pub fn log_in_mfa(
+    service: &AuthService,
+    mfa_token: &str,
+    code: &str,
+    client: &ClientInfo,
+    sender: &MsgSender<i32>,
+) -> Result<Webtoken> {
+    let (webtoken, second_factor) = Webtoken::complete_mfa(service, mfa_token, code, client)?;
+
+    if let SecondFactor::RecoveryCode(user_id) = second_factor {
+        match sender.send(Message::new("recovery_code_used", Some(user_id))) {
+            _ => (),
+        };
+    }
+
+    Ok(webtoken)
+}
+
+pub fn log_out(service: &AuthService, user_token: &str) -> Result<()> {
+    Webtoken::revoke(service, user_token)
+}
+
+pub fn log_out_everywhere<T>(service: &AuthService, code: Option<&str>, auth: &T) -> Result<()>
+where
+    T: ToAuth,
+{
+    let user = User::authenticate(service, auth)?;
+
+    super::second_factor(service, auth, &user, code)?;
+
+    Webtoken::revoke_all(service, &user)
+}
+
+pub fn is_authenticated<T>(
+    service: &AuthService,
+    auth: &T,
+    audience: Option<&str>,
+) -> Result<Authenticated>
+where
+    T: ToAuth,
+{
+    let audience = match audience {
+        Some(audience) => audience,
+        None => return User::authenticate(service, auth),
+    };
+
+    match auth.to_auth() {
+        Authenticatable::UserToken { user_token } => {
+            Authenticated::from_webtoken_for(service, user_token, audience)
+        }
+        Authenticatable::UserTokenAndPass { user_token, password } => {
+            let authenticated = Authenticated::from_webtoken_for(service, user_token, audience)?;
+
+            let authenticated =
+                AuthenticatedThisSession::from_authenticated(service, &authenticated, password)?;
+
+            Ok(authenticated.into())
+        }
+        Authenticatable::UserAndPass { .. } => User::authenticate(service, auth),
+    }
+}
+
+pub fn sessions<T>(service: &AuthService, target_user: &str, auth: &T) -> Result<Vec<Session>>
+where
+    T: ToAuth,
+{
+    let target_user = session_owner(service, target_user, auth)?;
+
+    Session::find_active(service, &target_user)
+}
+
+pub fn revoke_session<T>(
+    service: &AuthService,
+    target_user: &str,
+    session_id: i32,
+    auth: &T,
+) -> Result<()>
+where
+    T: ToAuth,
+{
+    let target_user = session_owner(service, target_user, auth)?;
+
+    let session = Session::find(service, session_id)?;
+
+    // Sessions belonging to someone else are reported as missing rather than forbidden
+    if session.user_id() != target_user.id() {
+        return Err(Error::DbError(DbError::NotFound));
+    }
+
+    session.revoke(service)
+}
+
+fn session_owner<T>(service: &AuthService, target_user: &str, auth: &T) -> Result<User>
+where
+    T: ToAuth,
+{
+    let user = User::authenticate(service, auth)?;
+
+    if user.username() != target_user {
+        Admin::from_authenticated(service, user)?;
+    }
+
+    User::find_by_name(service, target_user)
+}
+
+// Every token the user had is revoked, since whoever knew the old password may be logged in
+// elsewhere
+pub fn change_password<T>(
+    service: &AuthService,
+    target_user: &str,
+    new_password: &str,
+    code: Option<&str>,
+    auth: &T,
+    audience: Option<&str>,
+    client: &ClientInfo,
+) -> Result<LogIn>
+where
+    T: ToAuth,
+{
+    let mut user = changing_user(service, target_user, code, auth, audience)?;
+
+    user.update_password(service, new_password)?;
+
+    Webtoken::revoke_all(service, &user)?;
+
+    user.log_in(service, audience, client)
+}
+
+pub fn change_username<T>(
+    service: &AuthService,
+    target_user: &str,
+    new_username: &str,
+    code: Option<&str>,
+    auth: &T,
+    audience: Option<&str>,
+    client: &ClientInfo,
+) -> Result<LogIn>
+where
+    T: ToAuth,
+{
+    let mut user = changing_user(service, target_user, code, auth, audience)?;
+
+    user.update_username(service, new_username)?;
+
+    Webtoken::revoke_all(service, &user)?;
+
+    user.log_in(service, audience, client)
 }
 
-pub fn log_in<T>(auth: &T) -> Result<Webtoken>
+pub fn change_email<T>(
+    service: &AuthService,
+    target_user: &str,
+    new_email: &str,
+    code: Option<&str>,
+    auth: &T,
+    sender: &MsgSender<i32>,
+) -> Result<()>
 where
     T: ToAuth,
 {
-    let user = User::authenticate_session(auth)?;
+    let user = changing_user(service, target_user, code, auth, None)?;
+
+    user.request_email_change(service, new_email)?;
 
-    user.create_webtoken()
+    match sender.send(Message::new("mail", Some(user.id()))) {
+        _ => (),
+    };
+
+    Ok(())
 }
 
-pub fn is_authenticated<T>(auth: &T) -> Result<Authenticated>
+fn changing_user<T>(
+    service: &AuthService,
+    target_user: &str,
+    code: Option<&str>,
+    auth: &T,
+    audience: Option<&str>,
+) -> Result<AuthenticatedThisSession>
 where
     T: ToAuth,
 {
-    User::authenticate(auth)
+    service.check_audience(audience)?;
+
+    let user = super::own_session(service, target_user, auth)?;
+
+    super::second_factor(service, auth, &user, code)?;
+
+    if !user.is_verified() {
+        return Err(Error::UserNotVerifiedError);
+    }
+
+    Ok(user)
 }
 
-pub fn delete<T>(target_user: &str, auth: &T) -> Result<()>
+pub fn delete<T>(
+    service: &AuthService,
+    target_user: &str,
+    code: Option<&str>,
+    auth: &T,
+) -> Result<()>
 where
     T: ToAuth,
 {
-    let user = User::authenticate_session(auth)?;
+    let user = User::authenticate_session(service, auth)?;
+
+    super::second_factor(service, auth, &user, code)?;
 
     if user.username() == target_user {
-        user.delete()?;
-    } else if let Ok(admin) = Admin::from_authenticated(user) {
-        admin.delete_user(target_user)?;
+        user.delete(service)?;
+    } else if let Ok(admin) = Admin::from_authenticated(service, user) {
+        admin.delete_user(service, target_user)?;
     } else {
         return Err(Error::PermissionError);
     }
@@ -69,42 +290,62 @@ where
     Ok(())
 }
 
-pub fn grant_permission<T>(target_user: &str, permission: &str, auth: &T) -> Result<()>
+pub fn grant_permission<T>(
+    service: &AuthService,
+    target_user: &str,
+    permission: &str,
+    auth: &T,
+) -> Result<()>
 where
     T: ToAuth,
 {
-    let user = User::authenticate(auth)?;
-    let admin = Admin::from_authenticated(user)?;
+    let user = User::authenticate(service, auth)?;
+    let admin = Admin::from_authenticated(service, user)?;
 
-    let target_user = User::find_by_name(target_user)?;
+    let target_user = User::find_by_name(service, target_user)?;
 
-    admin.give_permission(&target_user, permission)?;
+    admin.give_permission(service, &target_user, permission)?;
 
     Ok(())
 }
 
-pub fn revoke_permission<T>(target_user: &str, permission: &str, auth: &T) -> Result<()>
+pub fn revoke_permission<T>(
+    service: &AuthService,
+    target_user: &str,
+    permission: &str,
+    auth: &T,
+) -> Result<()>
 where
     T: ToAuth,
 {
-    let user = User::authenticate(auth)?;
-    let admin = Admin::from_authenticated(user)?;
+    let user = User::authenticate(service, auth)?;
+    let admin = Admin::from_authenticated(service, user)?;
 
-    let target_user = User::find_by_name(target_user)?;
+    let target_user = User::find_by_name(service, target_user)?;
 
-    admin.revoke_permission(&target_user, permission)?;
+    admin.revoke_permission(service, &target_user, permission)?;
 
     Ok(())
 }
 
+pub fn unlock<T>(service: &AuthService, target_user: &str, auth: &T) -> Result<()>
+where
+    T: ToAuth,
+{
+    let user = User::authenticate(service, auth)?;
+    let admin = Admin::from_authenticated(service, user)?;
+
+    admin.unlock_user(service, target_user)
+}
+
 #[cfg(test)]
 mod tests {
-    use authentication_background::Message;
-    use std::sync::mpsc;
     use std::panic;
     use user_test_helper::{teardown_by_name, with_user, with_auth_session, with_admin};
     use authenticatable::Authenticatable;
-    use test_helper::{generate_string, test_password};
+    use models::{FailedLogin, TotpCredential, VerificationCode};
+    use totp_credential_test_helper::{current_code, next_code};
+    use test_helper::{generate_string, service, test_password, with_msg_sender};
     use super::*;
 
     #[test]
@@ -116,13 +357,73 @@ mod tests {
                     password: "Testp4ss$.",
                 };
 
-                let user = sign_up(&auth, &sender);
+                let user = sign_up(service(), &auth, None, &sender);
 
                 assert!(user.is_ok(), "Failed to sign in user");
             });
         });
     }
 
+    #[test]
+    fn sign_up_with_email_sends_code_to_address() {
+        test_wrapper(|username| {
+            with_msg_sender(1, |sender| {
+                let auth = Authenticatable::UserAndPass {
+                    username: username,
+                    password: "Testp4ss$.",
+                };
+                let email = format!("{}@Example.com", username);
+
+                let result = sign_up(service(), &auth, Some(&email), &sender);
+
+                assert!(result.is_ok(), "Failed to sign up user with email");
+            });
+
+            let user = User::find_by_name(service(), username).expect("Failed to find user");
+            let vc = VerificationCode::find_by_user_id(service(), user.id())
+                .expect("Failed to find verification code");
+
+            assert_eq!(user.email(), None, "Kept address before verifying it");
+            assert_eq!(
+                vc.email(),
+                Some(format!("{}@example.com", username.to_lowercase()).as_str()),
+                "Did not tie verification code to normalized address"
+            );
+        });
+    }
+
+    #[test]
+    fn sign_up_with_bad_email_doesnt_sign_up_user() {
+        test_wrapper(|username| {
+            with_msg_sender(0, |sender| {
+                let auth = Authenticatable::UserAndPass {
+                    username: username,
+                    password: "Testp4ss$.",
+                };
+
+                let result = sign_up(service(), &auth, Some("not an address"), &sender);
+
+                assert!(!result.is_ok(), "Signed up user with bad email");
+            });
+        });
+    }
+
+    #[test]
+    fn sign_up_with_taken_username_looks_successful() {
+        with_user(|user| {
+            with_msg_sender(1, |sender| {
+                let auth = Authenticatable::UserAndPass {
+                    username: user.username(),
+                    password: "Testp4ss$.",
+                };
+
+                let result = sign_up(service(), &auth, None, &sender);
+
+                assert!(result.is_ok(), "Revealed that username is taken");
+            });
+        });
+    }
+
     #[test]
     fn sign_up_with_bad_username_doesnt_sign_up_user() {
         with_msg_sender(0, |sender| {
@@ -131,7 +432,7 @@ mod tests {
                 password: "Testp4ss$.",
             };
 
-            let user = sign_up(&auth, &sender);
+            let user = sign_up(service(), &auth, None, &sender);
 
             assert!(!user.is_ok(), "Signed up user with empty username");
         });
@@ -146,7 +447,7 @@ mod tests {
                     password: "This is a bad password",
                 };
 
-                let user = sign_up(&auth, &sender);
+                let user = sign_up(service(), &auth, None, &sender);
 
                 assert!(!user.is_ok(), "Failed to sign in user");
             });
@@ -156,19 +457,193 @@ mod tests {
     #[test]
     fn log_in_logs_in() {
         with_user(|mut user| {
-            assert!(user.verify(), "Failed to verify user");
+            assert!(user.verify(service()), "Failed to verify user");
 
             let auth = Authenticatable::UserAndPass {
                 username: user.username(),
                 password: test_password(),
             };
 
-            let user = log_in(&auth);
+            let user = log_in(service(), &auth, None, &ClientInfo::default());
 
             assert!(user.is_ok(), "Failed to log in user");
         });
     }
 
+    #[test]
+    fn log_in_requires_code_with_two_factor_authentication() {
+        with_user(|mut user| {
+            assert!(user.verify(service()), "Failed to verify user");
+
+            let enrollment = TotpCredential::enroll(service(), &user).expect("Failed to enroll");
+            let _ = TotpCredential::confirm(service(), &user, &current_code(enrollment.secret()))
+                .expect("Failed to confirm enrollment");
+
+            let auth = Authenticatable::UserAndPass {
+                username: user.username(),
+                password: test_password(),
+            };
+
+            let mfa_token = match log_in(service(), &auth, None, &ClientInfo::default()) {
+                Ok(LogIn::MfaRequired(mfa_token)) => mfa_token,
+                _ => panic!("Logged in without two-factor code"),
+            };
+
+            with_msg_sender(0, |sender| {
+                let result = log_in_mfa(
+                    service(),
+                    &mfa_token,
+                    &next_code(enrollment.secret()),
+                    &ClientInfo::default(),
+                    &sender,
+                );
+
+                assert!(result.is_ok(), "Failed to log in with two-factor code");
+            });
+        });
+    }
+
+    #[test]
+    fn log_in_mfa_reports_recovery_code_use() {
+        with_user(|mut user| {
+            assert!(user.verify(service()), "Failed to verify user");
+
+            let enrollment = TotpCredential::enroll(service(), &user).expect("Failed to enroll");
+            let _ = TotpCredential::confirm(service(), &user, &current_code(enrollment.secret()))
+                .expect("Failed to confirm enrollment");
+
+            let auth = Authenticatable::UserAndPass {
+                username: user.username(),
+                password: test_password(),
+            };
+
+            let mfa_token = match log_in(service(), &auth, None, &ClientInfo::default()) {
+                Ok(LogIn::MfaRequired(mfa_token)) => mfa_token,
+                _ => panic!("Logged in without two-factor code"),
+            };
+
+            with_msg_sender(1, |sender| {
+                let result = log_in_mfa(
+                    service(),
+                    &mfa_token,
+                    &enrollment.recovery_codes()[0],
+                    &ClientInfo::default(),
+                    &sender,
+                );
+
+                assert!(result.is_ok(), "Failed to log in with recovery code");
+            });
+        });
+    }
+
+    #[test]
+    fn log_in_mfa_backs_off_after_wrong_code() {
+        with_user(|mut user| {
+            assert!(user.verify(service()), "Failed to verify user");
+
+            let (secret, mfa_token) = start_mfa_log_in(&user);
+
+            with_msg_sender(0, |sender| {
+                let result = log_in_mfa(
+                    service(),
+                    &mfa_token,
+                    "not a code",
+                    &ClientInfo::default(),
+                    &sender,
+                );
+
+                assert!(result.is_err(), "Logged in with wrong two-factor code");
+
+                let result = log_in_mfa(
+                    service(),
+                    &mfa_token,
+                    &next_code(&secret),
+                    &ClientInfo::default(),
+                    &sender,
+                );
+
+                match result {
+                    Err(Error::LoginThrottledError) => (),
+                    _ => panic!("Allowed two-factor code during back-off"),
+                }
+            });
+        });
+    }
+
+    #[test]
+    fn log_in_mfa_rejects_correct_code_after_too_many_wrong_ones() {
+        with_user(|mut user| {
+            assert!(user.verify(service()), "Failed to verify user");
+
+            let (secret, mfa_token) = start_mfa_log_in(&user);
+
+            with_msg_sender(0, |sender| {
+                for _ in 0..3 {
+                    FailedLogin::clear(service(), user.username()).unwrap();
+
+                    let result = log_in_mfa(
+                        service(),
+                        &mfa_token,
+                        "not a code",
+                        &ClientInfo::default(),
+                        &sender,
+                    );
+
+                    assert!(result.is_err(), "Logged in with wrong two-factor code");
+                }
+
+                FailedLogin::clear(service(), user.username()).unwrap();
+
+                let result = log_in_mfa(
+                    service(),
+                    &mfa_token,
+                    &next_code(&secret),
+                    &ClientInfo::default(),
+                    &sender,
+                );
+
+                match result {
+                    Err(Error::TokenRevokedError) => (),
+                    _ => panic!("Mfa token still worked after too many wrong codes"),
+                }
+            });
+        });
+    }
+
+    #[test]
+    fn log_in_mfa_token_only_works_once() {
+        with_user(|mut user| {
+            assert!(user.verify(service()), "Failed to verify user");
+
+            let (secret, mfa_token) = start_mfa_log_in(&user);
+
+            with_msg_sender(0, |sender| {
+                let result = log_in_mfa(
+                    service(),
+                    &mfa_token,
+                    &next_code(&secret),
+                    &ClientInfo::default(),
+                    &sender,
+                );
+
+                assert!(result.is_ok(), "Failed to log in with two-factor code");
+
+                let result = log_in_mfa(
+                    service(),
+                    &mfa_token,
+                    &next_code(&secret),
+                    &ClientInfo::default(),
+                    &sender,
+                );
+
+                match result {
+                    Err(Error::TokenRevokedError) => (),
+                    _ => panic!("Reused mfa token"),
+                }
+            });
+        });
+    }
+
     #[test]
     fn log_in_fails_with_bad_password() {
         with_user(|user| {
@@ -177,12 +652,126 @@ mod tests {
                 password: "This is not the password",
             };
 
-            let user = log_in(&auth);
+            let user = log_in(service(), &auth, None, &ClientInfo::default());
 
             assert!(!user.is_ok(), "Failed to log in user");
         });
     }
 
+    #[test]
+    fn log_in_fails_with_unknown_audience() {
+        with_user(|mut user| {
+            assert!(user.verify(service()), "Failed to verify user");
+
+            let auth = Authenticatable::UserAndPass {
+                username: user.username(),
+                password: test_password(),
+            };
+
+            let user = log_in(
+                service(),
+                &auth,
+                Some("not an audience"),
+                &ClientInfo::default(),
+            );
+
+            assert!(!user.is_ok(), "Logged in for unknown audience");
+        });
+    }
+
+    #[test]
+    fn is_authenticated_rejects_token_for_other_audience() {
+        with_auth_session(|mut auth| {
+            auth.verify(service());
+            let token = auth.create_webtoken(service(), None).expect("Failed to create webtoken");
+
+            let auth = Authenticatable::UserToken { user_token: token.user_token() };
+
+            let result = is_authenticated(service(), &auth, Some("billing"));
+
+            assert!(!result.is_ok(), "Accepted token without the expected audience");
+        });
+    }
+
+    #[test]
+    fn is_authenticated_accepts_token_for_its_audience() {
+        with_auth_session(|mut auth| {
+            auth.verify(service());
+            let token = auth.create_webtoken(service(), Some("billing")).expect(
+                "Failed to create webtoken",
+            );
+
+            let auth = Authenticatable::UserToken { user_token: token.user_token() };
+
+            let result = is_authenticated(service(), &auth, Some("billing"));
+
+            assert!(result.is_ok(), "Rejected token for its own audience");
+        });
+    }
+
+    #[test]
+    fn log_out_revokes_token() {
+        with_auth_session(|mut auth| {
+            auth.verify(service());
+            let token = auth.create_webtoken(service(), None).expect("Failed to create webtoken");
+
+            let result = log_out(service(), token.user_token());
+
+            assert!(result.is_ok(), "Failed to log out");
+
+            let auth = Authenticatable::UserToken { user_token: token.user_token() };
+
+            let result = is_authenticated(service(), &auth, None);
+
+            assert!(!result.is_ok(), "Logged out token is still authenticated");
+        });
+    }
+
+    #[test]
+    fn log_out_fails_with_bad_token() {
+        let result = log_out(service(), "this is not a token");
+
+        assert!(!result.is_ok(), "Logged out with fake token");
+    }
+
+    #[test]
+    fn log_out_leaves_other_tokens_alone() {
+        with_auth_session(|mut auth| {
+            auth.verify(service());
+            let token = auth.create_webtoken(service(), None).expect("Failed to create webtoken");
+            let token_2 = auth.create_webtoken(service(), None).expect("Failed to create webtoken");
+
+            let _ = log_out(service(), token.user_token()).expect("Failed to log out");
+
+            let auth = Authenticatable::UserToken { user_token: token_2.user_token() };
+
+            let result = is_authenticated(service(), &auth, None);
+
+            assert!(result.is_ok(), "Logging out revoked unrelated token");
+        });
+    }
+
+    #[test]
+    fn log_out_everywhere_revokes_all_tokens() {
+        with_auth_session(|mut auth| {
+            auth.verify(service());
+            let token = auth.create_webtoken(service(), None).expect("Failed to create webtoken");
+            let token_2 = auth.create_webtoken(service(), None).expect("Failed to create webtoken");
+
+            let user_auth = Authenticatable::UserToken { user_token: token.user_token() };
+
+            let result = log_out_everywhere(service(), None, &user_auth);
+
+            assert!(result.is_ok(), "Failed to log out everywhere");
+
+            let user_auth = Authenticatable::UserToken { user_token: token_2.user_token() };
+
+            let result = is_authenticated(service(), &user_auth, None);
+
+            assert!(!result.is_ok(), "Token survived logging out everywhere");
+        });
+    }
+
     #[test]
     fn is_authenticated_works() {
         with_user(|user| {
@@ -191,7 +780,7 @@ mod tests {
                 password: test_password(),
             };
 
-            let result = is_authenticated(&auth);
+            let result = is_authenticated(service(), &auth, None);
 
             assert!(result.is_ok(), "Failed to verify authentication");
         });
@@ -204,7 +793,7 @@ mod tests {
             password: "obviously fake",
         };
 
-        let result = is_authenticated(&auth);
+        let result = is_authenticated(service(), &auth, None);
 
         assert!(
             !result.is_ok(),
@@ -215,13 +804,13 @@ mod tests {
     #[test]
     fn is_authenticated_works_with_token() {
         with_auth_session(|mut auth| {
-            auth.verify();
+            auth.verify(service());
             let auth = auth;
-            let token = auth.create_webtoken().expect("Failed to create webtoken");
+            let token = auth.create_webtoken(service(), None).expect("Failed to create webtoken");
 
             let auth = Authenticatable::UserToken { user_token: token.user_token() };
 
-            let result = is_authenticated(&auth);
+            let result = is_authenticated(service(), &auth, None);
 
             assert!(result.is_ok(), "Failed to verify authentication");
         });
@@ -230,21 +819,262 @@ mod tests {
     #[test]
     fn is_authenticated_works_with_username_and_token() {
         with_auth_session(|mut auth| {
-            auth.verify();
+            auth.verify(service());
             let auth = auth;
-            let token = auth.create_webtoken().expect("Failed to create webtoken");
+            let token = auth.create_webtoken(service(), None).expect("Failed to create webtoken");
 
             let auth = Authenticatable::UserTokenAndPass {
                 user_token: token.user_token(),
                 password: test_password(),
             };
 
-            let result = is_authenticated(&auth);
+            let result = is_authenticated(service(), &auth, None);
 
             assert!(result.is_ok(), "Failed to verify authentication");
         });
     }
 
+    #[test]
+    fn change_password_changes_password() {
+        with_user(|mut user| {
+            assert!(user.verify(service()), "Failed to verify user");
+
+            let auth = Authenticatable::UserAndPass {
+                username: user.username(),
+                password: test_password(),
+            };
+
+            let result = change_password(
+                service(),
+                user.username(),
+                "N3w-Passw0rd",
+                None,
+                &auth,
+                None,
+                &ClientInfo::default(),
+            );
+
+            assert!(result.is_ok(), "Failed to change password");
+
+            let user = User::find_by_id(service(), user.id()).unwrap();
+
+            assert!(user.verify_password(service(), "N3w-Passw0rd").unwrap(), "Password was not changed");
+        });
+    }
+
+    #[test]
+    fn change_password_revokes_other_tokens() {
+        with_auth_session(|mut auth| {
+            auth.verify(service());
+            let token = auth.create_webtoken(service(), None).expect("Failed to create webtoken");
+
+            let user_auth = Authenticatable::UserTokenAndPass {
+                user_token: token.user_token(),
+                password: test_password(),
+            };
+
+            let result = change_password(
+                service(),
+                auth.username(),
+                "N3w-Passw0rd",
+                None,
+                &user_auth,
+                None,
+                &ClientInfo::default(),
+            );
+
+            match result {
+                Ok(LogIn::Authenticated(_)) => (),
+                _ => panic!("Failed to log in again after changing password"),
+            }
+
+            let user_auth = Authenticatable::UserToken { user_token: token.user_token() };
+
+            let result = is_authenticated(service(), &user_auth, None);
+
+            assert!(!result.is_ok(), "Token survived changing password");
+        });
+    }
+
+    #[test]
+    fn change_password_requires_password() {
+        with_auth_session(|mut auth| {
+            auth.verify(service());
+            let token = auth.create_webtoken(service(), None).expect("Failed to create webtoken");
+
+            let user_auth = Authenticatable::UserToken { user_token: token.user_token() };
+
+            let result = change_password(
+                service(),
+                auth.username(),
+                "N3w-Passw0rd",
+                None,
+                &user_auth,
+                None,
+                &ClientInfo::default(),
+            );
+
+            assert!(!result.is_ok(), "Changed password without giving old password");
+        });
+    }
+
+    #[test]
+    fn change_password_requires_code_with_two_factor_authentication() {
+        with_user(|mut user| {
+            assert!(user.verify(service()), "Failed to verify user");
+
+            let (secret, _) = start_mfa_log_in(&user);
+
+            let auth = Authenticatable::UserAndPass {
+                username: user.username(),
+                password: test_password(),
+            };
+
+            let result = change_password(
+                service(),
+                user.username(),
+                "N3w-Passw0rd",
+                None,
+                &auth,
+                None,
+                &ClientInfo::default(),
+            );
+
+            assert!(!result.is_ok(), "Changed password without a two-factor code");
+
+            let code = next_code(&secret);
+            let result = change_password(
+                service(),
+                user.username(),
+                "N3w-Passw0rd",
+                Some(&code),
+                &auth,
+                None,
+                &ClientInfo::default(),
+            );
+
+            match result {
+                Ok(LogIn::MfaRequired(_)) => (),
+                _ => panic!("Failed to change password with a two-factor code"),
+            }
+        });
+    }
+
+    #[test]
+    fn change_password_fails_with_weak_password() {
+        with_user(|mut user| {
+            assert!(user.verify(service()), "Failed to verify user");
+
+            let auth = Authenticatable::UserAndPass {
+                username: user.username(),
+                password: test_password(),
+            };
+
+            let result = change_password(
+                service(),
+                user.username(),
+                "weak",
+                None,
+                &auth,
+                None,
+                &ClientInfo::default(),
+            );
+
+            assert!(!result.is_ok(), "Changed to weak password");
+        });
+    }
+
+    #[test]
+    fn change_username_reissues_tokens() {
+        with_user(|mut user| {
+            assert!(user.verify(service()), "Failed to verify user");
+
+            let new_username = generate_string();
+
+            let auth = Authenticatable::UserAndPass {
+                username: user.username(),
+                password: test_password(),
+            };
+
+            let result = change_username(
+                service(),
+                user.username(),
+                &new_username,
+                None,
+                &auth,
+                None,
+                &ClientInfo::default(),
+            );
+
+            let token = match result {
+                Ok(LogIn::Authenticated(token)) => token,
+                _ => panic!("Failed to change username"),
+            };
+
+            let token_user = Webtoken::authenticate(service(), token.user_token())
+                .expect("Failed to authenticate new token");
+
+            assert_eq!(token_user.username(), new_username, "Token has old username");
+        });
+    }
+
+    #[test]
+    fn user_cannot_change_other_users_username() {
+        with_user(|mut user| {
+            assert!(user.verify(service()), "Failed to verify user");
+
+            with_user(|other_user| {
+                let auth = Authenticatable::UserAndPass {
+                    username: user.username(),
+                    password: test_password(),
+                };
+
+                let result = change_username(
+                    service(),
+                    other_user.username(),
+                    &generate_string(),
+                    None,
+                    &auth,
+                    None,
+                    &ClientInfo::default(),
+                );
+
+                assert!(!result.is_ok(), "Changed other user's username");
+            });
+        });
+    }
+
+    #[test]
+    fn change_email_waits_for_verification() {
+        with_user(|mut user| {
+            assert!(user.verify(service()), "Failed to verify user");
+
+            let email = format!("{}@example.com", generate_string().to_lowercase());
+
+            with_msg_sender(1, |sender| {
+                let auth = Authenticatable::UserAndPass {
+                    username: user.username(),
+                    password: test_password(),
+                };
+
+                let result = change_email(service(), user.username(), &email, None, &auth, &sender);
+
+                assert!(result.is_ok(), "Failed to request email change");
+            });
+
+            let found = User::find_by_id(service(), user.id()).expect("Failed to find user");
+
+            assert_eq!(found.email(), None, "Changed email before verifying it");
+
+            let vc = VerificationCode::find_by_user_id(service(), user.id())
+                .expect("Failed to find verification code");
+            let verified = User::verify_with_code(service(), vc.code())
+                .expect("Failed to verify new email");
+
+            assert_eq!(verified.email(), Some(email.as_str()), "Did not change email");
+        });
+    }
+
     #[test]
     fn delete_with_admin_deletes_user() {
         with_admin(|admin| {
@@ -254,7 +1084,7 @@ mod tests {
                     password: test_password(),
                 };
 
-                let result = delete(user.username(), &auth);
+                let result = delete(service(), user.username(), None, &auth);
 
                 assert!(result.is_ok(), "Failed to delete user");
             });
@@ -270,7 +1100,7 @@ mod tests {
                     password: test_password(),
                 };
 
-                let result = delete(user2.username(), &auth);
+                let result = delete(service(), user2.username(), None, &auth);
 
                 assert!(!result.is_ok(), "Deleted user with bad permissions");
             });
@@ -285,7 +1115,7 @@ mod tests {
                 password: test_password(),
             };
 
-            let result = delete(user.username(), &auth);
+            let result = delete(service(), user.username(), None, &auth);
 
             assert!(result.is_ok(), "User should be alowed to delete self");
         });
@@ -300,13 +1130,41 @@ mod tests {
                     password: test_password(),
                 };
 
-                let result = grant_permission(user.username(), "admin", &auth);
+                let result = grant_permission(service(), user.username(), "admin", &auth);
 
                 assert!(result.is_ok(), "Admin failed to grant User Permission");
             });
         });
     }
 
+    #[test]
+    fn grant_permission_rejects_token_for_other_audience() {
+        with_admin(|admin| {
+            with_user(|user| {
+                let mut admin_user = User::find_by_name(service(), admin.username()).unwrap();
+                assert!(admin_user.verify(service()), "Failed to verify admin");
+
+                let auth = Authenticatable::UserAndPass {
+                    username: admin.username(),
+                    password: test_password(),
+                };
+
+                let client = ClientInfo::default();
+
+                let token = match log_in(service(), &auth, Some("billing"), &client) {
+                    Ok(LogIn::Authenticated(token)) => token,
+                    _ => panic!("Failed to log in for audience"),
+                };
+
+                let auth = Authenticatable::UserToken { user_token: token.user_token() };
+
+                let result = grant_permission(service(), user.username(), "admin", &auth);
+
+                assert!(!result.is_ok(), "Granted permission with token for other audience");
+            });
+        });
+    }
+
     #[test]
     fn user_cannot_grant_permissions() {
         with_user(|user| {
@@ -316,7 +1174,7 @@ mod tests {
                     password: test_password(),
                 };
 
-                let result = grant_permission(user2.username(), "admin", &auth);
+                let result = grant_permission(service(), user2.username(), "admin", &auth);
 
                 assert!(!result.is_ok(), "Non-Admin User granted permission");
             });
@@ -331,7 +1189,7 @@ mod tests {
                 password: test_password(),
             };
 
-            let result = grant_permission(user.username(), "admin", &auth);
+            let result = grant_permission(service(), user.username(), "admin", &auth);
 
             assert!(!result.is_ok(), "Non-Admin User granted permission");
         });
@@ -346,7 +1204,7 @@ mod tests {
                     password: test_password(),
                 };
 
-                let result = revoke_permission(admin2.username(), "admin", &auth);
+                let result = revoke_permission(service(), admin2.username(), "admin", &auth);
 
                 assert!(result.is_ok(), "Failed to revoke permission");
             });
@@ -362,30 +1220,140 @@ mod tests {
                     password: test_password(),
                 };
 
-                let result = revoke_permission(admin.username(), "admin", &auth);
+                let result = revoke_permission(service(), admin.username(), "admin", &auth);
 
                 assert!(!result.is_ok(), "Non-Admin User revoked permission");
             });
         });
     }
 
-    fn with_msg_sender<T>(sent: usize, test: T) -> ()
-    where
-        T: FnOnce(MsgSender<i32>) -> () + panic::UnwindSafe,
-    {
-        let (sender, receiver) = mpsc::channel::<Message<i32>>();
+    #[test]
+    fn user_can_list_and_revoke_own_sessions() {
+        with_auth_session(|mut auth| {
+            auth.verify(service());
+            let client = ClientInfo::new(Some("Firefox"), None);
+            let token = auth.create_webtoken_for_client(service(), None, &client)
+                .expect("Failed to create webtoken");
+            let token_2 = auth.create_webtoken(service(), None).expect("Failed to create webtoken");
 
-        test(sender); // consume sender
+            let user_auth = Authenticatable::UserToken { user_token: token_2.user_token() };
 
-        let len = receiver.iter().collect::<Vec<_>>().len();
+            let result = sessions(service(), auth.username(), &user_auth);
 
-        assert_eq!(
-            len,
-            sent,
-            "Did not send correct number of messages, expected: {}, sent: {}",
-            sent,
-            len,
-        );
+            assert!(result.is_ok(), "Failed to list sessions");
+
+            let user_sessions = result.unwrap();
+
+            assert_eq!(user_sessions.len(), 2, "Listed wrong number of sessions");
+
+            let session = user_sessions
+                .iter()
+                .find(|session| session.user_agent() == Some("Firefox"))
+                .expect("Session is missing");
+
+            let result = revoke_session(service(), auth.username(), session.id(), &user_auth);
+
+            assert!(result.is_ok(), "Failed to revoke session");
+
+            let revoked_auth = Authenticatable::UserToken { user_token: token.user_token() };
+
+            let result = is_authenticated(service(), &revoked_auth, None);
+
+            assert!(!result.is_ok(), "Revoked session is still authenticated");
+
+            let user_sessions = sessions(service(), auth.username(), &user_auth).unwrap();
+
+            assert_eq!(user_sessions.len(), 1, "Revoked session is still listed");
+        });
+    }
+
+    #[test]
+    fn user_cannot_list_other_users_sessions() {
+        with_user(|user| {
+            with_user(|user2| {
+                let auth = Authenticatable::UserAndPass {
+                    username: user.username(),
+                    password: test_password(),
+                };
+
+                let result = sessions(service(), user2.username(), &auth);
+
+                assert!(!result.is_ok(), "Listed another user's sessions");
+            });
+        });
+    }
+
+    #[test]
+    fn admin_can_revoke_other_users_sessions() {
+        with_admin(|admin| {
+            with_auth_session(|mut auth| {
+                auth.verify(service());
+                let token = auth.create_webtoken(service(), None).expect(
+                    "Failed to create webtoken",
+                );
+
+                let admin_auth = Authenticatable::UserAndPass {
+                    username: admin.username(),
+                    password: test_password(),
+                };
+
+                let user_sessions = sessions(service(), auth.username(), &admin_auth)
+                    .expect("Admin failed to list sessions");
+
+                let result =
+                    revoke_session(service(), auth.username(), user_sessions[0].id(), &admin_auth);
+
+                assert!(result.is_ok(), "Admin failed to revoke session");
+
+                let user_auth = Authenticatable::UserToken { user_token: token.user_token() };
+
+                let result = is_authenticated(service(), &user_auth, None);
+
+                assert!(!result.is_ok(), "Revoked session is still authenticated");
+            });
+        });
+    }
+
+    #[test]
+    fn revoke_session_rejects_other_users_session() {
+        with_auth_session(|mut auth| {
+            auth.verify(service());
+            let _ = auth.create_webtoken(service(), None).expect("Failed to create webtoken");
+
+            with_user(|user| {
+                let user_auth = Authenticatable::UserAndPass {
+                    username: auth.username(),
+                    password: test_password(),
+                };
+                let other_auth = Authenticatable::UserAndPass {
+                    username: user.username(),
+                    password: test_password(),
+                };
+
+                let user_sessions = sessions(service(), auth.username(), &user_auth).unwrap();
+
+                let result =
+                    revoke_session(service(), user.username(), user_sessions[0].id(), &other_auth);
+
+                assert!(!result.is_ok(), "Revoked another user's session");
+            });
+        });
+    }
+
+    fn start_mfa_log_in(user: &User) -> (String, String) {
+        let enrollment = TotpCredential::enroll(service(), user).expect("Failed to enroll");
+        let _ = TotpCredential::confirm(service(), user, &current_code(enrollment.secret()))
+            .expect("Failed to confirm enrollment");
+
+        let auth = Authenticatable::UserAndPass {
+            username: user.username(),
+            password: test_password(),
+        };
+
+        match log_in(service(), &auth, None, &ClientInfo::default()) {
+            Ok(LogIn::MfaRequired(mfa_token)) => (enrollment.secret().to_owned(), mfa_token),
+            _ => panic!("Logged in without two-factor code"),
+        }
     }
 
     fn test_wrapper<T>(test: T) -> ()
@@ -18,10 +18,11 @@
  */
 
 use models::User;
+use service::AuthService;
 use error::Result;
 
-pub fn verify(code: &str) -> Result<()> {
-    User::verify_with_code(code)?;
+pub fn verify(service: &AuthService, code: &str) -> Result<()> {
+    User::verify_with_code(service, code)?;
 
     Ok(())
 }
@@ -0,0 +1,207 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use authenticatable::ToAuth;
+use models::{AuthenticatedThisSession, ClientInfo, FailedLogin, User, UserTrait};
+use service::AuthService;
+use webauthn::{self, Assertion, Attestation, AuthenticationOptions, RegistrationOptions};
+use webtoken::Webtoken;
+use error::{Error, Result, WebauthnErrorKind};
+use super::own_session;
+
+pub fn registration_options<T>(
+    service: &AuthService,
+    target_user: &str,
+    auth: &T,
+) -> Result<RegistrationOptions>
+where
+    T: ToAuth,
+{
+    let user = own_session(service, target_user, auth)?;
+
+    webauthn::registration_options(service, &user)
+}
+
+pub fn register<T>(
+    service: &AuthService,
+    target_user: &str,
+    attestation: &Attestation,
+    auth: &T,
+) -> Result<()>
+where
+    T: ToAuth,
+{
+    let user = own_session(service, target_user, auth)?;
+
+    webauthn::register(service, &user, attestation)?;
+
+    Ok(())
+}
+
+pub fn authentication_options(
+    service: &AuthService,
+    audience: Option<&str>,
+) -> Result<AuthenticationOptions> {
+    service.check_audience(audience)?;
+
+    webauthn::authentication_options(service, audience)
+}
+
+// Issues the same tokens as logging in with a password, to verified users who aren't locked out.
+// Rejected assertions count as failed log ins from the client's address.
+pub fn log_in(
+    service: &AuthService,
+    assertion: &Assertion,
+    client: &ClientInfo,
+) -> Result<Webtoken> {
+    FailedLogin::check(service, None, client.ip_address())?;
+
+    let (credential, audience) = match webauthn::authenticate(service, assertion) {
+        Ok(authenticated) => authenticated,
+        Err(Error::WebauthnError(WebauthnErrorKind::Unavailable)) => {
+            return Err(Error::WebauthnError(WebauthnErrorKind::Unavailable));
+        }
+        Err(err @ Error::WebauthnError(_)) => {
+            FailedLogin::record(service, None, None, client.ip_address())?;
+
+            return Err(err);
+        }
+        Err(err) => return Err(err),
+    };
+
+    let user = User::find_by_id(service, credential.user_id())?;
+
+    FailedLogin::check(service, Some(user.username()), client.ip_address())?;
+
+    let webtoken = AuthenticatedThisSession::from_user(&user).create_webtoken_for_client(
+        service,
+        audience.as_ref().map(|audience| audience.as_str()),
+        client,
+    )?;
+
+    FailedLogin::clear(service, user.username())?;
+
+    Ok(webtoken)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use authenticatable::Authenticatable;
+    use webauthn_test_helper::SoftwareAuthenticator;
+    use user_test_helper::with_user;
+    use test_helper::{service, test_password};
+
+    #[test]
+    fn log_in_issues_webtoken_for_registered_passkey() {
+        with_user(|user| {
+            let auth = Authenticatable::UserAndPass {
+                username: user.username(),
+                password: test_password(),
+            };
+            let mut authenticator = SoftwareAuthenticator::new();
+
+            let options = registration_options(service(), user.username(), &auth)
+                .expect("Failed to start registration");
+            let _ = register(
+                service(),
+                user.username(),
+                &authenticator.attest(&options),
+                &auth,
+            ).expect("Failed to register passkey");
+
+            let options = authentication_options(service(), None)
+                .expect("Failed to start log in");
+
+            let result = log_in(
+                service(),
+                &authenticator.assert(&options),
+                &ClientInfo::default(),
+            );
+
+            assert!(result.is_ok(), "Failed to log in with passkey");
+
+            let webtoken = result.unwrap();
+
+            let result = Webtoken::authenticate(service(), webtoken.user_token());
+
+            assert!(result.is_ok(), "Failed to authenticate passkey log in");
+        });
+    }
+
+    #[test]
+    fn log_in_backs_off_after_failed_password() {
+        with_user(|user| {
+            let auth = Authenticatable::UserAndPass {
+                username: user.username(),
+                password: test_password(),
+            };
+            let mut authenticator = SoftwareAuthenticator::new();
+
+            let options = registration_options(service(), user.username(), &auth)
+                .expect("Failed to start registration");
+            let _ = register(
+                service(),
+                user.username(),
+                &authenticator.attest(&options),
+                &auth,
+            ).expect("Failed to register passkey");
+
+            let _ = FailedLogin::record(service(), Some(user.username()), Some(user.id()), None)
+                .expect("Failed to record failed log in");
+
+            let options = authentication_options(service(), None)
+                .expect("Failed to start log in");
+
+            let result = log_in(
+                service(),
+                &authenticator.assert(&options),
+                &ClientInfo::default(),
+            );
+
+            match result {
+                Err(Error::LoginThrottledError) => (),
+                _ => panic!("Allowed passkey log in during back-off"),
+            }
+        });
+    }
+
+    #[test]
+    fn registration_options_fail_for_other_user() {
+        with_user(|user| {
+            with_user(|user2| {
+                let auth = Authenticatable::UserAndPass {
+                    username: user.username(),
+                    password: test_password(),
+                };
+
+                let result = registration_options(service(), user2.username(), &auth);
+
+                assert!(!result.is_ok(), "Started registration for another user");
+            });
+        });
+    }
+
+    #[test]
+    fn authentication_options_fail_with_unknown_audience() {
+        let result = authentication_options(service(), Some("not an audience"));
+
+        assert!(!result.is_ok(), "Started log in for unknown audience");
+    }
+}
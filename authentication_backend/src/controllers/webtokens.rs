@@ -17,33 +17,143 @@
  * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
  */
 
-use webtoken::Webtoken;
-use error::Result;
+use authentication_background::{Message, MsgSender};
+use base64;
+use webtoken::{Introspection, Webtoken};
+use service::AuthService;
+use error::{Error, Result};
 
-pub fn renew(renewal_token: &str) -> Result<Webtoken> {
-    Webtoken::renew(renewal_token)
+pub fn renew(
+    service: &AuthService,
+    renewal_token: &str,
+    audience: Option<&str>,
+    sender: &MsgSender<i32>,
+) -> Result<Webtoken> {
+    match Webtoken::renew(service, renewal_token, audience) {
+        Err(Error::TokenReuseError(user_id)) => {
+            match sender.send(Message::new("token_reuse", Some(user_id))) {
+                _ => (),
+            };
+
+            Err(Error::TokenReuseError(user_id))
+        }
+        result => result,
+    }
+}
+
+pub fn introspect(
+    service: &AuthService,
+    authorization: &str,
+    token: &str,
+) -> Result<Introspection> {
+    let authenticated = match client_credentials(authorization) {
+        Some((client_id, secret)) => service.clients().authenticate(&client_id, &secret),
+        None => false,
+    };
+
+    if !authenticated {
+        return Err(Error::ClientAuthenticationError);
+    }
+
+    Ok(Webtoken::introspect(service, token))
+}
+
+fn client_credentials(authorization: &str) -> Option<(String, Vec<u8>)> {
+    if !authorization.starts_with("Basic ") {
+        return None;
+    }
+
+    let credentials = match base64::decode(authorization["Basic ".len()..].trim()) {
+        Ok(credentials) => credentials,
+        Err(_) => return None,
+    };
+
+    let credentials = match String::from_utf8(credentials) {
+        Ok(credentials) => credentials,
+        Err(_) => return None,
+    };
+
+    let mut parts = credentials.splitn(2, ':');
+
+    match (parts.next(), parts.next()) {
+        (Some(client_id), Some(secret)) => {
+            Some((client_id.to_owned(), secret.as_bytes().to_vec()))
+        }
+        _ => None,
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use webtoken_test_helper::with_token;
+    use test_helper::{service, with_msg_sender};
 
     #[test]
     fn renew_renews_tokens() {
         with_token("renewal", |token| {
-            let result = renew(token);
+            with_msg_sender(0, |sender| {
+                let result = renew(service(), token, None, &sender);
 
-            assert!(result.is_ok(), "Failed to renew token");
+                assert!(result.is_ok(), "Failed to renew token");
+            });
         });
     }
 
     #[test]
     fn renew_fails_with_bad_token() {
         with_token("invalid", |token| {
-            let result = renew(token);
+            with_msg_sender(0, |sender| {
+                let result = renew(service(), token, None, &sender);
+
+                assert!(!result.is_ok(), "Renewed bad token");
+            });
+        });
+    }
+
+    #[test]
+    fn renew_reports_reused_token() {
+        with_token("renewal", |token| {
+            with_msg_sender(1, |sender| {
+                let _ = renew(service(), token, None, &sender).expect("Failed to renew token");
+
+                let result = renew(service(), token, None, &sender);
+
+                assert!(!result.is_ok(), "Renewed token twice");
+            });
+        });
+    }
+
+    #[test]
+    fn introspect_fails_without_client_credentials() {
+        with_token("user", |token| {
+            let result = introspect(service(), "", token);
 
-            assert!(!result.is_ok(), "Renewed bad token");
+            assert!(!result.is_ok(), "Introspected token without client credentials");
         });
     }
+
+    #[test]
+    fn introspect_fails_with_unknown_client() {
+        with_token("user", |token| {
+            let authorization = format!("Basic {}", base64::encode(b"unknown:secret"));
+
+            let result = introspect(service(), &authorization, token);
+
+            assert!(!result.is_ok(), "Introspected token for unknown client");
+        });
+    }
+
+    #[test]
+    fn client_credentials_reads_basic_authorization() {
+        let authorization = format!("Basic {}", base64::encode(b"client:secret:with:colons"));
+
+        let result = client_credentials(&authorization);
+
+        assert_eq!(
+            result,
+            Some(("client".to_owned(), b"secret:with:colons".to_vec())),
+            "Failed to read client credentials"
+        );
+    }
 }
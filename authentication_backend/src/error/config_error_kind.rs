@@ -0,0 +1,54 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+pub enum ConfigErrorKind {
+    Missing(String),
+    Malformed(String, String),
+    UnknownSetting(String),
+    UnreadableFile(String),
+    UnsupportedFormat(String),
+    InvalidFile(String),
+    Unreachable(String),
+}
+
+impl ToString for ConfigErrorKind {
+    fn to_string(&self) -> String {
+        match *self {
+            ConfigErrorKind::Missing(ref setting) => {
+                format!("Missing required setting '{}'", setting)
+            }
+            ConfigErrorKind::Malformed(ref setting, ref reason) => {
+                format!("Setting '{}' {}", setting, reason)
+            }
+            ConfigErrorKind::UnknownSetting(ref setting) => {
+                format!("Unknown setting '{}'", setting)
+            }
+            ConfigErrorKind::UnreadableFile(ref path) => format!("Could not read file '{}'", path),
+            ConfigErrorKind::UnsupportedFormat(ref path) => {
+                format!("Config file '{}' must be TOML or YAML", path)
+            }
+            ConfigErrorKind::InvalidFile(ref path) => {
+                format!("Could not parse config file '{}'", path)
+            }
+            ConfigErrorKind::Unreachable(ref setting) => {
+                format!("Could not connect to the database given by '{}'", setting)
+            }
+        }
+    }
+}
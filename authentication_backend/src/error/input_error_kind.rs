@@ -23,8 +23,10 @@ use super::UsernameErrorKind;
 pub enum InputErrorKind {
     Password(Vec<PasswordErrorKind>),
     Username(Vec<UsernameErrorKind>),
+    Email,
     Authenticatable,
     PermissionName,
+    Audience,
 }
 
 impl ToString for InputErrorKind {
@@ -40,8 +42,10 @@ impl ToString for InputErrorKind {
 
                 messages.join(", ")
             }
+            InputErrorKind::Email => "Invalid email address".to_string(),
             InputErrorKind::Authenticatable => "Invalid authentication format".to_string(),
             InputErrorKind::PermissionName => "Invalid permission name".to_string(),
+            InputErrorKind::Audience => "Unknown audience".to_string(),
         }
     }
 }
@@ -0,0 +1,50 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+pub enum JWTErrorKind {
+    InvalidToken,
+    InvalidSignature,
+    InvalidAlgorithm,
+    UnknownKey,
+    ExpiredSignature,
+    ImmatureSignature,
+    InvalidIssuedAt,
+    InvalidIssuer,
+    InvalidSubject,
+    InvalidAudience,
+    SigningFailed,
+}
+
+impl ToString for JWTErrorKind {
+    fn to_string(&self) -> String {
+        match *self {
+            JWTErrorKind::InvalidToken => "Token is malformed",
+            JWTErrorKind::InvalidSignature => "Token signature is invalid",
+            JWTErrorKind::InvalidAlgorithm => "Token algorithm does not match its key",
+            JWTErrorKind::UnknownKey => "Token was signed by an unknown key",
+            JWTErrorKind::ExpiredSignature => "Token has expired",
+            JWTErrorKind::ImmatureSignature => "Token is not valid yet",
+            JWTErrorKind::InvalidIssuedAt => "Token was issued in the future",
+            JWTErrorKind::InvalidIssuer => "Token has the wrong issuer",
+            JWTErrorKind::InvalidSubject => "Token has the wrong subject",
+            JWTErrorKind::InvalidAudience => "Token has the wrong audience",
+            JWTErrorKind::SigningFailed => "Failed to sign token",
+        }.to_owned()
+    }
+}
@@ -0,0 +1,38 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+pub enum MfaErrorKind {
+    Unavailable,
+    AlreadyEnabled,
+    NotEnrolled,
+    InvalidCode,
+    CodeRequired,
+}
+
+impl ToString for MfaErrorKind {
+    fn to_string(&self) -> String {
+        match *self {
+            MfaErrorKind::Unavailable => "Two-factor authentication is not configured",
+            MfaErrorKind::AlreadyEnabled => "Two-factor authentication is already enabled",
+            MfaErrorKind::NotEnrolled => "Two-factor authentication has not been set up",
+            MfaErrorKind::InvalidCode => "Invalid two-factor code",
+            MfaErrorKind::CodeRequired => "A two-factor code is required",
+        }.to_owned()
+    }
+}
@@ -18,20 +18,26 @@
  */
 
 mod result;
+mod config_error_kind;
 mod input_error_kind;
+mod jwt_error_kind;
+mod mfa_error_kind;
 mod password_error_kind;
 mod username_error_kind;
+mod webauthn_error_kind;
 
 pub use bcrypt::BcryptError;
 pub use diesel::result::Error as DbError;
 pub use diesel::result::DatabaseErrorKind as DbErrorKind;
-pub use jwt::errors::Error as JWTError;
-pub use jwt::errors::ErrorKind as JWTErrorKind;
 
 pub use self::result::Result;
+pub use self::config_error_kind::ConfigErrorKind;
 pub use self::input_error_kind::InputErrorKind;
+pub use self::jwt_error_kind::JWTErrorKind;
+pub use self::mfa_error_kind::MfaErrorKind;
 pub use self::password_error_kind::PasswordErrorKind;
 pub use self::username_error_kind::UsernameErrorKind;
+pub use self::webauthn_error_kind::WebauthnErrorKind;
 
 use std::io;
 use std::fmt;
@@ -41,14 +47,27 @@ use std::error::Error as StdError;
 
 pub enum Error {
     BcryptError(BcryptError),
+    ConfigError(Vec<ConfigErrorKind>),
     DbError(DbError),
     InputError(InputErrorKind),
-    JWTError(JWTError),
+    JWTError(JWTErrorKind),
+    MfaError(MfaErrorKind),
+    WebauthnError(WebauthnErrorKind),
+    AccountLockedError(i32),
+    ClientAuthenticationError,
     DbTimeout,
+    EncryptionError,
     IOError,
+    LoginThrottledError,
     ParseError,
+    PasswordHashError,
     PasswordMatchError,
+    PasswordResetError,
     PermissionError,
+    RateLimitedError(i64),
+    SessionExpiredError,
+    TokenReuseError(i32),
+    TokenRevokedError,
     UserNotVerifiedError,
 }
 
@@ -57,8 +76,10 @@ impl Error {
         match *input_error {
             InputErrorKind::Password(_) => "Invalid password",
             InputErrorKind::Username(_) => "Invalid username",
+            InputErrorKind::Email => "Invalid email address",
             InputErrorKind::Authenticatable => "Invalid authentication format",
             InputErrorKind::PermissionName => "Invalid permission name",
+            InputErrorKind::Audience => "Unknown audience",
         }
     }
 }
@@ -67,14 +88,27 @@ impl StdError for Error {
     fn description(&self) -> &str {
         match *self {
             Error::BcryptError(ref bcrypt_error) => bcrypt_error.description(),
+            Error::ConfigError(_) => "Invalid configuration",
             Error::DbError(ref db_error) => db_error.description(),
             Error::InputError(ref input_error) => Error::input_description(input_error),
-            Error::JWTError(ref jwt_error) => jwt_error.description(),
+            Error::JWTError(_) => "Invalid token",
+            Error::MfaError(_) => "Two-factor authentication failed",
+            Error::WebauthnError(_) => "Passkey authentication failed",
+            Error::AccountLockedError(_) => "Too many failed log ins, try again later",
+            Error::ClientAuthenticationError => "Client authentication failed",
             Error::DbTimeout => "Failed to get Database",
+            Error::EncryptionError => "Failed to encrypt or decrypt secret",
             Error::IOError => "Timed out while waiting for database",
+            Error::LoginThrottledError => "Too many failed log ins, try again later",
             Error::ParseError => "Could not parse data from string",
-            Error::PasswordMatchError => "Passwords do not match",
+            Error::PasswordHashError => "Failed to hash password",
+            Error::PasswordMatchError => "Invalid credentials",
+            Error::PasswordResetError => "Password reset token is invalid or has expired",
             Error::PermissionError => "Not allowed to perform this action",
+            Error::RateLimitedError(_) => "Too many requests, try again later",
+            Error::SessionExpiredError => "Session has expired, log in again",
+            Error::TokenReuseError(_) => "Renewal token has already been used",
+            Error::TokenRevokedError => "Token has been revoked",
             Error::UserNotVerifiedError => "User is not verified",
         }
     }
@@ -83,7 +117,6 @@ impl StdError for Error {
         match *self {
             Error::BcryptError(ref bcrypt_error) => Some(bcrypt_error),
             Error::DbError(ref db_error) => Some(db_error),
-            Error::JWTError(ref jwt_error) => Some(jwt_error),
             _ => None,
         }
     }
@@ -93,9 +126,16 @@ impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
             Error::BcryptError(ref err) => err.fmt(f),
+            Error::ConfigError(ref err_vec) => {
+                let messages: Vec<String> = err_vec.iter().map(|c| c.to_string()).collect();
+
+                write!(f, "{}", messages.join(", "))
+            }
             Error::DbError(ref err) => err.fmt(f),
             Error::InputError(ref err) => write!(f, "{}", err.to_string()),
-            Error::JWTError(ref err) => err.fmt(f),
+            Error::JWTError(ref err) => write!(f, "{}", err.to_string()),
+            Error::MfaError(ref err) => write!(f, "{}", err.to_string()),
+            Error::WebauthnError(ref err) => write!(f, "{}", err.to_string()),
             _ => write!(f, "{}", self.description()),
         }
     }
@@ -125,12 +165,6 @@ impl From<BcryptError> for Error {
     }
 }
 
-impl From<JWTError> for Error {
-    fn from(e: JWTError) -> Error {
-        Error::JWTError(e)
-    }
-}
-
 impl From<ParseIntError> for Error {
     fn from(_: ParseIntError) -> Error {
         Error::ParseError
@@ -17,17 +17,27 @@
  * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
  */
 
+#[derive(Debug, PartialEq)]
 pub enum PasswordErrorKind {
+    Breached,
+    Common,
     NoLowercase,
     NoNumber,
     NoSymbol,
     NoUppercase,
-    TooShort,
+    SimilarToUsername,
+    TooLong(usize),
+    TooShort(usize),
+    TooWeak,
 }
 
 impl ToString for PasswordErrorKind {
     fn to_string(&self) -> String {
         match *self {
+            PasswordErrorKind::Breached => {
+                "Password has appeared in a data breach".to_string()
+            }
+            PasswordErrorKind::Common => "Password is too common".to_string(),
             PasswordErrorKind::NoLowercase => {
                 "Password must contain at least one lowercase letter".to_string()
             }
@@ -36,7 +46,16 @@ impl ToString for PasswordErrorKind {
             PasswordErrorKind::NoUppercase => {
                 "Password must contain at least one uppercase letter".to_string()
             }
-            PasswordErrorKind::TooShort => "Password must be at least 8 characters".to_string(),
+            PasswordErrorKind::SimilarToUsername => {
+                "Password must not be similar to the username".to_string()
+            }
+            PasswordErrorKind::TooLong(max) => {
+                format!("Password must be at most {} characters", max)
+            }
+            PasswordErrorKind::TooShort(min) => {
+                format!("Password must be at least {} characters", min)
+            }
+            PasswordErrorKind::TooWeak => "Password is too easy to guess".to_string(),
         }
     }
 }
@@ -19,12 +19,14 @@
 
 pub enum UsernameErrorKind {
     Blank,
+    ContainsAt,
 }
 
 impl ToString for UsernameErrorKind {
     fn to_string(&self) -> String {
         match *self {
             UsernameErrorKind::Blank => "Username must not be blank".to_string(),
+            UsernameErrorKind::ContainsAt => "Username must not contain @".to_string(),
         }
     }
 }
@@ -0,0 +1,50 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+pub enum WebauthnErrorKind {
+    Unavailable,
+    Malformed,
+    UnsupportedAlgorithm,
+    ChallengeMismatch,
+    OriginMismatch,
+    RelyingPartyMismatch,
+    UserNotPresent,
+    UserNotVerified,
+    UnknownCredential,
+    InvalidSignature,
+    CounterRegression,
+}
+
+impl ToString for WebauthnErrorKind {
+    fn to_string(&self) -> String {
+        match *self {
+            WebauthnErrorKind::Unavailable => "Passkeys are not configured",
+            WebauthnErrorKind::Malformed => "Malformed authenticator response",
+            WebauthnErrorKind::UnsupportedAlgorithm => "Unsupported credential algorithm",
+            WebauthnErrorKind::ChallengeMismatch => "Response is not for this challenge",
+            WebauthnErrorKind::OriginMismatch => "Response came from another origin",
+            WebauthnErrorKind::RelyingPartyMismatch => "Credential is for another site",
+            WebauthnErrorKind::UserNotPresent => "Authenticator did not check for the user",
+            WebauthnErrorKind::UserNotVerified => "Authenticator did not verify the user",
+            WebauthnErrorKind::UnknownCredential => "Unknown credential",
+            WebauthnErrorKind::InvalidSignature => "Invalid credential signature",
+            WebauthnErrorKind::CounterRegression => "Signature counter went backwards",
+        }.to_owned()
+    }
+}
@@ -17,8 +17,7 @@
  * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
  */
 
-#![feature(plugin, custom_derive, custom_attribute)]
-#![plugin(dotenv_macros)]
+#![feature(custom_derive, custom_attribute)]
 
 #[macro_use]
 extern crate diesel;
@@ -34,27 +33,41 @@ extern crate serde;
 extern crate serde_json;
 extern crate rand;
 extern crate dotenv;
-extern crate jsonwebtoken as jwt;
+extern crate argon2;
 extern crate bcrypt;
+extern crate scrypt;
 extern crate r2d2;
 extern crate r2d2_diesel;
-extern crate regex;
 extern crate chrono;
-
-use config::Config;
+extern crate toml;
+extern crate serde_yaml;
+extern crate base64;
+extern crate ring;
+extern crate untrusted;
 
 mod config;
+mod service;
 mod schema;
+mod store;
 mod models;
 pub mod controllers;
 mod error;
 mod webtoken;
+mod webauthn;
 mod authenticatable;
+mod rate_limiter;
 
-pub use models::{Admin, Authenticated, AuthenticatedThisSession, User, UserTrait, Permission,
+pub use models::{Admin, Authenticated, AuthenticatedThisSession, ClientInfo, User, UserTrait,
+                 PasswordReset, Permission, RecoveryCodes, Session, TotpEnrollment, TotpStatus,
                  UserPermission, VerificationCode};
-pub use error::{Error, BcryptError, DbError, DbErrorKind, JWTError, JWTErrorKind};
-pub use webtoken::Webtoken;
+pub use config::{Algorithm, BreachFilter, Config, ConfigBuilder, Jwk, JwkSet, RateLimit};
+pub use service::AuthService;
+pub use store::{MemoryStore, PostgresStore, Store};
+pub use rate_limiter::{MemoryRateLimiter, RateLimiter};
+pub use error::{Error, BcryptError, ConfigErrorKind, DbError, DbErrorKind, JWTErrorKind,
+                MfaErrorKind, WebauthnErrorKind};
+pub use webtoken::{Introspection, LogIn, TokenUser, Webtoken};
+pub use webauthn::{Assertion, Attestation, AuthenticationOptions, RegistrationOptions};
 pub use authenticatable::{Authenticatable, ToAuth};
 
 #[cfg(feature = "test")]
@@ -64,10 +77,10 @@ pub use self::models::user_permission_test_helper;
 #[cfg(feature = "test")]
 pub use self::models::permission_test_helper;
 #[cfg(feature = "test")]
+pub use self::models::totp_credential_test_helper;
+#[cfg(feature = "test")]
 pub use self::webtoken::test_helper as webtoken_test_helper;
 #[cfg(feature = "test")]
+pub use self::webauthn::test_helper as webauthn_test_helper;
+#[cfg(feature = "test")]
 pub mod test_helper;
-
-lazy_static! {
-    pub static ref CONFIG: Config = Config::initialize();
-}
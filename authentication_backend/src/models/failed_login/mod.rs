@@ -0,0 +1,329 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+mod new_failed_login;
+
+pub use self::new_failed_login::NewFailedLogin;
+
+use chrono::{NaiveDateTime, Utc};
+use ring::digest;
+use service::AuthService;
+use schema::failed_logins;
+use error::{DbError, Error, Result};
+
+const USER_SUBJECT: &str = "user:";
+const IP_SUBJECT: &str = "ip:";
+const MFA_SUBJECT: &str = "mfa:";
+const MFA_TOKEN_ATTEMPTS: u32 = 3;
+const USERNAME_HASH_LEN: usize = 32;
+
+#[derive(Debug, Queryable, Identifiable)]
+#[table_name = "failed_logins"]
+pub struct FailedLogin {
+    id: i32,
+    subject: String,
+    user_id: Option<i32>,
+    failures: i32,
+    last_failed_at: NaiveDateTime,
+    locked_until: Option<NaiveDateTime>,
+}
+
+impl FailedLogin {
+    pub(crate) fn from_parts(
+        id: i32,
+        subject: String,
+        user_id: Option<i32>,
+        failures: i32,
+        last_failed_at: NaiveDateTime,
+        locked_until: Option<NaiveDateTime>,
+    ) -> Self {
+        FailedLogin {
+            id: id,
+            subject: subject,
+            user_id: user_id,
+            failures: failures,
+            last_failed_at: last_failed_at,
+            locked_until: locked_until,
+        }
+    }
+
+    // Failures are counted for the username and for the address the attempt came from. Attempts
+    // are refused while either is locked out or waiting out its back-off, before the password is
+    // checked, so refused attempts don't help anyone guess. Usernames are counted whether or not
+    // they belong to anyone, so lockouts don't reveal which ones do.
+    pub fn check(
+        service: &AuthService,
+        username: Option<&str>,
+        ip_address: Option<&str>,
+    ) -> Result<()> {
+        let now = Utc::now().naive_utc();
+
+        for (subject, _, _) in subjects(service, username, ip_address) {
+            let failed_login = match service.store().find_failed_login(&subject) {
+                Ok(failed_login) => failed_login,
+                Err(Error::DbError(DbError::NotFound)) => continue,
+                Err(err) => return Err(err),
+            };
+
+            if failed_login.is_throttled(service, now) {
+                return Err(Error::LoginThrottledError);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn record(
+        service: &AuthService,
+        username: Option<&str>,
+        user_id: Option<i32>,
+        ip_address: Option<&str>,
+    ) -> Result<bool> {
+        let mut locked_out = false;
+        let subjects = subjects(service, username, ip_address);
+
+        for (subject, is_username, threshold) in subjects {
+            let subject_user_id = if is_username { user_id } else { None };
+            let locked = FailedLogin::record_for(service, &subject, subject_user_id, threshold)?;
+
+            locked_out = locked_out || (locked && is_username);
+        }
+
+        Ok(locked_out)
+    }
+
+    // Only the username's failures are forgotten, or an attacker with one account could keep
+    // clearing their address's failures
+    pub fn clear(service: &AuthService, username: &str) -> Result<()> {
+        service.store().delete_failed_login(&username_subject(username))
+    }
+
+    pub fn record_mfa(service: &AuthService, family: &str, user_id: i32) -> Result<bool> {
+        FailedLogin::record_for(
+            service,
+            &mfa_subject(family),
+            Some(user_id),
+            MFA_TOKEN_ATTEMPTS,
+        )
+    }
+
+    pub fn clear_mfa(service: &AuthService, family: &str) -> Result<()> {
+        service.store().delete_failed_login(&mfa_subject(family))
+    }
+
+    pub fn failures(&self) -> i32 {
+        self.failures
+    }
+
+    pub fn last_failed_at(&self) -> NaiveDateTime {
+        self.last_failed_at
+    }
+
+    pub fn locked_until(&self) -> Option<NaiveDateTime> {
+        self.locked_until
+    }
+
+    fn is_stale(&self, service: &AuthService, now: NaiveDateTime) -> bool {
+        match self.locked_until {
+            Some(locked_until) => locked_until <= now,
+            None => self.last_failed_at + service.lockout().duration() <= now,
+        }
+    }
+
+    fn is_throttled(&self, service: &AuthService, now: NaiveDateTime) -> bool {
+        if self.is_stale(service, now) {
+            return false;
+        }
+
+        self.locked_until.is_some() ||
+            now < self.last_failed_at + service.lockout().backoff(self.failures)
+    }
+
+    fn record_for(
+        service: &AuthService,
+        subject: &str,
+        user_id: Option<i32>,
+        threshold: u32,
+    ) -> Result<bool> {
+        let now = Utc::now().naive_utc();
+
+        let existing = match service.store().find_failed_login(subject) {
+            Ok(failed_login) => Some(failed_login),
+            Err(Error::DbError(DbError::NotFound)) => None,
+            Err(err) => return Err(err),
+        };
+
+        let failures = match existing {
+            Some(ref failed_login) if !failed_login.is_stale(service, now) => {
+                failed_login.failures + 1
+            }
+            _ => 1,
+        };
+
+        let locked_until = if threshold > 0 && failures as u32 >= threshold {
+            Some(now + service.lockout().duration())
+        } else {
+            None
+        };
+
+        if existing.is_some() {
+            service.store().update_failed_login(
+                subject,
+                failures,
+                now,
+                locked_until,
+            )?;
+        } else {
+            NewFailedLogin::new(subject, user_id, failures, now, locked_until)
+                .save(service)?;
+        }
+
+        Ok(locked_until.is_some())
+    }
+}
+
+fn subjects(
+    service: &AuthService,
+    username: Option<&str>,
+    ip_address: Option<&str>,
+) -> Vec<(String, bool, u32)> {
+    let mut subjects = Vec::new();
+
+    if let Some(username) = username {
+        subjects.push((
+            username_subject(username),
+            true,
+            service.lockout().threshold(),
+        ));
+    }
+
+    if let Some(ip_address) = ip_address {
+        subjects.push((
+            format!("{}{}", IP_SUBJECT, ip_address),
+            false,
+            service.lockout().ip_threshold(),
+        ));
+    }
+
+    subjects
+}
+
+// Usernames are hashed, since people sometimes type their password into the username field
+fn username_subject(username: &str) -> String {
+    let hash: String = digest::digest(&digest::SHA256, username.as_bytes())
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+
+    format!("{}{}", USER_SUBJECT, &hash[..USERNAME_HASH_LEN])
+}
+
+fn mfa_subject(family: &str) -> String {
+    format!("{}{}", MFA_SUBJECT, family)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use models::user::UserTrait;
+    use models::user::test_helper::with_user;
+    use test_helper::{generate_string, service};
+
+    #[test]
+    fn record_backs_off_after_failure() {
+        with_user(|user| {
+            let locked =
+                FailedLogin::record(service(), Some(user.username()), Some(user.id()), None);
+
+            assert!(!locked.unwrap(), "Locked user out after one failure");
+
+            match FailedLogin::check(service(), Some(user.username()), None) {
+                Err(Error::LoginThrottledError) => (),
+                _ => panic!("Allowed attempt during back-off"),
+            }
+        });
+    }
+
+    #[test]
+    fn record_locks_user_out_at_threshold() {
+        with_user(|user| {
+            let new_failed_login = NewFailedLogin::new(
+                &username_subject(user.username()),
+                Some(user.id()),
+                service().lockout().threshold() as i32 - 1,
+                Utc::now().naive_utc(),
+                None,
+            );
+
+            new_failed_login.save(service()).expect(
+                "Failed to save failed log in",
+            );
+
+            let locked =
+                FailedLogin::record(service(), Some(user.username()), Some(user.id()), None);
+
+            assert!(locked.unwrap(), "Did not lock user out at threshold");
+        });
+    }
+
+    #[test]
+    fn clear_forgets_user_failures() {
+        with_user(|user| {
+            FailedLogin::record(service(), Some(user.username()), Some(user.id()), None).unwrap();
+            FailedLogin::clear(service(), user.username()).unwrap();
+
+            assert!(
+                FailedLogin::check(service(), Some(user.username()), None).is_ok(),
+                "Still throttled after clearing failures"
+            );
+        });
+    }
+
+    #[test]
+    fn check_throttles_unknown_username() {
+        let username = generate_string();
+
+        FailedLogin::record(service(), Some(&username), None, None).unwrap();
+
+        match FailedLogin::check(service(), Some(&username), None) {
+            Err(Error::LoginThrottledError) => (),
+            _ => panic!("Allowed attempt for throttled username"),
+        }
+
+        FailedLogin::clear(service(), &username).unwrap();
+    }
+
+    #[test]
+    fn check_throttles_address() {
+        let ip_address = generate_string();
+
+        FailedLogin::record(service(), None, None, Some(&ip_address)).unwrap();
+
+        match FailedLogin::check(service(), None, Some(&ip_address)) {
+            Err(Error::LoginThrottledError) => (),
+            _ => panic!("Allowed attempt from throttled address"),
+        }
+
+        service()
+            .store()
+            .delete_failed_login(&format!("{}{}", IP_SUBJECT, ip_address))
+            .unwrap();
+    }
+}
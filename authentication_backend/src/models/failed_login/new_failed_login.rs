@@ -0,0 +1,76 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use chrono::NaiveDateTime;
+use service::AuthService;
+use error::Result;
+use schema::failed_logins;
+use models::FailedLogin;
+
+#[derive(Debug, Insertable)]
+#[table_name = "failed_logins"]
+pub struct NewFailedLogin {
+    subject: String,
+    user_id: Option<i32>,
+    failures: i32,
+    last_failed_at: NaiveDateTime,
+    locked_until: Option<NaiveDateTime>,
+}
+
+impl NewFailedLogin {
+    pub fn new(
+        subject: &str,
+        user_id: Option<i32>,
+        failures: i32,
+        last_failed_at: NaiveDateTime,
+        locked_until: Option<NaiveDateTime>,
+    ) -> Self {
+        NewFailedLogin {
+            subject: subject.to_owned(),
+            user_id: user_id,
+            failures: failures,
+            last_failed_at: last_failed_at,
+            locked_until: locked_until,
+        }
+    }
+
+    pub(crate) fn subject(&self) -> &str {
+        &self.subject
+    }
+
+    pub(crate) fn user_id(&self) -> Option<i32> {
+        self.user_id
+    }
+
+    pub(crate) fn failures(&self) -> i32 {
+        self.failures
+    }
+
+    pub(crate) fn last_failed_at(&self) -> NaiveDateTime {
+        self.last_failed_at
+    }
+
+    pub(crate) fn locked_until(&self) -> Option<NaiveDateTime> {
+        self.locked_until
+    }
+
+    pub fn save(&self, service: &AuthService) -> Result<FailedLogin> {
+        service.store().create_failed_login(self)
+    }
+}
@@ -21,6 +21,14 @@ mod user;
 mod verification_code;
 mod permission;
 mod user_permission;
+mod token_family;
+mod session;
+mod totp_credential;
+mod recovery_code;
+mod webauthn_credential;
+mod webauthn_challenge;
+mod password_reset;
+mod failed_login;
 
 #[cfg(feature = "test")]
 pub use self::user::test_helper as user_test_helper;
@@ -28,8 +36,19 @@ pub use self::user::test_helper as user_test_helper;
 pub use self::user_permission::test_helper as user_permission_test_helper;
 #[cfg(feature = "test")]
 pub use self::permission::test_helper as permission_test_helper;
+#[cfg(feature = "test")]
+pub use self::totp_credential::test_helper as totp_credential_test_helper;
 
-pub use self::user::{Admin, Authenticated, AuthenticatedThisSession, User, UserTrait};
-pub use self::verification_code::VerificationCode;
-pub use self::permission::Permission;
-pub use self::user_permission::UserPermission;
+pub use self::user::{Admin, Authenticated, AuthenticatedThisSession, NewUser, User, UserTrait};
+pub use self::verification_code::{NewVerificationCode, VerificationCode};
+pub use self::permission::{NewPermission, Permission};
+pub use self::user_permission::{NewUserPermission, UserPermission};
+pub use self::token_family::{NewTokenFamily, TokenFamily};
+pub use self::session::{ClientInfo, NewSession, Session};
+pub use self::totp_credential::{NewTotpCredential, SecondFactor, TotpCredential, TotpEnrollment,
+                                TotpStatus};
+pub use self::recovery_code::{NewRecoveryCode, RecoveryCode, RecoveryCodes};
+pub use self::webauthn_credential::{NewWebauthnCredential, WebauthnCredential};
+pub use self::webauthn_challenge::{NewWebauthnChallenge, WebauthnChallenge};
+pub use self::password_reset::{NewPasswordReset, PasswordReset};
+pub use self::failed_login::{FailedLogin, NewFailedLogin};
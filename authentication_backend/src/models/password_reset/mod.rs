@@ -0,0 +1,248 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+mod new_password_reset;
+
+pub use self::new_password_reset::NewPasswordReset;
+
+use chrono::{NaiveDateTime, Utc};
+use ring::digest;
+use service::AuthService;
+use schema::password_resets;
+use webtoken::Webtoken;
+use models::user::{validate_password, User, UserTrait};
+use error::{DbError, Error, Result};
+
+const TOKEN_LEN: usize = 32;
+
+#[derive(Debug, Queryable, Identifiable, Associations)]
+#[table_name = "password_resets"]
+#[belongs_to(User)]
+pub struct PasswordReset {
+    id: i32,
+    user_id: i32,
+    token_hash: String,
+    expires_at: NaiveDateTime,
+}
+
+impl PasswordReset {
+    pub(crate) fn from_parts(
+        id: i32,
+        user_id: i32,
+        token_hash: String,
+        expires_at: NaiveDateTime,
+    ) -> Self {
+        PasswordReset {
+            id: id,
+            user_id: user_id,
+            token_hash: token_hash,
+            expires_at: expires_at,
+        }
+    }
+
+    pub fn create<T>(service: &AuthService, user: &T) -> Result<String>
+    where
+        T: UserTrait,
+    {
+        service.store().delete_password_resets_by_user_id(user.id())?;
+
+        let token = generate_token()?;
+        let expires_at = Utc::now().naive_utc() + service.token_lifetimes().password_reset();
+
+        NewPasswordReset::new(user, &hash(&token), expires_at).save(service)?;
+
+        Ok(token)
+    }
+
+    // Each token can be used once. Whoever knew the old password may still be logged in, so
+    // every token issued to the user is revoked.
+    pub fn confirm(service: &AuthService, token: &str, new_pass: &str) -> Result<User> {
+        let token_hash = hash(token);
+
+        let password_reset = match service.store().find_password_reset(&token_hash) {
+            Ok(password_reset) => password_reset,
+            Err(Error::DbError(DbError::NotFound)) => return Err(Error::PasswordResetError),
+            Err(err) => return Err(err),
+        };
+
+        if password_reset.is_expired() {
+            service.store().delete_password_reset(&token_hash)?;
+
+            return Err(Error::PasswordResetError);
+        }
+
+        let user = User::find_by_id(service, password_reset.user_id)?;
+
+        let new_pass = validate_password(service, user.username(), new_pass)?;
+
+        if !service.store().delete_password_reset(&token_hash)? {
+            return Err(Error::PasswordResetError);
+        }
+
+        let hash = service.password_hasher().hash(new_pass)?;
+
+        service.store().update_password(user.id(), &hash)?;
+
+        Webtoken::revoke_all(service, &user)?;
+
+        Ok(user)
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expires_at <= Utc::now().naive_utc()
+    }
+
+    pub fn id(&self) -> i32 {
+        self.id
+    }
+
+    pub fn user_id(&self) -> i32 {
+        self.user_id
+    }
+
+    pub fn token_hash(&self) -> &str {
+        &self.token_hash
+    }
+
+    pub fn expires_at(&self) -> NaiveDateTime {
+        self.expires_at
+    }
+}
+
+fn generate_token() -> Result<String> {
+    use rand::Rng;
+    use rand::OsRng;
+
+    let mut os_rng = OsRng::new()?;
+
+    Ok(os_rng.gen_ascii_chars().take(TOKEN_LEN).collect())
+}
+
+fn hash(token: &str) -> String {
+    digest::digest(&digest::SHA256, token.as_bytes())
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use authenticatable::Authenticatable;
+    use models::user::test_helper::with_user;
+    use test_helper::{service, test_password};
+
+    const NEW_PASSWORD: &str = "N3w-Passw0rd";
+
+    #[test]
+    fn confirm_sets_new_password() {
+        with_user(|user| {
+            let token = PasswordReset::create(service(), &user).expect("Failed to create reset");
+
+            let result = PasswordReset::confirm(service(), &token, NEW_PASSWORD);
+
+            assert!(result.is_ok(), "Failed to confirm password reset");
+
+            let user = User::find_by_id(service(), user.id()).unwrap();
+
+            assert!(
+                user.verify_password(service(), NEW_PASSWORD).unwrap(),
+                "Password was not changed"
+            );
+            assert!(
+                !user.verify_password(service(), test_password()).unwrap(),
+                "Old password still works"
+            );
+        });
+    }
+
+    #[test]
+    fn confirm_accepts_each_token_once() {
+        with_user(|user| {
+            let token = PasswordReset::create(service(), &user).expect("Failed to create reset");
+
+            let _ = PasswordReset::confirm(service(), &token, NEW_PASSWORD).unwrap();
+            let result = PasswordReset::confirm(service(), &token, NEW_PASSWORD);
+
+            assert!(!result.is_ok(), "Accepted reset token twice");
+        });
+    }
+
+    #[test]
+    fn confirm_keeps_token_after_weak_password() {
+        with_user(|user| {
+            let token = PasswordReset::create(service(), &user).expect("Failed to create reset");
+
+            let result = PasswordReset::confirm(service(), &token, "weak");
+
+            assert!(!result.is_ok(), "Accepted weak password");
+
+            let result = PasswordReset::confirm(service(), &token, NEW_PASSWORD);
+
+            assert!(result.is_ok(), "Weak password used up reset token");
+        });
+    }
+
+    #[test]
+    fn confirm_fails_with_expired_token() {
+        with_user(|user| {
+            let token = "expired-password-reset-token";
+            let expires_at = Utc::now().naive_utc() - Duration::minutes(1);
+
+            let _ = NewPasswordReset::new(&user, &hash(token), expires_at)
+                .save(service())
+                .expect("Failed to save expired reset");
+
+            let result = PasswordReset::confirm(service(), token, NEW_PASSWORD);
+
+            assert!(!result.is_ok(), "Accepted expired reset token");
+        });
+    }
+
+    #[test]
+    fn create_replaces_old_token() {
+        with_user(|user| {
+            let token = PasswordReset::create(service(), &user).expect("Failed to create reset");
+            let _ = PasswordReset::create(service(), &user).expect("Failed to create reset");
+
+            let result = PasswordReset::confirm(service(), &token, NEW_PASSWORD);
+
+            assert!(!result.is_ok(), "Accepted replaced reset token");
+        });
+    }
+
+    #[test]
+    fn confirm_revokes_tokens() {
+        with_user(|user| {
+            let webtoken = Webtoken::create(service(), &user, None).unwrap();
+            let token = PasswordReset::create(service(), &user).expect("Failed to create reset");
+
+            let _ = PasswordReset::confirm(service(), &token, NEW_PASSWORD).unwrap();
+
+            let auth = Authenticatable::UserToken { user_token: webtoken.user_token() };
+
+            assert!(
+                !User::authenticate(service(), &auth).is_ok(),
+                "User token still valid after password reset"
+            );
+        });
+    }
+}
@@ -0,0 +1,62 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use chrono::NaiveDateTime;
+use service::AuthService;
+use error::Result;
+use schema::password_resets;
+use models::PasswordReset;
+use models::user::UserTrait;
+
+#[derive(Debug, Insertable)]
+#[table_name = "password_resets"]
+pub struct NewPasswordReset {
+    user_id: i32,
+    token_hash: String,
+    expires_at: NaiveDateTime,
+}
+
+impl NewPasswordReset {
+    pub fn new<T>(user: &T, token_hash: &str, expires_at: NaiveDateTime) -> Self
+    where
+        T: UserTrait,
+    {
+        NewPasswordReset {
+            user_id: user.id(),
+            token_hash: token_hash.to_owned(),
+            expires_at: expires_at,
+        }
+    }
+
+    pub(crate) fn user_id(&self) -> i32 {
+        self.user_id
+    }
+
+    pub(crate) fn token_hash(&self) -> &str {
+        &self.token_hash
+    }
+
+    pub(crate) fn expires_at(&self) -> NaiveDateTime {
+        self.expires_at
+    }
+
+    pub fn save(&self, service: &AuthService) -> Result<PasswordReset> {
+        service.store().create_password_reset(self)
+    }
+}
@@ -24,7 +24,7 @@ pub use self::new_permission::NewPermission;
 #[cfg(feature = "test")]
 pub mod test_helper;
 
-use CONFIG;
+use service::AuthService;
 use schema::permissions;
 use error::Result;
 
@@ -35,10 +35,14 @@ pub struct Permission {
 }
 
 impl Permission {
-    pub fn create(name: &str) -> Result<Self> {
+    pub(crate) fn from_parts(id: i32, name: String) -> Self {
+        Permission { id: id, name: name }
+    }
+
+    pub fn create(service: &AuthService, name: &str) -> Result<Self> {
         let new_permission = NewPermission::new(name)?;
 
-        new_permission.save()
+        new_permission.save(service)
     }
 
     pub fn id(&self) -> i32 {
@@ -49,27 +53,12 @@ impl Permission {
         &self.name
     }
 
-    pub fn find(permission: &str) -> Result<Self> {
-        use diesel::prelude::*;
-        use schema::permissions::dsl::*;
-
-        let db = CONFIG.db()?;
-
-        let permission = permissions
-            .filter(name.eq(permission))
-            .first::<Permission>(db.conn())?;
-
-        Ok(permission)
+    pub fn find(service: &AuthService, permission: &str) -> Result<Self> {
+        service.store().find_permission(permission)
     }
 
-    pub fn delete(permission: &str) -> Result<()> {
-        use diesel::delete;
-        use diesel::prelude::*;
-        use schema::permissions::dsl::*;
-
-        let db = CONFIG.db()?;
-
-        let _ = delete(permissions.filter(name.eq(permission))).execute(db.conn());
+    pub fn delete(service: &AuthService, permission: &str) -> Result<()> {
+        let _ = service.store().delete_permission(permission);
 
         Ok(())
     }
@@ -78,30 +67,30 @@ impl Permission {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use test_helper::generate_string;
-    use models::permission::test_helper::teardown;
+    use test_helper::{generate_string, service};
+    use models::permission::test_helper::teardown_by_name;
 
     #[test]
     fn create_creates_permission() {
-        let result = Permission::create(&generate_string());
+        let result = Permission::create(service(), &generate_string());
 
         assert!(result.is_ok(), "Failed to create permission");
 
         if let Ok(permission) = result {
-            teardown(permission.id);
+            teardown_by_name(permission.name());
         }
     }
 
     #[test]
     fn find_finds_admin_permission() {
-        let result = Permission::find("admin");
+        let result = Permission::find(service(), "admin");
 
         assert!(result.is_ok(), "admin permission not found");
     }
 
     #[test]
     fn find_doesnt_find_fake_permission() {
-        let result = Permission::find("This is not a permission");
+        let result = Permission::find(service(), "This is not a permission");
 
         assert!(!result.is_ok(), "Fake permission found");
     }
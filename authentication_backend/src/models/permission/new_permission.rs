@@ -17,9 +17,7 @@
  * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
  */
 
-use diesel;
-use diesel::prelude::*;
-use CONFIG;
+use service::AuthService;
 use error::Result;
 use error::Error::InputError;
 use error::InputErrorKind::PermissionName;
@@ -39,12 +37,12 @@ impl NewPermission {
         Ok(NewPermission { name: name.to_string() })
     }
 
-    pub fn save(&self) -> Result<Permission> {
-        let db = CONFIG.db()?;
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
 
-        Ok(diesel::insert(self).into(permissions::table).get_result(
-            db.conn(),
-        )?)
+    pub fn save(&self, service: &AuthService) -> Result<Permission> {
+        service.store().create_permission(self)
     }
 
     fn validate_name(name: &str) -> Result<&str> {
@@ -59,8 +57,8 @@ impl NewPermission {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use models::permission::test_helper::teardown;
-    use test_helper::generate_string;
+    use models::permission::test_helper::teardown_by_name;
+    use test_helper::{generate_string, service};
 
     #[test]
     fn new_creates_new_permission() {
@@ -80,12 +78,12 @@ mod tests {
     fn save_saves_new_permission() {
         let new_permission = NewPermission::new(&generate_string()).unwrap();
 
-        let result = new_permission.save();
+        let result = new_permission.save(service());
 
         assert!(result.is_ok(), "Failed to save new_permission");
 
         if let Ok(permission) = result {
-            teardown(permission.id());
+            teardown_by_name(permission.name());
         }
     }
 
@@ -93,14 +91,14 @@ mod tests {
     fn save_fails_with_duplicate_name() {
         let new_permission = NewPermission::new(&generate_string()).unwrap();
 
-        let permission_one = new_permission.save();
-        let permission_two = new_permission.save();
+        let permission_one = new_permission.save(service());
+        let permission_two = new_permission.save(service());
 
         assert!(permission_one.is_ok(), "Failed to create permission");
         assert!(!permission_two.is_ok(), "Created duplicate permission");
 
         if let Ok(permission) = permission_one {
-            teardown(permission.id());
+            teardown_by_name(permission.name());
         }
     }
 }
@@ -18,9 +18,6 @@
  */
 
 use std::panic;
-use diesel;
-use diesel::prelude::*;
-use CONFIG;
 use test_helper::*;
 use super::Permission;
 use super::new_permission::NewPermission;
@@ -31,23 +28,14 @@ where
 {
     let new_permission =
         NewPermission::new(&generate_string()).expect("Failed to create New Permission");
-    let permission = new_permission.save().expect("Failed to save Permission");
+    let permission = new_permission.save(service()).expect("Failed to save Permission");
 
-    let p_id = permission.id();
+    let p_name = permission.name().to_owned();
     let result = panic::catch_unwind(|| test(permission));
-    teardown(p_id);
+    teardown_by_name(&p_name);
     result.unwrap();
 }
 
-pub fn teardown(p_id: i32) -> () {
-    use schema::permissions::dsl::*;
-
-    let _ = diesel::delete(permissions.filter(id.eq(p_id))).execute(CONFIG.db().unwrap().conn());
-}
-
 pub fn teardown_by_name(p_name: &str) -> () {
-    use schema::permissions::dsl::*;
-
-    let _ = diesel::delete(permissions.filter(name.eq(p_name)))
-        .execute(CONFIG.db().unwrap().conn());
+    let _ = service().store().delete_permission(p_name);
 }
@@ -0,0 +1,198 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+mod new_recovery_code;
+mod recovery_codes;
+
+pub use self::new_recovery_code::NewRecoveryCode;
+pub use self::recovery_codes::RecoveryCodes;
+
+use ring::digest;
+use service::AuthService;
+use schema::recovery_codes;
+use models::user::{User, UserTrait};
+use error::{Error, MfaErrorKind, Result};
+
+const CODE_COUNT: usize = 10;
+const CODE_LEN: usize = 16;
+const GROUP_LEN: usize = 4;
+const ALPHABET: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+
+#[derive(Debug, Queryable, Identifiable, Associations)]
+#[table_name = "recovery_codes"]
+#[belongs_to(User)]
+pub struct RecoveryCode {
+    id: i32,
+    user_id: i32,
+    code_hash: String,
+}
+
+impl RecoveryCode {
+    pub(crate) fn from_parts(id: i32, user_id: i32, code_hash: String) -> Self {
+        RecoveryCode {
+            id: id,
+            user_id: user_id,
+            code_hash: code_hash,
+        }
+    }
+
+    pub fn generate<T>(service: &AuthService, user: &T) -> Result<RecoveryCodes>
+    where
+        T: UserTrait,
+    {
+        service.store().delete_recovery_codes_by_user_id(user.id())?;
+
+        let mut codes = Vec::with_capacity(CODE_COUNT);
+
+        for _ in 0..CODE_COUNT {
+            let code = generate_code()?;
+
+            NewRecoveryCode::new(user, &hash(&code)).save(service)?;
+
+            codes.push(code);
+        }
+
+        Ok(RecoveryCodes::new(codes))
+    }
+
+    pub fn redeem<T>(service: &AuthService, user: &T, code: &str) -> Result<()>
+    where
+        T: UserTrait,
+    {
+        if service.store().delete_recovery_code(user.id(), &hash(code))? {
+            Ok(())
+        } else {
+            Err(Error::MfaError(MfaErrorKind::InvalidCode))
+        }
+    }
+
+    pub fn remaining<T>(service: &AuthService, user: &T) -> Result<usize>
+    where
+        T: UserTrait,
+    {
+        service.store().count_recovery_codes_by_user_id(user.id())
+    }
+
+    pub fn delete_all<T>(service: &AuthService, user: &T) -> Result<()>
+    where
+        T: UserTrait,
+    {
+        service.store().delete_recovery_codes_by_user_id(user.id())
+    }
+
+    pub fn id(&self) -> i32 {
+        self.id
+    }
+
+    pub fn user_id(&self) -> i32 {
+        self.user_id
+    }
+
+    pub fn code_hash(&self) -> &str {
+        &self.code_hash
+    }
+}
+
+fn generate_code() -> Result<String> {
+    use rand::Rng;
+    use rand::OsRng;
+
+    let mut os_rng = OsRng::new()?;
+    let mut code = String::new();
+
+    for i in 0..CODE_LEN {
+        if i > 0 && i % GROUP_LEN == 0 {
+            code.push('-');
+        }
+
+        code.push(ALPHABET[os_rng.gen_range(0, ALPHABET.len())] as char);
+    }
+
+    Ok(code)
+}
+
+fn hash(code: &str) -> String {
+    let normalized: String = code.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(|c| c.to_lowercase())
+        .collect();
+
+    digest::digest(&digest::SHA256, normalized.as_bytes())
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use models::user::test_helper::with_user;
+    use test_helper::service;
+
+    #[test]
+    fn redeem_accepts_each_code_once() {
+        with_user(|user| {
+            let codes = RecoveryCode::generate(service(), &user).expect("Failed to generate codes");
+            let code = codes.codes()[0].clone();
+
+            let result = RecoveryCode::redeem(service(), &user, &code);
+
+            assert!(result.is_ok(), "Rejected recovery code");
+            assert_eq!(
+                RecoveryCode::remaining(service(), &user).unwrap(),
+                CODE_COUNT - 1,
+                "Used code still counted"
+            );
+
+            let result = RecoveryCode::redeem(service(), &user, &code);
+
+            assert!(!result.is_ok(), "Accepted recovery code twice");
+        });
+    }
+
+    #[test]
+    fn redeem_ignores_formatting() {
+        with_user(|user| {
+            let codes = RecoveryCode::generate(service(), &user).expect("Failed to generate codes");
+            let code = codes.codes()[0].replace('-', " ").to_uppercase();
+
+            let result = RecoveryCode::redeem(service(), &user, &code);
+
+            assert!(result.is_ok(), "Rejected reformatted recovery code");
+        });
+    }
+
+    #[test]
+    fn generate_replaces_old_codes() {
+        with_user(|user| {
+            let codes = RecoveryCode::generate(service(), &user).expect("Failed to generate codes");
+            let _ = RecoveryCode::generate(service(), &user).expect("Failed to generate codes");
+
+            let result = RecoveryCode::redeem(service(), &user, &codes.codes()[0]);
+
+            assert!(!result.is_ok(), "Accepted replaced recovery code");
+            assert_eq!(
+                RecoveryCode::remaining(service(), &user).unwrap(),
+                CODE_COUNT,
+                "Replaced codes still counted"
+            );
+        });
+    }
+}
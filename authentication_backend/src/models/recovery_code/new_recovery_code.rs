@@ -0,0 +1,55 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use service::AuthService;
+use error::Result;
+use schema::recovery_codes;
+use models::RecoveryCode;
+use models::user::UserTrait;
+
+#[derive(Debug, Insertable)]
+#[table_name = "recovery_codes"]
+pub struct NewRecoveryCode {
+    user_id: i32,
+    code_hash: String,
+}
+
+impl NewRecoveryCode {
+    pub fn new<T>(user: &T, code_hash: &str) -> Self
+    where
+        T: UserTrait,
+    {
+        NewRecoveryCode {
+            user_id: user.id(),
+            code_hash: code_hash.to_owned(),
+        }
+    }
+
+    pub(crate) fn user_id(&self) -> i32 {
+        self.user_id
+    }
+
+    pub(crate) fn code_hash(&self) -> &str {
+        &self.code_hash
+    }
+
+    pub fn save(&self, service: &AuthService) -> Result<RecoveryCode> {
+        service.store().create_recovery_code(self)
+    }
+}
@@ -0,0 +1,48 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+const MAX_USER_AGENT_LENGTH: usize = 512;
+const MAX_IP_ADDRESS_LENGTH: usize = 45;
+
+#[derive(Clone, Debug, Default)]
+pub struct ClientInfo {
+    user_agent: Option<String>,
+    ip_address: Option<String>,
+}
+
+impl ClientInfo {
+    pub fn new(user_agent: Option<&str>, ip_address: Option<&str>) -> Self {
+        ClientInfo {
+            user_agent: user_agent.map(|user_agent| truncate(user_agent, MAX_USER_AGENT_LENGTH)),
+            ip_address: ip_address.map(|ip_address| truncate(ip_address, MAX_IP_ADDRESS_LENGTH)),
+        }
+    }
+
+    pub fn user_agent(&self) -> Option<&str> {
+        self.user_agent.as_ref().map(|user_agent| user_agent.as_str())
+    }
+
+    pub fn ip_address(&self) -> Option<&str> {
+        self.ip_address.as_ref().map(|ip_address| ip_address.as_str())
+    }
+}
+
+fn truncate(s: &str, length: usize) -> String {
+    s.chars().take(length).collect()
+}
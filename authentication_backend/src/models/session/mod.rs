@@ -0,0 +1,186 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+mod client_info;
+mod new_session;
+
+pub use self::client_info::ClientInfo;
+pub use self::new_session::NewSession;
+
+use chrono::{NaiveDateTime, Utc};
+use service::AuthService;
+use schema::sessions;
+use models::TokenFamily;
+use models::user::{User, UserTrait};
+use error::Result;
+
+#[derive(Debug, Queryable, Identifiable, Associations)]
+#[table_name = "sessions"]
+#[belongs_to(User)]
+pub struct Session {
+    id: i32,
+    user_id: i32,
+    family: String,
+    user_agent: Option<String>,
+    ip_address: Option<String>,
+    created_at: NaiveDateTime,
+    last_renewed_at: Option<NaiveDateTime>,
+}
+
+impl Session {
+    pub(crate) fn from_parts(
+        id: i32,
+        user_id: i32,
+        family: String,
+        user_agent: Option<String>,
+        ip_address: Option<String>,
+        created_at: NaiveDateTime,
+        last_renewed_at: Option<NaiveDateTime>,
+    ) -> Self {
+        Session {
+            id: id,
+            user_id: user_id,
+            family: family,
+            user_agent: user_agent,
+            ip_address: ip_address,
+            created_at: created_at,
+            last_renewed_at: last_renewed_at,
+        }
+    }
+
+    pub fn create<T>(
+        service: &AuthService,
+        user: &T,
+        family: &str,
+        client: &ClientInfo,
+    ) -> Result<Self>
+    where
+        T: UserTrait,
+    {
+        let new_session = NewSession::new(user, family, client);
+
+        new_session.save(service)
+    }
+
+    pub fn find(service: &AuthService, id: i32) -> Result<Self> {
+        service.store().find_session(id)
+    }
+
+    pub fn find_active<T>(service: &AuthService, user: &T) -> Result<Vec<Self>>
+    where
+        T: UserTrait,
+    {
+        let sessions = service.store().find_sessions_by_user_id(user.id())?;
+
+        Ok(
+            sessions
+                .into_iter()
+                .filter(|session| {
+                    TokenFamily::check(service, &session.family).is_ok()
+                })
+                .collect(),
+        )
+    }
+
+    pub fn touch(service: &AuthService, id: i32) -> Result<()> {
+        service.store().touch_session(id, Utc::now().naive_utc())
+    }
+
+    pub fn revoke(&self, service: &AuthService) -> Result<()> {
+        TokenFamily::revoke(service, &self.family)
+    }
+
+    pub fn id(&self) -> i32 {
+        self.id
+    }
+
+    pub fn user_id(&self) -> i32 {
+        self.user_id
+    }
+
+    pub fn family(&self) -> &str {
+        &self.family
+    }
+
+    pub fn user_agent(&self) -> Option<&str> {
+        self.user_agent.as_ref().map(|user_agent| user_agent.as_str())
+    }
+
+    pub fn ip_address(&self) -> Option<&str> {
+        self.ip_address.as_ref().map(|ip_address| ip_address.as_str())
+    }
+
+    pub fn created_at(&self) -> NaiveDateTime {
+        self.created_at
+    }
+
+    pub fn last_renewed_at(&self) -> Option<NaiveDateTime> {
+        self.last_renewed_at
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use models::user::test_helper::with_user;
+    use test_helper::{generate_string, service};
+
+    #[test]
+    fn find_active_skips_revoked_sessions() {
+        with_user(|user| {
+            let family = generate_string();
+            let family_2 = generate_string();
+            let session = Session::create(service(), &user, &family, &ClientInfo::default())
+                .expect("Failed to create Session");
+            let _ = Session::create(service(), &user, &family_2, &ClientInfo::default())
+                .expect("Failed to create Session");
+            let _ = TokenFamily::create(service(), &user, &family, "first")
+                .expect("Failed to create TokenFamily");
+            let _ = TokenFamily::create(service(), &user, &family_2, "first")
+                .expect("Failed to create TokenFamily");
+
+            let _ = session.revoke(service()).expect("Failed to revoke Session");
+
+            let sessions = Session::find_active(service(), &user).expect("Failed to find Sessions");
+
+            assert_eq!(sessions.len(), 1, "Found wrong number of Sessions");
+            assert_eq!(sessions[0].family(), family_2, "Found revoked Session");
+        });
+    }
+
+    #[test]
+    fn touch_records_renewal() {
+        with_user(|user| {
+            let session = Session::create(
+                service(),
+                &user,
+                &generate_string(),
+                &ClientInfo::default(),
+            ).expect("Failed to create Session");
+
+            assert!(session.last_renewed_at().is_none(), "New Session was renewed");
+
+            let _ = Session::touch(service(), session.id()).expect("Failed to touch Session");
+
+            let session = Session::find(service(), session.id()).unwrap();
+
+            assert!(session.last_renewed_at().is_some(), "Session renewal not recorded");
+        });
+    }
+}
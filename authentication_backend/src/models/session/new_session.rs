@@ -0,0 +1,111 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use chrono::{NaiveDateTime, Utc};
+use service::AuthService;
+use error::Result;
+use schema::sessions;
+use models::Session;
+use models::user::UserTrait;
+use super::ClientInfo;
+
+#[derive(Debug, Insertable)]
+#[table_name = "sessions"]
+pub struct NewSession {
+    user_id: i32,
+    family: String,
+    user_agent: Option<String>,
+    ip_address: Option<String>,
+    created_at: NaiveDateTime,
+}
+
+impl NewSession {
+    pub fn new<T>(user: &T, family: &str, client: &ClientInfo) -> Self
+    where
+        T: UserTrait,
+    {
+        NewSession {
+            user_id: user.id(),
+            family: family.to_owned(),
+            user_agent: client.user_agent().map(|user_agent| user_agent.to_owned()),
+            ip_address: client.ip_address().map(|ip_address| ip_address.to_owned()),
+            created_at: Utc::now().naive_utc(),
+        }
+    }
+
+    pub(crate) fn user_id(&self) -> i32 {
+        self.user_id
+    }
+
+    pub(crate) fn family(&self) -> &str {
+        &self.family
+    }
+
+    pub(crate) fn user_agent(&self) -> Option<&str> {
+        self.user_agent.as_ref().map(|user_agent| user_agent.as_str())
+    }
+
+    pub(crate) fn ip_address(&self) -> Option<&str> {
+        self.ip_address.as_ref().map(|ip_address| ip_address.as_str())
+    }
+
+    pub(crate) fn created_at(&self) -> NaiveDateTime {
+        self.created_at
+    }
+
+    pub fn save(&self, service: &AuthService) -> Result<Session> {
+        service.store().create_session(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use models::user::test_helper::with_user;
+    use test_helper::{generate_string, service};
+
+    #[test]
+    fn save_saves_new_session() {
+        with_user(|user| {
+            let client = ClientInfo::new(Some("Firefox"), Some("127.0.0.1"));
+
+            let result = NewSession::new(&user, &generate_string(), &client).save(service());
+
+            assert!(result.is_ok(), "Failed to save NewSession");
+
+            let session = result.unwrap();
+
+            assert_eq!(session.user_agent(), Some("Firefox"), "Session has bad user agent");
+            assert_eq!(session.ip_address(), Some("127.0.0.1"), "Session has bad IP address");
+        });
+    }
+
+    #[test]
+    fn save_fails_with_duplicate_family() {
+        with_user(|user| {
+            let new_session = NewSession::new(&user, &generate_string(), &ClientInfo::default());
+
+            let result = new_session.save(service());
+            let result2 = new_session.save(service());
+
+            assert!(result.is_ok(), "Failed to save NewSession");
+            assert!(!result2.is_ok(), "Saved duplicate Session");
+        });
+    }
+}
@@ -0,0 +1,231 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+mod new_token_family;
+
+pub use self::new_token_family::NewTokenFamily;
+
+use service::AuthService;
+use schema::token_families;
+use models::user::{User, UserTrait};
+use error::{DbError, Error, Result};
+
+#[derive(Debug, Queryable, Identifiable, Associations)]
+#[table_name = "token_families"]
+#[belongs_to(User)]
+pub struct TokenFamily {
+    id: i32,
+    family: String,
+    user_id: i32,
+    current_jti: String,
+    revoked: bool,
+}
+
+impl TokenFamily {
+    pub(crate) fn from_parts(
+        id: i32,
+        family: String,
+        user_id: i32,
+        current_jti: String,
+        revoked: bool,
+    ) -> Self {
+        TokenFamily {
+            id: id,
+            family: family,
+            user_id: user_id,
+            current_jti: current_jti,
+            revoked: revoked,
+        }
+    }
+
+    pub fn create<T>(service: &AuthService, user: &T, family: &str, jti: &str) -> Result<Self>
+    where
+        T: UserTrait,
+    {
+        let new_token_family = NewTokenFamily::new(user, family, jti);
+
+        new_token_family.save(service)
+    }
+
+    pub fn find(service: &AuthService, family: &str) -> Result<Self> {
+        service.store().find_token_family(family)
+    }
+
+    // Tokens whose family is missing have outlived their user, so they count as revoked too
+    pub fn check(service: &AuthService, family: &str) -> Result<()> {
+        match TokenFamily::find(service, family) {
+            Ok(ref token_family) if !token_family.revoked => Ok(()),
+            Ok(_) |
+            Err(Error::DbError(DbError::NotFound)) => Err(Error::TokenRevokedError),
+            Err(err) => Err(err),
+        }
+    }
+
+    // Swaps the family's current jti for the next one. If the presented jti has already been
+    // rotated out, the token was replayed, so the whole family is revoked.
+    pub fn rotate(service: &AuthService, family: &str, jti: &str, next_jti: &str) -> Result<()> {
+        TokenFamily::check(service, family)?;
+
+        if service.store().rotate_token_family(family, jti, next_jti)? {
+            return Ok(());
+        }
+
+        let token_family = TokenFamily::find(service, family)?;
+
+        service.store().revoke_token_family(family)?;
+
+        Err(Error::TokenReuseError(token_family.user_id))
+    }
+
+    pub fn revoke(service: &AuthService, family: &str) -> Result<()> {
+        service.store().revoke_token_family(family)
+    }
+
+    pub fn revoke_all<T>(service: &AuthService, user: &T) -> Result<()>
+    where
+        T: UserTrait,
+    {
+        service.store().revoke_token_families_by_user_id(user.id())
+    }
+
+    pub fn id(&self) -> i32 {
+        self.id
+    }
+
+    pub fn family(&self) -> &str {
+        &self.family
+    }
+
+    pub fn user_id(&self) -> i32 {
+        self.user_id
+    }
+
+    pub fn current_jti(&self) -> &str {
+        &self.current_jti
+    }
+
+    pub fn is_revoked(&self) -> bool {
+        self.revoked
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use models::user::test_helper::with_user;
+    use test_helper::{generate_string, service};
+
+    #[test]
+    fn rotate_rotates_current_jti() {
+        with_user(|user| {
+            let family = generate_string();
+            let _ = TokenFamily::create(service(), &user, &family, "first")
+                .expect("Failed to create TokenFamily");
+
+            let result = TokenFamily::rotate(service(), &family, "first", "second");
+
+            assert!(result.is_ok(), "Failed to rotate TokenFamily");
+
+            let token_family = TokenFamily::find(service(), &family).unwrap();
+
+            assert_eq!(
+                token_family.current_jti(),
+                "second",
+                "TokenFamily has wrong current jti"
+            );
+        });
+    }
+
+    #[test]
+    fn rotate_revokes_family_on_reuse() {
+        with_user(|user| {
+            let family = generate_string();
+            let _ = TokenFamily::create(service(), &user, &family, "first")
+                .expect("Failed to create TokenFamily");
+
+            let _ = TokenFamily::rotate(service(), &family, "first", "second")
+                .expect("Failed to rotate TokenFamily");
+
+            let result = TokenFamily::rotate(service(), &family, "first", "third");
+
+            match result {
+                Err(Error::TokenReuseError(user_id)) => {
+                    assert_eq!(user_id, user.id(), "Reuse reported for wrong user")
+                }
+                _ => panic!("Reused jti was not rejected"),
+            }
+
+            let result = TokenFamily::rotate(service(), &family, "second", "third");
+
+            assert!(!result.is_ok(), "Rotated revoked TokenFamily");
+        });
+    }
+
+    #[test]
+    fn check_fails_with_revoked_family() {
+        with_user(|user| {
+            let family = generate_string();
+            let _ = TokenFamily::create(service(), &user, &family, "first")
+                .expect("Failed to create TokenFamily");
+
+            assert!(
+                TokenFamily::check(service(), &family).is_ok(),
+                "New TokenFamily is revoked"
+            );
+
+            let _ = TokenFamily::revoke(service(), &family).expect("Failed to revoke TokenFamily");
+
+            assert!(
+                !TokenFamily::check(service(), &family).is_ok(),
+                "Revoked TokenFamily passed check"
+            );
+        });
+    }
+
+    #[test]
+    fn revoke_all_revokes_every_family() {
+        with_user(|user| {
+            let family = generate_string();
+            let family_2 = generate_string();
+            let _ = TokenFamily::create(service(), &user, &family, "first")
+                .expect("Failed to create TokenFamily");
+            let _ = TokenFamily::create(service(), &user, &family_2, "first")
+                .expect("Failed to create TokenFamily");
+
+            let _ = TokenFamily::revoke_all(service(), &user)
+                .expect("Failed to revoke TokenFamilies");
+
+            assert!(
+                !TokenFamily::check(service(), &family).is_ok(),
+                "Revoked TokenFamily passed check"
+            );
+            assert!(
+                !TokenFamily::check(service(), &family_2).is_ok(),
+                "Revoked TokenFamily passed check"
+            );
+        });
+    }
+
+    #[test]
+    fn rotate_fails_with_unknown_family() {
+        let result = TokenFamily::rotate(service(), "not a family", "first", "second");
+
+        assert!(!result.is_ok(), "Rotated unknown TokenFamily");
+    }
+}
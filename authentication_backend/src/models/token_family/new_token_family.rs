@@ -0,0 +1,90 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use service::AuthService;
+use error::Result;
+use schema::token_families;
+use models::TokenFamily;
+use models::user::UserTrait;
+
+#[derive(Debug, Insertable)]
+#[table_name = "token_families"]
+pub struct NewTokenFamily {
+    family: String,
+    user_id: i32,
+    current_jti: String,
+}
+
+impl NewTokenFamily {
+    pub fn new<T>(user: &T, family: &str, jti: &str) -> Self
+    where
+        T: UserTrait,
+    {
+        NewTokenFamily {
+            family: family.to_owned(),
+            user_id: user.id(),
+            current_jti: jti.to_owned(),
+        }
+    }
+
+    pub(crate) fn family(&self) -> &str {
+        &self.family
+    }
+
+    pub(crate) fn user_id(&self) -> i32 {
+        self.user_id
+    }
+
+    pub(crate) fn current_jti(&self) -> &str {
+        &self.current_jti
+    }
+
+    pub fn save(&self, service: &AuthService) -> Result<TokenFamily> {
+        service.store().create_token_family(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use models::user::test_helper::with_user;
+    use test_helper::{generate_string, service};
+
+    #[test]
+    fn save_saves_new_token_family() {
+        with_user(|user| {
+            let result = NewTokenFamily::new(&user, &generate_string(), "jti").save(service());
+
+            assert!(result.is_ok(), "Failed to save NewTokenFamily");
+        });
+    }
+
+    #[test]
+    fn save_fails_with_duplicate_family() {
+        with_user(|user| {
+            let new_token_family = NewTokenFamily::new(&user, &generate_string(), "jti");
+
+            let result = new_token_family.save(service());
+            let result2 = new_token_family.save(service());
+
+            assert!(result.is_ok(), "Failed to save NewTokenFamily");
+            assert!(!result2.is_ok(), "Saved duplicate TokenFamily");
+        });
+    }
+}
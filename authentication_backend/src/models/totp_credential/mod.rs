@@ -0,0 +1,394 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+mod new_totp_credential;
+mod second_factor;
+mod totp;
+mod totp_enrollment;
+mod totp_status;
+
+#[cfg(feature = "test")]
+pub mod test_helper;
+
+pub use self::new_totp_credential::NewTotpCredential;
+pub use self::second_factor::SecondFactor;
+pub use self::totp_enrollment::TotpEnrollment;
+pub use self::totp_status::TotpStatus;
+
+use chrono::Utc;
+use config::secret_box::SecretBox;
+use service::AuthService;
+use schema::totp_credentials;
+use models::recovery_code::{RecoveryCode, RecoveryCodes};
+use models::user::{User, UserTrait};
+use error::{DbError, Error, MfaErrorKind, Result};
+
+#[derive(Debug, Queryable, Identifiable, Associations)]
+#[table_name = "totp_credentials"]
+#[belongs_to(User)]
+pub struct TotpCredential {
+    id: i32,
+    user_id: i32,
+    secret: Vec<u8>,
+    confirmed: bool,
+    last_used_step: Option<i64>,
+}
+
+impl TotpCredential {
+    pub(crate) fn from_parts(
+        id: i32,
+        user_id: i32,
+        secret: Vec<u8>,
+        confirmed: bool,
+        last_used_step: Option<i64>,
+    ) -> Self {
+        TotpCredential {
+            id: id,
+            user_id: user_id,
+            secret: secret,
+            confirmed: confirmed,
+            last_used_step: last_used_step,
+        }
+    }
+
+    pub fn enroll<T>(service: &AuthService, user: &T) -> Result<TotpEnrollment>
+    where
+        T: UserTrait,
+    {
+        let secret_box = secret_box(service)?;
+
+        match service.store().find_totp_credential_by_user_id(user.id()) {
+            Ok(ref credential) if credential.confirmed => {
+                return Err(Error::MfaError(MfaErrorKind::AlreadyEnabled))
+            }
+            Ok(_) => service.store().delete_totp_credential_by_user_id(user.id())?,
+            Err(Error::DbError(DbError::NotFound)) => (),
+            Err(err) => return Err(err),
+        }
+
+        let secret = generate_secret()?;
+        let sealed_secret = secret_box.seal(&secret, &associated_data(user.id()))?;
+
+        NewTotpCredential::new(user, sealed_secret).save(service)?;
+
+        let recovery_codes = RecoveryCode::generate(service, user)?;
+
+        Ok(TotpEnrollment::new(
+            &secret,
+            service.totp_issuer(),
+            user.username(),
+            recovery_codes,
+        ))
+    }
+
+    pub fn confirm<T>(service: &AuthService, user: &T, code: &str) -> Result<()>
+    where
+        T: UserTrait,
+    {
+        let credential = TotpCredential::find(service, user)?;
+
+        if credential.confirmed {
+            return Err(Error::MfaError(MfaErrorKind::AlreadyEnabled));
+        }
+
+        credential.check_code(service, code)?;
+
+        service.store().confirm_totp_credential(user.id())
+    }
+
+    pub fn verify<T>(service: &AuthService, user: &T, code: &str) -> Result<SecondFactor>
+    where
+        T: UserTrait,
+    {
+        let credential = TotpCredential::find_enabled(service, user)?;
+
+        match credential.check_code(service, code) {
+            Ok(()) => Ok(SecondFactor::Totp),
+            Err(Error::MfaError(MfaErrorKind::InvalidCode)) => {
+                RecoveryCode::redeem(service, user, code)?;
+
+                Ok(SecondFactor::RecoveryCode(user.id()))
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    pub fn regenerate_recovery_codes<T>(service: &AuthService, user: &T) -> Result<RecoveryCodes>
+    where
+        T: UserTrait,
+    {
+        TotpCredential::find_enabled(service, user)?;
+
+        RecoveryCode::generate(service, user)
+    }
+
+    pub fn status<T>(service: &AuthService, user: &T) -> Result<TotpStatus>
+    where
+        T: UserTrait,
+    {
+        let enabled = TotpCredential::is_enabled(service, user)?;
+        let remaining = if enabled {
+            RecoveryCode::remaining(service, user)?
+        } else {
+            0
+        };
+
+        Ok(TotpStatus::new(enabled, remaining))
+    }
+
+    pub fn is_enabled<T>(service: &AuthService, user: &T) -> Result<bool>
+    where
+        T: UserTrait,
+    {
+        match service.store().find_totp_credential_by_user_id(user.id()) {
+            Ok(credential) => Ok(credential.confirmed),
+            Err(Error::DbError(DbError::NotFound)) => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+
+    pub fn disable<T>(service: &AuthService, user: &T) -> Result<()>
+    where
+        T: UserTrait,
+    {
+        RecoveryCode::delete_all(service, user)?;
+
+        service.store().delete_totp_credential_by_user_id(user.id())
+    }
+
+    fn find<T>(service: &AuthService, user: &T) -> Result<Self>
+    where
+        T: UserTrait,
+    {
+        match service.store().find_totp_credential_by_user_id(user.id()) {
+            Err(Error::DbError(DbError::NotFound)) => {
+                Err(Error::MfaError(MfaErrorKind::NotEnrolled))
+            }
+            result => result,
+        }
+    }
+
+    fn find_enabled<T>(service: &AuthService, user: &T) -> Result<Self>
+    where
+        T: UserTrait,
+    {
+        let credential = TotpCredential::find(service, user)?;
+
+        if credential.confirmed {
+            Ok(credential)
+        } else {
+            Err(Error::MfaError(MfaErrorKind::NotEnrolled))
+        }
+    }
+
+    // Each code is accepted once. The time step of the last accepted code is recorded, and codes
+    // from that step or earlier are rejected, so an intercepted code can't be replayed.
+    fn check_code(&self, service: &AuthService, code: &str) -> Result<()> {
+        let secret = secret_box(service)?.open(
+            &self.secret,
+            &associated_data(self.user_id),
+        )?;
+
+        let step = match totp::matching_step(&secret, code.trim(), Utc::now()) {
+            Some(step) => step,
+            None => return Err(Error::MfaError(MfaErrorKind::InvalidCode)),
+        };
+
+        if service.store().use_totp_step(self.user_id, step)? {
+            Ok(())
+        } else {
+            Err(Error::MfaError(MfaErrorKind::InvalidCode))
+        }
+    }
+
+    pub fn id(&self) -> i32 {
+        self.id
+    }
+
+    pub fn user_id(&self) -> i32 {
+        self.user_id
+    }
+
+    pub fn is_confirmed(&self) -> bool {
+        self.confirmed
+    }
+
+    pub fn last_used_step(&self) -> Option<i64> {
+        self.last_used_step
+    }
+}
+
+fn secret_box(service: &AuthService) -> Result<&SecretBox> {
+    service.totp_secret_box().ok_or(
+        Error::MfaError(MfaErrorKind::Unavailable),
+    )
+}
+
+fn generate_secret() -> Result<Vec<u8>> {
+    use rand::Rng;
+    use rand::OsRng;
+
+    let mut os_rng = OsRng::new()?;
+    let mut secret = vec![0; totp::SECRET_LEN];
+
+    os_rng.fill_bytes(&mut secret);
+
+    Ok(secret)
+}
+
+fn associated_data(user_id: i32) -> Vec<u8> {
+    user_id.to_string().into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::test_helper::{current_code, decode_base32, next_code};
+    use models::user::test_helper::with_user;
+    use test_helper::service;
+
+    #[test]
+    fn confirm_enables_two_factor_authentication() {
+        with_user(|user| {
+            let enrollment = TotpCredential::enroll(service(), &user).expect("Failed to enroll");
+
+            assert!(
+                !TotpCredential::is_enabled(service(), &user).unwrap(),
+                "Enabled before confirming"
+            );
+
+            let code = current_code(enrollment.secret());
+
+            let result = TotpCredential::confirm(service(), &user, &code);
+
+            assert!(result.is_ok(), "Failed to confirm enrollment");
+            assert!(
+                TotpCredential::is_enabled(service(), &user).unwrap(),
+                "Not enabled after confirming"
+            );
+        });
+    }
+
+    #[test]
+    fn confirm_fails_with_wrong_code() {
+        with_user(|user| {
+            let _ = TotpCredential::enroll(service(), &user).expect("Failed to enroll");
+
+            let result = TotpCredential::confirm(service(), &user, "000000x");
+
+            assert!(!result.is_ok(), "Confirmed with wrong code");
+        });
+    }
+
+    #[test]
+    fn verify_rejects_replayed_code() {
+        with_user(|user| {
+            let enrollment = TotpCredential::enroll(service(), &user).expect("Failed to enroll");
+            let _ = TotpCredential::confirm(service(), &user, &current_code(enrollment.secret()))
+                .expect("Failed to confirm enrollment");
+
+            let code = next_code(enrollment.secret());
+
+            let result = TotpCredential::verify(service(), &user, &code);
+
+            assert!(result.is_ok(), "Rejected valid code");
+
+            let result = TotpCredential::verify(service(), &user, &code);
+
+            assert!(!result.is_ok(), "Accepted replayed code");
+
+            let code = current_code(enrollment.secret());
+
+            let result = TotpCredential::verify(service(), &user, &code);
+
+            assert!(!result.is_ok(), "Accepted code from an earlier time step");
+        });
+    }
+
+    #[test]
+    fn enroll_fails_when_enabled() {
+        with_user(|user| {
+            let enrollment = TotpCredential::enroll(service(), &user).expect("Failed to enroll");
+            let _ = TotpCredential::confirm(service(), &user, &current_code(enrollment.secret()))
+                .expect("Failed to confirm enrollment");
+
+            let result = TotpCredential::enroll(service(), &user);
+
+            assert!(!result.is_ok(), "Replaced enabled TOTP secret");
+        });
+    }
+
+    #[test]
+    fn verify_accepts_recovery_code_once() {
+        with_user(|user| {
+            let enrollment = TotpCredential::enroll(service(), &user).expect("Failed to enroll");
+            let _ = TotpCredential::confirm(service(), &user, &current_code(enrollment.secret()))
+                .expect("Failed to confirm enrollment");
+
+            let code = enrollment.recovery_codes()[0].clone();
+
+            let result = TotpCredential::verify(service(), &user, &code);
+
+            assert_eq!(
+                result.ok(),
+                Some(SecondFactor::RecoveryCode(user.id())),
+                "Rejected recovery code"
+            );
+
+            let result = TotpCredential::verify(service(), &user, &code);
+
+            assert!(!result.is_ok(), "Accepted recovery code twice");
+        });
+    }
+
+    #[test]
+    fn disable_deletes_recovery_codes() {
+        with_user(|user| {
+            let enrollment = TotpCredential::enroll(service(), &user).expect("Failed to enroll");
+            let _ = TotpCredential::confirm(service(), &user, &current_code(enrollment.secret()))
+                .expect("Failed to confirm enrollment");
+
+            let _ = TotpCredential::disable(service(), &user).expect("Failed to disable");
+
+            assert_eq!(
+                RecoveryCode::remaining(service(), &user).unwrap(),
+                0,
+                "Recovery codes outlived two-factor authentication"
+            );
+        });
+    }
+
+    #[test]
+    fn secret_is_stored_encrypted() {
+        with_user(|user| {
+            let enrollment = TotpCredential::enroll(service(), &user).expect("Failed to enroll");
+            let secret = decode_base32(enrollment.secret());
+
+            let credential = service()
+                .store()
+                .find_totp_credential_by_user_id(user.id())
+                .unwrap();
+
+            assert!(
+                !credential.secret.windows(secret.len()).any(|window| window == &secret[..]),
+                "Secret is stored in the clear"
+            );
+        });
+    }
+}
@@ -0,0 +1,75 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use service::AuthService;
+use error::Result;
+use schema::totp_credentials;
+use models::TotpCredential;
+use models::user::UserTrait;
+
+#[derive(Debug, Insertable)]
+#[table_name = "totp_credentials"]
+pub struct NewTotpCredential {
+    user_id: i32,
+    secret: Vec<u8>,
+}
+
+impl NewTotpCredential {
+    pub fn new<T>(user: &T, sealed_secret: Vec<u8>) -> Self
+    where
+        T: UserTrait,
+    {
+        NewTotpCredential {
+            user_id: user.id(),
+            secret: sealed_secret,
+        }
+    }
+
+    pub(crate) fn user_id(&self) -> i32 {
+        self.user_id
+    }
+
+    pub(crate) fn secret(&self) -> &[u8] {
+        &self.secret
+    }
+
+    pub fn save(&self, service: &AuthService) -> Result<TotpCredential> {
+        service.store().create_totp_credential(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use models::user::test_helper::with_user;
+    use test_helper::service;
+
+    #[test]
+    fn save_fails_with_duplicate_user() {
+        with_user(|user| {
+            let new_totp_credential = NewTotpCredential::new(&user, b"sealed".to_vec());
+
+            let result = new_totp_credential.save(service());
+            let result2 = new_totp_credential.save(service());
+
+            assert!(result.is_ok(), "Failed to save NewTotpCredential");
+            assert!(!result2.is_ok(), "Saved second TotpCredential for User");
+        });
+    }
+}
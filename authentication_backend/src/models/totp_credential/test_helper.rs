@@ -0,0 +1,55 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use chrono::Utc;
+use super::totp;
+
+pub fn current_code(secret: &str) -> String {
+    let secret = decode_base32(secret);
+
+    totp::code_at(&secret, totp::time_step(Utc::now()))
+}
+
+pub fn next_code(secret: &str) -> String {
+    let secret = decode_base32(secret);
+
+    totp::code_at(&secret, totp::time_step(Utc::now()) + 1)
+}
+
+pub fn decode_base32(encoded: &str) -> Vec<u8> {
+    const ALPHABET: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+    let mut decoded = Vec::new();
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+
+    for c in encoded.chars() {
+        let value = ALPHABET.find(c).expect("Secret is not base32") as u32;
+
+        buffer = (buffer << 5) | value;
+        bits += 5;
+
+        if bits >= 8 {
+            decoded.push((buffer >> (bits - 8)) as u8);
+            bits -= 8;
+        }
+    }
+
+    decoded
+}
@@ -0,0 +1,174 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use chrono::{DateTime, Utc};
+use ring::{constant_time, digest, hmac};
+
+pub const SECRET_LEN: usize = 20;
+const DIGITS: usize = 6;
+const PERIOD: i64 = 30;
+const SKEW: i64 = 1;
+const BASE32_ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+pub fn time_step(time: DateTime<Utc>) -> i64 {
+    time.timestamp() / PERIOD
+}
+
+pub fn code_at(secret: &[u8], step: i64) -> String {
+    let key = hmac::SigningKey::new(&digest::SHA1, secret);
+
+    let mut counter = [0u8; 8];
+    for (i, byte) in counter.iter_mut().enumerate() {
+        *byte = (step >> (56 - 8 * i)) as u8;
+    }
+
+    let signature = hmac::sign(&key, &counter);
+    let hash = signature.as_ref();
+
+    let offset = (hash[hash.len() - 1] & 0x0f) as usize;
+    let binary = ((hash[offset] as u32 & 0x7f) << 24) | ((hash[offset + 1] as u32) << 16) |
+        ((hash[offset + 2] as u32) << 8) | (hash[offset + 3] as u32);
+
+    format!(
+        "{:0width$}",
+        binary % 10u32.pow(DIGITS as u32),
+        width = DIGITS
+    )
+}
+
+pub fn matching_step(secret: &[u8], code: &str, time: DateTime<Utc>) -> Option<i64> {
+    let current = time_step(time);
+
+    (current - SKEW..current + SKEW + 1).find(|step| {
+        constant_time::verify_slices_are_equal(code_at(secret, *step).as_bytes(), code.as_bytes())
+            .is_ok()
+    })
+}
+
+pub fn provisioning_uri(secret: &[u8], issuer: &str, username: &str) -> String {
+    format!(
+        "otpauth://totp/{}:{}?secret={}&issuer={}&algorithm=SHA1&digits={}&period={}",
+        percent_encode(issuer),
+        percent_encode(username),
+        base32(secret),
+        percent_encode(issuer),
+        DIGITS,
+        PERIOD
+    )
+}
+
+pub fn base32(bytes: &[u8]) -> String {
+    let mut encoded = String::new();
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+
+    for byte in bytes {
+        buffer = (buffer << 8) | *byte as u32;
+        bits += 8;
+
+        while bits >= 5 {
+            encoded.push(BASE32_ALPHABET[((buffer >> (bits - 5)) & 31) as usize] as char);
+            bits -= 5;
+        }
+    }
+
+    if bits > 0 {
+        encoded.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 31) as usize] as char);
+    }
+
+    encoded
+}
+
+fn percent_encode(s: &str) -> String {
+    s.bytes()
+        .map(|byte| match byte {
+            b'A'...b'Z' | b'a'...b'z' | b'0'...b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDateTime;
+
+    const RFC_SECRET: &[u8] = b"12345678901234567890";
+
+    fn at(seconds: i64) -> DateTime<Utc> {
+        DateTime::from_utc(NaiveDateTime::from_timestamp(seconds, 0), Utc)
+    }
+
+    #[test]
+    fn code_at_matches_rfc_6238() {
+        assert_eq!(code_at(RFC_SECRET, time_step(at(59))), "287082", "Bad code at 59");
+        assert_eq!(
+            code_at(RFC_SECRET, time_step(at(1111111109))),
+            "081804",
+            "Bad code at 1111111109"
+        );
+        assert_eq!(
+            code_at(RFC_SECRET, time_step(at(1234567890))),
+            "005924",
+            "Bad code at 1234567890"
+        );
+    }
+
+    #[test]
+    fn matching_step_allows_clock_drift() {
+        let now = at(1234567890);
+        let step = time_step(now);
+
+        assert_eq!(
+            matching_step(RFC_SECRET, &code_at(RFC_SECRET, step - 1), now),
+            Some(step - 1),
+            "Rejected code from previous time step"
+        );
+        assert_eq!(
+            matching_step(RFC_SECRET, &code_at(RFC_SECRET, step - 2), now),
+            None,
+            "Accepted code from two time steps ago"
+        );
+        assert_eq!(
+            matching_step(RFC_SECRET, "not a code", now),
+            None,
+            "Accepted malformed code"
+        );
+    }
+
+    #[test]
+    fn base32_matches_rfc_4648() {
+        assert_eq!(base32(b"f"), "MY", "Bad encoding of one byte");
+        assert_eq!(base32(b"foobar"), "MZXW6YTBOI", "Bad encoding of six bytes");
+    }
+
+    #[test]
+    fn provisioning_uri_escapes_labels() {
+        let uri = provisioning_uri(b"foobar", "My Service", "someone");
+
+        assert_eq!(
+            uri,
+            "otpauth://totp/My%20Service:someone?secret=MZXW6YTBOI&issuer=My%20Service&\
+             algorithm=SHA1&digits=6&period=30",
+            "Bad provisioning URI"
+        );
+    }
+}
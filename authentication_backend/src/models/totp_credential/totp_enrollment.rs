@@ -0,0 +1,55 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use models::RecoveryCodes;
+use super::totp;
+
+#[derive(Debug)]
+pub struct TotpEnrollment {
+    secret: String,
+    provisioning_uri: String,
+    recovery_codes: Vec<String>,
+}
+
+impl TotpEnrollment {
+    pub fn new(
+        secret: &[u8],
+        issuer: &str,
+        username: &str,
+        recovery_codes: RecoveryCodes,
+    ) -> Self {
+        TotpEnrollment {
+            secret: totp::base32(secret),
+            provisioning_uri: totp::provisioning_uri(secret, issuer, username),
+            recovery_codes: recovery_codes.into_codes(),
+        }
+    }
+
+    pub fn secret(&self) -> &str {
+        &self.secret
+    }
+
+    pub fn provisioning_uri(&self) -> &str {
+        &self.provisioning_uri
+    }
+
+    pub fn recovery_codes(&self) -> &[String] {
+        &self.recovery_codes
+    }
+}
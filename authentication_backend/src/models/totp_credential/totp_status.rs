@@ -17,38 +17,25 @@
  * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
  */
 
-use regex::Regex;
-
-pub struct PasswordRegex {
-    numbers: Regex,
-    symbols: Regex,
-    upper: Regex,
-    lower: Regex,
+#[derive(Debug)]
+pub struct TotpStatus {
+    enabled: bool,
+    recovery_codes_remaining: usize,
 }
 
-impl PasswordRegex {
-    pub fn initialize() -> Self {
-        PasswordRegex {
-            numbers: Regex::new("[0-9]").unwrap(),
-            symbols: Regex::new("[!@#$%^&*();\\\\/|<>\"'_+\\-\\.,?=]").unwrap(),
-            upper: Regex::new("[A-Z]").unwrap(),
-            lower: Regex::new("[a-z]").unwrap(),
+impl TotpStatus {
+    pub fn new(enabled: bool, recovery_codes_remaining: usize) -> Self {
+        TotpStatus {
+            enabled: enabled,
+            recovery_codes_remaining: recovery_codes_remaining,
         }
     }
 
-    pub fn numbers(&self) -> &Regex {
-        &self.numbers
-    }
-
-    pub fn symbols(&self) -> &Regex {
-        &self.symbols
-    }
-
-    pub fn upper(&self) -> &Regex {
-        &self.upper
+    pub fn enabled(&self) -> bool {
+        self.enabled
     }
 
-    pub fn lower(&self) -> &Regex {
-        &self.lower
+    pub fn recovery_codes_remaining(&self) -> usize {
+        self.recovery_codes_remaining
     }
 }
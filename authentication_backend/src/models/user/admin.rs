@@ -17,9 +17,9 @@
  * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
  */
 
-use CONFIG;
+use service::AuthService;
 use error::{Error, Result};
-use models::{User, UserPermission, Permission, VerificationCode};
+use models::{FailedLogin, User, UserPermission, Permission, VerificationCode};
 use models::user::{UserTrait, Authenticated};
 
 #[derive(Debug)]
@@ -44,19 +44,13 @@ impl UserTrait for Admin {
 }
 
 impl Admin {
-    pub fn from_authenticated<T>(auth: T) -> Result<Admin>
+    pub fn from_authenticated<T>(service: &AuthService, auth: T) -> Result<Admin>
     where
         T: Into<Authenticated>,
     {
-        use models::{UserPermission, Permission};
-
-        let permission = Permission::find("admin")?;
-
         let auth: Authenticated = auth.into();
 
-        let has_permission: bool = UserPermission::has_permission(&auth, &permission);
-
-        if has_permission {
+        if auth.has_permission(service, "admin") {
             Ok(Admin {
                 id: UserTrait::id(&auth),
                 username: auth.username().to_owned(),
@@ -67,52 +61,58 @@ impl Admin {
         }
     }
 
-    pub fn give_permission(&self, target: &User, permission: &str) -> Result<()> {
-        let permission = Permission::find(permission)?;
+    pub fn give_permission(
+        &self,
+        service: &AuthService,
+        target: &User,
+        permission: &str,
+    ) -> Result<()> {
+        let permission = Permission::find(service, permission)?;
 
-        let _ = UserPermission::create(target, &permission)?;
+        let _ = UserPermission::create(service, target, &permission)?;
 
         Ok(())
     }
 
-    pub fn revoke_permission(&self, target: &User, permission: &str) -> Result<()> {
-        let permission = Permission::find(permission)?;
+    pub fn revoke_permission(
+        &self,
+        service: &AuthService,
+        target: &User,
+        permission: &str,
+    ) -> Result<()> {
+        let permission = Permission::find(service, permission)?;
 
-        UserPermission::delete(target, &permission)
+        UserPermission::delete(service, target, &permission)
     }
 
-    pub fn create_permission(&self, permission: &str) -> Result<Permission> {
-        Permission::create(permission)
+    pub fn create_permission(&self, service: &AuthService, permission: &str) -> Result<Permission> {
+        Permission::create(service, permission)
     }
 
-    pub fn delete_permission(&self, permission: &str) -> Result<()> {
-        Permission::delete(permission)
+    pub fn delete_permission(&self, service: &AuthService, permission: &str) -> Result<()> {
+        Permission::delete(service, permission)
     }
 
-    pub fn verify_user(&self, username: &str) -> Result<()> {
-        let mut user = User::find_by_name(username)?;
+    pub fn verify_user(&self, service: &AuthService, username: &str) -> Result<()> {
+        let mut user = User::find_by_name(service, username)?;
 
-        if !user.verify() {
+        if !user.verify(service) {
             return Err(Error::UserNotVerifiedError);
         }
 
-        VerificationCode::delete_by_user_id(user.id())?;
+        VerificationCode::delete_by_user_id(service, user.id())?;
 
         Ok(())
     }
 
-    pub fn delete_user(&self, uname: &str) -> Result<()> {
-        use diesel;
-        use diesel::prelude::*;
-        use schema::users::dsl::*;
-
-        let db = CONFIG.db()?;
+    pub fn delete_user(&self, service: &AuthService, username: &str) -> Result<()> {
+        service.store().delete_user_by_name(username)
+    }
 
-        diesel::delete(users.filter(username.eq(uname))).execute(
-            db.conn(),
-        )?;
+    pub fn unlock_user(&self, service: &AuthService, username: &str) -> Result<()> {
+        let user = User::find_by_name(service, username)?;
 
-        Ok(())
+        FailedLogin::clear(service, user.username())
     }
 }
 
@@ -120,7 +120,7 @@ impl Admin {
 mod tests {
     use super::*;
     use test_helper::*;
-    use models::user::test_helper::{with_user, with_admin};
+    use models::user::test_helper::{with_admin, with_auth_session, with_user};
     use models::user_permission::test_helper::with_user_permission;
     use authenticatable::Authenticatable;
 
@@ -128,9 +128,9 @@ mod tests {
     fn from_authenticated_creates_admin() {
         with_user(|user| {
             let admin_permission =
-                Permission::find("admin").expect("Failed to find admin permission");
+                Permission::find(service(), "admin").expect("Failed to find admin permission");
 
-            let _ = UserPermission::create(&user, &admin_permission).expect(
+            let _ = UserPermission::create(service(), &user, &admin_permission).expect(
                 "Failed to make test admin user_permission",
             );
 
@@ -139,9 +139,9 @@ mod tests {
                 password: test_password(),
             };
 
-            let auth = User::authenticate(&auth).expect("Failed to authenticate");
+            let auth = User::authenticate(service(), &auth).expect("Failed to authenticate");
 
-            let admin = Admin::from_authenticated(auth);
+            let admin = Admin::from_authenticated(service(), auth);
 
             assert!(
                 admin.is_ok(),
@@ -150,6 +150,34 @@ mod tests {
         });
     }
 
+    #[test]
+    fn from_authenticated_reads_permissions_from_token() {
+        with_auth_session(|mut auth| {
+            auth.verify(service());
+
+            let admin_permission =
+                Permission::find(service(), "admin").expect("Failed to find admin permission");
+
+            let webtoken = auth.create_webtoken(service(), None).expect(
+                "Failed to create webtoken",
+            );
+
+            let _ = UserPermission::create(service(), &auth, &admin_permission).expect(
+                "Failed to make test admin user_permission",
+            );
+
+            let auth = Authenticated::from_webtoken(service(), webtoken.user_token())
+                .expect("Failed to authenticate webtoken");
+
+            let admin = Admin::from_authenticated(service(), auth);
+
+            assert!(
+                !admin.is_ok(),
+                "Created admin from token issued before the 'admin' permission"
+            );
+        });
+    }
+
     #[test]
     fn from_authenticated_fails_with_bad_user() {
         with_user(|user| {
@@ -158,9 +186,9 @@ mod tests {
                 password: test_password(),
             };
 
-            let auth = User::authenticate(&auth).expect("Failed to authenticate");
+            let auth = User::authenticate(service(), &auth).expect("Failed to authenticate");
 
-            let admin = Admin::from_authenticated(auth);
+            let admin = Admin::from_authenticated(service(), auth);
 
             assert!(
                 !admin.is_ok(),
@@ -173,7 +201,7 @@ mod tests {
     fn admin_can_give_permissions_to_non_admins() {
         with_admin(|admin| {
             with_user(|user| {
-                let result = admin.give_permission(&user, "admin");
+                let result = admin.give_permission(service(), &user, "admin");
 
                 assert!(result.is_ok(), "Admin failed to give user new permission");
             });
@@ -184,7 +212,7 @@ mod tests {
     fn admin_cannot_give_nonexistant_permission() {
         with_admin(|admin| {
             with_user(|user| {
-                let result = admin.give_permission(&user, "this is not a permission");
+                let result = admin.give_permission(service(), &user, "this is not a permission");
 
                 assert!(
                     !result.is_ok(),
@@ -198,7 +226,7 @@ mod tests {
     fn admin_can_revoke_permissions() {
         with_admin(|admin| {
             with_user_permission(|user, permission, _user_permission| {
-                let result = admin.revoke_permission(&user, &permission.name());
+                let result = admin.revoke_permission(service(), &user, &permission.name());
 
                 assert!(result.is_ok(), "Failed to revoke permission from user");
             });
@@ -209,7 +237,7 @@ mod tests {
     fn verify_user_verifies_user() {
         with_admin(|admin| {
             with_user(|user| {
-                let result = admin.verify_user(user.username());
+                let result = admin.verify_user(service(), user.username());
 
                 assert!(result.is_ok(), "Failed to verify user");
             });
@@ -219,9 +247,28 @@ mod tests {
     #[test]
     fn verify_user_fails_with_bad_user() {
         with_admin(|admin| {
-            let result = admin.verify_user("this is not a real username");
+            let result = admin.verify_user(service(), "this is not a real username");
 
             assert!(!result.is_ok(), "Verified invalid user");
         });
     }
+
+    #[test]
+    fn unlock_user_forgets_failed_log_ins() {
+        with_admin(|admin| {
+            with_user(|user| {
+                let username = Some(user.username());
+
+                FailedLogin::record(service(), username, Some(user.id()), None).unwrap();
+
+                let result = admin.unlock_user(service(), user.username());
+
+                assert!(result.is_ok(), "Failed to unlock user");
+                assert!(
+                    FailedLogin::check(service(), username, None).is_ok(),
+                    "User still locked out after unlock"
+                );
+            });
+        });
+    }
 }
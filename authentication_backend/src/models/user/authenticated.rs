@@ -17,10 +17,10 @@
  * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
  */
 
-use diesel::prelude::*;
-use CONFIG;
-use error::Result;
-use webtoken::Webtoken;
+use service::AuthService;
+use error::{DbError, Error, Result};
+use webtoken::{TokenUser, Webtoken};
+use models::{Permission, UserPermission};
 use super::{UserTrait, User, AuthenticatedThisSession};
 
 #[derive(Debug)]
@@ -28,6 +28,7 @@ pub struct Authenticated {
     id: i32,
     username: String,
     verified: bool,
+    token_user: Option<TokenUser>,
 }
 
 impl UserTrait for Authenticated {
@@ -45,45 +46,64 @@ impl UserTrait for Authenticated {
 }
 
 impl Authenticated {
-    pub fn verify_password(&self, password: &str) -> Result<bool> {
-        let user = self.fetch_user()?;
+    pub fn verify_password(&self, service: &AuthService, password: &str) -> Result<bool> {
+        let user = self.fetch_user(service)?;
 
-        user.verify_password(password)
+        user.verify_password(service, password)
     }
 
-    pub fn verify(&mut self) -> bool {
-        let mut user = match self.fetch_user() {
+    pub fn verify(&mut self, service: &AuthService) -> bool {
+        let mut user = match self.fetch_user(service) {
             Ok(user) => user,
             Err(_) => return false,
         };
 
-        self.verified = user.verify();
+        self.verified = user.verify(service);
         self.verified
     }
 
-    pub fn from_webtoken(webtoken: &str) -> Result<Self> {
-        use schema::users::dsl::*;
+    pub fn from_webtoken(service: &AuthService, webtoken: &str) -> Result<Self> {
+        let token_user = Webtoken::authenticate(service, webtoken)?;
 
-        let db = CONFIG.db()?;
+        Authenticated::from_token_user(service, token_user)
+    }
+
+    pub fn from_webtoken_for(
+        service: &AuthService,
+        webtoken: &str,
+        audience: &str,
+    ) -> Result<Self> {
+        let token_user = Webtoken::authenticate_for(service, webtoken, audience)?;
 
-        let (user_id, _, _, _) = Webtoken::authenticate(webtoken)?;
+        Authenticated::from_token_user(service, token_user)
+    }
 
-        let user = users
-            .filter(verified.eq(true))
-            .filter(id.eq(user_id))
-            .first::<User>(db.conn())?;
+    pub fn has_permission(&self, service: &AuthService, permission: &str) -> bool {
+        if let Some(ref token_user) = self.token_user {
+            return token_user.has_permission(service, permission);
+        }
 
-        Ok(Authenticated::from_user(&user))
+        match Permission::find(service, permission) {
+            Ok(permission) => UserPermission::has_permission(service, self, &permission),
+            Err(_) => false,
+        }
     }
 
-    fn fetch_user(&self) -> Result<User> {
-        use schema::users::dsl::*;
+    fn from_token_user(service: &AuthService, token_user: TokenUser) -> Result<Self> {
+        let user = User::find_by_id(service, token_user.id())?;
+
+        if !user.is_verified() {
+            return Err(Error::DbError(DbError::NotFound));
+        }
 
-        let db = CONFIG.db()?;
+        let mut authenticated = Authenticated::from_user(&user);
+        authenticated.token_user = Some(token_user);
 
-        let user = users.filter(id.eq(self.id)).first::<User>(db.conn())?;
+        Ok(authenticated)
+    }
 
-        Ok(user)
+    fn fetch_user(&self, service: &AuthService) -> Result<User> {
+        User::find_by_id(service, self.id)
     }
 
     fn from_user(user: &User) -> Self {
@@ -91,6 +111,7 @@ impl Authenticated {
             id: UserTrait::id(user),
             username: user.username().to_owned(),
             verified: user.is_verified(),
+            token_user: None,
         }
     }
 }
@@ -101,6 +122,7 @@ impl From<AuthenticatedThisSession> for Authenticated {
             id: session_auth.id(),
             username: session_auth.username().to_owned(),
             verified: session_auth.is_verified(),
+            token_user: None,
         }
     }
 }
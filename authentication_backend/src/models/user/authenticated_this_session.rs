@@ -17,14 +17,12 @@
  * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
  */
 
-use diesel;
-use diesel::prelude::*;
-use bcrypt::hash;
-use CONFIG;
-use error::{Error, Result};
-use webtoken::Webtoken;
+use service::AuthService;
+use error::{DbError, Error, Result};
+use webtoken::{LogIn, Webtoken};
+use models::{ClientInfo, FailedLogin, NewVerificationCode, TotpCredential, VerificationCode};
 use super::{UserTrait, User, Authenticated};
-use super::helpers::{validate_username, validate_password};
+use super::helpers::{validate_email, validate_username, validate_password};
 
 #[derive(Debug)]
 pub struct AuthenticatedThisSession {
@@ -48,101 +46,234 @@ impl UserTrait for AuthenticatedThisSession {
 }
 
 impl AuthenticatedThisSession {
-    pub fn delete(&self) -> Result<()> {
-        use schema::users::dsl::*;
-
-        let db = CONFIG.db()?;
-
-        diesel::delete(users.filter(username.eq(&self.username)))
-            .execute(db.conn())?;
+    pub fn delete(&self, service: &AuthService) -> Result<()> {
+        service.store().delete_user_by_name(&self.username)
+    }
 
-        Ok(())
+    pub fn create_webtoken(
+        &self,
+        service: &AuthService,
+        audience: Option<&str>,
+    ) -> Result<Webtoken> {
+        self.create_webtoken_for_client(service, audience, &ClientInfo::default())
     }
 
-    pub fn create_webtoken(&self) -> Result<Webtoken> {
+    pub fn create_webtoken_for_client(
+        &self,
+        service: &AuthService,
+        audience: Option<&str>,
+        client: &ClientInfo,
+    ) -> Result<Webtoken> {
         if !self.verified {
             return Err(Error::UserNotVerifiedError);
         }
 
-        let token = Webtoken::create(self)?;
+        let token = Webtoken::create_for_client(service, self, audience, client)?;
 
         Ok(token)
     }
 
-    pub fn update_username(&mut self, new_username: &str) -> Result<()> {
-        use schema::users::dsl::{users, id, username};
+    pub fn log_in(
+        &self,
+        service: &AuthService,
+        audience: Option<&str>,
+        client: &ClientInfo,
+    ) -> Result<LogIn> {
+        if !self.verified {
+            return Err(Error::UserNotVerifiedError);
+        }
 
-        let new_username = validate_username(new_username)?;
+        if TotpCredential::is_enabled(service, self)? {
+            let mfa_token = Webtoken::create_mfa_token(service, self, audience)?;
 
-        let db = CONFIG.db()?;
+            return Ok(LogIn::MfaRequired(mfa_token));
+        }
+
+        let token = Webtoken::create_for_client(service, self, audience, client)?;
 
-        let _ = diesel::update(users.filter(id.eq(self.id)))
-            .set(username.eq(new_username))
-            .execute(db.conn())?;
+        Ok(LogIn::Authenticated(token))
+    }
+
+    pub fn update_username(&mut self, service: &AuthService, new_username: &str) -> Result<()> {
+        let new_username = validate_username(new_username)?;
+
+        service.store().update_username(self.id, new_username)?;
 
         self.username = new_username.to_string();
         Ok(())
     }
 
-    pub fn update_password(&mut self, new_pass: &str) -> Result<()> {
-        use schema::users::dsl::*;
+    pub fn update_password(&mut self, service: &AuthService, new_pass: &str) -> Result<()> {
+        let new_pass = validate_password(service, &self.username, new_pass)?;
+
+        let hash = service.password_hasher().hash(new_pass)?;
 
-        let new_pass = validate_password(new_pass)?;
+        service.store().update_password(self.id, &hash)
+    }
 
-        let hash = hash(new_pass, CONFIG.bcrypt_cost())?;
+    pub fn request_email_change(&self, service: &AuthService, new_email: &str) -> Result<()> {
+        let new_email = validate_email(new_email)?;
 
-        let db = CONFIG.db()?;
+        VerificationCode::delete_by_user_id(service, self.id)?;
 
-        let _ = diesel::update(users.filter(id.eq(self.id)))
-            .set(password.eq(&hash))
-            .execute(db.conn())?;
+        let _ = NewVerificationCode::new_for_email(self.id, Some(&new_email))?
+            .save(service)?;
 
         Ok(())
     }
 
-    pub fn from_webtoken_and_password(webtoken: &str, password: &str) -> Result<Self> {
-        let authenticated = Authenticated::from_webtoken(webtoken)?;
+    pub fn from_webtoken_and_password(
+        service: &AuthService,
+        webtoken: &str,
+        password: &str,
+    ) -> Result<Self> {
+        AuthenticatedThisSession::from_webtoken_and_password_for_client(
+            service,
+            webtoken,
+            password,
+            &ClientInfo::default(),
+        )
+    }
+
+    pub fn from_webtoken_and_password_for_client(
+        service: &AuthService,
+        webtoken: &str,
+        password: &str,
+        client: &ClientInfo,
+    ) -> Result<Self> {
+        let authenticated = Authenticated::from_webtoken(service, webtoken)?;
+
+        AuthenticatedThisSession::from_authenticated_for_client(
+            service,
+            &authenticated,
+            password,
+            client,
+        )
+    }
 
-        AuthenticatedThisSession::from_authenticated(&authenticated, password)
+    pub fn from_username_and_password(
+        service: &AuthService,
+        uname: &str,
+        pword: &str,
+    ) -> Result<Self> {
+        AuthenticatedThisSession::from_username_and_password_for_client(
+            service,
+            uname,
+            pword,
+            &ClientInfo::default(),
+        )
     }
 
-    pub fn from_username_and_password(uname: &str, pword: &str) -> Result<Self> {
-        use schema::users::dsl::*;
+    // Users log in with their username or their email address. Unknown usernames fail the same
+    // way wrong passwords do, after about as long, so they can't be used to find out which
+    // usernames exist.
+    pub fn from_username_and_password_for_client(
+        service: &AuthService,
+        uname: &str,
+        pword: &str,
+        client: &ClientInfo,
+    ) -> Result<Self> {
+        let user = match User::find_by_name_or_email(service, uname) {
+            Ok(user) => Some(user),
+            Err(Error::DbError(DbError::NotFound)) => None,
+            Err(err) => return Err(err),
+        };
 
-        let db = CONFIG.db()?;
+        let uname = match user {
+            Some(ref user) => user.username(),
+            None => uname,
+        };
 
-        let user: User = users.filter(username.eq(uname)).first(db.conn())?;
+        let user = AuthenticatedThisSession::check_password(
+            service,
+            user.as_ref(),
+            uname,
+            pword,
+            client.ip_address(),
+        )?;
 
-        if user.verify_password(pword)? {
-            Ok(AuthenticatedThisSession::from_user(&user))
-        } else {
-            Err(Error::PasswordMatchError)
-        }
+        Ok(AuthenticatedThisSession::from_user(user))
     }
 
-    pub fn verify(&mut self) -> bool {
-        let mut user = match User::find_by_id(self.id) {
+    pub fn verify(&mut self, service: &AuthService) -> bool {
+        let mut user = match User::find_by_id(service, self.id) {
             Ok(user) => user,
             Err(_) => return false,
         };
 
-        self.verified = user.verify();
+        self.verified = user.verify(service);
         self.verified
     }
 
-    fn from_authenticated(auth: &Authenticated, password: &str) -> Result<Self> {
-        if auth.verify_password(password)? {
-            Ok(AuthenticatedThisSession {
-                id: auth.id(),
-                username: auth.username().to_owned(),
-                verified: auth.is_verified(),
-            })
-        } else {
-            Err(Error::PasswordMatchError)
+    pub fn from_authenticated(
+        service: &AuthService,
+        auth: &Authenticated,
+        password: &str,
+    ) -> Result<Self> {
+        AuthenticatedThisSession::from_authenticated_for_client(
+            service,
+            auth,
+            password,
+            &ClientInfo::default(),
+        )
+    }
+
+    fn from_authenticated_for_client(
+        service: &AuthService,
+        auth: &Authenticated,
+        password: &str,
+        client: &ClientInfo,
+    ) -> Result<Self> {
+        let user = User::find_by_id(service, auth.id())?;
+
+        let user = AuthenticatedThisSession::check_password(
+            service,
+            Some(&user),
+            user.username(),
+            password,
+            client.ip_address(),
+        )?;
+
+        Ok(AuthenticatedThisSession::from_user(user))
+    }
+
+    fn check_password<'a>(
+        service: &AuthService,
+        user: Option<&'a User>,
+        uname: &str,
+        pword: &str,
+        ip_address: Option<&str>,
+    ) -> Result<&'a User> {
+        FailedLogin::check(service, Some(uname), ip_address)?;
+
+        match user {
+            Some(user) => {
+                if user.verify_password(service, pword)? {
+                    // Users with two-factor authentication keep their failures until they give
+                    // a code, so wrong codes can't be forgotten by giving the password again
+                    if !TotpCredential::is_enabled(service, user)? {
+                        FailedLogin::clear(service, uname)?;
+                    }
+
+                    return Ok(user);
+                }
+            }
+            None => service.password_hasher().verify_dummy(pword),
+        }
+
+        let user_id = user.map(|user| user.id());
+
+        if !FailedLogin::record(service, Some(uname), user_id, ip_address)? {
+            return Err(Error::PasswordMatchError);
+        }
+
+        match user_id {
+            Some(user_id) => Err(Error::AccountLockedError(user_id)),
+            None => Err(Error::LoginThrottledError),
         }
     }
 
-    fn from_user(user: &User) -> Self {
+    pub(crate) fn from_user(user: &User) -> Self {
         AuthenticatedThisSession {
             id: UserTrait::id(user),
             username: user.username().to_owned(),
@@ -153,16 +284,15 @@ impl AuthenticatedThisSession {
 
 #[cfg(test)]
 mod tests {
-    use diesel::prelude::*;
     use super::*;
-    use schema::verification_codes::dsl::*;
-    use models::user::test_helper::with_auth_session;
+    use models::user::test_helper::{with_auth_session, with_user};
     use models::VerificationCode;
+    use test_helper::{generate_string, service, test_password};
 
     #[test]
     fn update_password_updates_password() {
         with_auth_session(|mut auth| {
-            let result = auth.update_password("P455w0rd$.");
+            let result = auth.update_password(service(), "P455w0rd$.");
 
             assert!(result.is_ok(), "Failed to update password");
         });
@@ -171,7 +301,7 @@ mod tests {
     #[test]
     fn update_password_fails_with_weak_password() {
         with_auth_session(|mut auth| {
-            let result = auth.update_password("asdfasdfasdf");
+            let result = auth.update_password(service(), "asdfasdfasdf");
 
             assert!(!result.is_ok(), "Allowed update to weak password");
         });
@@ -180,7 +310,7 @@ mod tests {
     #[test]
     fn update_username_updates_username() {
         with_auth_session(|mut auth| {
-            let result = auth.update_username("some_new_username");
+            let result = auth.update_username(service(), "some_new_username");
 
             assert!(result.is_ok(), "Failed to update username");
         });
@@ -189,18 +319,43 @@ mod tests {
     #[test]
     fn update_username_fails_with_empty_username() {
         with_auth_session(|mut auth| {
-            let result = auth.update_username("");
+            let result = auth.update_username(service(), "");
 
             assert!(!result.is_ok(), "Updated username to empty string");
         });
     }
 
+    #[test]
+    fn update_username_fails_with_another_users_email() {
+        with_user(|owner| {
+            let email = format!("{}@example.com", generate_string().to_lowercase());
+
+            service().store().update_email(owner.id(), &email).expect(
+                "Failed to set email",
+            );
+
+            with_auth_session(|mut auth| {
+                let result = auth.update_username(service(), &email);
+
+                assert!(!result.is_ok(), "Took another user's email as username");
+
+                let result = User::find_by_name_or_email(service(), &email);
+
+                assert_eq!(
+                    result.ok().map(|found| found.id()),
+                    Some(owner.id()),
+                    "Email no longer logs in its owner"
+                );
+            });
+        });
+    }
+
     #[test]
     fn create_webtoken_creates_webtoken() {
         with_auth_session(|mut auth| {
-            auth.verify();
+            auth.verify(service());
 
-            let result = auth.create_webtoken();
+            let result = auth.create_webtoken(service(), None);
 
             assert!(result.is_ok(), "Failed to create webtoken");
         });
@@ -209,7 +364,7 @@ mod tests {
     #[test]
     fn unverified_users_cant_create_webtoken() {
         with_auth_session(|auth| {
-            let result = auth.create_webtoken();
+            let result = auth.create_webtoken(service(), None);
 
             assert!(!result.is_ok(), "Unverified User created webtoken");
         });
@@ -218,7 +373,7 @@ mod tests {
     #[test]
     fn delete_deletes_existing_user() {
         with_auth_session(|auth| {
-            let result = auth.delete();
+            let result = auth.delete(service());
 
             assert!(result.is_ok(), "Failed to delete existing user");
         });
@@ -227,22 +382,108 @@ mod tests {
     #[test]
     fn delete_deletes_associated_verification_code() {
         with_auth_session(|auth_session| {
-            let user =
-                User::find_by_id(auth_session.id).expect("Failed to find user for auth_session");
+            let user = User::find_by_id(service(), auth_session.id)
+                .expect("Failed to find user for auth_session");
 
-            let vc = verification_codes
-                .filter(user_id.eq(UserTrait::id(&user)))
-                .first::<VerificationCode>(CONFIG.db().unwrap().conn());
+            let vc = VerificationCode::find_by_user_id(service(), UserTrait::id(&user));
 
             assert!(vc.is_ok(), "Could not get verification_code for user");
 
-            let _ = auth_session.delete().expect("Failed to delete User");
+            let _ = auth_session.delete(service()).expect("Failed to delete User");
 
-            let vc = verification_codes
-                .filter(user_id.eq(UserTrait::id(&user)))
-                .first::<VerificationCode>(CONFIG.db().unwrap().conn());
+            let vc = VerificationCode::find_by_user_id(service(), UserTrait::id(&user));
 
             assert!(!vc.is_ok(), "Verification code still exists after delete");
         });
     }
+
+    #[test]
+    fn from_username_and_password_accepts_email() {
+        with_user(|user| {
+            let email = format!("{}@example.com", generate_string().to_lowercase());
+
+            service().store().update_email(user.id(), &email).expect(
+                "Failed to set email",
+            );
+
+            let result = AuthenticatedThisSession::from_username_and_password(
+                service(),
+                &email.to_uppercase(),
+                test_password(),
+            );
+
+            assert!(result.is_ok(), "Failed to log in with email");
+        });
+    }
+
+    #[test]
+    fn from_username_and_password_hides_unknown_usernames() {
+        let result = AuthenticatedThisSession::from_username_and_password(
+            service(),
+            &generate_string(),
+            test_password(),
+        );
+
+        match result {
+            Err(Error::PasswordMatchError) => (),
+            _ => panic!("Unknown username failed differently from wrong password"),
+        }
+    }
+
+    #[test]
+    fn from_webtoken_and_password_backs_off_after_failure() {
+        with_auth_session(|mut auth| {
+            auth.verify(service());
+            let token = auth.create_webtoken(service(), None).expect("Failed to create webtoken");
+
+            let result = AuthenticatedThisSession::from_webtoken_and_password(
+                service(),
+                token.user_token(),
+                "not the password",
+            );
+
+            match result {
+                Err(Error::PasswordMatchError) => (),
+                _ => panic!("Authenticated with wrong password"),
+            }
+
+            let result = AuthenticatedThisSession::from_webtoken_and_password(
+                service(),
+                token.user_token(),
+                test_password(),
+            );
+
+            match result {
+                Err(Error::LoginThrottledError) => (),
+                _ => panic!("Allowed password during back-off"),
+            }
+        });
+    }
+
+    #[test]
+    fn from_username_and_password_backs_off_after_failure() {
+        with_user(|user| {
+            let result = AuthenticatedThisSession::from_username_and_password(
+                service(),
+                user.username(),
+                "not the password",
+            );
+
+            match result {
+                Err(Error::PasswordMatchError) => (),
+                _ => panic!("Authenticated with wrong password"),
+            }
+
+            let result = AuthenticatedThisSession::from_username_and_password(
+                service(),
+                user.username(),
+                test_password(),
+            );
+
+            match result {
+                Err(Error::LoginThrottledError) => (),
+                _ => panic!("Allowed log in during back-off"),
+            }
+        });
+    }
 }
@@ -17,36 +17,27 @@
  * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
  */
 
-use CONFIG;
+use service::AuthService;
 use error::Result;
 use error::Error::InputError;
-use error::InputErrorKind::{Username, Password};
+use error::InputErrorKind::{Email, Username, Password};
 use error::UsernameErrorKind;
-use error::UsernameErrorKind::Blank;
-use error::PasswordErrorKind;
-use error::PasswordErrorKind::{TooShort, NoNumber, NoSymbol, NoUppercase, NoLowercase};
+use error::UsernameErrorKind::{Blank, ContainsAt};
+use error::PasswordErrorKind::Breached;
 
-pub fn validate_password(password: &str) -> Result<&str> {
-    let mut error_vec: Vec<PasswordErrorKind> = Vec::new();
+const MAX_EMAIL_LENGTH: usize = 254;
 
-    if password.len() < 8 {
-        error_vec.push(TooShort);
-    }
-
-    if !CONFIG.password_regex().numbers().is_match(password) {
-        error_vec.push(NoNumber);
-    }
-
-    if !CONFIG.password_regex().symbols().is_match(password) {
-        error_vec.push(NoSymbol);
-    }
+pub fn validate_password<'a>(
+    service: &AuthService,
+    username: &str,
+    password: &'a str,
+) -> Result<&'a str> {
+    let mut error_vec = service.password_policy().check(username, password);
 
-    if !CONFIG.password_regex().upper().is_match(password) {
-        error_vec.push(NoUppercase);
-    }
-
-    if !CONFIG.password_regex().lower().is_match(password) {
-        error_vec.push(NoLowercase);
+    if let Some(corpus) = service.breach_corpus() {
+        if corpus.contains(password)? {
+            error_vec.push(Breached);
+        }
     }
 
     if error_vec.is_empty() {
@@ -63,9 +54,35 @@ pub fn validate_username(username: &str) -> Result<&str> {
         error_vec.push(Blank)
     }
 
+    if username.contains('@') {
+        error_vec.push(ContainsAt)
+    }
+
     if error_vec.is_empty() {
         Ok(username)
     } else {
         Err(InputError(Username(error_vec)))
     }
 }
+
+pub fn validate_email(email: &str) -> Result<String> {
+    let email = email.trim().to_lowercase();
+
+    if email.len() > MAX_EMAIL_LENGTH || email.chars().any(char::is_whitespace) {
+        return Err(InputError(Email));
+    }
+
+    let at = match email.rfind('@') {
+        Some(at) => at,
+        None => return Err(InputError(Email)),
+    };
+    let (local, domain) = (&email[..at], &email[at + 1..]);
+
+    if local.is_empty() || !domain.contains('.') || domain.starts_with('.') ||
+        domain.ends_with('.')
+    {
+        return Err(InputError(Email));
+    }
+
+    Ok(email)
+}
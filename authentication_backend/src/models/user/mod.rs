@@ -32,14 +32,13 @@ pub use self::admin::Admin;
 pub use self::authenticated::Authenticated;
 pub use self::authenticated_this_session::AuthenticatedThisSession;
 pub use self::new_user::NewUser;
+pub(crate) use self::helpers::{validate_email, validate_password};
 
-use diesel;
 use schema::users;
-use CONFIG;
+use service::AuthService;
 use authenticatable::{Authenticatable, ToAuth};
-use bcrypt::verify;
-use error::{Error, InputErrorKind, Result};
-use diesel::prelude::*;
+use models::ClientInfo;
+use error::{DbError, Error, InputErrorKind, Result};
 
 #[derive(Debug, PartialEq, Queryable, Identifiable, AsChangeset, Associations)]
 pub struct User {
@@ -47,6 +46,7 @@ pub struct User {
     username: String,
     password: String,
     verified: bool,
+    email: Option<String>,
 }
 
 impl UserTrait for User {
@@ -64,31 +64,84 @@ impl UserTrait for User {
 }
 
 impl User {
-    pub fn create<T>(auth: &T) -> Result<Self>
+    pub(crate) fn from_parts(
+        id: i32,
+        username: String,
+        password: String,
+        verified: bool,
+        email: Option<String>,
+    ) -> Self {
+        User {
+            id: id,
+            username: username,
+            password: password,
+            verified: verified,
+            email: email,
+        }
+    }
+
+    pub fn email(&self) -> Option<&str> {
+        self.email.as_ref().map(|email| email.as_str())
+    }
+
+    pub fn create<T>(service: &AuthService, auth: &T) -> Result<Self>
+    where
+        T: ToAuth,
+    {
+        User::create_with_email(service, auth, None)
+    }
+
+    // The address isn't the user's until they use the verification code sent to it
+    pub fn create_with_email<T>(
+        service: &AuthService,
+        auth: &T,
+        email: Option<&str>,
+    ) -> Result<Self>
     where
         T: ToAuth,
     {
         let auth = auth.to_auth();
 
-        let new_user = NewUser::new(&auth)?;
+        let email = match email {
+            Some(email) => Some(validate_email(email)?),
+            None => None,
+        };
+
+        let new_user = NewUser::new(service, &auth)?;
 
-        new_user.save()
+        new_user.save_with_email(service, email.as_ref().map(|email| email.as_str()))
     }
 
-    pub fn authenticate<T>(auth: &T) -> Result<Authenticated>
+    pub fn authenticate<T>(service: &AuthService, auth: &T) -> Result<Authenticated>
     where
         T: ToAuth,
     {
         match auth.to_auth() {
-            Authenticatable::UserToken { user_token: t } => Authenticated::from_webtoken(t),
+            Authenticatable::UserToken { user_token: t } => {
+                Authenticated::from_webtoken(service, t)
+            }
             _ => {
-                let authenticate_session = User::authenticate_session(auth)?;
+                let authenticate_session = User::authenticate_session(service, auth)?;
                 Ok(authenticate_session.into())
             }
         }
     }
 
-    pub fn authenticate_session<T>(auth: &T) -> Result<AuthenticatedThisSession>
+    pub fn authenticate_session<T>(
+        service: &AuthService,
+        auth: &T,
+    ) -> Result<AuthenticatedThisSession>
+    where
+        T: ToAuth,
+    {
+        User::authenticate_session_for_client(service, auth, &ClientInfo::default())
+    }
+
+    pub fn authenticate_session_for_client<T>(
+        service: &AuthService,
+        auth: &T,
+        client: &ClientInfo,
+    ) -> Result<AuthenticatedThisSession>
     where
         T: ToAuth,
     {
@@ -96,68 +149,75 @@ impl User {
             Authenticatable::UserAndPass {
                 username: u,
                 password: p,
-            } => AuthenticatedThisSession::from_username_and_password(u, p),
+            } => {
+                AuthenticatedThisSession::from_username_and_password_for_client(
+                    service,
+                    u,
+                    p,
+                    client,
+                )
+            }
             Authenticatable::UserTokenAndPass {
                 user_token: t,
                 password: p,
-            } => AuthenticatedThisSession::from_webtoken_and_password(t, p),
+            } => {
+                AuthenticatedThisSession::from_webtoken_and_password_for_client(
+                    service,
+                    t,
+                    p,
+                    client,
+                )
+            }
             _ => Err(Error::InputError(InputErrorKind::Authenticatable)),
         }
     }
 
-    pub fn find_by_name(u_name: &str) -> Result<Self> {
-        use schema::users::dsl::*;
-
-        let db = CONFIG.db()?;
-        let user = users.filter(username.eq(u_name)).first::<Self>(db.conn())?;
+    pub fn find_by_name(service: &AuthService, username: &str) -> Result<Self> {
+        service.store().find_user_by_name(username)
+    }
 
-        Ok(user)
+    pub fn find_by_email(service: &AuthService, email: &str) -> Result<Self> {
+        service.store().find_user_by_email(&validate_email(email)?)
     }
 
-    pub fn find_by_id(u_id: i32) -> Result<Self> {
-        use schema::users::dsl::*;
+    pub fn find_by_name_or_email(service: &AuthService, login: &str) -> Result<Self> {
+        if !login.contains('@') {
+            return User::find_by_name(service, login);
+        }
 
-        let db = CONFIG.db()?;
-        let user = users.filter(id.eq(u_id)).first::<Self>(db.conn())?;
+        match User::find_by_email(service, login) {
+            Err(Error::DbError(DbError::NotFound)) |
+            Err(Error::InputError(_)) => User::find_by_name(service, login),
+            result => result,
+        }
+    }
 
-        Ok(user)
+    pub fn find_by_id(service: &AuthService, id: i32) -> Result<Self> {
+        service.store().find_user_by_id(id)
     }
 
-    pub fn verify_with_code(vc: &str) -> Result<Self> {
-        use schema::verification_codes::dsl::{verification_codes, code, user_id};
-        use schema::users::dsl::*;
+    pub fn verify_with_code(service: &AuthService, vc: &str) -> Result<Self> {
         use models::verification_code::VerificationCode;
 
-        let db = CONFIG.db()?;
+        let vc = VerificationCode::find(service, vc)?;
+        let mut user = User::find_by_id(service, vc.user_id())?;
 
-        let (_, mut user) = verification_codes
-            .inner_join(users)
-            .filter(id.eq(user_id))
-            .filter(code.eq(vc))
-            .first::<(VerificationCode, User)>(db.conn())?;
+        if let Some(email) = vc.email() {
+            service.store().update_email(user.id, email)?;
+            user.email = Some(email.to_owned());
+        }
 
-        if !user.verify() {
+        if !user.verify(service) {
             return Err(Error::UserNotVerifiedError);
         }
 
-        VerificationCode::delete_by_user_id(user.id)?;
+        VerificationCode::delete_by_user_id(service, user.id)?;
 
         Ok(user)
     }
 
-    pub fn verify(&mut self) -> bool {
-        use schema::users::dsl::*;
-
-        let db = match CONFIG.db() {
-            Ok(db) => db,
-            Err(_) => return false,
-        };
-
-        let updated_record = diesel::update(users.filter(id.eq(self.id)))
-            .set(verified.eq(true))
-            .execute(db.conn());
-
-        match updated_record {
+    pub fn verify(&mut self, service: &AuthService) -> bool {
+        match service.store().verify_user(self.id) {
             Ok(_) => {
                 self.verified = true;
                 true
@@ -166,25 +226,37 @@ impl User {
         }
     }
 
-    pub fn verify_password(&self, password: &str) -> Result<bool> {
-        let verified = verify(password, &self.password)?;
+    pub fn verify_password(&self, service: &AuthService, password: &str) -> Result<bool> {
+        let hasher = service.password_hasher();
+
+        if !hasher.verify(password, &self.password)? {
+            return Ok(false);
+        }
+
+        if hasher.needs_rehash(&self.password) {
+            if let Ok(hash) = hasher.hash(password) {
+                match service.store().update_password(self.id, &hash) {
+                    _ => (),
+                };
+            }
+        }
 
-        Ok(verified)
+        Ok(true)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use bcrypt;
     use test_helper::*;
     use models::verification_code::VerificationCode;
-    use schema::verification_codes::dsl::{verification_codes, user_id};
     use models::user::test_helper::{with_user, teardown};
 
     #[test]
     fn find_by_name_finds_user() {
         with_user(|user| {
-            let result = User::find_by_name(user.username());
+            let result = User::find_by_name(service(), user.username());
 
             assert!(result.is_ok(), "Failed to find user");
 
@@ -201,15 +273,64 @@ mod tests {
 
     #[test]
     fn find_by_name_fails_with_bad_name() {
-        let result = User::find_by_name("This is not a valid username");
+        let result = User::find_by_name(service(), "This is not a valid username");
 
         assert!(!result.is_ok(), "Found user with invalid username");
     }
 
+    #[test]
+    fn find_by_email_normalizes_address() {
+        with_user(|user| {
+            let email = format!("{}@example.com", generate_string().to_lowercase());
+
+            service().store().update_email(user.id, &email).expect(
+                "Failed to set email",
+            );
+
+            let result = User::find_by_email(service(), &format!(" {} ", email.to_uppercase()));
+
+            assert_eq!(result.ok().map(|found| found.id), Some(user.id), "Failed to find user");
+        });
+    }
+
+    #[test]
+    fn find_by_email_rejects_malformed_address() {
+        let result = User::find_by_email(service(), "no-at-sign.example.com");
+
+        match result {
+            Err(Error::InputError(InputErrorKind::Email)) => (),
+            _ => panic!("Accepted malformed address"),
+        }
+    }
+
+    #[test]
+    fn find_by_name_or_email_prefers_address_over_username() {
+        with_user(|owner| {
+            with_user(|squatter| {
+                let email = format!("{}@example.com", generate_string().to_lowercase());
+
+                service().store().update_email(owner.id, &email).expect(
+                    "Failed to set email",
+                );
+                service().store().update_username(squatter.id, &email).expect(
+                    "Failed to set username",
+                );
+
+                let result = User::find_by_name_or_email(service(), &email);
+
+                assert_eq!(
+                    result.ok().map(|found| found.id),
+                    Some(owner.id),
+                    "Found username instead of address"
+                );
+            });
+        });
+    }
+
     #[test]
     fn find_by_id_finds_user() {
         with_user(|user| {
-            let result = User::find_by_id(UserTrait::id(&user));
+            let result = User::find_by_id(service(), UserTrait::id(&user));
 
             assert!(result.is_ok(), "Failed to find user");
 
@@ -226,7 +347,7 @@ mod tests {
 
     #[test]
     fn find_by_id_fails_with_bad_id() {
-        let result = User::find_by_id(-1);
+        let result = User::find_by_id(service(), -1);
 
         assert!(!result.is_ok(), "Found user with invalid id");
     }
@@ -238,7 +359,7 @@ mod tests {
             password: &test_password(),
         };
 
-        let result = User::create(&auth);
+        let result = User::create(service(), &auth);
 
         assert!(result.is_ok(), "Failed to create user");
         teardown(UserTrait::id(&result.unwrap()));
@@ -247,12 +368,9 @@ mod tests {
     #[test]
     fn verify_with_code_verifies_user() {
         with_user(|user| {
-            let vc = verification_codes
-                .filter(user_id.eq(user.id))
-                .first::<VerificationCode>(CONFIG.db().unwrap().conn())
-                .unwrap();
+            let vc = VerificationCode::find_by_user_id(service(), user.id).unwrap();
 
-            let result = User::verify_with_code(&vc.code());
+            let result = User::verify_with_code(service(), &vc.code());
             assert!(
                 result.is_ok(),
                 "Failed to verify user with verification code"
@@ -263,16 +381,11 @@ mod tests {
     #[test]
     fn verify_with_code_deletes_code() {
         with_user(|user| {
-            let vc = verification_codes
-                .filter(user_id.eq(user.id))
-                .first::<VerificationCode>(CONFIG.db().unwrap().conn())
-                .unwrap();
+            let vc = VerificationCode::find_by_user_id(service(), user.id).unwrap();
 
-            let user = User::verify_with_code(&vc.code()).unwrap();
+            let user = User::verify_with_code(service(), &vc.code()).unwrap();
 
-            let result = verification_codes
-                .filter(user_id.eq(user.id))
-                .first::<VerificationCode>(CONFIG.db().unwrap().conn());
+            let result = VerificationCode::find_by_user_id(service(), user.id);
             assert!(
                 !result.is_ok(),
                 "Verification code still exists after verify"
@@ -283,7 +396,7 @@ mod tests {
     #[test]
     fn verify_verifies_user() {
         with_user(|mut user| {
-            let result = user.verify();
+            let result = user.verify(service());
 
             assert!(result, "Failed to verify user");
             assert!(user.verified, "User not verified");
@@ -293,19 +406,20 @@ mod tests {
     #[test]
     fn authenticate_gets_user_from_valid_webtoken() {
         with_user(|mut user| {
-            user.verify();
+            user.verify(service());
 
             let auth = Authenticatable::UserAndPass {
                 username: user.username(),
                 password: test_password(),
             };
 
-            let auth = User::authenticate_session(&auth).expect("Failed to authenticate User");
+            let auth = User::authenticate_session(service(), &auth)
+                .expect("Failed to authenticate User");
 
-            let webtoken = auth.create_webtoken().unwrap();
+            let webtoken = auth.create_webtoken(service(), None).unwrap();
             let auth = Authenticatable::UserToken { user_token: webtoken.user_token() };
 
-            let result = User::authenticate(&auth);
+            let result = User::authenticate(service(), &auth);
 
             assert!(result.is_ok(), "Failed to fetch user from webtoken");
 
@@ -323,7 +437,7 @@ mod tests {
         with_user(|_| {
             let auth = Authenticatable::UserToken { user_token: "this is not a token" };
 
-            let result = User::authenticate(&auth);
+            let result = User::authenticate(service(), &auth);
 
             assert!(!result.is_ok(), "Fetched user from fake webtoken");
         });
@@ -332,23 +446,24 @@ mod tests {
     #[test]
     fn authenticate_with_token_and_password_works() {
         with_user(|mut user| {
-            assert!(user.verify(), "Failed to verify User");
+            assert!(user.verify(service()), "Failed to verify User");
 
             let auth = Authenticatable::UserAndPass {
                 username: user.username(),
                 password: test_password(),
             };
 
-            let auth = User::authenticate_session(&auth).expect("Failed to authenticate User");
+            let auth = User::authenticate_session(service(), &auth)
+                .expect("Failed to authenticate User");
 
-            let webtoken = auth.create_webtoken().unwrap();
+            let webtoken = auth.create_webtoken(service(), None).unwrap();
 
             let auth = Authenticatable::UserTokenAndPass {
                 user_token: webtoken.user_token(),
                 password: &test_password(),
             };
 
-            let result = User::authenticate(&auth);
+            let result = User::authenticate(service(), &auth);
 
             assert!(
                 result.is_ok(),
@@ -360,23 +475,24 @@ mod tests {
     #[test]
     fn authenticate_fails_with_token_and_bad_password() {
         with_user(|mut user| {
-            assert!(user.verify(), "Failed to verify User");
+            assert!(user.verify(service()), "Failed to verify User");
 
             let auth = Authenticatable::UserAndPass {
                 username: user.username(),
                 password: test_password(),
             };
 
-            let auth = User::authenticate_session(&auth).expect("Failed to authenticate User");
+            let auth = User::authenticate_session(service(), &auth)
+                .expect("Failed to authenticate User");
 
-            let webtoken = auth.create_webtoken().unwrap();
+            let webtoken = auth.create_webtoken(service(), None).unwrap();
 
             let auth = Authenticatable::UserTokenAndPass {
                 user_token: webtoken.user_token(),
                 password: "this is not the password",
             };
 
-            let result = User::authenticate(&auth);
+            let result = User::authenticate(service(), &auth);
 
             assert!(
                 !result.is_ok(),
@@ -393,7 +509,7 @@ mod tests {
                 password: test_password(),
             };
 
-            let result = User::authenticate(&auth);
+            let result = User::authenticate(service(), &auth);
 
             assert!(!result.is_ok(), "User should not have been authenticated");
         });
@@ -407,7 +523,7 @@ mod tests {
                 password: "not the password",
             };
 
-            let result = User::authenticate(&auth);
+            let result = User::authenticate(service(), &auth);
 
             assert!(!result.is_ok(), "User should not have been authenticated");
         });
@@ -421,7 +537,7 @@ mod tests {
                 password: test_password(),
             };
 
-            let result = User::authenticate(&auth);
+            let result = User::authenticate(service(), &auth);
 
             assert!(result.is_ok(), "Failed to authenticate user");
         });
@@ -430,7 +546,7 @@ mod tests {
     #[test]
     fn verify_password_verifies_password() {
         with_user(|user| {
-            let result = user.verify_password(test_password());
+            let result = user.verify_password(service(), test_password());
 
             assert!(result.is_ok(), "Failed to verify password");
 
@@ -443,7 +559,7 @@ mod tests {
     #[test]
     fn verify_password_fails_with_bad_password() {
         with_user(|user| {
-            let result = user.verify_password("This is not the password");
+            let result = user.verify_password(service(), "This is not the password");
 
             assert!(result.is_ok(), "Failed to verify password");
 
@@ -452,4 +568,26 @@ mod tests {
             assert!(!result, "Incorrect password was succesfully matched");
         });
     }
+
+    #[test]
+    fn verify_password_upgrades_outdated_hash() {
+        with_user(|user| {
+            let hash = bcrypt::hash(test_password(), 4).expect("Failed to hash password");
+            service().store().update_password(user.id(), &hash).unwrap();
+
+            let user = User::find_by_id(service(), user.id()).unwrap();
+
+            assert!(
+                user.verify_password(service(), test_password()).unwrap(),
+                "Rejected outdated hash"
+            );
+
+            let user = User::find_by_id(service(), user.id()).unwrap();
+
+            assert!(
+                !service().password_hasher().needs_rehash(&user.password),
+                "Outdated hash was not upgraded"
+            );
+        });
+    }
 }
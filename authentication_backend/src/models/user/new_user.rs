@@ -17,10 +17,7 @@
  * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
  */
 
-use diesel;
-use diesel::prelude::*;
-use bcrypt::hash;
-use CONFIG;
+use service::AuthService;
 use super::{UserTrait, User};
 use schema::users;
 use error::{InputErrorKind, Error, Result};
@@ -35,7 +32,7 @@ pub struct NewUser {
 }
 
 impl NewUser {
-    pub fn new(auth: &Authenticatable) -> Result<Self> {
+    pub fn new(service: &AuthService, auth: &Authenticatable) -> Result<Self> {
         let (username, password) = match *auth {
             Authenticatable::UserAndPass {
                 username: u,
@@ -44,10 +41,10 @@ impl NewUser {
             _ => return Err(Error::InputError(InputErrorKind::Authenticatable)),
         };
 
-        let password = validate_password(password)?;
+        let password = validate_password(service, username, password)?;
         let username = validate_username(username)?;
 
-        let hash = hash(password, CONFIG.bcrypt_cost())?;
+        let hash = service.password_hasher().hash(password)?;
 
         Ok(NewUser {
             username: username.to_string(),
@@ -55,19 +52,26 @@ impl NewUser {
         })
     }
 
-    pub fn save(&self) -> Result<User> {
-        use schema::users;
-        use models::verification_code::NewVerificationCode;
+    pub(crate) fn username(&self) -> &str {
+        &self.username
+    }
+
+    pub(crate) fn password(&self) -> &str {
+        &self.password
+    }
 
-        let db = CONFIG.db()?;
+    pub fn save(&self, service: &AuthService) -> Result<User> {
+        self.save_with_email(service, None)
+    }
+
+    pub fn save_with_email(&self, service: &AuthService, email: Option<&str>) -> Result<User> {
+        use models::verification_code::NewVerificationCode;
 
-        let user: User = diesel::insert(self).into(users::table).get_result(
-            db.conn(),
-        )?;
+        let user = service.store().create_user(self)?;
 
-        let verification_code = NewVerificationCode::new_by_id(UserTrait::id(&user))?;
+        let verification_code = NewVerificationCode::new_for_email(UserTrait::id(&user), email)?;
 
-        let _ = verification_code.save()?;
+        let _ = verification_code.save(service)?;
 
         Ok(user)
     }
@@ -96,7 +100,7 @@ mod tests {
             password: test_password(),
         };
 
-        let new_user: Result<NewUser> = NewUser::new(&auth);
+        let new_user: Result<NewUser> = NewUser::new(service(), &auth);
 
         assert!(!new_user.is_ok(), "Invalid username still created NewUser");
     }
@@ -108,7 +112,7 @@ mod tests {
             password: "4sdf$.",
         };
 
-        let new_user: Result<NewUser> = NewUser::new(&auth);
+        let new_user: Result<NewUser> = NewUser::new(service(), &auth);
 
         assert!(!new_user.is_ok(), "Short password still created NewUser");
     }
@@ -120,7 +124,7 @@ mod tests {
             password: "asdfasdfasdf",
         };
 
-        let new_user: Result<NewUser> = NewUser::new(&auth);
+        let new_user: Result<NewUser> = NewUser::new(service(), &auth);
 
         assert!(!new_user.is_ok(), "Weak password still created NewUser")
     }
@@ -128,7 +132,7 @@ mod tests {
     #[test]
     fn save_creates_user() {
         user::test_helper::with_new_user(|new_user| {
-            let user: Result<User> = new_user.save();
+            let user: Result<User> = new_user.save(service());
 
             assert!(user.is_ok(), "Failed to save NewUser");
             user::test_helper::teardown(UserTrait::id(&user.unwrap()));
@@ -137,15 +141,12 @@ mod tests {
 
     #[test]
     fn save_creates_verification_code() {
-        use schema::verification_codes::dsl::*;
         use models::verification_code::VerificationCode;
 
         user::test_helper::with_new_user(|new_user| {
-            let user = new_user.save().expect("Failed to save User");
+            let user = new_user.save(service()).expect("Failed to save User");
 
-            let vc = verification_codes
-                .filter(user_id.eq(UserTrait::id(&user)))
-                .first::<VerificationCode>(CONFIG.db().unwrap().conn());
+            let vc = VerificationCode::find_by_user_id(service(), UserTrait::id(&user));
 
             assert!(vc.is_ok(), "Failed to create Verification Code for User");
 
@@ -156,8 +157,8 @@ mod tests {
     #[test]
     fn cannot_save_multiple_identical_users() {
         user::test_helper::with_new_user(|new_user| {
-            let result: Result<User> = new_user.save();
-            let result2: Result<User> = new_user.save();
+            let result: Result<User> = new_user.save(service());
+            let result2: Result<User> = new_user.save(service());
 
             assert!(result.is_ok(), "Failed to save user");
             assert!(!result2.is_ok(), "Saved user with same username");
@@ -17,9 +17,6 @@
  * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
  */
 
-use diesel;
-use diesel::prelude::*;
-use CONFIG;
 use std::panic;
 use test_helper::*;
 use error::Result;
@@ -28,15 +25,11 @@ use models::{Permission, UserPermission};
 use authenticatable::Authenticatable;
 
 pub fn teardown(u_id: i32) -> () {
-    use schema::users::dsl::{users, id};
-
-    let _ = diesel::delete(users.filter(id.eq(u_id))).execute(CONFIG.db().unwrap().conn());
+    let _ = service().store().delete_user(u_id);
 }
 
 pub fn teardown_by_name(u_name: &str) -> () {
-    use schema::users::dsl::{users, username};
-
-    let _ = diesel::delete(users.filter(username.eq(u_name))).execute(CONFIG.db().unwrap().conn());
+    let _ = service().store().delete_user_by_name(u_name);
 }
 
 pub fn with_new_user<T>(test: T) -> ()
@@ -52,7 +45,7 @@ where
     T: FnOnce(User) -> () + panic::UnwindSafe,
 {
     with_new_user(|new_user| {
-        let user = new_user.save().expect(
+        let user = new_user.save(service()).expect(
             "Failed to create User for with_user",
         );
 
@@ -68,9 +61,10 @@ where
     T: FnOnce(Admin) -> () + panic::UnwindSafe,
 {
     with_user(|user| {
-        let admin_permission = Permission::find("admin").expect("Failed to find admin permission");
+        let admin_permission =
+            Permission::find(service(), "admin").expect("Failed to find admin permission");
 
-        let _ = UserPermission::create(&user, &admin_permission).expect(
+        let _ = UserPermission::create(service(), &user, &admin_permission).expect(
             "Failed to make test admin user_permission",
         );
 
@@ -79,9 +73,9 @@ where
             password: test_password(),
         };
 
-        let auth = User::authenticate(&auth).expect("Failed to authenticate");
+        let auth = User::authenticate(service(), &auth).expect("Failed to authenticate");
 
-        let admin = Admin::from_authenticated(auth).expect(
+        let admin = Admin::from_authenticated(service(), auth).expect(
             "Failed to get Admin from User with 'admin' permission",
         );
 
@@ -99,7 +93,7 @@ where
             password: test_password(),
         };
 
-        let auth = User::authenticate(&auth).expect("Failed to authenticate");
+        let auth = User::authenticate(service(), &auth).expect("Failed to authenticate");
 
         panic::catch_unwind(|| test(auth)).unwrap();
     });
@@ -115,7 +109,7 @@ where
             password: test_password(),
         };
 
-        let auth = User::authenticate_session(&auth).expect("Failed to authenticate");
+        let auth = User::authenticate_session(service(), &auth).expect("Failed to authenticate");
 
         panic::catch_unwind(|| test(auth)).unwrap();
     });
@@ -127,5 +121,5 @@ pub fn generate_new_user() -> Result<NewUser> {
         password: test_password(),
     };
 
-    NewUser::new(&auth)
+    NewUser::new(service(), &auth)
 }
@@ -24,9 +24,7 @@ pub mod test_helper;
 
 pub use self::new_user_permission::NewUserPermission;
 
-use diesel;
-use diesel::prelude::*;
-use CONFIG;
+use service::AuthService;
 use error::Result;
 use schema::user_permissions;
 use models::user::{User, UserTrait};
@@ -42,6 +40,14 @@ pub struct UserPermission {
 }
 
 impl UserPermission {
+    pub(crate) fn from_parts(id: i32, user_id: i32, permission_id: i32) -> Self {
+        UserPermission {
+            id: id,
+            user_id: user_id,
+            permission_id: permission_id,
+        }
+    }
+
     pub fn id(&self) -> i32 {
         self.id
     }
@@ -54,30 +60,23 @@ impl UserPermission {
         self.permission_id
     }
 
-    pub fn create<T>(user: &T, permission: &Permission) -> Result<Self>
+    pub fn create<T>(service: &AuthService, user: &T, permission: &Permission) -> Result<Self>
     where
         T: UserTrait,
     {
         let new_user_permission = NewUserPermission::new(user, permission);
 
-        new_user_permission.save()
+        new_user_permission.save(service)
     }
 
-    pub fn has_permission<T>(user: &T, permission: &Permission) -> bool
+    pub fn has_permission<T>(service: &AuthService, user: &T, permission: &Permission) -> bool
     where
         T: UserTrait,
     {
-        use schema::user_permissions::dsl::{user_permissions, user_id, permission_id};
-
-        let db = match CONFIG.db() {
-            Ok(db) => db,
-            _ => return false,
-        };
-
-        let user_permission = user_permissions
-            .filter(user_id.eq(user.id()))
-            .filter(permission_id.eq(permission.id()))
-            .first::<UserPermission>(db.conn());
+        let user_permission = service.store().find_user_permission(
+            user.id(),
+            permission.id(),
+        );
 
         match user_permission {
             Ok(_permission) => true,
@@ -85,58 +84,22 @@ impl UserPermission {
         }
     }
 
-    pub fn get_permissions<T>(user: &T) -> Result<Vec<Permission>>
+    pub fn get_permissions<T>(service: &AuthService, user: &T) -> Result<Vec<Permission>>
     where
         T: UserTrait,
     {
-        use schema::user_permissions::dsl::{user_permissions, user_id, permission_id};
-        use schema::permissions::dsl::{id, permissions};
-
-        let db = CONFIG.db()?;
-
-        let results: Vec<(UserPermission, Permission)> =
-            user_permissions
-                .inner_join::<permissions>(permissions)
-                .filter(permission_id.eq(id))
-                .filter(user_id.eq(user.id()))
-                .load::<(UserPermission, Permission)>(db.conn())?;
-
-        Ok(
-            results
-                .into_iter()
-                .map(|(_, permission)| permission)
-                .collect(),
-        )
+        service.store().find_permissions_by_user_id(user.id())
     }
 
-    pub fn get_users(permission: &Permission) -> Result<Vec<User>> {
-        use schema::user_permissions::dsl::{user_permissions, user_id, permission_id};
-        use schema::users::dsl::{id, users};
-
-        let db = CONFIG.db()?;
-
-        let results: Vec<(UserPermission, User)> = user_permissions
-            .inner_join::<users>(users)
-            .filter(user_id.eq(id))
-            .filter(permission_id.eq(permission.id()))
-            .load::<(UserPermission, User)>(db.conn())?;
-
-        Ok(results.into_iter().map(|(_, user)| user).collect())
+    pub fn get_users(service: &AuthService, permission: &Permission) -> Result<Vec<User>> {
+        service.store().find_users_by_permission_id(permission.id())
     }
 
-    pub fn delete<T>(user: &T, permission: &Permission) -> Result<()>
+    pub fn delete<T>(service: &AuthService, user: &T, permission: &Permission) -> Result<()>
     where
         T: UserTrait,
     {
-        use schema::user_permissions::dsl::{user_permissions, user_id, permission_id};
-
-        let db = CONFIG.db()?;
-
-        diesel::delete(user_permissions.filter(user_id.eq(user.id())).filter(
-            permission_id.eq(permission.id()),
-        )).execute(db.conn())?;
-
-        Ok(())
+        service.store().delete_user_permission(user.id(), permission.id())
     }
 }
 
@@ -147,13 +110,14 @@ mod tests {
     use models::permission::Permission;
     use models::permission::test_helper::with_permission;
     use models::user_permission::test_helper::with_user_permission;
+    use test_helper::service;
 
     #[test]
     fn new_user_is_not_admin() {
         with_user(|user| {
-            let admin = Permission::find("admin").unwrap();
+            let admin = Permission::find(service(), "admin").unwrap();
 
-            let result = UserPermission::has_permission(&user, &admin);
+            let result = UserPermission::has_permission(service(), &user, &admin);
 
             assert!(!result, "New User is Admin");
         });
@@ -162,11 +126,11 @@ mod tests {
     #[test]
     fn can_make_user_admin() {
         with_user(|user| {
-            let admin = Permission::find("admin").unwrap();
+            let admin = Permission::find(service(), "admin").unwrap();
 
-            let _ = UserPermission::create(&user, &admin).unwrap();
+            let _ = UserPermission::create(service(), &user, &admin).unwrap();
 
-            let result = UserPermission::has_permission(&user, &admin);
+            let result = UserPermission::has_permission(service(), &user, &admin);
 
             assert!(result, "User can become admin");
         });
@@ -175,7 +139,7 @@ mod tests {
     #[test]
     fn get_permissions_gets_permissions() {
         with_user_permission(|user, permission, _user_permission| {
-            let result = UserPermission::get_permissions(&user);
+            let result = UserPermission::get_permissions(service(), &user);
 
             assert!(result.is_ok(), "Failed to get Permissions for User");
 
@@ -192,7 +156,7 @@ mod tests {
     #[test]
     fn get_users_gets_users() {
         with_user_permission(|user, permission, _user_permission| {
-            let result = UserPermission::get_users(&permission);
+            let result = UserPermission::get_users(service(), &permission);
 
             assert!(result.is_ok(), "Failed to get Users with Permission");
 
@@ -206,7 +170,7 @@ mod tests {
     fn create_creates_user_permission() {
         with_user(|user| {
             with_permission(|permission| {
-                let result = UserPermission::create(&user, &permission);
+                let result = UserPermission::create(service(), &user, &permission);
 
                 assert!(result.is_ok(), "Failed to create UserPermission");
             });
@@ -17,9 +17,7 @@
  * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
  */
 
-use diesel;
-use diesel::prelude::*;
-use CONFIG;
+use service::AuthService;
 use error::Result;
 use schema::user_permissions;
 use models::{Permission, UserPermission};
@@ -43,14 +41,16 @@ impl NewUserPermission {
         }
     }
 
-    pub fn save(&self) -> Result<UserPermission> {
-        use schema::user_permissions::dsl::*;
+    pub(crate) fn user_id(&self) -> i32 {
+        self.user_id
+    }
 
-        let db = CONFIG.db()?;
+    pub(crate) fn permission_id(&self) -> i32 {
+        self.permission_id
+    }
 
-        Ok(diesel::insert(self).into(user_permissions).get_result(
-            db.conn(),
-        )?)
+    pub fn save(&self, service: &AuthService) -> Result<UserPermission> {
+        service.store().create_user_permission(self)
     }
 }
 
@@ -59,12 +59,13 @@ mod tests {
     use super::*;
     use models::user::test_helper::with_user;
     use models::permission::test_helper::with_permission;
+    use test_helper::service;
 
     #[test]
     fn save_saves_new_user_permission() {
         with_user(|user| {
             with_permission(|permission| {
-                let result = NewUserPermission::new(&user, &permission).save();
+                let result = NewUserPermission::new(&user, &permission).save(service());
 
                 assert!(result.is_ok(), "Failed to save NewUserPermission");
             });
@@ -17,9 +17,7 @@
  * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
  */
 
-use diesel;
-use diesel::prelude::*;
-use CONFIG;
+use test_helper::service;
 use std::panic;
 use models::user::User;
 use models::user::test_helper::with_user;
@@ -34,21 +32,19 @@ where
 {
     with_user(|user| {
         with_permission(|permission| {
-            let user_permission = NewUserPermission::new(&user, &permission).save().expect(
+            let user_permission = NewUserPermission::new(&user, &permission).save(service()).expect(
                 "Failed to save NewUserPermission",
             );
 
-            let up_id = user_permission.id();
+            let user_id = user_permission.user_id();
+            let permission_id = user_permission.permission_id();
             let result = panic::catch_unwind(|| test(user, permission, user_permission));
-            teardown(up_id);
+            teardown(user_id, permission_id);
             result.unwrap();
         });
     });
 }
 
-pub fn teardown(up_id: i32) -> () {
-    use schema::user_permissions::dsl::*;
-
-    let _ = diesel::delete(user_permissions.filter(id.eq(up_id)))
-        .execute(CONFIG.db().unwrap().conn());
+pub fn teardown(user_id: i32, permission_id: i32) -> () {
+    let _ = service().store().delete_user_permission(user_id, permission_id);
 }
@@ -21,9 +21,7 @@ mod new_verification_code;
 
 pub use self::new_verification_code::NewVerificationCode;
 
-use diesel;
-use diesel::prelude::*;
-use CONFIG;
+use service::AuthService;
 use schema::verification_codes;
 use models::user::User;
 use error::Result;
@@ -34,31 +32,37 @@ pub struct VerificationCode {
     id: i32,
     code: String,
     user_id: i32,
+    email: Option<String>,
 }
 
 impl VerificationCode {
-    pub fn create_by_username(username: &str) -> Result<Self> {
-        let new_verification_code = NewVerificationCode::new_by_username(username)?;
-
-        new_verification_code.save()
+    pub(crate) fn from_parts(id: i32, code: String, user_id: i32, email: Option<String>) -> Self {
+        VerificationCode {
+            id: id,
+            code: code,
+            user_id: user_id,
+            email: email,
+        }
     }
 
-    pub fn create_by_id(user_id: i32) -> Result<Self> {
-        let new_verification_code = NewVerificationCode::new_by_id(user_id)?;
+    pub fn create_by_username(service: &AuthService, username: &str) -> Result<Self> {
+        let new_verification_code = NewVerificationCode::new_by_username(service, username)?;
 
-        new_verification_code.save()
+        new_verification_code.save(service)
     }
 
-    pub fn find_by_user_id(u_id: i32) -> Result<Self> {
-        use schema::verification_codes::dsl::{verification_codes, user_id};
+    pub fn create_by_id(service: &AuthService, user_id: i32) -> Result<Self> {
+        let new_verification_code = NewVerificationCode::new_by_id(user_id)?;
 
-        let db = CONFIG.db()?;
+        new_verification_code.save(service)
+    }
 
-        let verification_code = verification_codes
-            .filter(user_id.eq(u_id))
-            .first::<VerificationCode>(db.conn())?;
+    pub fn find(service: &AuthService, code: &str) -> Result<Self> {
+        service.store().find_verification_code(code)
+    }
 
-        Ok(verification_code)
+    pub fn find_by_user_id(service: &AuthService, user_id: i32) -> Result<Self> {
+        service.store().find_verification_code_by_user_id(user_id)
     }
 
     pub fn id(&self) -> i32 {
@@ -73,15 +77,12 @@ impl VerificationCode {
         self.user_id
     }
 
-    pub fn delete_by_user_id(u_id: i32) -> Result<()> {
-        use schema::verification_codes::dsl::{verification_codes, user_id};
-
-        let db = CONFIG.db()?;
-
-        let _ = diesel::delete(verification_codes.filter(user_id.eq(u_id)))
-            .execute(db.conn())?;
+    pub fn email(&self) -> Option<&str> {
+        self.email.as_ref().map(|email| email.as_str())
+    }
 
-        Ok(())
+    pub fn delete_by_user_id(service: &AuthService, user_id: i32) -> Result<()> {
+        service.store().delete_verification_codes_by_user_id(user_id)
     }
 }
 
@@ -90,11 +91,12 @@ mod tests {
     use super::*;
     use models::user::UserTrait;
     use models::user::test_helper::with_user;
+    use test_helper::service;
 
     #[test]
     fn find_by_user_id_finds_verification_code() {
         with_user(|user| {
-            let result = VerificationCode::find_by_user_id(UserTrait::id(&user));
+            let result = VerificationCode::find_by_user_id(service(), UserTrait::id(&user));
 
             assert!(
                 result.is_ok(),
@@ -103,9 +105,20 @@ mod tests {
         });
     }
 
+    #[test]
+    fn find_finds_verification_code() {
+        with_user(|user| {
+            let vc = VerificationCode::find_by_user_id(service(), UserTrait::id(&user)).unwrap();
+
+            let result = VerificationCode::find(service(), vc.code());
+
+            assert!(result.is_ok(), "Failed to find verification_code by code");
+        });
+    }
+
     #[test]
     fn find_by_user_id_fails_with_bad_id() {
-        let result = VerificationCode::find_by_user_id(-1);
+        let result = VerificationCode::find_by_user_id(service(), -1);
 
         assert!(!result.is_ok(), "Found verification_code for bad user id");
     }
@@ -113,7 +126,7 @@ mod tests {
     #[test]
     fn delete_by_user_id_deletes_verification_code() {
         with_user(|user| {
-            let result = VerificationCode::delete_by_user_id(UserTrait::id(&user));
+            let result = VerificationCode::delete_by_user_id(service(), UserTrait::id(&user));
 
             assert!(result.is_ok(), "Failed to delete verification_code");
         });
@@ -17,10 +17,8 @@
  * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
  */
 
-use diesel;
-use diesel::prelude::*;
 use error::Result;
-use CONFIG;
+use service::AuthService;
 use schema::verification_codes;
 use models::{User, VerificationCode};
 use models::user::UserTrait;
@@ -30,20 +28,21 @@ use models::user::UserTrait;
 pub struct NewVerificationCode {
     code: String,
     user_id: i32,
+    email: Option<String>,
 }
 
 impl NewVerificationCode {
-    pub fn new_by_username(uname: &str) -> Result<Self> {
-        use schema::users::dsl::*;
-
-        let db = CONFIG.db()?;
-
-        let user: User = users.filter(username.eq(uname)).first::<User>(db.conn())?;
+    pub fn new_by_username(service: &AuthService, uname: &str) -> Result<Self> {
+        let user = User::find_by_name(service, uname)?;
 
         Self::new_by_id(UserTrait::id(&user))
     }
 
     pub fn new_by_id(user_id: i32) -> Result<Self> {
+        NewVerificationCode::new_for_email(user_id, None)
+    }
+
+    pub fn new_for_email(user_id: i32, email: Option<&str>) -> Result<Self> {
         use rand::Rng;
         use rand::OsRng;
 
@@ -52,19 +51,24 @@ impl NewVerificationCode {
         Ok(NewVerificationCode {
             code: os_rng.gen_ascii_chars().take(30).collect(),
             user_id: user_id,
+            email: email.map(|email| email.to_owned()),
         })
     }
 
-    pub fn save(&self) -> Result<VerificationCode> {
-        use schema::verification_codes;
+    pub(crate) fn code(&self) -> &str {
+        &self.code
+    }
 
-        let db = CONFIG.db()?;
+    pub(crate) fn user_id(&self) -> i32 {
+        self.user_id
+    }
 
-        let verification_code = diesel::insert(self)
-            .into(verification_codes::table)
-            .get_result(db.conn())?;
+    pub(crate) fn email(&self) -> Option<&str> {
+        self.email.as_ref().map(|email| email.as_str())
+    }
 
-        Ok(verification_code)
+    pub fn save(&self, service: &AuthService) -> Result<VerificationCode> {
+        service.store().create_verification_code(self)
     }
 }
 
@@ -79,7 +83,7 @@ mod tests {
     #[test]
     fn new_by_username_creates_verification_code() {
         with_user(|user| {
-            let result = NewVerificationCode::new_by_username(&user.username());
+            let result = NewVerificationCode::new_by_username(service(), &user.username());
 
             assert!(
                 result.is_ok(),
@@ -90,7 +94,7 @@ mod tests {
 
     #[test]
     fn new_by_username_fails_with_bad_username() {
-        let result = NewVerificationCode::new_by_username("this username doesn't exist");
+        let result = NewVerificationCode::new_by_username(service(), "this username doesn't exist");
 
         assert!(
             !result.is_ok(),
@@ -107,23 +111,18 @@ mod tests {
 
     #[test]
     fn save_saves_verification_code() {
-        use schema::users;
-
         let auth = Authenticatable::UserAndPass {
             username: &generate_string(),
             password: "P4ssw0rd$.",
         };
 
-        let new_user = NewUser::new(&auth).unwrap();
+        let new_user = NewUser::new(service(), &auth).unwrap();
 
-        let user: User = diesel::insert(&new_user)
-            .into(users::table)
-            .get_result(CONFIG.db().unwrap().conn())
-            .unwrap();
+        let user = service().store().create_user(&new_user).unwrap();
 
         let new_verification_code = NewVerificationCode::new_by_id(UserTrait::id(&user)).unwrap();
 
-        let result = new_verification_code.save();
+        let result = new_verification_code.save(service());
 
         assert!(result.is_ok(), "Failed to save verification_code");
         teardown(UserTrait::id(&user));
@@ -133,7 +132,7 @@ mod tests {
     fn save_fails_with_bad_user_id() {
         let new_verification_code = NewVerificationCode::new_by_id(-1).unwrap();
 
-        let result = new_verification_code.save();
+        let result = new_verification_code.save(service());
 
         assert!(
             !result.is_ok(),
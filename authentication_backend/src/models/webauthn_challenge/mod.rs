@@ -0,0 +1,121 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+mod new_webauthn_challenge;
+
+pub use self::new_webauthn_challenge::NewWebauthnChallenge;
+
+use chrono::{NaiveDateTime, Utc};
+use service::AuthService;
+use schema::webauthn_challenges;
+use error::{Error, Result, WebauthnErrorKind};
+
+#[derive(Debug, Queryable, Identifiable)]
+#[table_name = "webauthn_challenges"]
+pub struct WebauthnChallenge {
+    id: i32,
+    challenge: String,
+    expires_at: NaiveDateTime,
+}
+
+impl WebauthnChallenge {
+    pub(crate) fn from_parts(id: i32, challenge: String, expires_at: NaiveDateTime) -> Self {
+        WebauthnChallenge {
+            id: id,
+            challenge: challenge,
+            expires_at: expires_at,
+        }
+    }
+
+    pub fn create(
+        service: &AuthService,
+        challenge: &str,
+        expires_at: NaiveDateTime,
+    ) -> Result<Self> {
+        service.store().delete_expired_webauthn_challenges(
+            Utc::now().naive_utc(),
+        )?;
+
+        NewWebauthnChallenge::new(challenge, expires_at).save(service)
+    }
+
+    pub fn use_up(service: &AuthService, challenge: &str) -> Result<()> {
+        if !service.store().delete_webauthn_challenge(challenge)? {
+            return Err(Error::WebauthnError(WebauthnErrorKind::ChallengeMismatch));
+        }
+
+        Ok(())
+    }
+
+    pub fn id(&self) -> i32 {
+        self.id
+    }
+
+    pub fn challenge(&self) -> &str {
+        &self.challenge
+    }
+
+    pub fn expires_at(&self) -> NaiveDateTime {
+        self.expires_at
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use test_helper::{generate_string, service};
+
+    #[test]
+    fn use_up_accepts_each_challenge_once() {
+        let challenge = generate_string();
+        let expires_at = Utc::now().naive_utc() + Duration::minutes(5);
+
+        let _ = WebauthnChallenge::create(service(), &challenge, expires_at)
+            .expect("Failed to store challenge");
+
+        assert!(
+            WebauthnChallenge::use_up(service(), &challenge).is_ok(),
+            "Failed to use challenge"
+        );
+        assert!(
+            !WebauthnChallenge::use_up(service(), &challenge).is_ok(),
+            "Used challenge twice"
+        );
+    }
+
+    #[test]
+    fn create_clears_expired_challenges() {
+        let expired = generate_string();
+        let expires_at = Utc::now().naive_utc() - Duration::minutes(5);
+
+        let _ = WebauthnChallenge::create(service(), &expired, expires_at)
+            .expect("Failed to store challenge");
+        let _ = WebauthnChallenge::create(
+            service(),
+            &generate_string(),
+            Utc::now().naive_utc() + Duration::minutes(5),
+        ).expect("Failed to store challenge");
+
+        assert!(
+            !WebauthnChallenge::use_up(service(), &expired).is_ok(),
+            "Kept expired challenge"
+        );
+    }
+}
@@ -0,0 +1,52 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use chrono::NaiveDateTime;
+use service::AuthService;
+use error::Result;
+use schema::webauthn_challenges;
+use models::WebauthnChallenge;
+
+#[derive(Debug, Insertable)]
+#[table_name = "webauthn_challenges"]
+pub struct NewWebauthnChallenge {
+    challenge: String,
+    expires_at: NaiveDateTime,
+}
+
+impl NewWebauthnChallenge {
+    pub fn new(challenge: &str, expires_at: NaiveDateTime) -> Self {
+        NewWebauthnChallenge {
+            challenge: challenge.to_owned(),
+            expires_at: expires_at,
+        }
+    }
+
+    pub(crate) fn challenge(&self) -> &str {
+        &self.challenge
+    }
+
+    pub(crate) fn expires_at(&self) -> NaiveDateTime {
+        self.expires_at
+    }
+
+    pub fn save(&self, service: &AuthService) -> Result<WebauthnChallenge> {
+        service.store().create_webauthn_challenge(self)
+    }
+}
@@ -0,0 +1,175 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+mod new_webauthn_credential;
+
+pub use self::new_webauthn_credential::NewWebauthnCredential;
+
+use chrono::NaiveDateTime;
+use service::AuthService;
+use schema::webauthn_credentials;
+use models::user::{User, UserTrait};
+use webauthn::PublicKey;
+use error::{DbError, Error, Result, WebauthnErrorKind};
+
+#[derive(Debug, Queryable, Identifiable, Associations)]
+#[table_name = "webauthn_credentials"]
+#[belongs_to(User)]
+pub struct WebauthnCredential {
+    id: i32,
+    user_id: i32,
+    credential_id: Vec<u8>,
+    public_key: Vec<u8>,
+    algorithm: i32,
+    sign_count: i64,
+    created_at: NaiveDateTime,
+}
+
+impl WebauthnCredential {
+    pub(crate) fn from_parts(
+        id: i32,
+        user_id: i32,
+        credential_id: Vec<u8>,
+        public_key: Vec<u8>,
+        algorithm: i32,
+        sign_count: i64,
+        created_at: NaiveDateTime,
+    ) -> Self {
+        WebauthnCredential {
+            id: id,
+            user_id: user_id,
+            credential_id: credential_id,
+            public_key: public_key,
+            algorithm: algorithm,
+            sign_count: sign_count,
+            created_at: created_at,
+        }
+    }
+
+    pub fn find(service: &AuthService, credential_id: &[u8]) -> Result<Self> {
+        match service.store().find_webauthn_credential(credential_id) {
+            Err(Error::DbError(DbError::NotFound)) => {
+                Err(Error::WebauthnError(WebauthnErrorKind::UnknownCredential))
+            }
+            result => result,
+        }
+    }
+
+    pub fn find_by_user<T>(service: &AuthService, user: &T) -> Result<Vec<Self>>
+    where
+        T: UserTrait,
+    {
+        service.store().find_webauthn_credentials_by_user_id(user.id())
+    }
+
+    // Authenticators without a counter always report zero. Otherwise the counter has to go up
+    // with every use, and a counter that doesn't suggests the credential was cloned.
+    pub fn record_use(&self, service: &AuthService, sign_count: u32) -> Result<()> {
+        if sign_count == 0 && self.sign_count == 0 {
+            return Ok(());
+        }
+
+        if service.store().update_webauthn_sign_count(
+            self.id,
+            sign_count as i64,
+        )?
+        {
+            Ok(())
+        } else {
+            Err(Error::WebauthnError(WebauthnErrorKind::CounterRegression))
+        }
+    }
+
+    pub fn id(&self) -> i32 {
+        self.id
+    }
+
+    pub fn user_id(&self) -> i32 {
+        self.user_id
+    }
+
+    pub fn credential_id(&self) -> &[u8] {
+        &self.credential_id
+    }
+
+    pub fn public_key(&self) -> PublicKey {
+        PublicKey::new(self.algorithm, self.public_key.clone())
+    }
+
+    pub fn sign_count(&self) -> i64 {
+        self.sign_count
+    }
+
+    pub fn created_at(&self) -> NaiveDateTime {
+        self.created_at
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic;
+    use super::*;
+    use models::user::test_helper::with_user;
+    use test_helper::{generate_string, service};
+
+    fn with_credential<T>(test: T)
+    where
+        T: FnOnce(WebauthnCredential) -> () + panic::UnwindSafe,
+    {
+        with_user(|user| {
+            let credential_id = generate_string().into_bytes();
+            let public_key = PublicKey::new(-7, vec![4]);
+
+            let credential = NewWebauthnCredential::new(&user, credential_id, &public_key)
+                .save(service())
+                .expect("Failed to save credential");
+
+            test(credential);
+        });
+    }
+
+    #[test]
+    fn record_use_rejects_counter_regression() {
+        with_credential(|credential| {
+            let result = credential.record_use(service(), 5);
+
+            assert!(result.is_ok(), "Rejected increased counter");
+
+            let result = credential.record_use(service(), 5);
+
+            assert!(!result.is_ok(), "Accepted repeated counter");
+        });
+    }
+
+    #[test]
+    fn record_use_accepts_authenticators_without_counter() {
+        with_credential(|credential| {
+            let _ = credential.record_use(service(), 0).expect("Rejected zero counter");
+            let _ = credential.record_use(service(), 0).expect("Rejected zero counter twice");
+        });
+    }
+
+    #[test]
+    fn find_fails_with_unknown_credential() {
+        match WebauthnCredential::find(service(), b"not a credential") {
+            Err(Error::WebauthnError(WebauthnErrorKind::UnknownCredential)) => (),
+            _ => panic!("Found unknown credential"),
+        }
+    }
+}
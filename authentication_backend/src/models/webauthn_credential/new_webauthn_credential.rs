@@ -0,0 +1,75 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use chrono::{NaiveDateTime, Utc};
+use service::AuthService;
+use error::Result;
+use schema::webauthn_credentials;
+use models::WebauthnCredential;
+use models::user::UserTrait;
+use webauthn::PublicKey;
+
+#[derive(Debug, Insertable)]
+#[table_name = "webauthn_credentials"]
+pub struct NewWebauthnCredential {
+    user_id: i32,
+    credential_id: Vec<u8>,
+    public_key: Vec<u8>,
+    algorithm: i32,
+    created_at: NaiveDateTime,
+}
+
+impl NewWebauthnCredential {
+    pub fn new<T>(user: &T, credential_id: Vec<u8>, public_key: &PublicKey) -> Self
+    where
+        T: UserTrait,
+    {
+        NewWebauthnCredential {
+            user_id: user.id(),
+            credential_id: credential_id,
+            public_key: public_key.key().to_vec(),
+            algorithm: public_key.algorithm(),
+            created_at: Utc::now().naive_utc(),
+        }
+    }
+
+    pub(crate) fn user_id(&self) -> i32 {
+        self.user_id
+    }
+
+    pub(crate) fn credential_id(&self) -> &[u8] {
+        &self.credential_id
+    }
+
+    pub(crate) fn public_key(&self) -> &[u8] {
+        &self.public_key
+    }
+
+    pub(crate) fn algorithm(&self) -> i32 {
+        self.algorithm
+    }
+
+    pub(crate) fn created_at(&self) -> NaiveDateTime {
+        self.created_at
+    }
+
+    pub fn save(&self, service: &AuthService) -> Result<WebauthnCredential> {
+        service.store().create_webauthn_credential(self)
+    }
+}
@@ -0,0 +1,231 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use std::time::Instant;
+use chrono::Duration;
+use config::RateLimit;
+use error::{Error, Result};
+use super::RateLimiter;
+
+const MAX_BUCKETS: usize = 10000;
+
+struct Bucket {
+    requests: f64,
+    capacity: f64,
+    rate: f64,
+    updated_at: Instant,
+    touched: u64,
+}
+
+impl Bucket {
+    fn new(limit: &RateLimit, now: Instant) -> Self {
+        let capacity = limit.requests() as f64;
+        let period = limit.period().num_milliseconds() as f64 / 1000.0;
+
+        Bucket {
+            requests: capacity,
+            capacity: capacity,
+            rate: capacity / period,
+            updated_at: now,
+            touched: 0,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.duration_since(self.updated_at);
+        let elapsed = elapsed.as_secs() as f64 + elapsed.subsec_nanos() as f64 / 1e9;
+
+        self.requests = (self.requests + elapsed * self.rate).min(self.capacity);
+        self.updated_at = now;
+    }
+
+    fn is_full(&self) -> bool {
+        self.requests >= self.capacity
+    }
+}
+
+#[derive(Default)]
+struct Buckets {
+    buckets: HashMap<String, Bucket>,
+    by_touch: BTreeMap<u64, String>,
+    next_touch: u64,
+}
+
+impl Buckets {
+    fn touch(&mut self, key: &str, limit: &RateLimit, now: Instant) -> &mut Bucket {
+        let touched = self.next_touch;
+        self.next_touch += 1;
+
+        if let Some(bucket) = self.buckets.get(key) {
+            self.by_touch.remove(&bucket.touched);
+        }
+        self.by_touch.insert(touched, key.to_owned());
+
+        let bucket = self.buckets.entry(key.to_owned()).or_insert_with(
+            || Bucket::new(limit, now),
+        );
+
+        bucket.touched = touched;
+        bucket
+    }
+
+    fn evict(&mut self, now: Instant) {
+        loop {
+            let (touched, key) = match self.by_touch.iter().next() {
+                Some((touched, key)) => (*touched, key.clone()),
+                None => return,
+            };
+
+            let refilled = match self.buckets.get_mut(&key) {
+                Some(bucket) => {
+                    bucket.refill(now);
+                    bucket.is_full()
+                }
+                None => true,
+            };
+
+            if !refilled && self.buckets.len() <= MAX_BUCKETS {
+                return;
+            }
+
+            self.by_touch.remove(&touched);
+            self.buckets.remove(&key);
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct MemoryRateLimiter {
+    buckets: Mutex<Buckets>,
+}
+
+impl MemoryRateLimiter {
+    pub fn new() -> Self {
+        MemoryRateLimiter::default()
+    }
+}
+
+impl RateLimiter for MemoryRateLimiter {
+    fn take(&self, key: &str, limit: &RateLimit) -> Result<Option<Duration>> {
+        let mut buckets = match self.buckets.lock() {
+            Ok(buckets) => buckets,
+            Err(_) => return Err(Error::IOError),
+        };
+        let now = Instant::now();
+
+        let wait = {
+            let bucket = buckets.touch(key, limit, now);
+
+            bucket.refill(now);
+
+            if bucket.requests >= 1.0 {
+                bucket.requests -= 1.0;
+
+                None
+            } else {
+                let wait = (1.0 - bucket.requests) / bucket.rate;
+
+                Some(Duration::milliseconds((wait * 1000.0).ceil() as i64))
+            }
+        };
+
+        buckets.evict(now);
+
+        Ok(wait)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use std::time;
+    use super::*;
+
+    #[test]
+    fn take_refuses_once_bucket_is_empty() {
+        let limiter = MemoryRateLimiter::new();
+        let limit = RateLimit::new("test", 2, Duration::hours(1));
+
+        assert_eq!(limiter.take("key", &limit).unwrap(), None, "Refused first request");
+        assert_eq!(limiter.take("key", &limit).unwrap(), None, "Refused second request");
+
+        match limiter.take("key", &limit).unwrap() {
+            Some(wait) => {
+                assert!(wait <= Duration::minutes(30), "Waited longer than one refill")
+            }
+            None => panic!("Allowed request past the limit"),
+        }
+    }
+
+    #[test]
+    fn take_keeps_keys_apart() {
+        let limiter = MemoryRateLimiter::new();
+        let limit = RateLimit::new("test", 1, Duration::hours(1));
+
+        assert_eq!(limiter.take("first", &limit).unwrap(), None, "Refused first key");
+        assert_eq!(limiter.take("second", &limit).unwrap(), None, "Refused second key");
+    }
+
+    #[test]
+    fn take_refills_bucket_over_time() {
+        let limiter = MemoryRateLimiter::new();
+        let limit = RateLimit::new("test", 1, Duration::milliseconds(10));
+
+        assert_eq!(limiter.take("key", &limit).unwrap(), None, "Refused first request");
+        assert!(limiter.take("key", &limit).unwrap().is_some(), "Bucket didn't empty");
+
+        thread::sleep(time::Duration::from_millis(20));
+
+        assert_eq!(limiter.take("key", &limit).unwrap(), None, "Bucket didn't refill");
+    }
+
+    #[test]
+    fn take_drops_least_recently_used_buckets() {
+        let limiter = MemoryRateLimiter::new();
+        let limit = RateLimit::new("test", 1, Duration::hours(1));
+
+        for i in 0..MAX_BUCKETS + 100 {
+            assert_eq!(limiter.take(&i.to_string(), &limit).unwrap(), None, "Refused new key");
+        }
+
+        let buckets = limiter.buckets.lock().unwrap();
+
+        assert_eq!(buckets.buckets.len(), MAX_BUCKETS, "Kept too many buckets");
+        assert_eq!(buckets.by_touch.len(), MAX_BUCKETS, "Index lost track of buckets");
+        assert!(!buckets.buckets.contains_key("0"), "Kept least recently used bucket");
+    }
+
+    #[test]
+    fn take_drops_refilled_buckets() {
+        let limiter = MemoryRateLimiter::new();
+        let limit = RateLimit::new("test", 1, Duration::milliseconds(10));
+
+        assert_eq!(limiter.take("first", &limit).unwrap(), None, "Refused first key");
+
+        thread::sleep(time::Duration::from_millis(20));
+
+        assert_eq!(limiter.take("second", &limit).unwrap(), None, "Refused second key");
+
+        let buckets = limiter.buckets.lock().unwrap();
+
+        assert!(!buckets.buckets.contains_key("first"), "Kept refilled bucket");
+    }
+}
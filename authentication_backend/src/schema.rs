@@ -17,4 +17,116 @@
  * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
  */
 
-infer_schema!("dotenv:DATABASE_URL");
+table! {
+    users {
+        id -> Integer,
+        username -> Varchar,
+        password -> Varchar,
+        verified -> Bool,
+        email -> Nullable<Varchar>,
+    }
+}
+
+table! {
+    verification_codes {
+        id -> Integer,
+        code -> Varchar,
+        user_id -> Integer,
+        email -> Nullable<Varchar>,
+    }
+}
+
+table! {
+    permissions {
+        id -> Integer,
+        name -> Varchar,
+    }
+}
+
+table! {
+    user_permissions {
+        id -> Integer,
+        user_id -> Integer,
+        permission_id -> Integer,
+    }
+}
+
+table! {
+    token_families {
+        id -> Integer,
+        family -> Varchar,
+        user_id -> Integer,
+        current_jti -> Varchar,
+        revoked -> Bool,
+    }
+}
+
+table! {
+    sessions {
+        id -> Integer,
+        user_id -> Integer,
+        family -> Varchar,
+        user_agent -> Nullable<Varchar>,
+        ip_address -> Nullable<Varchar>,
+        created_at -> Timestamp,
+        last_renewed_at -> Nullable<Timestamp>,
+    }
+}
+
+table! {
+    totp_credentials {
+        id -> Integer,
+        user_id -> Integer,
+        secret -> Binary,
+        confirmed -> Bool,
+        last_used_step -> Nullable<BigInt>,
+    }
+}
+
+table! {
+    recovery_codes {
+        id -> Integer,
+        user_id -> Integer,
+        code_hash -> Varchar,
+    }
+}
+
+table! {
+    webauthn_credentials {
+        id -> Integer,
+        user_id -> Integer,
+        credential_id -> Binary,
+        public_key -> Binary,
+        algorithm -> Integer,
+        sign_count -> BigInt,
+        created_at -> Timestamp,
+    }
+}
+
+table! {
+    password_resets {
+        id -> Integer,
+        user_id -> Integer,
+        token_hash -> Varchar,
+        expires_at -> Timestamp,
+    }
+}
+
+table! {
+    failed_logins {
+        id -> Integer,
+        subject -> Varchar,
+        user_id -> Nullable<Integer>,
+        failures -> Integer,
+        last_failed_at -> Timestamp,
+        locked_until -> Nullable<Timestamp>,
+    }
+}
+
+table! {
+    webauthn_challenges {
+        id -> Integer,
+        challenge -> Varchar,
+        expires_at -> Timestamp,
+    }
+}
@@ -0,0 +1,181 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::cmp;
+use std::env;
+use std::sync::Arc;
+use config::{Clients, Config, JwkSet, RateLimit, DATABASE_URL};
+use config::breach_corpus::BreachCorpus;
+use config::jwt_secret::JWTSecret;
+use config::lockout::Lockout;
+use config::password_hasher::PasswordHasher;
+use config::password_policy::PasswordPolicy;
+use config::rate_limits::RateLimits;
+use config::relying_party::RelyingParty;
+use config::secret_box::SecretBox;
+use config::token_lifetimes::TokenLifetimes;
+use error::{ConfigErrorKind, Error, InputErrorKind, Result};
+use rate_limiter::{MemoryRateLimiter, RateLimiter};
+use store::{MemoryStore, PostgresStore, Store};
+
+#[derive(Clone)]
+pub struct AuthService {
+    config: Arc<Config>,
+    store: Arc<Store>,
+    rate_limiter: Arc<RateLimiter>,
+}
+
+impl AuthService {
+    pub fn new<S>(config: Config, store: S) -> Self
+    where
+        S: Store + 'static,
+    {
+        AuthService {
+            config: Arc::new(config),
+            store: Arc::new(store),
+            rate_limiter: Arc::new(MemoryRateLimiter::new()),
+        }
+    }
+
+    pub fn with_rate_limiter<R>(mut self, rate_limiter: R) -> Self
+    where
+        R: RateLimiter + 'static,
+    {
+        self.rate_limiter = Arc::new(rate_limiter);
+        self
+    }
+
+    pub fn postgres(config: Config) -> Result<Self> {
+        let store = match config.database_url() {
+            Some(database_url) => PostgresStore::new(database_url),
+            None => {
+                return Err(Error::ConfigError(
+                    vec![ConfigErrorKind::Missing(DATABASE_URL.to_owned())],
+                ))
+            }
+        };
+
+        match store {
+            Ok(store) => Ok(AuthService::new(config, store)),
+            Err(_) => Err(Error::ConfigError(
+                vec![ConfigErrorKind::Unreachable(DATABASE_URL.to_owned())],
+            )),
+        }
+    }
+
+    pub fn memory(config: Config) -> Self {
+        AuthService::new(config, MemoryStore::new())
+    }
+
+    pub fn initialize() -> Result<Self> {
+        let config = Config::builder().env().args(env::args().skip(1)).build()?;
+
+        AuthService::postgres(config)
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    pub fn store(&self) -> &Store {
+        &*self.store
+    }
+
+    pub fn rate_limiter(&self) -> &RateLimiter {
+        &*self.rate_limiter
+    }
+
+    pub fn check_audience(&self, audience: Option<&str>) -> Result<()> {
+        match audience {
+            Some(audience) if !self.config.audiences().iter().any(|known| known == audience) => {
+                Err(Error::InputError(InputErrorKind::Audience))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn check_rate_limit(&self, limit: &RateLimit, subject: &str) -> Result<()> {
+        if !limit.is_enabled() {
+            return Ok(());
+        }
+
+        let key = format!("{}:{}", limit.name(), subject);
+
+        match self.rate_limiter.take(&key, limit)? {
+            Some(wait) => {
+                let seconds = (wait.num_milliseconds() + 999) / 1000;
+
+                Err(Error::RateLimitedError(cmp::max(seconds, 1)))
+            }
+            None => Ok(()),
+        }
+    }
+
+    pub fn jwt_secret(&self) -> &JWTSecret {
+        self.config.jwt_secret()
+    }
+
+    pub fn jwks(&self) -> JwkSet {
+        self.config.jwt_secret().jwks()
+    }
+
+    pub fn password_policy(&self) -> &PasswordPolicy {
+        self.config.password_policy()
+    }
+
+    pub fn breach_corpus(&self) -> Option<&BreachCorpus> {
+        self.config.breach_corpus()
+    }
+
+    pub fn password_hasher(&self) -> &PasswordHasher {
+        self.config.password_hasher()
+    }
+
+    pub fn clients(&self) -> &Clients {
+        self.config.clients()
+    }
+
+    pub fn max_token_permissions(&self) -> usize {
+        self.config.max_token_permissions()
+    }
+
+    pub fn token_lifetimes(&self) -> &TokenLifetimes {
+        self.config.token_lifetimes()
+    }
+
+    pub fn lockout(&self) -> &Lockout {
+        self.config.lockout()
+    }
+
+    pub fn rate_limits(&self) -> &RateLimits {
+        self.config.rate_limits()
+    }
+
+    pub fn totp_secret_box(&self) -> Option<&SecretBox> {
+        self.config.totp_secret_box()
+    }
+
+    pub fn totp_issuer(&self) -> &str {
+        self.config.totp_issuer()
+    }
+
+    pub fn relying_party(&self) -> Option<&RelyingParty> {
+        self.config.relying_party()
+    }
+}
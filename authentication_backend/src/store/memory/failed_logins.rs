@@ -0,0 +1,95 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use chrono::NaiveDateTime;
+use error::Result;
+use models::{FailedLogin, NewFailedLogin};
+use store::FailedLoginStore;
+use super::{FailedLoginRow, MemoryStore, not_found, unique_violation};
+
+impl FailedLoginStore for MemoryStore {
+    fn create_failed_login(&self, new_failed_login: &NewFailedLogin) -> Result<FailedLogin> {
+        let mut tables = self.tables()?;
+
+        if let Some(user_id) = new_failed_login.user_id() {
+            if !tables.has_user(user_id) {
+                return Err(not_found());
+            }
+        }
+
+        if tables.failed_logins.iter().any(|fl| {
+            fl.subject == new_failed_login.subject()
+        })
+        {
+            return Err(unique_violation("failed_logins_subject_key"));
+        }
+
+        let id = tables.next_id();
+        let row = FailedLoginRow {
+            id: id,
+            subject: new_failed_login.subject().to_owned(),
+            user_id: new_failed_login.user_id(),
+            failures: new_failed_login.failures(),
+            last_failed_at: new_failed_login.last_failed_at(),
+            locked_until: new_failed_login.locked_until(),
+        };
+        let failed_login = row.to_failed_login();
+
+        tables.failed_logins.push(row);
+
+        Ok(failed_login)
+    }
+
+    fn find_failed_login(&self, subject: &str) -> Result<FailedLogin> {
+        let tables = self.tables()?;
+
+        tables
+            .failed_logins
+            .iter()
+            .find(|fl| fl.subject == subject)
+            .map(|fl| fl.to_failed_login())
+            .ok_or_else(not_found)
+    }
+
+    fn update_failed_login(
+        &self,
+        subject: &str,
+        failures: i32,
+        last_failed_at: NaiveDateTime,
+        locked_until: Option<NaiveDateTime>,
+    ) -> Result<()> {
+        let mut tables = self.tables()?;
+
+        for fl in tables.failed_logins.iter_mut().filter(|fl| fl.subject == subject) {
+            fl.failures = failures;
+            fl.last_failed_at = last_failed_at;
+            fl.locked_until = locked_until;
+        }
+
+        Ok(())
+    }
+
+    fn delete_failed_login(&self, subject: &str) -> Result<()> {
+        let mut tables = self.tables()?;
+
+        tables.failed_logins.retain(|fl| fl.subject != subject);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,340 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+mod users;
+mod permissions;
+mod verification_codes;
+mod token_families;
+mod sessions;
+mod totp_credentials;
+mod recovery_codes;
+mod webauthn_credentials;
+mod webauthn_challenges;
+mod password_resets;
+mod failed_logins;
+
+use std::sync::{Mutex, MutexGuard};
+use chrono::NaiveDateTime;
+use error::{DbError, DbErrorKind, Error, Result};
+use models::{FailedLogin, PasswordReset, Permission, RecoveryCode, Session, TokenFamily,
+             TotpCredential, User, UserPermission, VerificationCode, WebauthnChallenge,
+             WebauthnCredential};
+
+struct UserRow {
+    id: i32,
+    username: String,
+    password: String,
+    verified: bool,
+    email: Option<String>,
+}
+
+impl UserRow {
+    fn to_user(&self) -> User {
+        User::from_parts(
+            self.id,
+            self.username.clone(),
+            self.password.clone(),
+            self.verified,
+            self.email.clone(),
+        )
+    }
+}
+
+struct PermissionRow {
+    id: i32,
+    name: String,
+}
+
+impl PermissionRow {
+    fn to_permission(&self) -> Permission {
+        Permission::from_parts(self.id, self.name.clone())
+    }
+}
+
+struct UserPermissionRow {
+    id: i32,
+    user_id: i32,
+    permission_id: i32,
+}
+
+impl UserPermissionRow {
+    fn to_user_permission(&self) -> UserPermission {
+        UserPermission::from_parts(self.id, self.user_id, self.permission_id)
+    }
+}
+
+struct VerificationCodeRow {
+    id: i32,
+    code: String,
+    user_id: i32,
+    email: Option<String>,
+}
+
+impl VerificationCodeRow {
+    fn to_verification_code(&self) -> VerificationCode {
+        VerificationCode::from_parts(self.id, self.code.clone(), self.user_id, self.email.clone())
+    }
+}
+
+struct TokenFamilyRow {
+    id: i32,
+    family: String,
+    user_id: i32,
+    current_jti: String,
+    revoked: bool,
+}
+
+impl TokenFamilyRow {
+    fn to_token_family(&self) -> TokenFamily {
+        TokenFamily::from_parts(
+            self.id,
+            self.family.clone(),
+            self.user_id,
+            self.current_jti.clone(),
+            self.revoked,
+        )
+    }
+}
+
+struct SessionRow {
+    id: i32,
+    user_id: i32,
+    family: String,
+    user_agent: Option<String>,
+    ip_address: Option<String>,
+    created_at: NaiveDateTime,
+    last_renewed_at: Option<NaiveDateTime>,
+}
+
+impl SessionRow {
+    fn to_session(&self) -> Session {
+        Session::from_parts(
+            self.id,
+            self.user_id,
+            self.family.clone(),
+            self.user_agent.clone(),
+            self.ip_address.clone(),
+            self.created_at,
+            self.last_renewed_at,
+        )
+    }
+}
+
+struct TotpCredentialRow {
+    id: i32,
+    user_id: i32,
+    secret: Vec<u8>,
+    confirmed: bool,
+    last_used_step: Option<i64>,
+}
+
+impl TotpCredentialRow {
+    fn to_totp_credential(&self) -> TotpCredential {
+        TotpCredential::from_parts(
+            self.id,
+            self.user_id,
+            self.secret.clone(),
+            self.confirmed,
+            self.last_used_step,
+        )
+    }
+}
+
+struct RecoveryCodeRow {
+    id: i32,
+    user_id: i32,
+    code_hash: String,
+}
+
+impl RecoveryCodeRow {
+    fn to_recovery_code(&self) -> RecoveryCode {
+        RecoveryCode::from_parts(self.id, self.user_id, self.code_hash.clone())
+    }
+}
+
+struct WebauthnCredentialRow {
+    id: i32,
+    user_id: i32,
+    credential_id: Vec<u8>,
+    public_key: Vec<u8>,
+    algorithm: i32,
+    sign_count: i64,
+    created_at: NaiveDateTime,
+}
+
+impl WebauthnCredentialRow {
+    fn to_webauthn_credential(&self) -> WebauthnCredential {
+        WebauthnCredential::from_parts(
+            self.id,
+            self.user_id,
+            self.credential_id.clone(),
+            self.public_key.clone(),
+            self.algorithm,
+            self.sign_count,
+            self.created_at,
+        )
+    }
+}
+
+struct WebauthnChallengeRow {
+    id: i32,
+    challenge: String,
+    expires_at: NaiveDateTime,
+}
+
+impl WebauthnChallengeRow {
+    fn to_webauthn_challenge(&self) -> WebauthnChallenge {
+        WebauthnChallenge::from_parts(self.id, self.challenge.clone(), self.expires_at)
+    }
+}
+
+struct PasswordResetRow {
+    id: i32,
+    user_id: i32,
+    token_hash: String,
+    expires_at: NaiveDateTime,
+}
+
+impl PasswordResetRow {
+    fn to_password_reset(&self) -> PasswordReset {
+        PasswordReset::from_parts(
+            self.id,
+            self.user_id,
+            self.token_hash.clone(),
+            self.expires_at,
+        )
+    }
+}
+
+struct FailedLoginRow {
+    id: i32,
+    subject: String,
+    user_id: Option<i32>,
+    failures: i32,
+    last_failed_at: NaiveDateTime,
+    locked_until: Option<NaiveDateTime>,
+}
+
+impl FailedLoginRow {
+    fn to_failed_login(&self) -> FailedLogin {
+        FailedLogin::from_parts(
+            self.id,
+            self.subject.clone(),
+            self.user_id,
+            self.failures,
+            self.last_failed_at,
+            self.locked_until,
+        )
+    }
+}
+
+#[derive(Default)]
+struct Tables {
+    last_id: i32,
+    users: Vec<UserRow>,
+    permissions: Vec<PermissionRow>,
+    user_permissions: Vec<UserPermissionRow>,
+    verification_codes: Vec<VerificationCodeRow>,
+    token_families: Vec<TokenFamilyRow>,
+    sessions: Vec<SessionRow>,
+    totp_credentials: Vec<TotpCredentialRow>,
+    recovery_codes: Vec<RecoveryCodeRow>,
+    webauthn_credentials: Vec<WebauthnCredentialRow>,
+    webauthn_challenges: Vec<WebauthnChallengeRow>,
+    password_resets: Vec<PasswordResetRow>,
+    failed_logins: Vec<FailedLoginRow>,
+}
+
+impl Tables {
+    fn next_id(&mut self) -> i32 {
+        self.last_id += 1;
+        self.last_id
+    }
+
+    fn has_user(&self, user_id: i32) -> bool {
+        self.users.iter().any(|user| user.id == user_id)
+    }
+
+    fn has_permission(&self, permission_id: i32) -> bool {
+        self.permissions.iter().any(
+            |permission| permission.id == permission_id,
+        )
+    }
+
+    fn remove_user(&mut self, user_id: i32) {
+        self.users.retain(|user| user.id != user_id);
+        self.verification_codes.retain(|vc| vc.user_id != user_id);
+        self.user_permissions.retain(|up| up.user_id != user_id);
+        self.token_families.retain(|tf| tf.user_id != user_id);
+        self.sessions.retain(|session| session.user_id != user_id);
+        self.totp_credentials.retain(|tc| tc.user_id != user_id);
+        self.recovery_codes.retain(|rc| rc.user_id != user_id);
+        self.webauthn_credentials.retain(|wc| wc.user_id != user_id);
+        self.password_resets.retain(|pr| pr.user_id != user_id);
+        self.failed_logins.retain(|fl| fl.user_id != Some(user_id));
+    }
+
+    fn remove_permission(&mut self, permission_id: i32) {
+        self.permissions.retain(
+            |permission| permission.id != permission_id,
+        );
+        self.user_permissions.retain(
+            |up| up.permission_id != permission_id,
+        );
+    }
+}
+
+pub struct MemoryStore {
+    tables: Mutex<Tables>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        let mut tables = Tables::default();
+
+        let id = tables.next_id();
+        tables.permissions.push(PermissionRow {
+            id: id,
+            name: "admin".to_owned(),
+        });
+
+        MemoryStore { tables: Mutex::new(tables) }
+    }
+
+    fn tables(&self) -> Result<MutexGuard<Tables>> {
+        self.tables.lock().map_err(|_| Error::DbTimeout)
+    }
+}
+
+impl Default for MemoryStore {
+    fn default() -> Self {
+        MemoryStore::new()
+    }
+}
+
+fn not_found() -> Error {
+    Error::DbError(DbError::NotFound)
+}
+
+fn unique_violation(message: &str) -> Error {
+    Error::DbError(DbError::DatabaseError(
+        DbErrorKind::UniqueViolation,
+        Box::new(message.to_owned()),
+    ))
+}
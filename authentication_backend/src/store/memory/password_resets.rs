@@ -0,0 +1,87 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use error::Result;
+use models::{NewPasswordReset, PasswordReset};
+use store::PasswordResetStore;
+use super::{MemoryStore, PasswordResetRow, not_found, unique_violation};
+
+impl PasswordResetStore for MemoryStore {
+    fn create_password_reset(
+        &self,
+        new_password_reset: &NewPasswordReset,
+    ) -> Result<PasswordReset> {
+        let mut tables = self.tables()?;
+
+        let user_id = new_password_reset.user_id();
+
+        if !tables.has_user(user_id) {
+            return Err(not_found());
+        }
+
+        if tables.password_resets.iter().any(|pr| {
+            pr.token_hash == new_password_reset.token_hash()
+        })
+        {
+            return Err(unique_violation("password_resets_token_hash_key"));
+        }
+
+        let id = tables.next_id();
+        let row = PasswordResetRow {
+            id: id,
+            user_id: user_id,
+            token_hash: new_password_reset.token_hash().to_owned(),
+            expires_at: new_password_reset.expires_at(),
+        };
+        let password_reset = row.to_password_reset();
+
+        tables.password_resets.push(row);
+
+        Ok(password_reset)
+    }
+
+    fn find_password_reset(&self, token_hash: &str) -> Result<PasswordReset> {
+        let tables = self.tables()?;
+
+        tables
+            .password_resets
+            .iter()
+            .find(|pr| pr.token_hash == token_hash)
+            .map(|pr| pr.to_password_reset())
+            .ok_or_else(not_found)
+    }
+
+    fn delete_password_reset(&self, token_hash: &str) -> Result<bool> {
+        let mut tables = self.tables()?;
+
+        let before = tables.password_resets.len();
+
+        tables.password_resets.retain(|pr| pr.token_hash != token_hash);
+
+        Ok(tables.password_resets.len() < before)
+    }
+
+    fn delete_password_resets_by_user_id(&self, user_id: i32) -> Result<()> {
+        let mut tables = self.tables()?;
+
+        tables.password_resets.retain(|pr| pr.user_id != user_id);
+
+        Ok(())
+    }
+}
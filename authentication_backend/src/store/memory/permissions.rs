@@ -0,0 +1,168 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use error::Result;
+use models::{NewPermission, NewUserPermission, Permission, User, UserPermission};
+use store::PermissionStore;
+use super::{MemoryStore, PermissionRow, UserPermissionRow, not_found, unique_violation};
+
+impl PermissionStore for MemoryStore {
+    fn create_permission(&self, new_permission: &NewPermission) -> Result<Permission> {
+        let mut tables = self.tables()?;
+
+        if tables.permissions.iter().any(|permission| {
+            permission.name == new_permission.name()
+        })
+        {
+            return Err(unique_violation("permissions_name_key"));
+        }
+
+        let id = tables.next_id();
+        let row = PermissionRow {
+            id: id,
+            name: new_permission.name().to_owned(),
+        };
+        let permission = row.to_permission();
+
+        tables.permissions.push(row);
+
+        Ok(permission)
+    }
+
+    fn find_permission(&self, name: &str) -> Result<Permission> {
+        let tables = self.tables()?;
+
+        tables
+            .permissions
+            .iter()
+            .find(|permission| permission.name == name)
+            .map(|permission| permission.to_permission())
+            .ok_or_else(not_found)
+    }
+
+    fn delete_permission(&self, name: &str) -> Result<()> {
+        let mut tables = self.tables()?;
+
+        let permission_id =
+            match tables.permissions.iter().find(|permission| permission.name == name) {
+                Some(permission) => permission.id,
+                None => return Ok(()),
+            };
+
+        tables.remove_permission(permission_id);
+
+        Ok(())
+    }
+
+    fn create_user_permission(
+        &self,
+        new_user_permission: &NewUserPermission,
+    ) -> Result<UserPermission> {
+        let mut tables = self.tables()?;
+
+        let user_id = new_user_permission.user_id();
+        let permission_id = new_user_permission.permission_id();
+
+        if !tables.has_user(user_id) || !tables.has_permission(permission_id) {
+            return Err(not_found());
+        }
+
+        if tables.user_permissions.iter().any(|up| {
+            up.user_id == user_id && up.permission_id == permission_id
+        })
+        {
+            return Err(unique_violation(
+                "user_permissions_user_id_permission_id_key",
+            ));
+        }
+
+        let id = tables.next_id();
+        let row = UserPermissionRow {
+            id: id,
+            user_id: user_id,
+            permission_id: permission_id,
+        };
+        let user_permission = row.to_user_permission();
+
+        tables.user_permissions.push(row);
+
+        Ok(user_permission)
+    }
+
+    fn find_user_permission(&self, user_id: i32, permission_id: i32) -> Result<UserPermission> {
+        let tables = self.tables()?;
+
+        tables
+            .user_permissions
+            .iter()
+            .find(|up| up.user_id == user_id && up.permission_id == permission_id)
+            .map(|up| up.to_user_permission())
+            .ok_or_else(not_found)
+    }
+
+    fn find_permissions_by_user_id(&self, user_id: i32) -> Result<Vec<Permission>> {
+        let tables = self.tables()?;
+
+        let permission_ids: Vec<i32> = tables
+            .user_permissions
+            .iter()
+            .filter(|up| up.user_id == user_id)
+            .map(|up| up.permission_id)
+            .collect();
+
+        Ok(
+            tables
+                .permissions
+                .iter()
+                .filter(|permission| permission_ids.contains(&permission.id))
+                .map(|permission| permission.to_permission())
+                .collect(),
+        )
+    }
+
+    fn find_users_by_permission_id(&self, permission_id: i32) -> Result<Vec<User>> {
+        let tables = self.tables()?;
+
+        let user_ids: Vec<i32> = tables
+            .user_permissions
+            .iter()
+            .filter(|up| up.permission_id == permission_id)
+            .map(|up| up.user_id)
+            .collect();
+
+        Ok(
+            tables
+                .users
+                .iter()
+                .filter(|user| user_ids.contains(&user.id))
+                .map(|user| user.to_user())
+                .collect(),
+        )
+    }
+
+    fn delete_user_permission(&self, user_id: i32, permission_id: i32) -> Result<()> {
+        let mut tables = self.tables()?;
+
+        tables.user_permissions.retain(|up| {
+            up.user_id != user_id || up.permission_id != permission_id
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,86 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use error::Result;
+use models::{NewRecoveryCode, RecoveryCode};
+use store::RecoveryCodeStore;
+use super::{MemoryStore, RecoveryCodeRow, not_found, unique_violation};
+
+impl RecoveryCodeStore for MemoryStore {
+    fn create_recovery_code(&self, new_recovery_code: &NewRecoveryCode) -> Result<RecoveryCode> {
+        let mut tables = self.tables()?;
+
+        let user_id = new_recovery_code.user_id();
+
+        if !tables.has_user(user_id) {
+            return Err(not_found());
+        }
+
+        if tables.recovery_codes.iter().any(|rc| {
+            rc.user_id == user_id && rc.code_hash == new_recovery_code.code_hash()
+        })
+        {
+            return Err(unique_violation("recovery_codes_user_id_code_hash_key"));
+        }
+
+        let id = tables.next_id();
+        let row = RecoveryCodeRow {
+            id: id,
+            user_id: user_id,
+            code_hash: new_recovery_code.code_hash().to_owned(),
+        };
+        let recovery_code = row.to_recovery_code();
+
+        tables.recovery_codes.push(row);
+
+        Ok(recovery_code)
+    }
+
+    fn count_recovery_codes_by_user_id(&self, user_id: i32) -> Result<usize> {
+        let tables = self.tables()?;
+
+        Ok(
+            tables
+                .recovery_codes
+                .iter()
+                .filter(|rc| rc.user_id == user_id)
+                .count(),
+        )
+    }
+
+    fn delete_recovery_code(&self, user_id: i32, code_hash: &str) -> Result<bool> {
+        let mut tables = self.tables()?;
+
+        let before = tables.recovery_codes.len();
+
+        tables.recovery_codes.retain(|rc| {
+            rc.user_id != user_id || rc.code_hash != code_hash
+        });
+
+        Ok(tables.recovery_codes.len() < before)
+    }
+
+    fn delete_recovery_codes_by_user_id(&self, user_id: i32) -> Result<()> {
+        let mut tables = self.tables()?;
+
+        tables.recovery_codes.retain(|rc| rc.user_id != user_id);
+
+        Ok(())
+    }
+}
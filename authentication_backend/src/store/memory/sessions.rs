@@ -0,0 +1,94 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use chrono::NaiveDateTime;
+use error::Result;
+use models::{NewSession, Session};
+use store::SessionStore;
+use super::{MemoryStore, SessionRow, not_found, unique_violation};
+
+impl SessionStore for MemoryStore {
+    fn create_session(&self, new_session: &NewSession) -> Result<Session> {
+        let mut tables = self.tables()?;
+
+        let user_id = new_session.user_id();
+
+        if !tables.has_user(user_id) {
+            return Err(not_found());
+        }
+
+        if tables.sessions.iter().any(
+            |session| session.family == new_session.family(),
+        )
+        {
+            return Err(unique_violation("sessions_family_key"));
+        }
+
+        let id = tables.next_id();
+        let row = SessionRow {
+            id: id,
+            user_id: user_id,
+            family: new_session.family().to_owned(),
+            user_agent: new_session.user_agent().map(|user_agent| user_agent.to_owned()),
+            ip_address: new_session.ip_address().map(|ip_address| ip_address.to_owned()),
+            created_at: new_session.created_at(),
+            last_renewed_at: None,
+        };
+        let session = row.to_session();
+
+        tables.sessions.push(row);
+
+        Ok(session)
+    }
+
+    fn find_session(&self, id: i32) -> Result<Session> {
+        let tables = self.tables()?;
+
+        tables
+            .sessions
+            .iter()
+            .find(|session| session.id == id)
+            .map(|session| session.to_session())
+            .ok_or_else(not_found)
+    }
+
+    fn find_sessions_by_user_id(&self, user_id: i32) -> Result<Vec<Session>> {
+        let tables = self.tables()?;
+
+        Ok(
+            tables
+                .sessions
+                .iter()
+                .rev()
+                .filter(|session| session.user_id == user_id)
+                .map(|session| session.to_session())
+                .collect(),
+        )
+    }
+
+    fn touch_session(&self, id: i32, renewed_at: NaiveDateTime) -> Result<()> {
+        let mut tables = self.tables()?;
+
+        for session in tables.sessions.iter_mut().filter(|session| session.id == id) {
+            session.last_renewed_at = Some(renewed_at);
+        }
+
+        Ok(())
+    }
+}
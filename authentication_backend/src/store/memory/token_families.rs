@@ -0,0 +1,106 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use error::Result;
+use models::{NewTokenFamily, TokenFamily};
+use store::TokenFamilyStore;
+use super::{MemoryStore, TokenFamilyRow, not_found, unique_violation};
+
+impl TokenFamilyStore for MemoryStore {
+    fn create_token_family(&self, new_token_family: &NewTokenFamily) -> Result<TokenFamily> {
+        let mut tables = self.tables()?;
+
+        let user_id = new_token_family.user_id();
+
+        if !tables.has_user(user_id) {
+            return Err(not_found());
+        }
+
+        if tables.token_families.iter().any(|tf| {
+            tf.family == new_token_family.family()
+        })
+        {
+            return Err(unique_violation("token_families_family_key"));
+        }
+
+        let id = tables.next_id();
+        let row = TokenFamilyRow {
+            id: id,
+            family: new_token_family.family().to_owned(),
+            user_id: user_id,
+            current_jti: new_token_family.current_jti().to_owned(),
+            revoked: false,
+        };
+        let token_family = row.to_token_family();
+
+        tables.token_families.push(row);
+
+        Ok(token_family)
+    }
+
+    fn find_token_family(&self, family: &str) -> Result<TokenFamily> {
+        let tables = self.tables()?;
+
+        tables
+            .token_families
+            .iter()
+            .find(|tf| tf.family == family)
+            .map(|tf| tf.to_token_family())
+            .ok_or_else(not_found)
+    }
+
+    fn rotate_token_family(&self, family: &str, jti: &str, next_jti: &str) -> Result<bool> {
+        let mut tables = self.tables()?;
+
+        let token_family = tables.token_families.iter_mut().find(|tf| {
+            tf.family == family && tf.current_jti == jti && !tf.revoked
+        });
+
+        match token_family {
+            Some(token_family) => {
+                token_family.current_jti = next_jti.to_owned();
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    fn revoke_token_family(&self, family: &str) -> Result<()> {
+        let mut tables = self.tables()?;
+
+        for tf in tables.token_families.iter_mut().filter(|tf| tf.family == family) {
+            tf.revoked = true;
+        }
+
+        Ok(())
+    }
+
+    fn revoke_token_families_by_user_id(&self, user_id: i32) -> Result<()> {
+        let mut tables = self.tables()?;
+
+        for tf in tables.token_families.iter_mut().filter(
+            |tf| tf.user_id == user_id,
+        )
+        {
+            tf.revoked = true;
+        }
+
+        Ok(())
+    }
+}
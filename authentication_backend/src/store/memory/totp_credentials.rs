@@ -0,0 +1,104 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use error::Result;
+use models::{NewTotpCredential, TotpCredential};
+use store::TotpCredentialStore;
+use super::{MemoryStore, TotpCredentialRow, not_found, unique_violation};
+
+impl TotpCredentialStore for MemoryStore {
+    fn create_totp_credential(
+        &self,
+        new_totp_credential: &NewTotpCredential,
+    ) -> Result<TotpCredential> {
+        let mut tables = self.tables()?;
+
+        let user_id = new_totp_credential.user_id();
+
+        if !tables.has_user(user_id) {
+            return Err(not_found());
+        }
+
+        if tables.totp_credentials.iter().any(|tc| tc.user_id == user_id) {
+            return Err(unique_violation("totp_credentials_user_id_key"));
+        }
+
+        let id = tables.next_id();
+        let row = TotpCredentialRow {
+            id: id,
+            user_id: user_id,
+            secret: new_totp_credential.secret().to_vec(),
+            confirmed: false,
+            last_used_step: None,
+        };
+        let totp_credential = row.to_totp_credential();
+
+        tables.totp_credentials.push(row);
+
+        Ok(totp_credential)
+    }
+
+    fn find_totp_credential_by_user_id(&self, user_id: i32) -> Result<TotpCredential> {
+        let tables = self.tables()?;
+
+        tables
+            .totp_credentials
+            .iter()
+            .find(|tc| tc.user_id == user_id)
+            .map(|tc| tc.to_totp_credential())
+            .ok_or_else(not_found)
+    }
+
+    fn confirm_totp_credential(&self, user_id: i32) -> Result<()> {
+        let mut tables = self.tables()?;
+
+        for tc in tables.totp_credentials.iter_mut().filter(
+            |tc| tc.user_id == user_id,
+        )
+        {
+            tc.confirmed = true;
+        }
+
+        Ok(())
+    }
+
+    fn use_totp_step(&self, user_id: i32, step: i64) -> Result<bool> {
+        let mut tables = self.tables()?;
+
+        let totp_credential = tables.totp_credentials.iter_mut().find(|tc| {
+            tc.user_id == user_id && tc.last_used_step.map_or(true, |last| last < step)
+        });
+
+        match totp_credential {
+            Some(totp_credential) => {
+                totp_credential.last_used_step = Some(step);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    fn delete_totp_credential_by_user_id(&self, user_id: i32) -> Result<()> {
+        let mut tables = self.tables()?;
+
+        tables.totp_credentials.retain(|tc| tc.user_id != user_id);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,174 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use error::Result;
+use models::{NewUser, User};
+use store::UserStore;
+use super::{MemoryStore, UserRow, not_found, unique_violation};
+
+impl UserStore for MemoryStore {
+    fn create_user(&self, new_user: &NewUser) -> Result<User> {
+        let mut tables = self.tables()?;
+
+        if tables.users.iter().any(
+            |user| user.username == new_user.username(),
+        )
+        {
+            return Err(unique_violation("users_username_key"));
+        }
+
+        let id = tables.next_id();
+        let row = UserRow {
+            id: id,
+            username: new_user.username().to_owned(),
+            password: new_user.password().to_owned(),
+            verified: false,
+            email: None,
+        };
+        let user = row.to_user();
+
+        tables.users.push(row);
+
+        Ok(user)
+    }
+
+    fn find_user_by_id(&self, user_id: i32) -> Result<User> {
+        let tables = self.tables()?;
+
+        tables
+            .users
+            .iter()
+            .find(|user| user.id == user_id)
+            .map(|user| user.to_user())
+            .ok_or_else(not_found)
+    }
+
+    fn find_user_by_name(&self, username: &str) -> Result<User> {
+        let tables = self.tables()?;
+
+        tables
+            .users
+            .iter()
+            .find(|user| user.username == username)
+            .map(|user| user.to_user())
+            .ok_or_else(not_found)
+    }
+
+    fn find_user_by_email(&self, email: &str) -> Result<User> {
+        let tables = self.tables()?;
+
+        tables
+            .users
+            .iter()
+            .find(|user| user.email.as_ref().map(|e| e.as_str()) == Some(email))
+            .map(|user| user.to_user())
+            .ok_or_else(not_found)
+    }
+
+    fn find_user_by_verification_code(&self, code: &str) -> Result<User> {
+        let tables = self.tables()?;
+
+        let user_id = match tables.verification_codes.iter().find(|vc| vc.code == code) {
+            Some(vc) => vc.user_id,
+            None => return Err(not_found()),
+        };
+
+        tables
+            .users
+            .iter()
+            .find(|user| user.id == user_id)
+            .map(|user| user.to_user())
+            .ok_or_else(not_found)
+    }
+
+    fn verify_user(&self, user_id: i32) -> Result<()> {
+        let mut tables = self.tables()?;
+
+        for user in tables.users.iter_mut().filter(|user| user.id == user_id) {
+            user.verified = true;
+        }
+
+        Ok(())
+    }
+
+    fn update_username(&self, user_id: i32, username: &str) -> Result<()> {
+        let mut tables = self.tables()?;
+
+        if tables.users.iter().any(|user| {
+            user.id != user_id && user.username == username
+        })
+        {
+            return Err(unique_violation("users_username_key"));
+        }
+
+        for user in tables.users.iter_mut().filter(|user| user.id == user_id) {
+            user.username = username.to_owned();
+        }
+
+        Ok(())
+    }
+
+    fn update_password(&self, user_id: i32, password: &str) -> Result<()> {
+        let mut tables = self.tables()?;
+
+        for user in tables.users.iter_mut().filter(|user| user.id == user_id) {
+            user.password = password.to_owned();
+        }
+
+        Ok(())
+    }
+
+    fn update_email(&self, user_id: i32, email: &str) -> Result<()> {
+        let mut tables = self.tables()?;
+
+        if tables.users.iter().any(|user| {
+            user.id != user_id && user.email.as_ref().map(|e| e.as_str()) == Some(email)
+        })
+        {
+            return Err(unique_violation("users_email_key"));
+        }
+
+        for user in tables.users.iter_mut().filter(|user| user.id == user_id) {
+            user.email = Some(email.to_owned());
+        }
+
+        Ok(())
+    }
+
+    fn delete_user(&self, user_id: i32) -> Result<()> {
+        let mut tables = self.tables()?;
+
+        tables.remove_user(user_id);
+
+        Ok(())
+    }
+
+    fn delete_user_by_name(&self, username: &str) -> Result<()> {
+        let mut tables = self.tables()?;
+
+        let user_id = match tables.users.iter().find(|user| user.username == username) {
+            Some(user) => user.id,
+            None => return Ok(()),
+        };
+
+        tables.remove_user(user_id);
+
+        Ok(())
+    }
+}
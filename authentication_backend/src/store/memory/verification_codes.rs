@@ -0,0 +1,88 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use error::Result;
+use models::{NewVerificationCode, VerificationCode};
+use store::VerificationCodeStore;
+use super::{MemoryStore, VerificationCodeRow, not_found, unique_violation};
+
+impl VerificationCodeStore for MemoryStore {
+    fn create_verification_code(
+        &self,
+        new_verification_code: &NewVerificationCode,
+    ) -> Result<VerificationCode> {
+        let mut tables = self.tables()?;
+
+        let user_id = new_verification_code.user_id();
+
+        if !tables.has_user(user_id) {
+            return Err(not_found());
+        }
+
+        if tables.verification_codes.iter().any(|vc| {
+            vc.code == new_verification_code.code()
+        })
+        {
+            return Err(unique_violation("verification_codes_code_key"));
+        }
+
+        let id = tables.next_id();
+        let row = VerificationCodeRow {
+            id: id,
+            code: new_verification_code.code().to_owned(),
+            user_id: user_id,
+            email: new_verification_code.email().map(|email| email.to_owned()),
+        };
+        let verification_code = row.to_verification_code();
+
+        tables.verification_codes.push(row);
+
+        Ok(verification_code)
+    }
+
+    fn find_verification_code(&self, code: &str) -> Result<VerificationCode> {
+        let tables = self.tables()?;
+
+        tables
+            .verification_codes
+            .iter()
+            .find(|vc| vc.code == code)
+            .map(|vc| vc.to_verification_code())
+            .ok_or_else(not_found)
+    }
+
+    fn find_verification_code_by_user_id(&self, user_id: i32) -> Result<VerificationCode> {
+        let tables = self.tables()?;
+
+        tables
+            .verification_codes
+            .iter()
+            .find(|vc| vc.user_id == user_id)
+            .map(|vc| vc.to_verification_code())
+            .ok_or_else(not_found)
+    }
+
+    fn delete_verification_codes_by_user_id(&self, user_id: i32) -> Result<()> {
+        let mut tables = self.tables()?;
+
+        tables.verification_codes.retain(|vc| vc.user_id != user_id);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,70 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use chrono::NaiveDateTime;
+use error::Result;
+use models::{NewWebauthnChallenge, WebauthnChallenge};
+use store::WebauthnChallengeStore;
+use super::{MemoryStore, WebauthnChallengeRow, unique_violation};
+
+impl WebauthnChallengeStore for MemoryStore {
+    fn create_webauthn_challenge(
+        &self,
+        new_webauthn_challenge: &NewWebauthnChallenge,
+    ) -> Result<WebauthnChallenge> {
+        let mut tables = self.tables()?;
+
+        if tables.webauthn_challenges.iter().any(|wc| {
+            wc.challenge == new_webauthn_challenge.challenge()
+        })
+        {
+            return Err(unique_violation("webauthn_challenges_challenge_key"));
+        }
+
+        let id = tables.next_id();
+        let row = WebauthnChallengeRow {
+            id: id,
+            challenge: new_webauthn_challenge.challenge().to_owned(),
+            expires_at: new_webauthn_challenge.expires_at(),
+        };
+        let webauthn_challenge = row.to_webauthn_challenge();
+
+        tables.webauthn_challenges.push(row);
+
+        Ok(webauthn_challenge)
+    }
+
+    fn delete_webauthn_challenge(&self, challenge: &str) -> Result<bool> {
+        let mut tables = self.tables()?;
+
+        let before = tables.webauthn_challenges.len();
+
+        tables.webauthn_challenges.retain(|wc| wc.challenge != challenge);
+
+        Ok(tables.webauthn_challenges.len() < before)
+    }
+
+    fn delete_expired_webauthn_challenges(&self, now: NaiveDateTime) -> Result<()> {
+        let mut tables = self.tables()?;
+
+        tables.webauthn_challenges.retain(|wc| wc.expires_at > now);
+
+        Ok(())
+    }
+}
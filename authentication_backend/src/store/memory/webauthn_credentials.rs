@@ -0,0 +1,104 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use error::Result;
+use models::{NewWebauthnCredential, WebauthnCredential};
+use store::WebauthnCredentialStore;
+use super::{MemoryStore, WebauthnCredentialRow, not_found, unique_violation};
+
+impl WebauthnCredentialStore for MemoryStore {
+    fn create_webauthn_credential(
+        &self,
+        new_webauthn_credential: &NewWebauthnCredential,
+    ) -> Result<WebauthnCredential> {
+        let mut tables = self.tables()?;
+
+        let user_id = new_webauthn_credential.user_id();
+
+        if !tables.has_user(user_id) {
+            return Err(not_found());
+        }
+
+        if tables.webauthn_credentials.iter().any(|wc| {
+            wc.credential_id == new_webauthn_credential.credential_id()
+        })
+        {
+            return Err(unique_violation("webauthn_credentials_credential_id_key"));
+        }
+
+        let id = tables.next_id();
+        let row = WebauthnCredentialRow {
+            id: id,
+            user_id: user_id,
+            credential_id: new_webauthn_credential.credential_id().to_vec(),
+            public_key: new_webauthn_credential.public_key().to_vec(),
+            algorithm: new_webauthn_credential.algorithm(),
+            sign_count: 0,
+            created_at: new_webauthn_credential.created_at(),
+        };
+        let webauthn_credential = row.to_webauthn_credential();
+
+        tables.webauthn_credentials.push(row);
+
+        Ok(webauthn_credential)
+    }
+
+    fn find_webauthn_credential(&self, credential_id: &[u8]) -> Result<WebauthnCredential> {
+        let tables = self.tables()?;
+
+        tables
+            .webauthn_credentials
+            .iter()
+            .find(|wc| wc.credential_id == credential_id)
+            .map(|wc| wc.to_webauthn_credential())
+            .ok_or_else(not_found)
+    }
+
+    fn find_webauthn_credentials_by_user_id(
+        &self,
+        user_id: i32,
+    ) -> Result<Vec<WebauthnCredential>> {
+        let tables = self.tables()?;
+
+        Ok(
+            tables
+                .webauthn_credentials
+                .iter()
+                .filter(|wc| wc.user_id == user_id)
+                .map(|wc| wc.to_webauthn_credential())
+                .collect(),
+        )
+    }
+
+    fn update_webauthn_sign_count(&self, id: i32, sign_count: i64) -> Result<bool> {
+        let mut tables = self.tables()?;
+
+        let webauthn_credential = tables.webauthn_credentials.iter_mut().find(|wc| {
+            wc.id == id && wc.sign_count < sign_count
+        });
+
+        match webauthn_credential {
+            Some(webauthn_credential) => {
+                webauthn_credential.sign_count = sign_count;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+}
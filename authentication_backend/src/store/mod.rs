@@ -0,0 +1,180 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+mod postgres;
+mod memory;
+
+pub use self::postgres::PostgresStore;
+pub use self::memory::MemoryStore;
+
+use error::Result;
+use chrono::NaiveDateTime;
+use models::{FailedLogin, NewFailedLogin, NewPasswordReset, NewPermission, NewRecoveryCode,
+             NewSession, NewTokenFamily, NewTotpCredential, NewUser, NewUserPermission,
+             NewVerificationCode, NewWebauthnChallenge, NewWebauthnCredential, PasswordReset,
+             Permission, RecoveryCode, Session, TokenFamily, TotpCredential, User, UserPermission,
+             VerificationCode, WebauthnChallenge, WebauthnCredential};
+
+pub trait UserStore {
+    fn create_user(&self, new_user: &NewUser) -> Result<User>;
+    fn find_user_by_id(&self, id: i32) -> Result<User>;
+    fn find_user_by_name(&self, username: &str) -> Result<User>;
+    fn find_user_by_email(&self, email: &str) -> Result<User>;
+    fn find_user_by_verification_code(&self, code: &str) -> Result<User>;
+    fn verify_user(&self, id: i32) -> Result<()>;
+    fn update_username(&self, id: i32, username: &str) -> Result<()>;
+    fn update_password(&self, id: i32, password: &str) -> Result<()>;
+    fn update_email(&self, id: i32, email: &str) -> Result<()>;
+    fn delete_user(&self, id: i32) -> Result<()>;
+    fn delete_user_by_name(&self, username: &str) -> Result<()>;
+}
+
+pub trait PermissionStore {
+    fn create_permission(&self, new_permission: &NewPermission) -> Result<Permission>;
+    fn find_permission(&self, name: &str) -> Result<Permission>;
+    fn delete_permission(&self, name: &str) -> Result<()>;
+    fn create_user_permission(
+        &self,
+        new_user_permission: &NewUserPermission,
+    ) -> Result<UserPermission>;
+    fn find_user_permission(&self, user_id: i32, permission_id: i32) -> Result<UserPermission>;
+    fn find_permissions_by_user_id(&self, user_id: i32) -> Result<Vec<Permission>>;
+    fn find_users_by_permission_id(&self, permission_id: i32) -> Result<Vec<User>>;
+    fn delete_user_permission(&self, user_id: i32, permission_id: i32) -> Result<()>;
+}
+
+pub trait VerificationCodeStore {
+    fn create_verification_code(
+        &self,
+        new_verification_code: &NewVerificationCode,
+    ) -> Result<VerificationCode>;
+    fn find_verification_code(&self, code: &str) -> Result<VerificationCode>;
+    fn find_verification_code_by_user_id(&self, user_id: i32) -> Result<VerificationCode>;
+    fn delete_verification_codes_by_user_id(&self, user_id: i32) -> Result<()>;
+}
+
+pub trait TokenFamilyStore {
+    fn create_token_family(&self, new_token_family: &NewTokenFamily) -> Result<TokenFamily>;
+    fn find_token_family(&self, family: &str) -> Result<TokenFamily>;
+    // Returns false without changing anything unless the family is unrevoked and its current
+    // jti matches
+    fn rotate_token_family(&self, family: &str, jti: &str, next_jti: &str) -> Result<bool>;
+    fn revoke_token_family(&self, family: &str) -> Result<()>;
+    fn revoke_token_families_by_user_id(&self, user_id: i32) -> Result<()>;
+}
+
+pub trait SessionStore {
+    fn create_session(&self, new_session: &NewSession) -> Result<Session>;
+    fn find_session(&self, id: i32) -> Result<Session>;
+    fn find_sessions_by_user_id(&self, user_id: i32) -> Result<Vec<Session>>;
+    fn touch_session(&self, id: i32, renewed_at: NaiveDateTime) -> Result<()>;
+}
+
+pub trait TotpCredentialStore {
+    fn create_totp_credential(
+        &self,
+        new_totp_credential: &NewTotpCredential,
+    ) -> Result<TotpCredential>;
+    fn find_totp_credential_by_user_id(&self, user_id: i32) -> Result<TotpCredential>;
+    fn confirm_totp_credential(&self, user_id: i32) -> Result<()>;
+    // Returns false without changing anything unless the step is later than the last one used
+    fn use_totp_step(&self, user_id: i32, step: i64) -> Result<bool>;
+    fn delete_totp_credential_by_user_id(&self, user_id: i32) -> Result<()>;
+}
+
+pub trait RecoveryCodeStore {
+    fn create_recovery_code(&self, new_recovery_code: &NewRecoveryCode) -> Result<RecoveryCode>;
+    fn count_recovery_codes_by_user_id(&self, user_id: i32) -> Result<usize>;
+    fn delete_recovery_code(&self, user_id: i32, code_hash: &str) -> Result<bool>;
+    fn delete_recovery_codes_by_user_id(&self, user_id: i32) -> Result<()>;
+}
+
+pub trait WebauthnCredentialStore {
+    fn create_webauthn_credential(
+        &self,
+        new_webauthn_credential: &NewWebauthnCredential,
+    ) -> Result<WebauthnCredential>;
+    fn find_webauthn_credential(&self, credential_id: &[u8]) -> Result<WebauthnCredential>;
+    fn find_webauthn_credentials_by_user_id(&self, user_id: i32) -> Result<Vec<WebauthnCredential>>;
+    // Returns false, without updating, unless sign_count is higher than the stored counter
+    fn update_webauthn_sign_count(&self, id: i32, sign_count: i64) -> Result<bool>;
+}
+
+pub trait WebauthnChallengeStore {
+    fn create_webauthn_challenge(
+        &self,
+        new_webauthn_challenge: &NewWebauthnChallenge,
+    ) -> Result<WebauthnChallenge>;
+    fn delete_webauthn_challenge(&self, challenge: &str) -> Result<bool>;
+    fn delete_expired_webauthn_challenges(&self, now: NaiveDateTime) -> Result<()>;
+}
+
+pub trait PasswordResetStore {
+    fn create_password_reset(&self, new_password_reset: &NewPasswordReset) -> Result<PasswordReset>;
+    fn find_password_reset(&self, token_hash: &str) -> Result<PasswordReset>;
+    fn delete_password_reset(&self, token_hash: &str) -> Result<bool>;
+    fn delete_password_resets_by_user_id(&self, user_id: i32) -> Result<()>;
+}
+
+pub trait FailedLoginStore {
+    fn create_failed_login(&self, new_failed_login: &NewFailedLogin) -> Result<FailedLogin>;
+    fn find_failed_login(&self, subject: &str) -> Result<FailedLogin>;
+    fn update_failed_login(
+        &self,
+        subject: &str,
+        failures: i32,
+        last_failed_at: NaiveDateTime,
+        locked_until: Option<NaiveDateTime>,
+    ) -> Result<()>;
+    fn delete_failed_login(&self, subject: &str) -> Result<()>;
+}
+
+pub trait Store
+    : UserStore
+    + PermissionStore
+    + VerificationCodeStore
+    + TokenFamilyStore
+    + SessionStore
+    + TotpCredentialStore
+    + RecoveryCodeStore
+    + WebauthnCredentialStore
+    + WebauthnChallengeStore
+    + PasswordResetStore
+    + FailedLoginStore
+    + Send
+    + Sync {
+}
+
+impl<T> Store for T
+where
+    T: UserStore
+        + PermissionStore
+        + VerificationCodeStore
+        + TokenFamilyStore
+        + SessionStore
+        + TotpCredentialStore
+        + RecoveryCodeStore
+        + WebauthnCredentialStore
+        + WebauthnChallengeStore
+        + PasswordResetStore
+        + FailedLoginStore
+        + Send
+        + Sync,
+{
+}
@@ -17,32 +17,25 @@
  * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
  */
 
-use std::env;
-use dotenv::dotenv;
 use diesel::pg::PgConnection;
 use r2d2;
 use r2d2::{Pool, PooledConnection};
 use r2d2_diesel::ConnectionManager;
-use error::Result;
-use config::ManagedConnection;
+use error::{Error, Result};
+use super::ManagedConnection;
 
+#[derive(Clone)]
 pub struct ConnectionPool(Pool<ManagedConnection>);
 
 impl ConnectionPool {
-    pub fn initialize() -> ConnectionPool {
-        dotenv().ok();
-
-        let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
-
-        let kept_url = database_url.clone();
-
+    pub fn new(database_url: &str) -> Result<ConnectionPool> {
         let config = r2d2::Config::default();
         let manager = ConnectionManager::<PgConnection>::new(database_url);
 
-        ConnectionPool(Pool::new(config, manager).expect(&format!(
-            "Could not create connection pool for: {}",
-            kept_url
-        )))
+        match Pool::new(config, manager) {
+            Ok(pool) => Ok(ConnectionPool(pool)),
+            Err(_) => Err(Error::DbTimeout),
+        }
     }
 
     pub fn get(&self) -> Result<PooledConnection<ManagedConnection>> {
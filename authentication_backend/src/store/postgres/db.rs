@@ -19,7 +19,7 @@
 
 use r2d2::PooledConnection;
 use diesel::pg::PgConnection;
-use config::ManagedConnection;
+use super::ManagedConnection;
 
 pub struct DB(pub PooledConnection<ManagedConnection>);
 
@@ -0,0 +1,84 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use chrono::NaiveDateTime;
+use diesel;
+use diesel::prelude::*;
+use error::Result;
+use models::{FailedLogin, NewFailedLogin};
+use store::FailedLoginStore;
+use super::PostgresStore;
+
+impl FailedLoginStore for PostgresStore {
+    fn create_failed_login(&self, new_failed_login: &NewFailedLogin) -> Result<FailedLogin> {
+        use schema::failed_logins;
+
+        let db = self.db()?;
+
+        let failed_login = diesel::insert(new_failed_login)
+            .into(failed_logins::table)
+            .get_result(db.conn())?;
+
+        Ok(failed_login)
+    }
+
+    fn find_failed_login(&self, subj: &str) -> Result<FailedLogin> {
+        use schema::failed_logins::dsl::*;
+
+        let db = self.db()?;
+
+        let failed_login = failed_logins
+            .filter(subject.eq(subj))
+            .first::<FailedLogin>(db.conn())?;
+
+        Ok(failed_login)
+    }
+
+    fn update_failed_login(
+        &self,
+        subj: &str,
+        failure_count: i32,
+        failed_at: NaiveDateTime,
+        until: Option<NaiveDateTime>,
+    ) -> Result<()> {
+        use schema::failed_logins::dsl::*;
+
+        let db = self.db()?;
+
+        diesel::update(failed_logins.filter(subject.eq(subj)))
+            .set((
+                failures.eq(failure_count),
+                last_failed_at.eq(failed_at),
+                locked_until.eq(until),
+            ))
+            .execute(db.conn())?;
+
+        Ok(())
+    }
+
+    fn delete_failed_login(&self, subj: &str) -> Result<()> {
+        use schema::failed_logins::dsl::*;
+
+        let db = self.db()?;
+
+        diesel::delete(failed_logins.filter(subject.eq(subj))).execute(db.conn())?;
+
+        Ok(())
+    }
+}
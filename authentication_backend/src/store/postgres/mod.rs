@@ -0,0 +1,54 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+mod connection_pool;
+mod db;
+mod users;
+mod permissions;
+mod verification_codes;
+mod token_families;
+mod sessions;
+mod totp_credentials;
+mod recovery_codes;
+mod webauthn_credentials;
+mod webauthn_challenges;
+mod password_resets;
+mod failed_logins;
+
+use diesel::pg::PgConnection;
+use r2d2_diesel::ConnectionManager;
+use error::Result;
+use self::connection_pool::ConnectionPool;
+use self::db::DB;
+
+type ManagedConnection = ConnectionManager<PgConnection>;
+
+pub struct PostgresStore {
+    db_pool: ConnectionPool,
+}
+
+impl PostgresStore {
+    pub fn new(database_url: &str) -> Result<Self> {
+        Ok(PostgresStore { db_pool: ConnectionPool::new(database_url)? })
+    }
+
+    fn db(&self) -> Result<DB> {
+        Ok(DB(self.db_pool.get()?))
+    }
+}
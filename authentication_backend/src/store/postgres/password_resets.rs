@@ -0,0 +1,75 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use diesel;
+use diesel::prelude::*;
+use error::Result;
+use models::{NewPasswordReset, PasswordReset};
+use store::PasswordResetStore;
+use super::PostgresStore;
+
+impl PasswordResetStore for PostgresStore {
+    fn create_password_reset(
+        &self,
+        new_password_reset: &NewPasswordReset,
+    ) -> Result<PasswordReset> {
+        use schema::password_resets;
+
+        let db = self.db()?;
+
+        let password_reset = diesel::insert(new_password_reset)
+            .into(password_resets::table)
+            .get_result(db.conn())?;
+
+        Ok(password_reset)
+    }
+
+    fn find_password_reset(&self, hash: &str) -> Result<PasswordReset> {
+        use schema::password_resets::dsl::*;
+
+        let db = self.db()?;
+
+        let password_reset = password_resets
+            .filter(token_hash.eq(hash))
+            .first::<PasswordReset>(db.conn())?;
+
+        Ok(password_reset)
+    }
+
+    fn delete_password_reset(&self, hash: &str) -> Result<bool> {
+        use schema::password_resets::dsl::*;
+
+        let db = self.db()?;
+
+        let deleted = diesel::delete(password_resets.filter(token_hash.eq(hash)))
+            .execute(db.conn())?;
+
+        Ok(deleted == 1)
+    }
+
+    fn delete_password_resets_by_user_id(&self, u_id: i32) -> Result<()> {
+        use schema::password_resets::dsl::*;
+
+        let db = self.db()?;
+
+        diesel::delete(password_resets.filter(user_id.eq(u_id))).execute(db.conn())?;
+
+        Ok(())
+    }
+}
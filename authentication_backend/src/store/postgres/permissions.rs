@@ -0,0 +1,139 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use diesel;
+use diesel::prelude::*;
+use error::Result;
+use models::{NewPermission, NewUserPermission, Permission, User, UserPermission};
+use store::PermissionStore;
+use super::PostgresStore;
+
+impl PermissionStore for PostgresStore {
+    fn create_permission(&self, new_permission: &NewPermission) -> Result<Permission> {
+        use schema::permissions;
+
+        let db = self.db()?;
+
+        let permission = diesel::insert(new_permission)
+            .into(permissions::table)
+            .get_result(db.conn())?;
+
+        Ok(permission)
+    }
+
+    fn find_permission(&self, permission: &str) -> Result<Permission> {
+        use schema::permissions::dsl::*;
+
+        let db = self.db()?;
+
+        let permission = permissions
+            .filter(name.eq(permission))
+            .first::<Permission>(db.conn())?;
+
+        Ok(permission)
+    }
+
+    fn delete_permission(&self, permission: &str) -> Result<()> {
+        use schema::permissions::dsl::*;
+
+        let db = self.db()?;
+
+        diesel::delete(permissions.filter(name.eq(permission))).execute(
+            db.conn(),
+        )?;
+
+        Ok(())
+    }
+
+    fn create_user_permission(
+        &self,
+        new_user_permission: &NewUserPermission,
+    ) -> Result<UserPermission> {
+        use schema::user_permissions;
+
+        let db = self.db()?;
+
+        let user_permission = diesel::insert(new_user_permission)
+            .into(user_permissions::table)
+            .get_result(db.conn())?;
+
+        Ok(user_permission)
+    }
+
+    fn find_user_permission(&self, u_id: i32, p_id: i32) -> Result<UserPermission> {
+        use schema::user_permissions::dsl::{user_permissions, user_id, permission_id};
+
+        let db = self.db()?;
+
+        let user_permission = user_permissions
+            .filter(user_id.eq(u_id))
+            .filter(permission_id.eq(p_id))
+            .first::<UserPermission>(db.conn())?;
+
+        Ok(user_permission)
+    }
+
+    fn find_permissions_by_user_id(&self, u_id: i32) -> Result<Vec<Permission>> {
+        use schema::user_permissions::dsl::{user_permissions, user_id, permission_id};
+        use schema::permissions::dsl::{id, permissions};
+
+        let db = self.db()?;
+
+        let results: Vec<(UserPermission, Permission)> =
+            user_permissions
+                .inner_join::<permissions>(permissions)
+                .filter(permission_id.eq(id))
+                .filter(user_id.eq(u_id))
+                .load::<(UserPermission, Permission)>(db.conn())?;
+
+        Ok(
+            results
+                .into_iter()
+                .map(|(_, permission)| permission)
+                .collect(),
+        )
+    }
+
+    fn find_users_by_permission_id(&self, p_id: i32) -> Result<Vec<User>> {
+        use schema::user_permissions::dsl::{user_permissions, user_id, permission_id};
+        use schema::users::dsl::{id, users};
+
+        let db = self.db()?;
+
+        let results: Vec<(UserPermission, User)> = user_permissions
+            .inner_join::<users>(users)
+            .filter(user_id.eq(id))
+            .filter(permission_id.eq(p_id))
+            .load::<(UserPermission, User)>(db.conn())?;
+
+        Ok(results.into_iter().map(|(_, user)| user).collect())
+    }
+
+    fn delete_user_permission(&self, u_id: i32, p_id: i32) -> Result<()> {
+        use schema::user_permissions::dsl::{user_permissions, user_id, permission_id};
+
+        let db = self.db()?;
+
+        diesel::delete(user_permissions.filter(user_id.eq(u_id)).filter(
+            permission_id.eq(p_id),
+        )).execute(db.conn())?;
+
+        Ok(())
+    }
+}
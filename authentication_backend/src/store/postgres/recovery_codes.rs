@@ -0,0 +1,74 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use diesel;
+use diesel::prelude::*;
+use error::Result;
+use models::{NewRecoveryCode, RecoveryCode};
+use store::RecoveryCodeStore;
+use super::PostgresStore;
+
+impl RecoveryCodeStore for PostgresStore {
+    fn create_recovery_code(&self, new_recovery_code: &NewRecoveryCode) -> Result<RecoveryCode> {
+        use schema::recovery_codes;
+
+        let db = self.db()?;
+
+        let recovery_code = diesel::insert(new_recovery_code)
+            .into(recovery_codes::table)
+            .get_result(db.conn())?;
+
+        Ok(recovery_code)
+    }
+
+    fn count_recovery_codes_by_user_id(&self, u_id: i32) -> Result<usize> {
+        use schema::recovery_codes::dsl::*;
+
+        let db = self.db()?;
+
+        let count = recovery_codes
+            .filter(user_id.eq(u_id))
+            .count()
+            .get_result::<i64>(db.conn())?;
+
+        Ok(count as usize)
+    }
+
+    fn delete_recovery_code(&self, u_id: i32, hash: &str) -> Result<bool> {
+        use schema::recovery_codes::dsl::*;
+
+        let db = self.db()?;
+
+        let deleted = diesel::delete(recovery_codes.filter(user_id.eq(u_id)).filter(
+            code_hash.eq(hash),
+        )).execute(db.conn())?;
+
+        Ok(deleted == 1)
+    }
+
+    fn delete_recovery_codes_by_user_id(&self, u_id: i32) -> Result<()> {
+        use schema::recovery_codes::dsl::*;
+
+        let db = self.db()?;
+
+        diesel::delete(recovery_codes.filter(user_id.eq(u_id))).execute(db.conn())?;
+
+        Ok(())
+    }
+}
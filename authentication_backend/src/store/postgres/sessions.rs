@@ -0,0 +1,77 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use chrono::NaiveDateTime;
+use diesel;
+use diesel::prelude::*;
+use error::Result;
+use models::{NewSession, Session};
+use store::SessionStore;
+use super::PostgresStore;
+
+impl SessionStore for PostgresStore {
+    fn create_session(&self, new_session: &NewSession) -> Result<Session> {
+        use schema::sessions;
+
+        let db = self.db()?;
+
+        let session = diesel::insert(new_session)
+            .into(sessions::table)
+            .get_result(db.conn())?;
+
+        Ok(session)
+    }
+
+    fn find_session(&self, session_id: i32) -> Result<Session> {
+        use schema::sessions::dsl::*;
+
+        let db = self.db()?;
+
+        let session = sessions
+            .filter(id.eq(session_id))
+            .first::<Session>(db.conn())?;
+
+        Ok(session)
+    }
+
+    fn find_sessions_by_user_id(&self, u_id: i32) -> Result<Vec<Session>> {
+        use schema::sessions::dsl::*;
+
+        let db = self.db()?;
+
+        let user_sessions = sessions
+            .filter(user_id.eq(u_id))
+            .order(created_at.desc())
+            .load::<Session>(db.conn())?;
+
+        Ok(user_sessions)
+    }
+
+    fn touch_session(&self, session_id: i32, renewed_at: NaiveDateTime) -> Result<()> {
+        use schema::sessions::dsl::*;
+
+        let db = self.db()?;
+
+        diesel::update(sessions.filter(id.eq(session_id)))
+            .set(last_renewed_at.eq(Some(renewed_at)))
+            .execute(db.conn())?;
+
+        Ok(())
+    }
+}
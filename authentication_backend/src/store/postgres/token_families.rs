@@ -0,0 +1,91 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use diesel;
+use diesel::prelude::*;
+use error::Result;
+use models::{NewTokenFamily, TokenFamily};
+use store::TokenFamilyStore;
+use super::PostgresStore;
+
+impl TokenFamilyStore for PostgresStore {
+    fn create_token_family(&self, new_token_family: &NewTokenFamily) -> Result<TokenFamily> {
+        use schema::token_families;
+
+        let db = self.db()?;
+
+        let token_family = diesel::insert(new_token_family)
+            .into(token_families::table)
+            .get_result(db.conn())?;
+
+        Ok(token_family)
+    }
+
+    fn find_token_family(&self, fam: &str) -> Result<TokenFamily> {
+        use schema::token_families::dsl::*;
+
+        let db = self.db()?;
+
+        let token_family = token_families
+            .filter(family.eq(fam))
+            .first::<TokenFamily>(db.conn())?;
+
+        Ok(token_family)
+    }
+
+    fn rotate_token_family(&self, fam: &str, jti: &str, next_jti: &str) -> Result<bool> {
+        use schema::token_families::dsl::*;
+
+        let db = self.db()?;
+
+        let updated = diesel::update(
+            token_families
+                .filter(family.eq(fam))
+                .filter(current_jti.eq(jti))
+                .filter(revoked.eq(false)),
+        ).set(current_jti.eq(next_jti))
+            .execute(db.conn())?;
+
+        Ok(updated == 1)
+    }
+
+    fn revoke_token_family(&self, fam: &str) -> Result<()> {
+        use schema::token_families::dsl::*;
+
+        let db = self.db()?;
+
+        diesel::update(token_families.filter(family.eq(fam)))
+            .set(revoked.eq(true))
+            .execute(db.conn())?;
+
+        Ok(())
+    }
+
+    fn revoke_token_families_by_user_id(&self, u_id: i32) -> Result<()> {
+        use schema::token_families::dsl::*;
+
+        let db = self.db()?;
+
+        diesel::update(token_families.filter(user_id.eq(u_id)))
+            .set(revoked.eq(true))
+            .execute(db.conn())?;
+
+        Ok(())
+    }
+}
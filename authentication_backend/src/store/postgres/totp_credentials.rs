@@ -0,0 +1,89 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use diesel;
+use diesel::prelude::*;
+use error::Result;
+use models::{NewTotpCredential, TotpCredential};
+use store::TotpCredentialStore;
+use super::PostgresStore;
+
+impl TotpCredentialStore for PostgresStore {
+    fn create_totp_credential(
+        &self,
+        new_totp_credential: &NewTotpCredential,
+    ) -> Result<TotpCredential> {
+        use schema::totp_credentials;
+
+        let db = self.db()?;
+
+        let totp_credential = diesel::insert(new_totp_credential)
+            .into(totp_credentials::table)
+            .get_result(db.conn())?;
+
+        Ok(totp_credential)
+    }
+
+    fn find_totp_credential_by_user_id(&self, u_id: i32) -> Result<TotpCredential> {
+        use schema::totp_credentials::dsl::*;
+
+        let db = self.db()?;
+
+        let totp_credential = totp_credentials
+            .filter(user_id.eq(u_id))
+            .first::<TotpCredential>(db.conn())?;
+
+        Ok(totp_credential)
+    }
+
+    fn confirm_totp_credential(&self, u_id: i32) -> Result<()> {
+        use schema::totp_credentials::dsl::*;
+
+        let db = self.db()?;
+
+        diesel::update(totp_credentials.filter(user_id.eq(u_id)))
+            .set(confirmed.eq(true))
+            .execute(db.conn())?;
+
+        Ok(())
+    }
+
+    fn use_totp_step(&self, u_id: i32, step: i64) -> Result<bool> {
+        use schema::totp_credentials::dsl::*;
+
+        let db = self.db()?;
+
+        let updated = diesel::update(totp_credentials.filter(user_id.eq(u_id)).filter(
+            last_used_step.is_null().or(last_used_step.lt(step)),
+        )).set(last_used_step.eq(Some(step)))
+            .execute(db.conn())?;
+
+        Ok(updated == 1)
+    }
+
+    fn delete_totp_credential_by_user_id(&self, u_id: i32) -> Result<()> {
+        use schema::totp_credentials::dsl::*;
+
+        let db = self.db()?;
+
+        diesel::delete(totp_credentials.filter(user_id.eq(u_id))).execute(db.conn())?;
+
+        Ok(())
+    }
+}
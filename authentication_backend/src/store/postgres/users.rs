@@ -0,0 +1,151 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use diesel;
+use diesel::prelude::*;
+use error::Result;
+use models::{NewUser, User, VerificationCode};
+use store::UserStore;
+use super::PostgresStore;
+
+impl UserStore for PostgresStore {
+    fn create_user(&self, new_user: &NewUser) -> Result<User> {
+        use schema::users;
+
+        let db = self.db()?;
+
+        let user = diesel::insert(new_user).into(users::table).get_result(
+            db.conn(),
+        )?;
+
+        Ok(user)
+    }
+
+    fn find_user_by_id(&self, u_id: i32) -> Result<User> {
+        use schema::users::dsl::*;
+
+        let db = self.db()?;
+        let user = users.filter(id.eq(u_id)).first::<User>(db.conn())?;
+
+        Ok(user)
+    }
+
+    fn find_user_by_name(&self, u_name: &str) -> Result<User> {
+        use schema::users::dsl::*;
+
+        let db = self.db()?;
+        let user = users.filter(username.eq(u_name)).first::<User>(db.conn())?;
+
+        Ok(user)
+    }
+
+    fn find_user_by_email(&self, u_email: &str) -> Result<User> {
+        use schema::users::dsl::*;
+
+        let db = self.db()?;
+        let user = users.filter(email.eq(u_email)).first::<User>(db.conn())?;
+
+        Ok(user)
+    }
+
+    fn find_user_by_verification_code(&self, vc: &str) -> Result<User> {
+        use schema::verification_codes::dsl::{verification_codes, code, user_id};
+        use schema::users::dsl::*;
+
+        let db = self.db()?;
+
+        let (_, user) = verification_codes
+            .inner_join(users)
+            .filter(id.eq(user_id))
+            .filter(code.eq(vc))
+            .first::<(VerificationCode, User)>(db.conn())?;
+
+        Ok(user)
+    }
+
+    fn verify_user(&self, u_id: i32) -> Result<()> {
+        use schema::users::dsl::*;
+
+        let db = self.db()?;
+
+        diesel::update(users.filter(id.eq(u_id)))
+            .set(verified.eq(true))
+            .execute(db.conn())?;
+
+        Ok(())
+    }
+
+    fn update_username(&self, u_id: i32, new_username: &str) -> Result<()> {
+        use schema::users::dsl::*;
+
+        let db = self.db()?;
+
+        diesel::update(users.filter(id.eq(u_id)))
+            .set(username.eq(new_username))
+            .execute(db.conn())?;
+
+        Ok(())
+    }
+
+    fn update_password(&self, u_id: i32, new_password: &str) -> Result<()> {
+        use schema::users::dsl::*;
+
+        let db = self.db()?;
+
+        diesel::update(users.filter(id.eq(u_id)))
+            .set(password.eq(new_password))
+            .execute(db.conn())?;
+
+        Ok(())
+    }
+
+    fn update_email(&self, u_id: i32, new_email: &str) -> Result<()> {
+        use schema::users::dsl::*;
+
+        let db = self.db()?;
+
+        diesel::update(users.filter(id.eq(u_id)))
+            .set(email.eq(new_email))
+            .execute(db.conn())?;
+
+        Ok(())
+    }
+
+    fn delete_user(&self, u_id: i32) -> Result<()> {
+        use schema::users::dsl::*;
+
+        let db = self.db()?;
+
+        diesel::delete(users.filter(id.eq(u_id))).execute(db.conn())?;
+
+        Ok(())
+    }
+
+    fn delete_user_by_name(&self, u_name: &str) -> Result<()> {
+        use schema::users::dsl::*;
+
+        let db = self.db()?;
+
+        diesel::delete(users.filter(username.eq(u_name))).execute(
+            db.conn(),
+        )?;
+
+        Ok(())
+    }
+}
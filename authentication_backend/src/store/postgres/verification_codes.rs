@@ -0,0 +1,77 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use diesel;
+use diesel::prelude::*;
+use error::Result;
+use models::{NewVerificationCode, VerificationCode};
+use store::VerificationCodeStore;
+use super::PostgresStore;
+
+impl VerificationCodeStore for PostgresStore {
+    fn create_verification_code(
+        &self,
+        new_verification_code: &NewVerificationCode,
+    ) -> Result<VerificationCode> {
+        use schema::verification_codes;
+
+        let db = self.db()?;
+
+        let verification_code = diesel::insert(new_verification_code)
+            .into(verification_codes::table)
+            .get_result(db.conn())?;
+
+        Ok(verification_code)
+    }
+
+    fn find_verification_code(&self, vc: &str) -> Result<VerificationCode> {
+        use schema::verification_codes::dsl::{verification_codes, code};
+
+        let db = self.db()?;
+
+        let verification_code = verification_codes.filter(code.eq(vc)).first::<VerificationCode>(
+            db.conn(),
+        )?;
+
+        Ok(verification_code)
+    }
+
+    fn find_verification_code_by_user_id(&self, u_id: i32) -> Result<VerificationCode> {
+        use schema::verification_codes::dsl::{verification_codes, user_id};
+
+        let db = self.db()?;
+
+        let verification_code = verification_codes
+            .filter(user_id.eq(u_id))
+            .first::<VerificationCode>(db.conn())?;
+
+        Ok(verification_code)
+    }
+
+    fn delete_verification_codes_by_user_id(&self, u_id: i32) -> Result<()> {
+        use schema::verification_codes::dsl::{verification_codes, user_id};
+
+        let db = self.db()?;
+
+        diesel::delete(verification_codes.filter(user_id.eq(u_id)))
+            .execute(db.conn())?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,64 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use chrono::NaiveDateTime;
+use diesel;
+use diesel::prelude::*;
+use error::Result;
+use models::{NewWebauthnChallenge, WebauthnChallenge};
+use store::WebauthnChallengeStore;
+use super::PostgresStore;
+
+impl WebauthnChallengeStore for PostgresStore {
+    fn create_webauthn_challenge(
+        &self,
+        new_webauthn_challenge: &NewWebauthnChallenge,
+    ) -> Result<WebauthnChallenge> {
+        use schema::webauthn_challenges;
+
+        let db = self.db()?;
+
+        let webauthn_challenge = diesel::insert(new_webauthn_challenge)
+            .into(webauthn_challenges::table)
+            .get_result(db.conn())?;
+
+        Ok(webauthn_challenge)
+    }
+
+    fn delete_webauthn_challenge(&self, chal: &str) -> Result<bool> {
+        use schema::webauthn_challenges::dsl::*;
+
+        let db = self.db()?;
+
+        let deleted = diesel::delete(webauthn_challenges.filter(challenge.eq(chal)))
+            .execute(db.conn())?;
+
+        Ok(deleted == 1)
+    }
+
+    fn delete_expired_webauthn_challenges(&self, now: NaiveDateTime) -> Result<()> {
+        use schema::webauthn_challenges::dsl::*;
+
+        let db = self.db()?;
+
+        diesel::delete(webauthn_challenges.filter(expires_at.le(now))).execute(db.conn())?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,80 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use diesel;
+use diesel::prelude::*;
+use error::Result;
+use models::{NewWebauthnCredential, WebauthnCredential};
+use store::WebauthnCredentialStore;
+use super::PostgresStore;
+
+impl WebauthnCredentialStore for PostgresStore {
+    fn create_webauthn_credential(
+        &self,
+        new_webauthn_credential: &NewWebauthnCredential,
+    ) -> Result<WebauthnCredential> {
+        use schema::webauthn_credentials;
+
+        let db = self.db()?;
+
+        let webauthn_credential = diesel::insert(new_webauthn_credential)
+            .into(webauthn_credentials::table)
+            .get_result(db.conn())?;
+
+        Ok(webauthn_credential)
+    }
+
+    fn find_webauthn_credential(&self, c_id: &[u8]) -> Result<WebauthnCredential> {
+        use schema::webauthn_credentials::dsl::*;
+
+        let db = self.db()?;
+
+        let webauthn_credential = webauthn_credentials
+            .filter(credential_id.eq(c_id))
+            .first::<WebauthnCredential>(db.conn())?;
+
+        Ok(webauthn_credential)
+    }
+
+    fn find_webauthn_credentials_by_user_id(&self, u_id: i32) -> Result<Vec<WebauthnCredential>> {
+        use schema::webauthn_credentials::dsl::*;
+
+        let db = self.db()?;
+
+        let user_credentials = webauthn_credentials
+            .filter(user_id.eq(u_id))
+            .order(created_at.asc())
+            .load::<WebauthnCredential>(db.conn())?;
+
+        Ok(user_credentials)
+    }
+
+    fn update_webauthn_sign_count(&self, w_id: i32, count: i64) -> Result<bool> {
+        use schema::webauthn_credentials::dsl::*;
+
+        let db = self.db()?;
+
+        let updated = diesel::update(webauthn_credentials.filter(id.eq(w_id)).filter(
+            sign_count.lt(count),
+        )).set(sign_count.eq(count))
+            .execute(db.conn())?;
+
+        Ok(updated == 1)
+    }
+}
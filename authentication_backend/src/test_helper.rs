@@ -17,6 +17,54 @@
  * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
  */
 
+use std::env;
+use std::fs::File;
+use std::io::Write;
+use std::panic;
+use std::sync::mpsc;
+use authentication_background::{Message, MsgSender};
+use config::Config;
+use config::secret_box::KEY_LEN;
+use service::AuthService;
+
+pub const WEBAUTHN_ORIGIN: &str = "https://localhost";
+
+lazy_static! {
+    static ref SERVICE: AuthService = AuthService::memory(
+        Config::builder()
+            .env()
+            .args(vec![
+                format!("--totp-encryption-key={}", totp_encryption_key()),
+                format!("--webauthn-origin={}", WEBAUTHN_ORIGIN),
+                "--argon2-memory-cost=1024".to_owned(),
+                "--argon2-time-cost=1".to_owned(),
+                "--audiences=billing,support".to_owned(),
+            ])
+            .build()
+            .expect("Failed to load Config for tests"),
+    );
+}
+
+fn totp_encryption_key() -> String {
+    use rand::Rng;
+    use rand::OsRng;
+
+    let mut key = [0; KEY_LEN];
+    OsRng::new().unwrap().fill_bytes(&mut key);
+
+    let path = env::temp_dir().join(format!("{}.key", generate_string()));
+
+    File::create(&path)
+        .and_then(|mut f| f.write_all(&key))
+        .expect("Failed to write TOTP encryption key");
+
+    path.display().to_string()
+}
+
+pub fn service() -> &'static AuthService {
+    &SERVICE
+}
+
 pub fn generate_string() -> String {
     use rand::Rng;
     use rand::OsRng;
@@ -27,3 +75,22 @@ pub fn generate_string() -> String {
 pub fn test_password() -> &'static str {
     "Passw0rd$."
 }
+
+pub fn with_msg_sender<T>(sent: usize, test: T) -> ()
+where
+    T: FnOnce(MsgSender<i32>) -> () + panic::UnwindSafe,
+{
+    let (sender, receiver) = mpsc::channel::<Message<i32>>();
+
+    test(sender); // consume sender
+
+    let len = receiver.iter().collect::<Vec<_>>().len();
+
+    assert_eq!(
+        len,
+        sent,
+        "Did not send correct number of messages, expected: {}, sent: {}",
+        sent,
+        len,
+    );
+}
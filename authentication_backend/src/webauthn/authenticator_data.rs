@@ -0,0 +1,139 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use config::relying_party::RelyingParty;
+use error::{Error, Result, WebauthnErrorKind};
+use super::cbor;
+use super::public_key::PublicKey;
+
+const USER_PRESENT: u8 = 0x01;
+const USER_VERIFIED: u8 = 0x04;
+const ATTESTED_CREDENTIAL: u8 = 0x40;
+
+const RP_ID_HASH_LEN: usize = 32;
+const HEADER_LEN: usize = RP_ID_HASH_LEN + 1 + 4;
+const AAGUID_LEN: usize = 16;
+
+#[derive(Debug)]
+pub struct AuthenticatorData {
+    rp_id_hash: Vec<u8>,
+    flags: u8,
+    sign_count: u32,
+    credential: Option<(Vec<u8>, PublicKey)>,
+}
+
+impl AuthenticatorData {
+    pub fn parse(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() < HEADER_LEN {
+            return Err(malformed());
+        }
+
+        let flags = bytes[RP_ID_HASH_LEN];
+        let sign_count = bytes[RP_ID_HASH_LEN + 1..HEADER_LEN].iter().fold(0, |count, byte| {
+            count << 8 | *byte as u32
+        });
+
+        let credential = if flags & ATTESTED_CREDENTIAL != 0 {
+            Some(attested_credential(&bytes[HEADER_LEN..])?)
+        } else {
+            None
+        };
+
+        Ok(AuthenticatorData {
+            rp_id_hash: bytes[..RP_ID_HASH_LEN].to_vec(),
+            flags: flags,
+            sign_count: sign_count,
+            credential: credential,
+        })
+    }
+
+    pub fn check(&self, relying_party: &RelyingParty, require_verification: bool) -> Result<()> {
+        if self.rp_id_hash != relying_party.id_hash() {
+            return Err(Error::WebauthnError(WebauthnErrorKind::RelyingPartyMismatch));
+        }
+
+        if self.flags & USER_PRESENT == 0 {
+            return Err(Error::WebauthnError(WebauthnErrorKind::UserNotPresent));
+        }
+
+        if require_verification && self.flags & USER_VERIFIED == 0 {
+            return Err(Error::WebauthnError(WebauthnErrorKind::UserNotVerified));
+        }
+
+        Ok(())
+    }
+
+    pub fn sign_count(&self) -> u32 {
+        self.sign_count
+    }
+
+    pub fn into_credential(self) -> Option<(Vec<u8>, PublicKey)> {
+        self.credential
+    }
+}
+
+fn attested_credential(bytes: &[u8]) -> Result<(Vec<u8>, PublicKey)> {
+    if bytes.len() < AAGUID_LEN + 2 {
+        return Err(malformed());
+    }
+
+    let id_len = (bytes[AAGUID_LEN] as usize) << 8 | bytes[AAGUID_LEN + 1] as usize;
+    let rest = &bytes[AAGUID_LEN + 2..];
+
+    if rest.len() < id_len {
+        return Err(malformed());
+    }
+
+    let (credential_id, rest) = rest.split_at(id_len);
+    let (cose_key, _) = cbor::decode(rest)?;
+
+    Ok((credential_id.to_vec(), PublicKey::from_cose(&cose_key)?))
+}
+
+fn malformed() -> Error {
+    Error::WebauthnError(WebauthnErrorKind::Malformed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn authenticator_data(flags: u8) -> Vec<u8> {
+        let mut bytes = vec![0; RP_ID_HASH_LEN];
+        bytes.push(flags);
+        bytes.extend_from_slice(&[0, 0, 1, 2]);
+        bytes
+    }
+
+    #[test]
+    fn parse_reads_flags_and_counter() {
+        let data = AuthenticatorData::parse(&authenticator_data(USER_PRESENT))
+            .expect("Failed to parse authenticator data");
+
+        assert_eq!(data.sign_count(), 258, "Wrong signature counter");
+        assert!(data.into_credential().is_none(), "Found credential that wasn't there");
+    }
+
+    #[test]
+    fn parse_rejects_truncated_credential() {
+        let result = AuthenticatorData::parse(&authenticator_data(ATTESTED_CREDENTIAL));
+
+        assert!(result.is_err(), "Parsed missing credential");
+    }
+}
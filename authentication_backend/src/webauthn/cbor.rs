@@ -0,0 +1,235 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use error::{Error, Result, WebauthnErrorKind};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Integer(i64),
+    Bytes(Vec<u8>),
+    Text(String),
+    Array(Vec<Value>),
+    Map(Vec<(Value, Value)>),
+    Bool(bool),
+    Null,
+}
+
+const MAX_DEPTH: usize = 16;
+
+impl Value {
+    pub fn get(&self, key: &Value) -> Option<&Value> {
+        match *self {
+            Value::Map(ref entries) => {
+                entries.iter().find(|&&(ref k, _)| k == key).map(
+                    |&(_, ref value)| value,
+                )
+            }
+            _ => None,
+        }
+    }
+
+    pub fn as_integer(&self) -> Option<i64> {
+        match *self {
+            Value::Integer(integer) => Some(integer),
+            _ => None,
+        }
+    }
+
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match *self {
+            Value::Bytes(ref bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+
+    pub fn as_text(&self) -> Option<&str> {
+        match *self {
+            Value::Text(ref text) => Some(text),
+            _ => None,
+        }
+    }
+}
+
+pub fn decode(input: &[u8]) -> Result<(Value, &[u8])> {
+    decode_item(input, 0)
+}
+
+fn decode_item(input: &[u8], depth: usize) -> Result<(Value, &[u8])> {
+    if depth > MAX_DEPTH {
+        return Err(malformed());
+    }
+
+    let (major, argument, rest) = header(input)?;
+
+    match major {
+        0 => {
+            if argument > i64::max_value() as u64 {
+                return Err(malformed());
+            }
+
+            Ok((Value::Integer(argument as i64), rest))
+        }
+        1 => {
+            if argument > i64::max_value() as u64 {
+                return Err(malformed());
+            }
+
+            Ok((Value::Integer(-1 - argument as i64), rest))
+        }
+        2 => {
+            let (bytes, rest) = take(rest, argument)?;
+
+            Ok((Value::Bytes(bytes.to_vec()), rest))
+        }
+        3 => {
+            let (bytes, rest) = take(rest, argument)?;
+            let text = String::from_utf8(bytes.to_vec()).map_err(|_| malformed())?;
+
+            Ok((Value::Text(text), rest))
+        }
+        4 => {
+            let mut items = Vec::new();
+            let mut rest = rest;
+
+            for _ in 0..argument {
+                let (item, remaining) = decode_item(rest, depth + 1)?;
+
+                items.push(item);
+                rest = remaining;
+            }
+
+            Ok((Value::Array(items), rest))
+        }
+        5 => {
+            let mut entries = Vec::new();
+            let mut rest = rest;
+
+            for _ in 0..argument {
+                let (key, remaining) = decode_item(rest, depth + 1)?;
+                let (value, remaining) = decode_item(remaining, depth + 1)?;
+
+                entries.push((key, value));
+                rest = remaining;
+            }
+
+            Ok((Value::Map(entries), rest))
+        }
+        6 => decode_item(rest, depth + 1),
+        _ => {
+            match argument {
+                20 => Ok((Value::Bool(false), rest)),
+                21 => Ok((Value::Bool(true), rest)),
+                22 | 23 => Ok((Value::Null, rest)),
+                _ => Err(malformed()),
+            }
+        }
+    }
+}
+
+fn header(input: &[u8]) -> Result<(u8, u64, &[u8])> {
+    let (first, rest) = match input.split_first() {
+        Some((first, rest)) => (*first, rest),
+        None => return Err(malformed()),
+    };
+
+    let major = first >> 5;
+    let additional = first & 0x1f;
+
+    let length = match additional {
+        0...23 => return Ok((major, additional as u64, rest)),
+        24 => 1,
+        25 => 2,
+        26 => 4,
+        27 => 8,
+        _ => return Err(malformed()),
+    };
+
+    if major == 7 && additional > 24 {
+        return Err(malformed());
+    }
+
+    let (bytes, rest) = take(rest, length)?;
+    let argument = bytes.iter().fold(0, |argument, byte| argument << 8 | *byte as u64);
+
+    Ok((major, argument, rest))
+}
+
+fn take(input: &[u8], length: u64) -> Result<(&[u8], &[u8])> {
+    if length > input.len() as u64 {
+        return Err(malformed());
+    }
+
+    Ok(input.split_at(length as usize))
+}
+
+fn malformed() -> Error {
+    Error::WebauthnError(WebauthnErrorKind::Malformed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_reads_nested_items() {
+        let input = b"\xa3\x63fmt\x64none\x61n\x83\x01\x26\x41\xff\x62ok\xf5\x00";
+
+        let (value, rest) = decode(input).expect("Failed to decode");
+
+        assert_eq!(
+            value.get(&Value::Text("fmt".to_owned())),
+            Some(&Value::Text("none".to_owned())),
+            "Wrong text value"
+        );
+        assert_eq!(
+            value.get(&Value::Text("n".to_owned())),
+            Some(&Value::Array(vec![
+                Value::Integer(1),
+                Value::Integer(-7),
+                Value::Bytes(vec![0xff]),
+            ])),
+            "Wrong array value"
+        );
+        assert_eq!(
+            value.get(&Value::Text("ok".to_owned())),
+            Some(&Value::Bool(true)),
+            "Wrong bool value"
+        );
+        assert_eq!(rest, &[0x00][..], "Wrong remaining input");
+    }
+
+    #[test]
+    fn decode_reads_long_arguments() {
+        let (value, _) = decode(b"\x19\x01\x00").expect("Failed to decode");
+
+        assert_eq!(value, Value::Integer(256), "Wrong integer");
+    }
+
+    #[test]
+    fn decode_rejects_truncated_input() {
+        assert!(decode(b"\x44\x01\x02").is_err(), "Decoded truncated byte string");
+    }
+
+    #[test]
+    fn decode_rejects_deep_nesting() {
+        let input = vec![0x81; MAX_DEPTH + 2];
+
+        assert!(decode(&input).is_err(), "Decoded deeply nested arrays");
+    }
+}
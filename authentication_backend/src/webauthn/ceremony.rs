@@ -0,0 +1,121 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use base64;
+use chrono::Utc;
+use config::jwt_secret::Validation;
+use service::AuthService;
+use models::WebauthnChallenge;
+use error::{Error, Result, WebauthnErrorKind};
+
+pub const REGISTRATION: &str = "webauthn_registration";
+pub const AUTHENTICATION: &str = "webauthn_authentication";
+
+const CHALLENGE_LEN: usize = 32;
+
+// Challenges are handed out in signed tokens, like mfa_pending tokens. They are also stored until
+// the ceremony is finished, so each can only be answered once. Registration challenges are bound
+// to the user.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Ceremony {
+    iss: String,
+    sub: String,
+    iat: i64,
+    exp: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    aud: Option<String>,
+    challenge: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    user_id: Option<i32>,
+}
+
+impl Ceremony {
+    pub fn new(
+        service: &AuthService,
+        kind: &str,
+        user_id: Option<i32>,
+        audience: Option<&str>,
+    ) -> Result<Self> {
+        let now = Utc::now();
+        let expires_at = now + service.token_lifetimes().mfa_token();
+        let challenge = generate_challenge()?;
+
+        WebauthnChallenge::create(service, &challenge, expires_at.naive_utc())?;
+
+        Ok(Ceremony {
+            iss: "authentication".to_owned(),
+            sub: kind.to_owned(),
+            iat: now.timestamp(),
+            exp: expires_at.timestamp(),
+            aud: audience.map(|audience| audience.to_owned()),
+            challenge: challenge,
+            user_id: user_id,
+        })
+    }
+
+    pub fn encode(&self, service: &AuthService) -> Result<String> {
+        service.jwt_secret().encode(self)
+    }
+
+    // Uses up the challenge, even if the response to it turns out to be wrong
+    pub fn finish(service: &AuthService, token: &str, kind: &str) -> Result<Self> {
+        let validation = Validation {
+            leeway: service.token_lifetimes().leeway().num_seconds(),
+            iss: Some("authentication".to_owned()),
+            sub: Some(kind.to_owned()),
+            ..Default::default()
+        };
+
+        let ceremony: Ceremony = service
+            .jwt_secret()
+            .decode(token, &validation)
+            .map_err(|err| match err {
+                Error::JWTError(_) => Error::WebauthnError(WebauthnErrorKind::ChallengeMismatch),
+                err => err,
+            })?;
+
+        WebauthnChallenge::use_up(service, &ceremony.challenge)?;
+
+        Ok(ceremony)
+    }
+
+    pub fn challenge(&self) -> &str {
+        &self.challenge
+    }
+
+    pub fn audience(&self) -> Option<&str> {
+        self.aud.as_ref().map(|aud| aud.as_str())
+    }
+
+    pub fn user_id(&self) -> Option<i32> {
+        self.user_id
+    }
+}
+
+fn generate_challenge() -> Result<String> {
+    use rand::Rng;
+    use rand::OsRng;
+
+    let mut os_rng = OsRng::new()?;
+    let mut challenge = [0; CHALLENGE_LEN];
+
+    os_rng.fill_bytes(&mut challenge);
+
+    Ok(base64::encode_config(&challenge, base64::URL_SAFE_NO_PAD))
+}
@@ -0,0 +1,98 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use serde_json;
+use config::relying_party::RelyingParty;
+use error::{Error, Result, WebauthnErrorKind};
+
+pub const CREATE: &str = "webauthn.create";
+pub const GET: &str = "webauthn.get";
+
+#[derive(Deserialize)]
+struct ClientData {
+    #[serde(rename = "type")]
+    kind: String,
+    challenge: String,
+    origin: String,
+}
+
+pub fn check(
+    client_data_json: &[u8],
+    kind: &str,
+    challenge: &str,
+    relying_party: &RelyingParty,
+) -> Result<()> {
+    let client_data: ClientData = serde_json::from_slice(client_data_json).map_err(|_| {
+        Error::WebauthnError(WebauthnErrorKind::Malformed)
+    })?;
+
+    if client_data.kind != kind {
+        return Err(Error::WebauthnError(WebauthnErrorKind::Malformed));
+    }
+
+    if client_data.challenge.trim_right_matches('=') != challenge {
+        return Err(Error::WebauthnError(WebauthnErrorKind::ChallengeMismatch));
+    }
+
+    if client_data.origin != relying_party.origin() {
+        return Err(Error::WebauthnError(WebauthnErrorKind::OriginMismatch));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn relying_party() -> RelyingParty {
+        RelyingParty::new("https://example.com", None, "Example").unwrap()
+    }
+
+    #[test]
+    fn check_accepts_matching_client_data() {
+        let client_data =
+            br#"{"type":"webauthn.get","challenge":"abc","origin":"https://example.com"}"#;
+
+        let result = check(client_data, GET, "abc", &relying_party());
+
+        assert!(result.is_ok(), "Rejected matching client data");
+    }
+
+    #[test]
+    fn check_rejects_other_origin() {
+        let client_data =
+            br#"{"type":"webauthn.get","challenge":"abc","origin":"https://example.org"}"#;
+
+        match check(client_data, GET, "abc", &relying_party()) {
+            Err(Error::WebauthnError(WebauthnErrorKind::OriginMismatch)) => (),
+            _ => panic!("Accepted client data from another origin"),
+        }
+    }
+
+    #[test]
+    fn check_rejects_registration_data_when_logging_in() {
+        let client_data =
+            br#"{"type":"webauthn.create","challenge":"abc","origin":"https://example.com"}"#;
+
+        let result = check(client_data, GET, "abc", &relying_party());
+
+        assert!(!result.is_ok(), "Accepted registration client data");
+    }
+}
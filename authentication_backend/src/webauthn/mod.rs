@@ -0,0 +1,319 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+mod authenticator_data;
+mod cbor;
+mod ceremony;
+mod client_data;
+mod options;
+mod public_key;
+mod response;
+
+#[cfg(feature = "test")]
+pub mod test_helper;
+
+pub use self::options::{AuthenticationOptions, RegistrationOptions};
+pub use self::public_key::PublicKey;
+pub use self::response::{Assertion, Attestation};
+
+use base64;
+use ring::digest;
+use config::relying_party::RelyingParty;
+use service::AuthService;
+use models::{NewWebauthnCredential, UserTrait, WebauthnCredential};
+use error::{Error, Result, WebauthnErrorKind};
+use self::authenticator_data::AuthenticatorData;
+use self::cbor::Value;
+use self::ceremony::{AUTHENTICATION, Ceremony, REGISTRATION};
+
+pub fn registration_options<T>(service: &AuthService, user: &T) -> Result<RegistrationOptions>
+where
+    T: UserTrait,
+{
+    let relying_party = relying_party(service)?;
+
+    let ceremony = Ceremony::new(service, REGISTRATION, Some(user.id()), None)?;
+
+    let exclude_credentials = WebauthnCredential::find_by_user(service, user)?
+        .iter()
+        .map(|credential| encode(credential.credential_id()))
+        .collect();
+
+    Ok(RegistrationOptions::new(
+        ceremony.encode(service)?,
+        ceremony.challenge(),
+        relying_party,
+        user,
+        exclude_credentials,
+    ))
+}
+
+// Attestation statements aren't checked, since registration asks for none: the credential is
+// trusted because the user registering it has just given their password
+pub fn register<T>(
+    service: &AuthService,
+    user: &T,
+    attestation: &Attestation,
+) -> Result<WebauthnCredential>
+where
+    T: UserTrait,
+{
+    let relying_party = relying_party(service)?;
+
+    let ceremony = Ceremony::finish(service, attestation.challenge_token(), REGISTRATION)?;
+
+    if ceremony.user_id() != Some(user.id()) {
+        return Err(Error::WebauthnError(WebauthnErrorKind::ChallengeMismatch));
+    }
+
+    client_data::check(
+        &attestation.client_data_json()?,
+        client_data::CREATE,
+        ceremony.challenge(),
+        relying_party,
+    )?;
+
+    let (attestation_object, _) = cbor::decode(&attestation.attestation_object()?)?;
+
+    let authenticator_data = match attestation_object
+        .get(&Value::Text("authData".to_owned()))
+        .and_then(|value| value.as_bytes()) {
+        Some(bytes) => AuthenticatorData::parse(bytes)?,
+        None => return Err(Error::WebauthnError(WebauthnErrorKind::Malformed)),
+    };
+
+    authenticator_data.check(relying_party, false)?;
+
+    let (credential_id, public_key) = match authenticator_data.into_credential() {
+        Some(credential) => credential,
+        None => return Err(Error::WebauthnError(WebauthnErrorKind::Malformed)),
+    };
+
+    NewWebauthnCredential::new(user, credential_id, &public_key).save(service)
+}
+
+pub fn authentication_options(
+    service: &AuthService,
+    audience: Option<&str>,
+) -> Result<AuthenticationOptions> {
+    let relying_party = relying_party(service)?;
+
+    let ceremony = Ceremony::new(service, AUTHENTICATION, None, audience)?;
+
+    Ok(AuthenticationOptions::new(
+        ceremony.encode(service)?,
+        ceremony.challenge(),
+        relying_party.id(),
+    ))
+}
+
+// Logging in with a passkey needs the authenticator to verify the user, with a PIN or
+// biometrics, so it stands in for both a password and a second factor
+pub fn authenticate(
+    service: &AuthService,
+    assertion: &Assertion,
+) -> Result<(WebauthnCredential, Option<String>)> {
+    let relying_party = relying_party(service)?;
+
+    let ceremony = Ceremony::finish(service, assertion.challenge_token(), AUTHENTICATION)?;
+
+    let credential = WebauthnCredential::find(service, &assertion.credential_id()?)?;
+    let client_data_json = assertion.client_data_json()?;
+    let authenticator_data = assertion.authenticator_data()?;
+
+    client_data::check(
+        &client_data_json,
+        client_data::GET,
+        ceremony.challenge(),
+        relying_party,
+    )?;
+
+    let parsed = AuthenticatorData::parse(&authenticator_data)?;
+
+    parsed.check(relying_party, true)?;
+
+    let mut signed = authenticator_data.clone();
+    signed.extend_from_slice(digest::digest(&digest::SHA256, &client_data_json).as_ref());
+
+    credential.public_key().verify(
+        &signed,
+        &assertion.signature()?,
+    )?;
+
+    credential.record_use(service, parsed.sign_count())?;
+
+    Ok((credential, ceremony.audience().map(|audience| audience.to_owned())))
+}
+
+fn relying_party(service: &AuthService) -> Result<&RelyingParty> {
+    service.relying_party().ok_or(
+        Error::WebauthnError(WebauthnErrorKind::Unavailable),
+    )
+}
+
+fn encode(bytes: &[u8]) -> String {
+    base64::encode_config(bytes, base64::URL_SAFE_NO_PAD)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::test_helper::SoftwareAuthenticator;
+    use models::user::test_helper::with_user;
+    use test_helper::service;
+
+    #[test]
+    fn authenticate_accepts_registered_credential() {
+        with_user(|user| {
+            let mut authenticator = SoftwareAuthenticator::new();
+
+            let options = registration_options(service(), &user).expect("Failed to start");
+            let credential = register(service(), &user, &authenticator.attest(&options))
+                .expect("Failed to register credential");
+
+            let options = authentication_options(service(), None).expect("Failed to start");
+            let result = authenticate(service(), &authenticator.assert(&options));
+
+            assert!(result.is_ok(), "Failed to authenticate");
+
+            let (authenticated, _) = result.unwrap();
+
+            assert_eq!(authenticated.id(), credential.id(), "Found wrong credential");
+            assert_eq!(authenticated.user_id(), user.id(), "Found wrong user");
+        });
+    }
+
+    #[test]
+    fn registration_options_exclude_registered_credentials() {
+        with_user(|user| {
+            let authenticator = SoftwareAuthenticator::new();
+
+            let options = registration_options(service(), &user).expect("Failed to start");
+            let _ = register(service(), &user, &authenticator.attest(&options))
+                .expect("Failed to register credential");
+
+            let options = registration_options(service(), &user).expect("Failed to start");
+
+            assert_eq!(
+                options.exclude_credentials(),
+                &[authenticator.credential_id()][..],
+                "Did not exclude registered credential"
+            );
+        });
+    }
+
+    #[test]
+    fn register_fails_with_challenge_for_other_user() {
+        with_user(|user| {
+            with_user(|user2| {
+                let authenticator = SoftwareAuthenticator::new();
+
+                let options = registration_options(service(), &user).expect("Failed to start");
+                let result = register(service(), &user2, &authenticator.attest(&options));
+
+                assert!(!result.is_ok(), "Registered with another user's challenge");
+            });
+        });
+    }
+
+    #[test]
+    fn authenticate_rejects_repeated_counter() {
+        with_user(|user| {
+            let mut authenticator = SoftwareAuthenticator::new();
+
+            let options = registration_options(service(), &user).expect("Failed to start");
+            let _ = register(service(), &user, &authenticator.attest(&options))
+                .expect("Failed to register credential");
+
+            let options = authentication_options(service(), None).expect("Failed to start");
+            let _ = authenticate(service(), &authenticator.assert(&options))
+                .expect("Failed to authenticate");
+
+            authenticator.set_sign_count(0);
+
+            let options = authentication_options(service(), None).expect("Failed to start");
+
+            match authenticate(service(), &authenticator.assert(&options)) {
+                Err(Error::WebauthnError(WebauthnErrorKind::CounterRegression)) => (),
+                _ => panic!("Accepted cloned authenticator"),
+            }
+        });
+    }
+
+    #[test]
+    fn authenticate_rejects_replayed_assertion() {
+        with_user(|user| {
+            let mut authenticator = SoftwareAuthenticator::new();
+
+            let options = registration_options(service(), &user).expect("Failed to start");
+            let _ = register(service(), &user, &authenticator.attest(&options))
+                .expect("Failed to register credential");
+
+            let options = authentication_options(service(), None).expect("Failed to start");
+            let assertion = authenticator.assert(&options);
+
+            let _ = authenticate(service(), &assertion).expect("Failed to authenticate");
+
+            match authenticate(service(), &assertion) {
+                Err(Error::WebauthnError(WebauthnErrorKind::ChallengeMismatch)) => (),
+                _ => panic!("Accepted replayed assertion"),
+            }
+        });
+    }
+
+    #[test]
+    fn register_rejects_replayed_attestation() {
+        with_user(|user| {
+            let authenticator = SoftwareAuthenticator::new();
+
+            let options = registration_options(service(), &user).expect("Failed to start");
+            let attestation = authenticator.attest(&options);
+
+            let _ = register(service(), &user, &attestation)
+                .expect("Failed to register credential");
+
+            assert!(
+                !register(service(), &user, &attestation).is_ok(),
+                "Registered with a used challenge"
+            );
+        });
+    }
+
+    #[test]
+    fn authenticate_rejects_registration_challenge() {
+        with_user(|user| {
+            let mut authenticator = SoftwareAuthenticator::new();
+
+            let options = registration_options(service(), &user).expect("Failed to start");
+            let _ = register(service(), &user, &authenticator.attest(&options))
+                .expect("Failed to register credential");
+
+            let assertion = authenticator.assert(&AuthenticationOptions::new(
+                options.challenge_token().to_owned(),
+                options.challenge(),
+                options.rp_id(),
+            ));
+
+            assert!(
+                !authenticate(service(), &assertion).is_ok(),
+                "Logged in with a registration challenge"
+            );
+        });
+    }
+}
@@ -0,0 +1,126 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use base64;
+use config::relying_party::RelyingParty;
+use models::UserTrait;
+use super::public_key::{EDDSA, ES256, RS256};
+
+#[derive(Debug)]
+pub struct RegistrationOptions {
+    challenge_token: String,
+    challenge: String,
+    rp_id: String,
+    rp_name: String,
+    user_handle: String,
+    username: String,
+    algorithms: Vec<i32>,
+    exclude_credentials: Vec<String>,
+}
+
+impl RegistrationOptions {
+    pub fn new<T>(
+        challenge_token: String,
+        challenge: &str,
+        relying_party: &RelyingParty,
+        user: &T,
+        exclude_credentials: Vec<String>,
+    ) -> Self
+    where
+        T: UserTrait,
+    {
+        RegistrationOptions {
+            challenge_token: challenge_token,
+            challenge: challenge.to_owned(),
+            rp_id: relying_party.id().to_owned(),
+            rp_name: relying_party.name().to_owned(),
+            user_handle: user_handle(user.id()),
+            username: user.username().to_owned(),
+            algorithms: vec![ES256, EDDSA, RS256],
+            exclude_credentials: exclude_credentials,
+        }
+    }
+
+    pub fn challenge_token(&self) -> &str {
+        &self.challenge_token
+    }
+
+    pub fn challenge(&self) -> &str {
+        &self.challenge
+    }
+
+    pub fn rp_id(&self) -> &str {
+        &self.rp_id
+    }
+
+    pub fn rp_name(&self) -> &str {
+        &self.rp_name
+    }
+
+    pub fn user_handle(&self) -> &str {
+        &self.user_handle
+    }
+
+    pub fn username(&self) -> &str {
+        &self.username
+    }
+
+    pub fn algorithms(&self) -> &[i32] {
+        &self.algorithms
+    }
+
+    pub fn exclude_credentials(&self) -> &[String] {
+        &self.exclude_credentials
+    }
+}
+
+#[derive(Debug)]
+pub struct AuthenticationOptions {
+    challenge_token: String,
+    challenge: String,
+    rp_id: String,
+}
+
+impl AuthenticationOptions {
+    pub fn new(challenge_token: String, challenge: &str, rp_id: &str) -> Self {
+        AuthenticationOptions {
+            challenge_token: challenge_token,
+            challenge: challenge.to_owned(),
+            rp_id: rp_id.to_owned(),
+        }
+    }
+
+    pub fn challenge_token(&self) -> &str {
+        &self.challenge_token
+    }
+
+    pub fn challenge(&self) -> &str {
+        &self.challenge
+    }
+
+    pub fn rp_id(&self) -> &str {
+        &self.rp_id
+    }
+}
+
+// The handle is stored on the authenticator, so it is the user's id rather than anything that
+// identifies them outside this service
+fn user_handle(user_id: i32) -> String {
+    base64::encode_config(user_id.to_string().as_bytes(), base64::URL_SAFE_NO_PAD)
+}
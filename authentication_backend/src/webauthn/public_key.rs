@@ -0,0 +1,219 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use ring::signature;
+use untrusted::Input;
+use error::{Error, Result, WebauthnErrorKind};
+use super::cbor::Value;
+
+pub const ES256: i32 = -7;
+pub const EDDSA: i32 = -8;
+pub const RS256: i32 = -257;
+
+const KTY: i64 = 1;
+const ALG: i64 = 3;
+const KTY_OKP: i64 = 1;
+const KTY_EC2: i64 = 2;
+const KTY_RSA: i64 = 3;
+const CRV_P256: i64 = 1;
+const CRV_ED25519: i64 = 6;
+
+#[derive(Debug)]
+pub struct PublicKey {
+    algorithm: i32,
+    key: Vec<u8>,
+}
+
+impl PublicKey {
+    pub fn new(algorithm: i32, key: Vec<u8>) -> Self {
+        PublicKey {
+            algorithm: algorithm,
+            key: key,
+        }
+    }
+
+    pub fn from_cose(cose_key: &Value) -> Result<Self> {
+        let integer = |label: i64| {
+            cose_key.get(&Value::Integer(label)).and_then(
+                |value| value.as_integer(),
+            )
+        };
+        let bytes = |label: i64| {
+            cose_key.get(&Value::Integer(label)).and_then(
+                |value| value.as_bytes(),
+            )
+        };
+
+        let key = match (integer(KTY), integer(ALG)) {
+            (Some(KTY_EC2), Some(alg)) if alg == ES256 as i64 => {
+                match (integer(-1), bytes(-2), bytes(-3)) {
+                    (Some(CRV_P256), Some(x), Some(y)) if x.len() == 32 && y.len() == 32 => {
+                        let mut point = vec![0x04];
+                        point.extend_from_slice(x);
+                        point.extend_from_slice(y);
+                        PublicKey::new(ES256, point)
+                    }
+                    _ => return Err(malformed()),
+                }
+            }
+            (Some(KTY_OKP), Some(alg)) if alg == EDDSA as i64 => {
+                match (integer(-1), bytes(-2)) {
+                    (Some(CRV_ED25519), Some(x)) if x.len() == 32 => {
+                        PublicKey::new(EDDSA, x.to_vec())
+                    }
+                    _ => return Err(malformed()),
+                }
+            }
+            (Some(KTY_RSA), Some(alg)) if alg == RS256 as i64 => {
+                match (bytes(-1), bytes(-2)) {
+                    (Some(n), Some(e)) => PublicKey::new(RS256, rsa_public_key(n, e)),
+                    _ => return Err(malformed()),
+                }
+            }
+            (Some(_), Some(_)) => {
+                return Err(Error::WebauthnError(WebauthnErrorKind::UnsupportedAlgorithm))
+            }
+            _ => return Err(malformed()),
+        };
+
+        Ok(key)
+    }
+
+    pub fn algorithm(&self) -> i32 {
+        self.algorithm
+    }
+
+    pub fn key(&self) -> &[u8] {
+        &self.key
+    }
+
+    pub fn verify(&self, message: &[u8], signature: &[u8]) -> Result<()> {
+        let algorithm: &'static signature::VerificationAlgorithm = match self.algorithm {
+            ES256 => &signature::ECDSA_P256_SHA256_ASN1,
+            EDDSA => &signature::ED25519,
+            RS256 => &signature::RSA_PKCS1_2048_8192_SHA256,
+            _ => return Err(Error::WebauthnError(WebauthnErrorKind::UnsupportedAlgorithm)),
+        };
+
+        signature::verify(
+            algorithm,
+            Input::from(&self.key),
+            Input::from(message),
+            Input::from(signature),
+        ).map_err(|_| Error::WebauthnError(WebauthnErrorKind::InvalidSignature))
+    }
+}
+
+fn rsa_public_key(n: &[u8], e: &[u8]) -> Vec<u8> {
+    let mut integers = der_integer(n);
+    integers.extend(der_integer(e));
+
+    der(0x30, &integers)
+}
+
+fn der_integer(bytes: &[u8]) -> Vec<u8> {
+    let start = bytes.iter().position(|byte| *byte != 0).unwrap_or(bytes.len());
+    let mut integer = Vec::new();
+
+    if start == bytes.len() || bytes[start] & 0x80 != 0 {
+        integer.push(0);
+    }
+
+    integer.extend_from_slice(&bytes[start..]);
+
+    der(0x02, &integer)
+}
+
+fn der(tag: u8, contents: &[u8]) -> Vec<u8> {
+    let mut encoded = vec![tag];
+    let length = contents.len();
+
+    if length < 0x80 {
+        encoded.push(length as u8);
+    } else if length < 0x100 {
+        encoded.push(0x81);
+        encoded.push(length as u8);
+    } else {
+        encoded.push(0x82);
+        encoded.push((length >> 8) as u8);
+        encoded.push(length as u8);
+    }
+
+    encoded.extend_from_slice(contents);
+    encoded
+}
+
+fn malformed() -> Error {
+    Error::WebauthnError(WebauthnErrorKind::Malformed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cose_key(entries: Vec<(i64, Value)>) -> Value {
+        Value::Map(
+            entries
+                .into_iter()
+                .map(|(label, value)| (Value::Integer(label), value))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn from_cose_reads_p256_key() {
+        let key = cose_key(vec![
+            (KTY, Value::Integer(KTY_EC2)),
+            (ALG, Value::Integer(ES256 as i64)),
+            (-1, Value::Integer(CRV_P256)),
+            (-2, Value::Bytes(vec![1; 32])),
+            (-3, Value::Bytes(vec![2; 32])),
+        ]);
+
+        let public_key = PublicKey::from_cose(&key).expect("Failed to read key");
+
+        assert_eq!(public_key.algorithm(), ES256, "Wrong algorithm");
+        assert_eq!(public_key.key().len(), 65, "Key is not an uncompressed point");
+        assert_eq!(public_key.key()[0], 0x04, "Key is not an uncompressed point");
+    }
+
+    #[test]
+    fn from_cose_rejects_unsupported_algorithm() {
+        let key = cose_key(vec![
+            (KTY, Value::Integer(KTY_EC2)),
+            (ALG, Value::Integer(-35)),
+            (-1, Value::Integer(2)),
+        ]);
+
+        match PublicKey::from_cose(&key) {
+            Err(Error::WebauthnError(WebauthnErrorKind::UnsupportedAlgorithm)) => (),
+            _ => panic!("Accepted ES384 key"),
+        }
+    }
+
+    #[test]
+    fn der_integer_keeps_integers_positive() {
+        assert_eq!(der_integer(&[0x00, 0x80]), vec![0x02, 0x02, 0x00, 0x80], "Wrong encoding");
+        assert_eq!(
+            der_integer(&[0x01, 0x00, 0x01]),
+            vec![0x02, 0x03, 0x01, 0x00, 0x01],
+            "Wrong encoding"
+        );
+    }
+}
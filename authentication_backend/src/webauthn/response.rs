@@ -0,0 +1,102 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use base64;
+use error::{Error, Result, WebauthnErrorKind};
+
+#[derive(Debug)]
+pub struct Attestation {
+    challenge_token: String,
+    client_data_json: String,
+    attestation_object: String,
+}
+
+impl Attestation {
+    pub fn new(challenge_token: &str, client_data_json: &str, attestation_object: &str) -> Self {
+        Attestation {
+            challenge_token: challenge_token.to_owned(),
+            client_data_json: client_data_json.to_owned(),
+            attestation_object: attestation_object.to_owned(),
+        }
+    }
+
+    pub fn challenge_token(&self) -> &str {
+        &self.challenge_token
+    }
+
+    pub fn client_data_json(&self) -> Result<Vec<u8>> {
+        decode(&self.client_data_json)
+    }
+
+    pub fn attestation_object(&self) -> Result<Vec<u8>> {
+        decode(&self.attestation_object)
+    }
+}
+
+#[derive(Debug)]
+pub struct Assertion {
+    challenge_token: String,
+    credential_id: String,
+    client_data_json: String,
+    authenticator_data: String,
+    signature: String,
+}
+
+impl Assertion {
+    pub fn new(
+        challenge_token: &str,
+        credential_id: &str,
+        client_data_json: &str,
+        authenticator_data: &str,
+        signature: &str,
+    ) -> Self {
+        Assertion {
+            challenge_token: challenge_token.to_owned(),
+            credential_id: credential_id.to_owned(),
+            client_data_json: client_data_json.to_owned(),
+            authenticator_data: authenticator_data.to_owned(),
+            signature: signature.to_owned(),
+        }
+    }
+
+    pub fn challenge_token(&self) -> &str {
+        &self.challenge_token
+    }
+
+    pub fn credential_id(&self) -> Result<Vec<u8>> {
+        decode(&self.credential_id)
+    }
+
+    pub fn client_data_json(&self) -> Result<Vec<u8>> {
+        decode(&self.client_data_json)
+    }
+
+    pub fn authenticator_data(&self) -> Result<Vec<u8>> {
+        decode(&self.authenticator_data)
+    }
+
+    pub fn signature(&self) -> Result<Vec<u8>> {
+        decode(&self.signature)
+    }
+}
+
+fn decode(value: &str) -> Result<Vec<u8>> {
+    base64::decode_config(value.trim_right_matches('='), base64::URL_SAFE_NO_PAD)
+        .map_err(|_| Error::WebauthnError(WebauthnErrorKind::Malformed))
+}
@@ -0,0 +1,222 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use base64;
+use ring::{digest, signature};
+use ring::rand::{SecureRandom, SystemRandom};
+use untrusted::Input;
+use test_helper::WEBAUTHN_ORIGIN;
+use super::{Assertion, Attestation, AuthenticationOptions, RegistrationOptions};
+use super::cbor::Value;
+
+const USER_PRESENT_AND_VERIFIED: u8 = 0x05;
+const ATTESTED_CREDENTIAL: u8 = 0x40;
+const P256_PUBLIC_KEY_LEN: usize = 65;
+
+pub struct SoftwareAuthenticator {
+    key_pair: signature::ECDSAKeyPair,
+    public_key: Vec<u8>,
+    credential_id: Vec<u8>,
+    sign_count: u32,
+}
+
+impl SoftwareAuthenticator {
+    pub fn new() -> Self {
+        let rng = SystemRandom::new();
+        let algorithm = &signature::ECDSA_P256_SHA256_ASN1_SIGNING;
+
+        let pkcs8 = signature::ECDSAKeyPair::generate_pkcs8(algorithm, &rng)
+            .expect("Failed to generate key");
+        let key_pair = signature::ECDSAKeyPair::from_pkcs8(algorithm, Input::from(pkcs8.as_ref()))
+            .expect("Failed to read generated key");
+
+        let public_key = pkcs8.as_ref()[pkcs8.as_ref().len() - P256_PUBLIC_KEY_LEN..].to_vec();
+
+        let mut credential_id = vec![0; 16];
+        rng.fill(&mut credential_id).expect("Failed to generate credential id");
+
+        SoftwareAuthenticator {
+            key_pair: key_pair,
+            public_key: public_key,
+            credential_id: credential_id,
+            sign_count: 0,
+        }
+    }
+
+    pub fn credential_id(&self) -> String {
+        encode(&self.credential_id)
+    }
+
+    pub fn set_sign_count(&mut self, sign_count: u32) {
+        self.sign_count = sign_count;
+    }
+
+    pub fn attest(&self, options: &RegistrationOptions) -> Attestation {
+        let client_data_json = client_data_json("webauthn.create", options.challenge());
+
+        let flags = USER_PRESENT_AND_VERIFIED | ATTESTED_CREDENTIAL;
+
+        let mut authenticator_data = self.authenticator_data(options.rp_id(), flags);
+        authenticator_data.extend_from_slice(&[0; 16]);
+        authenticator_data.push((self.credential_id.len() >> 8) as u8);
+        authenticator_data.push(self.credential_id.len() as u8);
+        authenticator_data.extend_from_slice(&self.credential_id);
+        authenticator_data.extend(cbor(&self.cose_key()));
+
+        let attestation_object = cbor(&Value::Map(vec![
+            (Value::Text("fmt".to_owned()), Value::Text("none".to_owned())),
+            (Value::Text("attStmt".to_owned()), Value::Map(Vec::new())),
+            (Value::Text("authData".to_owned()), Value::Bytes(authenticator_data)),
+        ]));
+
+        Attestation::new(
+            options.challenge_token(),
+            &encode(&client_data_json),
+            &encode(&attestation_object),
+        )
+    }
+
+    pub fn assert(&mut self, options: &AuthenticationOptions) -> Assertion {
+        self.sign_count += 1;
+
+        let client_data_json = client_data_json("webauthn.get", options.challenge());
+        let authenticator_data =
+            self.authenticator_data(options.rp_id(), USER_PRESENT_AND_VERIFIED);
+
+        let mut signed = authenticator_data.clone();
+        signed.extend_from_slice(digest::digest(&digest::SHA256, &client_data_json).as_ref());
+
+        let signature = self.key_pair
+            .sign(Input::from(&signed), &SystemRandom::new())
+            .expect("Failed to sign assertion");
+
+        Assertion::new(
+            options.challenge_token(),
+            &self.credential_id(),
+            &encode(&client_data_json),
+            &encode(&authenticator_data),
+            &encode(signature.as_ref()),
+        )
+    }
+
+    fn authenticator_data(&self, rp_id: &str, flags: u8) -> Vec<u8> {
+        let mut authenticator_data = digest::digest(&digest::SHA256, rp_id.as_bytes())
+            .as_ref()
+            .to_vec();
+
+        authenticator_data.push(flags);
+        authenticator_data.extend_from_slice(&[
+            (self.sign_count >> 24) as u8,
+            (self.sign_count >> 16) as u8,
+            (self.sign_count >> 8) as u8,
+            self.sign_count as u8,
+        ]);
+
+        authenticator_data
+    }
+
+    fn cose_key(&self) -> Value {
+        Value::Map(vec![
+            (Value::Integer(1), Value::Integer(2)),
+            (Value::Integer(3), Value::Integer(-7)),
+            (Value::Integer(-1), Value::Integer(1)),
+            (Value::Integer(-2), Value::Bytes(self.public_key[1..33].to_vec())),
+            (Value::Integer(-3), Value::Bytes(self.public_key[33..].to_vec())),
+        ])
+    }
+}
+
+impl Default for SoftwareAuthenticator {
+    fn default() -> Self {
+        SoftwareAuthenticator::new()
+    }
+}
+
+fn client_data_json(kind: &str, challenge: &str) -> Vec<u8> {
+    format!(
+        r#"{{"type":"{}","challenge":"{}","origin":"{}"}}"#,
+        kind,
+        challenge,
+        WEBAUTHN_ORIGIN
+    ).into_bytes()
+}
+
+fn encode(bytes: &[u8]) -> String {
+    base64::encode_config(bytes, base64::URL_SAFE_NO_PAD)
+}
+
+fn cbor(value: &Value) -> Vec<u8> {
+    let mut encoded = Vec::new();
+
+    match *value {
+        Value::Integer(integer) if integer >= 0 => cbor_header(&mut encoded, 0, integer as u64),
+        Value::Integer(integer) => cbor_header(&mut encoded, 1, (-1 - integer) as u64),
+        Value::Bytes(ref bytes) => {
+            cbor_header(&mut encoded, 2, bytes.len() as u64);
+            encoded.extend_from_slice(bytes);
+        }
+        Value::Text(ref text) => {
+            cbor_header(&mut encoded, 3, text.len() as u64);
+            encoded.extend_from_slice(text.as_bytes());
+        }
+        Value::Array(ref items) => {
+            cbor_header(&mut encoded, 4, items.len() as u64);
+
+            for item in items {
+                encoded.extend(cbor(item));
+            }
+        }
+        Value::Map(ref entries) => {
+            cbor_header(&mut encoded, 5, entries.len() as u64);
+
+            for &(ref key, ref value) in entries {
+                encoded.extend(cbor(key));
+                encoded.extend(cbor(value));
+            }
+        }
+        Value::Bool(false) => encoded.push(0xf4),
+        Value::Bool(true) => encoded.push(0xf5),
+        Value::Null => encoded.push(0xf6),
+    }
+
+    encoded
+}
+
+fn cbor_header(encoded: &mut Vec<u8>, major: u8, argument: u64) {
+    let major = major << 5;
+
+    if argument < 24 {
+        encoded.push(major | argument as u8);
+    } else if argument < 0x100 {
+        encoded.push(major | 24);
+        encoded.push(argument as u8);
+    } else if argument < 0x10000 {
+        encoded.push(major | 25);
+        encoded.push((argument >> 8) as u8);
+        encoded.push(argument as u8);
+    } else {
+        encoded.push(major | 26);
+        encoded.extend_from_slice(&[
+            (argument >> 24) as u8,
+            (argument >> 16) as u8,
+            (argument >> 8) as u8,
+            argument as u8,
+        ]);
+    }
+}
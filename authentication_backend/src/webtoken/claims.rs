@@ -17,11 +17,14 @@
  * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
  */
 
-use jwt::{Algorithm, Validation};
-use chrono::{Utc, Duration};
-use CONFIG;
-use models::{UserTrait, UserPermission, Permission};
-use error::Result;
+use config::jwt_secret::Validation;
+use chrono::{DateTime, Utc};
+use service::AuthService;
+use models::{UserTrait, UserPermission};
+use error::{Error, JWTErrorKind, Result};
+use super::generate_id;
+
+const OWN_AUDIENCE: &str = "authentication";
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Claims {
@@ -29,10 +32,19 @@ pub struct Claims {
     sub: String,
     iat: i64,
     exp: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    aud: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    auth_time: Option<i64>,
+    jti: String,
+    fam: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sid: Option<i32>,
     user_id: i32,
     username: String,
     verified: bool,
-    admin: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    permissions: Option<Vec<String>>,
 }
 
 impl UserTrait for Claims {
@@ -50,76 +62,200 @@ impl UserTrait for Claims {
 }
 
 impl Claims {
-    pub fn is_admin(&self) -> bool {
-        self.admin
+    pub fn jti(&self) -> &str {
+        &self.jti
+    }
+
+    pub fn family(&self) -> &str {
+        &self.fam
+    }
+
+    pub fn session_id(&self) -> Option<i32> {
+        self.sid
+    }
+
+    pub fn authenticated_at(&self) -> i64 {
+        self.auth_time.unwrap_or(self.iat)
+    }
+
+    pub fn audience(&self) -> Option<&str> {
+        self.aud.as_ref().map(|aud| aud.as_str())
+    }
+
+    pub fn subject(&self) -> &str {
+        &self.sub
+    }
+
+    pub fn issued_at(&self) -> i64 {
+        self.iat
+    }
+
+    pub fn expires_at(&self) -> i64 {
+        self.exp
     }
 
-    pub fn new<T>(user: &T, subject: &str, days: i64) -> Self
+    pub fn permissions(&self) -> Option<&[String]> {
+        self.permissions.as_ref().map(|permissions| permissions.as_slice())
+    }
+
+    pub fn new<T>(
+        service: &AuthService,
+        user: &T,
+        subject: &str,
+        authenticated_at: DateTime<Utc>,
+        expiration: DateTime<Utc>,
+        family: &str,
+        audience: Option<&str>,
+    ) -> Result<Self>
     where
         T: UserTrait,
     {
         let issued_at = Utc::now();
-        let expiration = issued_at + Duration::days(days);
 
-        let admin = match Permission::find("admin") {
-            Ok(permission) => UserPermission::has_permission(user, &permission),
-            Err(_) => false,
+        let permissions = if subject == "user" {
+            Claims::token_permissions(service, user, audience)?
+        } else {
+            None
         };
 
-        Claims {
+        Ok(Claims {
             iss: "authentication".to_owned(),
             sub: subject.to_owned(),
             iat: issued_at.timestamp(),
             exp: expiration.timestamp(),
+            aud: audience.map(|audience| audience.to_owned()),
+            auth_time: Some(authenticated_at.timestamp()),
+            jti: generate_id()?,
+            fam: family.to_owned(),
+            sid: None,
             user_id: user.id(),
             username: user.username().to_owned(),
             verified: user.is_verified(),
-            admin: admin,
+            permissions: permissions,
+        })
+    }
+
+    pub fn in_session(self, session_id: Option<i32>) -> Self {
+        Claims { sid: session_id, ..self }
+    }
+
+    fn token_permissions<T>(
+        service: &AuthService,
+        user: &T,
+        audience: Option<&str>,
+    ) -> Result<Option<Vec<String>>>
+    where
+        T: UserTrait,
+    {
+        let permissions: Vec<String> = UserPermission::get_permissions(service, user)?
+            .iter()
+            .map(|permission| permission.name().to_owned())
+            .filter(|permission| in_audience(permission, audience))
+            .collect();
+
+        if permissions.len() > service.max_token_permissions() {
+            Ok(None)
+        } else {
+            Ok(Some(permissions))
         }
     }
 
-    pub fn authenticate(token: &str) -> Result<Self> {
+    // Without an audience, tokens for any audience are accepted
+    pub fn authenticate(
+        service: &AuthService,
+        token: &str,
+        audience: Option<&str>,
+    ) -> Result<Self> {
         let validation = Validation {
-            leeway: 1000 * 30,
-            algorithms: Some(vec![Algorithm::RS512]),
+            leeway: service.token_lifetimes().leeway().num_seconds(),
             iss: Some("authentication".to_owned()),
             sub: Some("user".to_owned()),
+            aud: audience.map(|audience| audience.to_owned()),
             ..Default::default()
         };
 
-        CONFIG.jwt_secret().decode(token, &validation)
+        service.jwt_secret().decode(token, &validation)
     }
 
-    pub fn renew(token: &str) -> Result<Self> {
+    // This service's own routes only accept tokens issued for no audience, or for this service
+    pub fn authenticate_internal(service: &AuthService, token: &str) -> Result<Self> {
+        let claims = Claims::authenticate(service, token, None)?;
+
+        match claims.audience() {
+            Some(audience) if audience != OWN_AUDIENCE => {
+                Err(Error::JWTError(JWTErrorKind::InvalidAudience))
+            }
+            _ => Ok(claims),
+        }
+    }
+
+    pub fn renew(service: &AuthService, token: &str) -> Result<Self> {
         let validation = Validation {
-            leeway: 1000 * 30,
-            algorithms: Some(vec![Algorithm::RS512]),
+            leeway: service.token_lifetimes().leeway().num_seconds(),
             iss: Some("authentication".to_owned()),
             sub: Some("renewal".to_owned()),
             ..Default::default()
         };
 
-        CONFIG.jwt_secret().decode(token, &validation)
+        service.jwt_secret().decode(token, &validation)
+    }
+
+    pub fn mfa_pending(service: &AuthService, token: &str) -> Result<Self> {
+        let validation = Validation {
+            leeway: service.token_lifetimes().leeway().num_seconds(),
+            iss: Some("authentication".to_owned()),
+            sub: Some("mfa_pending".to_owned()),
+            ..Default::default()
+        };
+
+        service.jwt_secret().decode(token, &validation)
+    }
+
+    pub fn introspect(service: &AuthService, token: &str) -> Result<Self> {
+        let validation = Validation {
+            leeway: service.token_lifetimes().leeway().num_seconds(),
+            iss: Some("authentication".to_owned()),
+            ..Default::default()
+        };
+
+        let claims: Claims = service.jwt_secret().decode(token, &validation)?;
+
+        let known_subject = match claims.subject() {
+            "user" | "renewal" => true,
+            _ => false,
+        };
+
+        if known_subject {
+            Ok(claims)
+        } else {
+            Err(Error::JWTError(JWTErrorKind::InvalidSubject))
+        }
+    }
+}
+
+fn in_audience(permission: &str, audience: Option<&str>) -> bool {
+    match (permission.find(':'), audience) {
+        (Some(index), Some(audience)) => &permission[..index] == audience,
+        _ => true,
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::Duration;
     use webtoken::test_helper::{with_claims, with_token};
-    use jwt::Header;
+    use test_helper::{generate_string, service};
+    use user_test_helper::with_authenticated;
 
     #[test]
     fn renew_creates_claims() {
         with_claims("renewal", |claims| {
-            let mut header = Header::default();
-            header.alg = Algorithm::RS512;
-
-            let token: String = CONFIG.jwt_secret().encode(&header, &claims).expect(
+            let token: String = service().jwt_secret().encode(&claims).expect(
                 "Failed to create token from claims",
             );
 
-            let result = Claims::renew(&token);
+            let result = Claims::renew(service(), &token);
 
             assert!(result.is_ok(), "Failed to get claims from token");
 
@@ -141,14 +277,11 @@ mod tests {
     #[test]
     fn authenticate_creates_claims() {
         with_claims("user", |claims| {
-            let mut header = Header::default();
-            header.alg = Algorithm::RS512;
-
-            let token: String = CONFIG.jwt_secret().encode(&header, &claims).expect(
+            let token: String = service().jwt_secret().encode(&claims).expect(
                 "Failed to create token from claims",
             );
 
-            let result = Claims::authenticate(&token);
+            let result = Claims::authenticate(service(), &token, None);
 
             assert!(result.is_ok(), "Failed to get claims from token");
 
@@ -170,7 +303,7 @@ mod tests {
     #[test]
     fn renew_fails_with_user_token() {
         with_token("user", |token| {
-            let result = Claims::renew(token);
+            let result = Claims::renew(service(), token);
 
             assert!(!result.is_ok(), "Validated User token as Renewal token");
         });
@@ -179,7 +312,7 @@ mod tests {
     #[test]
     fn authenticate_fails_with_renewal_token() {
         with_token("renewal", |token| {
-            let result = Claims::authenticate(token);
+            let result = Claims::authenticate(service(), token, None);
 
             assert!(!result.is_ok(), "Validated User token as Renewal token");
         });
@@ -187,15 +320,108 @@ mod tests {
 
     #[test]
     fn authenticate_fails_with_fake_token() {
-        let result = Claims::authenticate("This is not a webtoken");
+        let result = Claims::authenticate(service(), "This is not a webtoken", None);
 
         assert!(!result.is_ok(), "Created claims from fake webtoken");
     }
 
     #[test]
     fn renew_fails_with_fake_token() {
-        let result = Claims::renew("This is not a webtoken");
+        let result = Claims::renew(service(), "This is not a webtoken");
 
         assert!(!result.is_ok(), "Created claims from fake webtoken");
     }
+
+    #[test]
+    fn introspect_accepts_user_and_renewal_tokens() {
+        with_token("user", |token| {
+            let result = Claims::introspect(service(), token);
+
+            assert!(result.is_ok(), "Failed to introspect User token");
+        });
+
+        with_token("renewal", |token| {
+            let result = Claims::introspect(service(), token);
+
+            assert!(result.is_ok(), "Failed to introspect Renewal token");
+        });
+    }
+
+    #[test]
+    fn introspect_fails_with_unknown_subject() {
+        with_token("invalid", |token| {
+            let result = Claims::introspect(service(), token);
+
+            assert!(!result.is_ok(), "Introspected token with unknown subject");
+        });
+    }
+
+    #[test]
+    fn new_lists_permissions_in_user_tokens() {
+        with_claims("user", |claims| {
+            assert_eq!(
+                claims.permissions(),
+                Some(&[][..]),
+                "User token has wrong permissions"
+            );
+        });
+    }
+
+    #[test]
+    fn new_leaves_permissions_out_of_renewal_tokens() {
+        with_claims("renewal", |claims| {
+            assert!(
+                claims.permissions().is_none(),
+                "Renewal token lists permissions"
+            );
+        });
+    }
+
+    #[test]
+    fn in_audience_filters_scoped_permissions() {
+        assert!(in_audience("admin", Some("billing")), "Filtered global permission");
+        assert!(
+            in_audience("billing:refund", Some("billing")),
+            "Filtered permission for audience"
+        );
+        assert!(
+            !in_audience("support:refund", Some("billing")),
+            "Kept permission for other audience"
+        );
+        assert!(
+            in_audience("support:refund", None),
+            "Filtered permission without audience"
+        );
+    }
+
+    #[test]
+    fn authenticate_checks_audience() {
+        with_authenticated(|authenticated| {
+            let claims = Claims::new(
+                service(),
+                &authenticated,
+                "user",
+                Utc::now(),
+                Utc::now() + Duration::days(2),
+                &generate_string(),
+                Some("billing"),
+            ).expect("Failed to create Claims");
+
+            let token: String = service().jwt_secret().encode(&claims).expect(
+                "Failed to create token from claims",
+            );
+
+            let result = Claims::authenticate(service(), &token, Some("billing"));
+
+            assert!(result.is_ok(), "Rejected token for expected audience");
+
+            let result = Claims::authenticate(service(), &token, Some("support"));
+
+            assert!(!result.is_ok(), "Accepted token for another audience");
+
+            let result = Claims::authenticate(service(), &token, None);
+
+            assert!(result.is_ok(), "Rejected token with audience");
+        });
+    }
 }
@@ -0,0 +1,88 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::claims::Claims;
+use models::UserTrait;
+
+#[derive(Debug, Serialize)]
+pub struct Introspection {
+    active: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sub: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    username: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    exp: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    iat: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    aud: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    scope: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    token_type: Option<String>,
+}
+
+impl Introspection {
+    pub fn inactive() -> Self {
+        Introspection {
+            active: false,
+            sub: None,
+            username: None,
+            exp: None,
+            iat: None,
+            aud: None,
+            scope: None,
+            token_type: None,
+        }
+    }
+
+    pub(crate) fn active(claims: &Claims, scope: Vec<String>) -> Self {
+        Introspection {
+            active: true,
+            sub: Some(claims.id().to_string()),
+            username: Some(claims.username().to_owned()),
+            exp: Some(claims.expires_at()),
+            iat: Some(claims.issued_at()),
+            aud: claims.audience().map(|aud| aud.to_owned()),
+            scope: Some(scope.join(" ")),
+            token_type: Some(format!("{}_token", claims.subject())),
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    pub fn username(&self) -> Option<&str> {
+        self.username.as_ref().map(|username| username.as_str())
+    }
+
+    pub fn audience(&self) -> Option<&str> {
+        self.aud.as_ref().map(|aud| aud.as_str())
+    }
+
+    pub fn scope(&self) -> Option<&str> {
+        self.scope.as_ref().map(|scope| scope.as_str())
+    }
+
+    pub fn token_type(&self) -> Option<&str> {
+        self.token_type.as_ref().map(|token_type| token_type.as_str())
+    }
+}
@@ -18,13 +18,23 @@
  */
 
 mod claims;
+mod introspection;
+mod log_in;
 mod new_webtoken;
+mod token_user;
 
 #[cfg(feature = "test")]
 pub mod test_helper;
 
-use error::Result;
-use models::UserTrait;
+pub use self::introspection::Introspection;
+pub use self::log_in::LogIn;
+pub use self::token_user::TokenUser;
+
+use chrono::Utc;
+use error::{Error, MfaErrorKind, Result};
+use service::AuthService;
+use models::{ClientInfo, FailedLogin, SecondFactor, Session, TokenFamily, TotpCredential,
+             UserPermission, UserTrait};
 use self::claims::Claims;
 use self::new_webtoken::NewWebtoken;
 
@@ -42,32 +52,204 @@ impl Webtoken {
         }
     }
 
-    pub fn create<T>(user: &T) -> Result<Self>
+    pub fn create<T>(service: &AuthService, user: &T, audience: Option<&str>) -> Result<Self>
+    where
+        T: UserTrait,
+    {
+        Webtoken::create_for_client(service, user, audience, &ClientInfo::default())
+    }
+
+    pub fn create_for_client<T>(
+        service: &AuthService,
+        user: &T,
+        audience: Option<&str>,
+        client: &ClientInfo,
+    ) -> Result<Self>
+    where
+        T: UserTrait,
+    {
+        service.check_audience(audience)?;
+
+        let family = generate_id()?;
+        let session = Session::create(service, user, &family, client)?;
+
+        let new_webtoken = NewWebtoken::new(service, user, &family, Some(session.id()), audience)?;
+
+        TokenFamily::create(
+            service,
+            user,
+            new_webtoken.family(),
+            new_webtoken.renewal_jti(),
+        )?;
+
+        new_webtoken.to_token(service)
+    }
+
+    pub fn create_mfa_token<T>(
+        service: &AuthService,
+        user: &T,
+        audience: Option<&str>,
+    ) -> Result<String>
     where
         T: UserTrait,
     {
-        let new_webtoken = NewWebtoken::new(user);
+        service.check_audience(audience)?;
 
-        let webtoken = new_webtoken.to_token()?;
+        let now = Utc::now();
 
-        Ok(webtoken)
+        let claims = Claims::new(
+            service,
+            user,
+            "mfa_pending",
+            now,
+            now + service.token_lifetimes().mfa_token(),
+            &generate_id()?,
+            audience,
+        )?;
+
+        TokenFamily::create(service, user, claims.family(), claims.jti())?;
+
+        service.jwt_secret().encode(&claims)
     }
 
-    pub fn authenticate(token: &str) -> Result<(i32, String, bool, bool)> {
-        let claims = Claims::authenticate(token)?;
+    // Wrong codes count as failed log ins for the user, and use up one of the mfa token's few
+    // attempts
+    pub fn complete_mfa(
+        service: &AuthService,
+        mfa_token: &str,
+        code: &str,
+        client: &ClientInfo,
+    ) -> Result<(Self, SecondFactor)> {
+        let claims = Claims::mfa_pending(service, mfa_token)?;
+
+        TokenFamily::check(service, claims.family())?;
+        FailedLogin::check(service, Some(claims.username()), None)?;
 
-        Ok((
-            claims.id(),
-            claims.username().to_owned(),
-            claims.is_verified(),
-            claims.is_admin(),
-        ))
+        let second_factor = match TotpCredential::verify(service, &claims, code) {
+            Ok(second_factor) => second_factor,
+            Err(Error::MfaError(MfaErrorKind::InvalidCode)) => {
+                return Webtoken::fail_mfa(service, &claims);
+            }
+            Err(err) => return Err(err),
+        };
+
+        TokenFamily::revoke(service, claims.family())?;
+        FailedLogin::clear_mfa(service, claims.family())?;
+        FailedLogin::clear(service, claims.username())?;
+
+        let webtoken = Webtoken::create_for_client(service, &claims, claims.audience(), client)?;
+
+        Ok((webtoken, second_factor))
     }
 
-    pub fn renew(token: &str) -> Result<Self> {
-        let claims = Claims::renew(token)?;
+    fn fail_mfa(service: &AuthService, claims: &Claims) -> Result<(Self, SecondFactor)> {
+        if FailedLogin::record_mfa(service, claims.family(), claims.id())? {
+            TokenFamily::revoke(service, claims.family())?;
+            FailedLogin::clear_mfa(service, claims.family())?;
+        }
+
+        if FailedLogin::record(service, Some(claims.username()), Some(claims.id()), None)? {
+            return Err(Error::AccountLockedError(claims.id()));
+        }
 
-        Webtoken::create(&claims)
+        Err(Error::MfaError(MfaErrorKind::InvalidCode))
+    }
+
+    // Tokens issued for other services are rejected, so they can't be used against this one
+    pub fn authenticate(service: &AuthService, token: &str) -> Result<TokenUser> {
+        let claims = Claims::authenticate_internal(service, token)?;
+
+        Webtoken::token_user(service, &claims)
+    }
+
+    pub fn authenticate_for(
+        service: &AuthService,
+        token: &str,
+        audience: &str,
+    ) -> Result<TokenUser> {
+        let claims = Claims::authenticate(service, token, Some(audience))?;
+
+        Webtoken::token_user(service, &claims)
+    }
+
+    fn token_user(service: &AuthService, claims: &Claims) -> Result<TokenUser> {
+        TokenFamily::check(service, claims.family())?;
+
+        Ok(TokenUser::from_claims(claims))
+    }
+
+    pub fn renew(service: &AuthService, token: &str, audience: Option<&str>) -> Result<Self> {
+        service.check_audience(audience)?;
+
+        let claims = Claims::renew(service, token)?;
+
+        let new_webtoken = NewWebtoken::renew(service, &claims, audience.or(claims.audience()))?;
+
+        TokenFamily::rotate(
+            service,
+            claims.family(),
+            claims.jti(),
+            new_webtoken.renewal_jti(),
+        )?;
+
+        if let Some(session_id) = claims.session_id() {
+            Session::touch(service, session_id)?;
+        }
+
+        new_webtoken.to_token(service)
+    }
+
+    pub fn introspect(service: &AuthService, token: &str) -> Introspection {
+        let claims = match Webtoken::active_claims(service, token) {
+            Ok(claims) => claims,
+            Err(_) => return Introspection::inactive(),
+        };
+
+        if let Some(permissions) = claims.permissions() {
+            return Introspection::active(&claims, permissions.to_vec());
+        }
+
+        match UserPermission::get_permissions(service, &claims) {
+            Ok(permissions) => {
+                Introspection::active(
+                    &claims,
+                    permissions
+                        .iter()
+                        .map(|permission| permission.name().to_owned())
+                        .collect(),
+                )
+            }
+            Err(_) => Introspection::inactive(),
+        }
+    }
+
+    fn active_claims(service: &AuthService, token: &str) -> Result<Claims> {
+        let claims = Claims::introspect(service, token)?;
+
+        let token_family = TokenFamily::find(service, claims.family())?;
+
+        if token_family.is_revoked() {
+            return Err(Error::TokenRevokedError);
+        }
+
+        if claims.subject() == "renewal" && token_family.current_jti() != claims.jti() {
+            return Err(Error::TokenReuseError(claims.id()));
+        }
+
+        Ok(claims)
+    }
+
+    pub fn revoke(service: &AuthService, token: &str) -> Result<()> {
+        let claims = Claims::authenticate(service, token, None)?;
+
+        TokenFamily::revoke(service, claims.family())
+    }
+
+    pub fn revoke_all<T>(service: &AuthService, user: &T) -> Result<()>
+    where
+        T: UserTrait,
+    {
+        TokenFamily::revoke_all(service, user)
     }
 
     pub fn user_token(&self) -> &str {
@@ -79,15 +261,27 @@ impl Webtoken {
     }
 }
 
+fn generate_id() -> Result<String> {
+    use rand::Rng;
+    use rand::OsRng;
+
+    let mut os_rng = OsRng::new()?;
+
+    Ok(os_rng.gen_ascii_chars().take(30).collect())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use error::Error;
     use user_test_helper::with_authenticated;
+    use totp_credential_test_helper::{current_code, next_code};
+    use test_helper::{generate_string, service};
 
     #[test]
     fn create_creates_webtoken() {
         with_authenticated(|authenticated| {
-            let result = Webtoken::create(&authenticated);
+            let result = Webtoken::create(service(), &authenticated, None);
 
             assert!(result.is_ok(), "Failed to create webtoken");
         });
@@ -96,23 +290,24 @@ mod tests {
     #[test]
     fn full_authentication_cycle_works() {
         with_authenticated(|authenticated| {
-            let webtoken = Webtoken::create(&authenticated).expect("Failed to create webtoken");
+            let webtoken = Webtoken::create(service(), &authenticated, None)
+                .expect("Failed to create webtoken");
 
-            let result = Webtoken::authenticate(webtoken.user_token());
+            let result = Webtoken::authenticate(service(), webtoken.user_token());
 
             assert!(result.is_ok(), "Failed to get claims from User Token");
 
-            let (result_id, result_name, result_verified, _admin) = result.unwrap();
+            let token_user = result.unwrap();
 
-            assert_eq!(authenticated.id(), result_id, "User from Token has bad ID");
+            assert_eq!(authenticated.id(), token_user.id(), "User from Token has bad ID");
             assert_eq!(
                 authenticated.username(),
-                result_name,
+                token_user.username(),
                 "User from Token has bad username"
             );
             assert_eq!(
                 authenticated.is_verified(),
-                result_verified,
+                token_user.is_verified(),
                 "User from Token has bad Verification status"
             );
         });
@@ -121,31 +316,283 @@ mod tests {
     #[test]
     fn full_renewal_cycle_works() {
         with_authenticated(|authenticated| {
-            let webtoken = Webtoken::create(&authenticated).expect("Failed to create webtoken");
+            let webtoken = Webtoken::create(service(), &authenticated, None)
+                .expect("Failed to create webtoken");
 
-            let webtoken_2 = Webtoken::renew(webtoken.renewal_token());
+            let webtoken_2 = Webtoken::renew(service(), webtoken.renewal_token(), None);
 
             assert!(webtoken_2.is_ok(), "Failed to renew webtoken");
 
             let webtoken_2 = webtoken_2.unwrap();
 
-            let result = Webtoken::authenticate(webtoken_2.user_token());
+            let result = Webtoken::authenticate(service(), webtoken_2.user_token());
 
             assert!(result.is_ok(), "Failed to get claims from User Token");
 
-            let (result_id, result_name, result_verified, _admin) = result.unwrap();
+            let token_user = result.unwrap();
 
-            assert_eq!(authenticated.id(), result_id, "User from Token has bad ID");
+            assert_eq!(authenticated.id(), token_user.id(), "User from Token has bad ID");
             assert_eq!(
                 authenticated.username(),
-                result_name,
+                token_user.username(),
                 "User from Token has bad username"
             );
             assert_eq!(
                 authenticated.is_verified(),
-                result_verified,
+                token_user.is_verified(),
                 "User from Token has bad username"
             );
         });
     }
+
+    #[test]
+    fn renew_uses_current_username() {
+        with_authenticated(|authenticated| {
+            let webtoken = Webtoken::create(service(), &authenticated, None)
+                .expect("Failed to create webtoken");
+
+            let new_username = generate_string();
+
+            service()
+                .store()
+                .update_username(authenticated.id(), &new_username)
+                .expect("Failed to update username");
+
+            let webtoken_2 = Webtoken::renew(service(), webtoken.renewal_token(), None)
+                .expect("Failed to renew webtoken");
+
+            let token_user = Webtoken::authenticate(service(), webtoken_2.user_token())
+                .expect("Failed to get claims from User Token");
+
+            assert_eq!(
+                token_user.username(),
+                new_username.as_str(),
+                "Renewed token has the old username"
+            );
+        });
+    }
+
+    #[test]
+    fn renewal_token_can_only_be_used_once() {
+        with_authenticated(|authenticated| {
+            let webtoken = Webtoken::create(service(), &authenticated, None)
+                .expect("Failed to create webtoken");
+
+            let _ = Webtoken::renew(service(), webtoken.renewal_token(), None)
+                .expect("Failed to renew webtoken");
+
+            let result = Webtoken::renew(service(), webtoken.renewal_token(), None);
+
+            match result {
+                Err(Error::TokenReuseError(user_id)) => {
+                    assert_eq!(user_id, authenticated.id(), "Reuse reported for wrong user")
+                }
+                _ => panic!("Renewed webtoken with a used renewal token"),
+            }
+        });
+    }
+
+    #[test]
+    fn reuse_revokes_token_family() {
+        with_authenticated(|authenticated| {
+            let webtoken = Webtoken::create(service(), &authenticated, None)
+                .expect("Failed to create webtoken");
+
+            let webtoken_2 = Webtoken::renew(service(), webtoken.renewal_token(), None)
+                .expect("Failed to renew webtoken");
+
+            let _ = Webtoken::renew(service(), webtoken.renewal_token(), None);
+
+            let result = Webtoken::renew(service(), webtoken_2.renewal_token(), None);
+
+            assert!(
+                !result.is_ok(),
+                "Renewed webtoken from a revoked token family"
+            );
+        });
+    }
+
+    #[test]
+    fn revoke_revokes_user_and_renewal_tokens() {
+        with_authenticated(|authenticated| {
+            let webtoken = Webtoken::create(service(), &authenticated, None)
+                .expect("Failed to create webtoken");
+
+            let result = Webtoken::revoke(service(), webtoken.user_token());
+
+            assert!(result.is_ok(), "Failed to revoke webtoken");
+
+            let result = Webtoken::authenticate(service(), webtoken.user_token());
+
+            assert!(!result.is_ok(), "Authenticated revoked User Token");
+
+            let result = Webtoken::renew(service(), webtoken.renewal_token(), None);
+
+            match result {
+                Err(Error::TokenRevokedError) => (),
+                _ => panic!("Renewed revoked Renewal Token"),
+            }
+        });
+    }
+
+    #[test]
+    fn create_for_client_records_session() {
+        with_authenticated(|authenticated| {
+            let client = ClientInfo::new(Some("Firefox"), Some("127.0.0.1"));
+
+            let webtoken = Webtoken::create_for_client(service(), &authenticated, None, &client)
+                .expect("Failed to create webtoken");
+
+            let claims = Claims::authenticate(service(), webtoken.user_token(), None).unwrap();
+            let session_id = claims.session_id().expect("User Token has no session");
+
+            let session = Session::find(service(), session_id).expect("Failed to find Session");
+
+            assert_eq!(session.family(), claims.family(), "Session has wrong family");
+            assert_eq!(session.user_agent(), Some("Firefox"), "Session has bad user agent");
+
+            let webtoken_2 = Webtoken::renew(service(), webtoken.renewal_token(), None)
+                .expect("Failed to renew webtoken");
+
+            let claims = Claims::renew(service(), webtoken_2.renewal_token()).unwrap();
+
+            assert_eq!(claims.session_id(), Some(session_id), "Renewal left the session");
+
+            let session = Session::find(service(), session_id).unwrap();
+
+            assert!(session.last_renewed_at().is_some(), "Renewal was not recorded");
+        });
+    }
+
+    #[test]
+    fn mfa_token_only_completes_log_in() {
+        with_authenticated(|authenticated| {
+            let mfa_token = Webtoken::create_mfa_token(service(), &authenticated, None)
+                .expect("Failed to create mfa_pending token");
+
+            let result = Webtoken::authenticate(service(), &mfa_token);
+
+            assert!(!result.is_ok(), "Authenticated with mfa_pending token");
+
+            let result = Webtoken::renew(service(), &mfa_token, None);
+
+            assert!(!result.is_ok(), "Renewed with mfa_pending token");
+
+            let introspection = Webtoken::introspect(service(), &mfa_token);
+
+            assert!(!introspection.is_active(), "mfa_pending token is active");
+        });
+    }
+
+    #[test]
+    fn complete_mfa_creates_webtoken() {
+        with_authenticated(|authenticated| {
+            let enrollment = TotpCredential::enroll(service(), &authenticated)
+                .expect("Failed to enroll");
+            let _ = TotpCredential::confirm(
+                service(),
+                &authenticated,
+                &current_code(enrollment.secret()),
+            ).expect("Failed to confirm enrollment");
+
+            let mfa_token = Webtoken::create_mfa_token(service(), &authenticated, None)
+                .expect("Failed to create mfa_pending token");
+
+            let result = Webtoken::complete_mfa(
+                service(),
+                &mfa_token,
+                "not a code",
+                &ClientInfo::default(),
+            );
+
+            assert!(!result.is_ok(), "Completed log in with wrong code");
+
+            FailedLogin::clear(service(), authenticated.username()).unwrap();
+
+            let result = Webtoken::complete_mfa(
+                service(),
+                &mfa_token,
+                &next_code(enrollment.secret()),
+                &ClientInfo::default(),
+            );
+
+            assert!(result.is_ok(), "Failed to complete log in");
+
+            let (webtoken, second_factor) = result.unwrap();
+
+            assert_eq!(second_factor, SecondFactor::Totp, "Wrong second factor");
+
+            let result = Webtoken::authenticate(service(), webtoken.user_token());
+
+            assert!(result.is_ok(), "Failed to authenticate completed log in");
+        });
+    }
+
+    #[test]
+    fn introspect_describes_active_tokens() {
+        with_authenticated(|authenticated| {
+            let webtoken = Webtoken::create(service(), &authenticated, None)
+                .expect("Failed to create webtoken");
+
+            let introspection = Webtoken::introspect(service(), webtoken.user_token());
+
+            assert!(introspection.is_active(), "User Token is not active");
+            assert_eq!(
+                introspection.username(),
+                Some(authenticated.username()),
+                "Introspection has bad username"
+            );
+            assert_eq!(
+                introspection.token_type(),
+                Some("user_token"),
+                "Introspection has bad token type"
+            );
+
+            let introspection = Webtoken::introspect(service(), webtoken.renewal_token());
+
+            assert!(introspection.is_active(), "Renewal Token is not active");
+            assert_eq!(
+                introspection.token_type(),
+                Some("renewal_token"),
+                "Introspection has bad token type"
+            );
+        });
+    }
+
+    #[test]
+    fn introspect_reports_used_renewal_token_as_inactive() {
+        with_authenticated(|authenticated| {
+            let webtoken = Webtoken::create(service(), &authenticated, None)
+                .expect("Failed to create webtoken");
+
+            let _ = Webtoken::renew(service(), webtoken.renewal_token(), None)
+                .expect("Failed to renew webtoken");
+
+            let introspection = Webtoken::introspect(service(), webtoken.renewal_token());
+
+            assert!(!introspection.is_active(), "Used Renewal Token is active");
+        });
+    }
+
+    #[test]
+    fn introspect_reports_revoked_token_as_inactive() {
+        with_authenticated(|authenticated| {
+            let webtoken = Webtoken::create(service(), &authenticated, None)
+                .expect("Failed to create webtoken");
+
+            let _ = Webtoken::revoke(service(), webtoken.user_token())
+                .expect("Failed to revoke webtoken");
+
+            let introspection = Webtoken::introspect(service(), webtoken.user_token());
+
+            assert!(!introspection.is_active(), "Revoked User Token is active");
+        });
+    }
+
+    #[test]
+    fn introspect_reports_fake_token_as_inactive() {
+        let introspection = Webtoken::introspect(service(), "This is not a webtoken");
+
+        assert!(!introspection.is_active(), "Fake token is active");
+    }
 }
@@ -17,10 +17,11 @@
  * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
  */
 
-use jwt::{Header, Algorithm};
-use CONFIG;
-use error::Result;
-use models::UserTrait;
+use std::cmp;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use service::AuthService;
+use error::{DbError, Error, Result};
+use models::{User, UserTrait};
 use super::claims::Claims;
 use super::Webtoken;
 
@@ -31,40 +32,176 @@ pub struct NewWebtoken {
 }
 
 impl NewWebtoken {
-    pub fn new<T>(user: &T) -> Self
+    pub fn new<T>(
+        service: &AuthService,
+        user: &T,
+        family: &str,
+        session_id: Option<i32>,
+        audience: Option<&str>,
+    ) -> Result<Self>
     where
         T: UserTrait,
     {
-        NewWebtoken {
-            user_claims: Claims::new(user, "user", 2),
-            renewal_claims: Claims::new(user, "renewal", 7),
-        }
+        NewWebtoken::in_family(
+            service,
+            user,
+            family,
+            session_id,
+            Utc::now(),
+            None,
+            audience,
+        )
+    }
+
+    pub fn renew(service: &AuthService, claims: &Claims, audience: Option<&str>) -> Result<Self> {
+        let user = match User::find_by_id(service, claims.id()) {
+            Ok(user) => user,
+            Err(Error::DbError(DbError::NotFound)) => return Err(Error::TokenRevokedError),
+            Err(err) => return Err(err),
+        };
+
+        NewWebtoken::in_family(
+            service,
+            &user,
+            claims.family(),
+            claims.session_id(),
+            timestamp(claims.authenticated_at()),
+            Some(timestamp(claims.expires_at())),
+            audience,
+        )
+    }
+
+    pub fn family(&self) -> &str {
+        self.renewal_claims.family()
+    }
+
+    pub fn renewal_jti(&self) -> &str {
+        self.renewal_claims.jti()
     }
 
-    pub fn to_token(&self) -> Result<Webtoken> {
-        let mut header = Header::default();
-        header.alg = Algorithm::RS512;
+    fn in_family<T>(
+        service: &AuthService,
+        user: &T,
+        family: &str,
+        session_id: Option<i32>,
+        authenticated_at: DateTime<Utc>,
+        renewal_expiration: Option<DateTime<Utc>>,
+        audience: Option<&str>,
+    ) -> Result<Self>
+    where
+        T: UserTrait,
+    {
+        let lifetimes = service.token_lifetimes();
+        let now = Utc::now();
+        let session_end = authenticated_at + lifetimes.session();
+
+        if session_end <= now {
+            return Err(Error::SessionExpiredError);
+        }
+
+        let renewal_expiration = match renewal_expiration {
+            Some(expiration) if !lifetimes.is_sliding() => expiration,
+            _ => now + lifetimes.renewal_token(),
+        };
+        let renewal_expiration = cmp::min(renewal_expiration, session_end);
+        let user_expiration = cmp::min(now + lifetimes.user_token(), renewal_expiration);
+
+        Ok(NewWebtoken {
+            user_claims: Claims::new(
+                service,
+                user,
+                "user",
+                authenticated_at,
+                user_expiration,
+                family,
+                audience,
+            )?
+                .in_session(session_id),
+            renewal_claims: Claims::new(
+                service,
+                user,
+                "renewal",
+                authenticated_at,
+                renewal_expiration,
+                family,
+                audience,
+            )?
+                .in_session(session_id),
+        })
+    }
 
-        let secret = CONFIG.jwt_secret();
+    pub fn to_token(&self, service: &AuthService) -> Result<Webtoken> {
+        let secret = service.jwt_secret();
 
         Ok(Webtoken::new(
-            &secret.encode(&header, &self.user_claims)?,
-            &secret.encode(&header, &self.renewal_claims)?,
+            &secret.encode(&self.user_claims)?,
+            &secret.encode(&self.renewal_claims)?,
         ))
     }
 }
 
+fn timestamp(seconds: i64) -> DateTime<Utc> {
+    DateTime::from_utc(NaiveDateTime::from_timestamp(seconds, 0), Utc)
+}
+
 #[cfg(test)]
 mod tests {
     use user_test_helper::with_authenticated;
     use super::*;
+    use test_helper::{generate_string, service};
+    use webtoken::test_helper::with_claims;
 
     #[test]
     fn to_token_creates_webtoken() {
         with_authenticated(|authenticated| {
-            let result = NewWebtoken::new(&authenticated).to_token();
+            let new_webtoken =
+                NewWebtoken::new(service(), &authenticated, &generate_string(), None, None)
+                    .expect("Failed to create NewWebtoken");
+
+            let result = new_webtoken.to_token(service());
 
             assert!(result.is_ok(), "Failed to create webtoken");
         });
     }
+
+    #[test]
+    fn renew_does_not_outlive_session() {
+        with_authenticated(|authenticated| {
+            let new_webtoken =
+                NewWebtoken::new(service(), &authenticated, &generate_string(), None, None)
+                    .expect("Failed to create NewWebtoken");
+
+            let session_end = Utc::now() + service().token_lifetimes().session();
+
+            assert!(
+                new_webtoken.renewal_claims.expires_at() <= session_end.timestamp(),
+                "Renewal token outlives session"
+            );
+            assert!(
+                new_webtoken.user_claims.expires_at() <=
+                    new_webtoken.renewal_claims.expires_at(),
+                "User token outlives renewal token"
+            );
+        });
+    }
+
+    #[test]
+    fn renew_fails_after_session_ends() {
+        with_claims("renewal", |claims| {
+            let ended_session = NewWebtoken::in_family(
+                service(),
+                &claims,
+                claims.family(),
+                None,
+                Utc::now() - service().token_lifetimes().session(),
+                None,
+                None,
+            );
+
+            match ended_session {
+                Err(Error::SessionExpiredError) => (),
+                _ => panic!("Renewed token after session ended"),
+            }
+        });
+    }
 }
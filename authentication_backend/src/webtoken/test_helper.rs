@@ -18,9 +18,10 @@
  */
 
 use std::panic;
-use jwt::{Header, Algorithm};
-use CONFIG;
+use chrono::{Duration, Utc};
+use test_helper::{generate_string, service};
 use super::claims::Claims;
+use models::TokenFamily;
 use user_test_helper::with_authenticated;
 
 pub fn with_claims<T>(sub: &str, test: T) -> ()
@@ -28,7 +29,18 @@ where
     T: FnOnce(Claims) -> () + panic::UnwindSafe,
 {
     with_authenticated(|authenticated| {
-        let claims = Claims::new(&authenticated, sub, 2);
+        let claims = Claims::new(
+            service(),
+            &authenticated,
+            sub,
+            Utc::now(),
+            Utc::now() + Duration::days(2),
+            &generate_string(),
+            None,
+        ).expect("Failed to create Claims");
+
+        let _ = TokenFamily::create(service(), &authenticated, claims.family(), claims.jti())
+            .expect("Failed to create TokenFamily");
 
         panic::catch_unwind(|| test(claims)).unwrap();
     });
@@ -39,10 +51,7 @@ where
     T: FnOnce(&str) -> () + panic::UnwindSafe,
 {
     with_claims(sub, |claims| {
-        let mut header = Header::default();
-        header.alg = Algorithm::RS512;
-
-        let token: String = CONFIG.jwt_secret().encode(&header, &claims).expect(
+        let token: String = service().jwt_secret().encode(&claims).expect(
             "Failed to create token from claims",
         );
 
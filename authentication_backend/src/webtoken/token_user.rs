@@ -0,0 +1,109 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use service::AuthService;
+use models::{Permission, UserPermission, UserTrait};
+use super::claims::Claims;
+
+#[derive(Debug)]
+pub struct TokenUser {
+    id: i32,
+    username: String,
+    verified: bool,
+    permissions: Option<Vec<String>>,
+}
+
+impl UserTrait for TokenUser {
+    fn id(&self) -> i32 {
+        self.id
+    }
+
+    fn username(&self) -> &str {
+        &self.username
+    }
+
+    fn is_verified(&self) -> bool {
+        self.verified
+    }
+}
+
+impl TokenUser {
+    pub(crate) fn from_claims(claims: &Claims) -> Self {
+        TokenUser {
+            id: claims.id(),
+            username: claims.username().to_owned(),
+            verified: claims.is_verified(),
+            permissions: claims.permissions().map(|permissions| permissions.to_vec()),
+        }
+    }
+
+    pub fn permissions(&self) -> Option<&[String]> {
+        self.permissions.as_ref().map(|permissions| permissions.as_slice())
+    }
+
+    pub fn has_permission(&self, service: &AuthService, permission: &str) -> bool {
+        match self.permissions {
+            Some(ref permissions) => permissions.iter().any(|name| name == permission),
+            None => {
+                match Permission::find(service, permission) {
+                    Ok(permission) => UserPermission::has_permission(service, self, &permission),
+                    Err(_) => false,
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use webtoken::Webtoken;
+    use test_helper::service;
+    use user_test_helper::with_authenticated;
+
+    #[test]
+    fn has_permission_reads_permissions_from_token() {
+        with_authenticated(|authenticated| {
+            let admin = Permission::find(service(), "admin").expect("Failed to find admin");
+
+            let _ = UserPermission::create(service(), &authenticated, &admin)
+                .expect("Failed to grant admin");
+
+            let webtoken = Webtoken::create(service(), &authenticated, None)
+                .expect("Failed to create webtoken");
+
+            let token_user = Webtoken::authenticate(service(), webtoken.user_token())
+                .expect("Failed to authenticate webtoken");
+
+            assert_eq!(
+                token_user.permissions(),
+                Some(&["admin".to_owned()][..]),
+                "Token has wrong permissions"
+            );
+            assert!(
+                token_user.has_permission(service(), "admin"),
+                "Token user is missing admin permission"
+            );
+            assert!(
+                !token_user.has_permission(service(), "not a permission"),
+                "Token user has fake permission"
+            );
+        });
+    }
+}
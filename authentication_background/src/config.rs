@@ -19,6 +19,7 @@
 
 use std::collections::HashMap;
 use std::fmt;
+use std::sync::Arc;
 use error::{Error, Result};
 use handler::Handler;
 
@@ -29,7 +30,7 @@ pub struct Config<'a, T>
 where
     T: 'a,
 {
-    handlers: HashMap<&'a str, &'a Handler<T>>,
+    handlers: HashMap<&'a str, Arc<Handler<T>>>,
 }
 
 impl<'a, T> fmt::Debug for Config<'a, T> {
@@ -53,11 +54,15 @@ impl<'a, T> Config<'a, T> {
         Default::default()
     }
 
-    pub fn handlers(self) -> HashMap<&'a str, &'a Handler<T>> {
+    pub fn handlers(self) -> HashMap<&'a str, Arc<Handler<T>>> {
         self.handlers
     }
 
-    pub fn register_handler(&mut self, name: &'a str, handler: &'a Handler<T>) -> Result {
+    pub fn register_handler<H>(&mut self, name: &'a str, handler: H) -> Result
+    where
+        T: Send + Sync,
+        H: Handler<T> + 'static,
+    {
         if name == EXIT_STR {
             return Err(Error::ExitHandler);
         }
@@ -66,7 +71,7 @@ impl<'a, T> Config<'a, T> {
             return Err(Error::DuplicateHandler(name.to_owned()));
         };
 
-        self.handlers.insert(name, handler);
+        self.handlers.insert(name, Arc::new(handler));
 
         Ok(())
     }
@@ -33,3 +33,17 @@ where
         }
     }
 }
+
+impl<'a, T, H> Handler<T> for &'a H
+where
+    T: Send + Sync,
+    H: Handler<T>,
+{
+    fn handle_present(&self, msg: &T) -> Result {
+        (**self).handle_present(msg)
+    }
+
+    fn handle_missing(&self) -> Result {
+        (**self).handle_missing()
+    }
+}
@@ -24,7 +24,7 @@ use futures::Future;
 use futures::future::{FutureResult, IntoFuture};
 use futures_cpupool::{CpuPool, CpuFuture};
 use std::thread;
-use std::sync::mpsc;
+use std::sync::{mpsc, Arc};
 
 mod message;
 mod receiver;
@@ -49,7 +49,7 @@ type FutReceiver = mpsc::Receiver<CpuFuture<(), Error>>;
 
 fn future_thread<T>(
     pool: &CpuPool,
-    handler: &'static Handler<T>,
+    handler: Arc<Handler<T>>,
     msg: Message<T>,
     msg_sender: MsgSender<T>,
 ) -> CpuFuture<(), Error>
@@ -88,7 +88,7 @@ where
                 None => continue,
             };
 
-            let cpu_future = future_thread(&pool, *handler, msg, msg_sender.clone());
+            let cpu_future = future_thread(&pool, handler.clone(), msg, msg_sender.clone());
             if let Err(err) = fut_sender.send(cpu_future) {
                 println!("Error: '{}'", err);
             }
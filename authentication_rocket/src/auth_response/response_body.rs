@@ -18,7 +18,9 @@
  */
 
 use std::convert::From;
-use authentication_backend::{Permission, User, UserTrait, Webtoken};
+use authentication_backend::{AuthenticationOptions, LogIn, Permission, RecoveryCodes,
+                            RegistrationOptions, Session, TotpEnrollment, TotpStatus, User,
+                            UserTrait, Webtoken};
 
 #[derive(Serialize)]
 #[serde(tag = "type")]
@@ -28,10 +30,64 @@ pub enum ResponseBody {
         user_token: String,
         renewal_token: String,
     },
+    MfaRequired { mfa_token: String },
     Permission { id: i32, name: String },
+    TotpEnrollment {
+        secret: String,
+        provisioning_uri: String,
+        recovery_codes: Vec<String>,
+    },
+    TotpStatus {
+        enabled: bool,
+        recovery_codes_remaining: usize,
+    },
+    RecoveryCodes { recovery_codes: Vec<String> },
+    RegistrationOptions {
+        challenge_token: String,
+        challenge: String,
+        rp_id: String,
+        rp_name: String,
+        user_handle: String,
+        username: String,
+        algorithms: Vec<i32>,
+        exclude_credentials: Vec<String>,
+    },
+    AuthenticationOptions {
+        challenge_token: String,
+        challenge: String,
+        rp_id: String,
+    },
+    Sessions { sessions: Vec<SessionBody> },
     NoData,
 }
 
+#[derive(Serialize)]
+pub struct SessionBody {
+    id: i32,
+    user_agent: Option<String>,
+    ip_address: Option<String>,
+    created_at: i64,
+    last_renewed_at: Option<i64>,
+}
+
+impl<'a> From<&'a Session> for SessionBody {
+    fn from(session: &'a Session) -> Self {
+        SessionBody {
+            id: session.id(),
+            user_agent: session.user_agent().map(|user_agent| user_agent.to_owned()),
+            ip_address: session.ip_address().map(|ip_address| ip_address.to_owned()),
+            created_at: session.created_at().timestamp(),
+            last_renewed_at: session.last_renewed_at().map(|renewed| renewed.timestamp()),
+        }
+    }
+}
+
+impl From<Vec<Session>> for ResponseBody {
+    fn from(sessions: Vec<Session>) -> Self {
+        ResponseBody::Sessions { sessions: sessions.iter().map(SessionBody::from).collect() }
+    }
+}
+
 impl From<Permission> for ResponseBody {
     fn from(permission: Permission) -> Self {
         ResponseBody::Permission {
@@ -60,6 +116,65 @@ impl From<Webtoken> for ResponseBody {
     }
 }
 
+impl From<LogIn> for ResponseBody {
+    fn from(log_in: LogIn) -> Self {
+        match log_in {
+            LogIn::Authenticated(webtoken) => webtoken.into(),
+            LogIn::MfaRequired(mfa_token) => ResponseBody::MfaRequired { mfa_token: mfa_token },
+        }
+    }
+}
+
+impl From<TotpEnrollment> for ResponseBody {
+    fn from(enrollment: TotpEnrollment) -> Self {
+        ResponseBody::TotpEnrollment {
+            secret: enrollment.secret().to_owned(),
+            provisioning_uri: enrollment.provisioning_uri().to_owned(),
+            recovery_codes: enrollment.recovery_codes().to_vec(),
+        }
+    }
+}
+
+impl From<RegistrationOptions> for ResponseBody {
+    fn from(options: RegistrationOptions) -> Self {
+        ResponseBody::RegistrationOptions {
+            challenge_token: options.challenge_token().to_owned(),
+            challenge: options.challenge().to_owned(),
+            rp_id: options.rp_id().to_owned(),
+            rp_name: options.rp_name().to_owned(),
+            user_handle: options.user_handle().to_owned(),
+            username: options.username().to_owned(),
+            algorithms: options.algorithms().to_vec(),
+            exclude_credentials: options.exclude_credentials().to_vec(),
+        }
+    }
+}
+
+impl From<AuthenticationOptions> for ResponseBody {
+    fn from(options: AuthenticationOptions) -> Self {
+        ResponseBody::AuthenticationOptions {
+            challenge_token: options.challenge_token().to_owned(),
+            challenge: options.challenge().to_owned(),
+            rp_id: options.rp_id().to_owned(),
+        }
+    }
+}
+
+impl From<TotpStatus> for ResponseBody {
+    fn from(status: TotpStatus) -> Self {
+        ResponseBody::TotpStatus {
+            enabled: status.enabled(),
+            recovery_codes_remaining: status.recovery_codes_remaining(),
+        }
+    }
+}
+
+impl From<RecoveryCodes> for ResponseBody {
+    fn from(recovery_codes: RecoveryCodes) -> Self {
+        ResponseBody::RecoveryCodes { recovery_codes: recovery_codes.into_codes() }
+    }
+}
+
 impl From<User> for ResponseBody {
     fn from(user: User) -> Self {
         ResponseBody::User {
@@ -17,13 +17,16 @@
  * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
  */
 
+use std::sync::Mutex;
 use rocket::response::{self, Responder};
 use rocket::http::Status;
 use rocket::request::Request;
-use rocket::Response;
+use rocket::{Outcome, Response, State};
 use rocket_contrib::Json;
+use authentication_background::{Message, MsgSender};
 use authentication_backend::Error as BackendError;
-use authentication_backend::{BcryptError, DbError, DbErrorKind, JWTError, JWTErrorKind};
+use authentication_backend::{BcryptError, DbError, DbErrorKind, JWTErrorKind, MfaErrorKind,
+                             WebauthnErrorKind};
 use self::error_response::ErrorResponse;
 
 mod error_response;
@@ -52,10 +55,11 @@ impl Error {
         }
     }
 
-    fn jwt_status(err: &JWTError) -> Status {
-        match *err.kind() {
+    fn jwt_status(err: &JWTErrorKind) -> Status {
+        match *err {
             JWTErrorKind::InvalidToken |
-            JWTErrorKind::InvalidSignature => Status::BadRequest,
+            JWTErrorKind::InvalidSignature |
+            JWTErrorKind::UnknownKey => Status::BadRequest,
             JWTErrorKind::ExpiredSignature |
             JWTErrorKind::InvalidIssuer |
             JWTErrorKind::InvalidAudience |
@@ -63,7 +67,43 @@ impl Error {
             JWTErrorKind::InvalidIssuedAt |
             JWTErrorKind::ImmatureSignature |
             JWTErrorKind::InvalidAlgorithm => Status::Unauthorized,
-            _ => Status::InternalServerError,
+            JWTErrorKind::SigningFailed => Status::InternalServerError,
+        }
+    }
+
+    fn mfa_status(err: &MfaErrorKind) -> Status {
+        match *err {
+            MfaErrorKind::Unavailable => Status::NotImplemented,
+            MfaErrorKind::AlreadyEnabled |
+            MfaErrorKind::NotEnrolled => Status::BadRequest,
+            MfaErrorKind::InvalidCode |
+            MfaErrorKind::CodeRequired => Status::Unauthorized,
+        }
+    }
+
+    fn notify_lockout(req: &Request, user_id: i32) {
+        if let Outcome::Success(sender) = req.guard::<State<Mutex<MsgSender<i32>>>>() {
+            if let Ok(sender) = sender.lock() {
+                match sender.send(Message::new("account_locked", Some(user_id))) {
+                    _ => (),
+                };
+            }
+        }
+    }
+
+    fn webauthn_status(err: &WebauthnErrorKind) -> Status {
+        match *err {
+            WebauthnErrorKind::Unavailable => Status::NotImplemented,
+            WebauthnErrorKind::Malformed |
+            WebauthnErrorKind::UnsupportedAlgorithm => Status::BadRequest,
+            WebauthnErrorKind::ChallengeMismatch |
+            WebauthnErrorKind::OriginMismatch |
+            WebauthnErrorKind::RelyingPartyMismatch |
+            WebauthnErrorKind::UserNotPresent |
+            WebauthnErrorKind::UserNotVerified |
+            WebauthnErrorKind::UnknownCredential |
+            WebauthnErrorKind::InvalidSignature |
+            WebauthnErrorKind::CounterRegression => Status::Unauthorized,
         }
     }
 }
@@ -82,26 +122,60 @@ impl ToString for Error {
 
 impl<'r> Responder<'r> for Error {
     fn respond_to(self, req: &Request) -> response::Result<'r> {
+        if let BackendError::AccountLockedError(user_id) = self.0 {
+            Error::notify_lockout(req, user_id);
+        }
+
         let status = match self.0 {
             BackendError::BcryptError(ref err) => Error::bcrypt_status(err),
             BackendError::DbError(ref err) => Error::db_status(err),
             BackendError::InputError(_) => Status::BadRequest,
             BackendError::JWTError(ref err) => Error::jwt_status(err),
-            BackendError::DbTimeout | BackendError::IOError | BackendError::ParseError => {
-                Status::InternalServerError
-            }
+            BackendError::MfaError(ref err) => Error::mfa_status(err),
+            BackendError::WebauthnError(ref err) => Error::webauthn_status(err),
+            BackendError::ConfigError(_) |
+            BackendError::DbTimeout |
+            BackendError::EncryptionError |
+            BackendError::IOError |
+            BackendError::ParseError |
+            BackendError::PasswordHashError => Status::InternalServerError,
+            BackendError::AccountLockedError(_) |
+            BackendError::LoginThrottledError |
+            BackendError::RateLimitedError(_) => Status::TooManyRequests,
+            BackendError::ClientAuthenticationError |
             BackendError::PasswordMatchError |
+            BackendError::PasswordResetError |
             BackendError::PermissionError |
+            BackendError::SessionExpiredError |
+            BackendError::TokenReuseError(_) |
+            BackendError::TokenRevokedError |
             BackendError::UserNotVerifiedError => Status::Unauthorized,
         };
 
+        let challenge = match self.0 {
+            BackendError::ClientAuthenticationError => true,
+            _ => false,
+        };
+
+        let retry_after = match self.0 {
+            BackendError::RateLimitedError(seconds) => Some(seconds),
+            _ => None,
+        };
+
         let json_response = Json(ErrorResponse::from_error(self.0)).respond_to(req)?;
 
-        let response = Response::build()
-            .status(status)
-            .join(json_response)
-            .finalize();
+        let mut response = Response::build();
+
+        response.status(status).join(json_response);
+
+        if challenge {
+            response.raw_header("WWW-Authenticate", "Basic realm=\"authentication\"");
+        }
+
+        if let Some(seconds) = retry_after {
+            response.raw_header("Retry-After", seconds.to_string());
+        }
 
-        Ok(response)
+        Ok(response.finalize())
     }
 }
@@ -22,12 +22,55 @@ use authentication_backend::{Authenticatable, ToAuth};
 #[derive(Deserialize)]
 #[serde(untagged)]
 pub enum Auth {
-    UserAndPass { username: String, password: String },
+    UserAndPass {
+        username: String,
+        password: String,
+        email: Option<String>,
+        audience: Option<String>,
+        code: Option<String>,
+    },
     WebtokenAndPass {
         user_token: String,
         password: String,
+        audience: Option<String>,
+    },
+    Webtoken {
+        user_token: String,
+        audience: Option<String>,
     },
-    Webtoken { user_token: String },
+}
+
+impl Auth {
+    pub fn audience(&self) -> Option<&str> {
+        let audience = match *self {
+            Auth::UserAndPass { ref audience, .. } |
+            Auth::WebtokenAndPass { ref audience, .. } |
+            Auth::Webtoken { ref audience, .. } => audience,
+        };
+
+        audience.as_ref().map(|audience| audience.as_str())
+    }
+
+    pub fn email(&self) -> Option<&str> {
+        match *self {
+            Auth::UserAndPass { ref email, .. } => email.as_ref().map(|email| email.as_str()),
+            _ => None,
+        }
+    }
+
+    pub fn code(&self) -> Option<&str> {
+        match *self {
+            Auth::UserAndPass { ref code, .. } => code.as_ref().map(|code| code.as_str()),
+            _ => None,
+        }
+    }
+
+    pub fn username(&self) -> Option<&str> {
+        match *self {
+            Auth::UserAndPass { ref username, .. } => Some(username.as_str()),
+            _ => None,
+        }
+    }
 }
 
 impl ToAuth for Auth {
@@ -36,6 +79,7 @@ impl ToAuth for Auth {
             Auth::UserAndPass {
                 username: ref u,
                 password: ref p,
+                ..
             } => {
                 Authenticatable::UserAndPass {
                     username: u,
@@ -45,13 +89,16 @@ impl ToAuth for Auth {
             Auth::WebtokenAndPass {
                 user_token: ref w,
                 password: ref p,
+                ..
             } => {
                 Authenticatable::UserTokenAndPass {
                     user_token: w,
                     password: p,
                 }
             }
-            Auth::Webtoken { user_token: ref w } => Authenticatable::UserToken { user_token: w },
+            Auth::Webtoken { user_token: ref w, .. } => {
+                Authenticatable::UserToken { user_token: w }
+            }
         }
     }
 }
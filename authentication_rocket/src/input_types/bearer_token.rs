@@ -0,0 +1,39 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use rocket::Outcome;
+use rocket::request::{self, FromRequest, Request};
+
+pub struct BearerToken(pub String);
+
+impl<'a, 'r> FromRequest<'a, 'r> for BearerToken {
+    type Error = ();
+
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Self, ()> {
+        let authorization = request.headers().get_one("Authorization").unwrap_or("");
+
+        let token = if authorization.starts_with("Bearer ") {
+            authorization["Bearer ".len()..].trim()
+        } else {
+            ""
+        };
+
+        Outcome::Success(BearerToken(token.to_owned()))
+    }
+}
@@ -0,0 +1,38 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use authentication_backend::ClientInfo;
+use rocket::Outcome;
+use rocket::request::{self, FromRequest, Request};
+
+pub struct Client(pub ClientInfo);
+
+impl<'a, 'r> FromRequest<'a, 'r> for Client {
+    type Error = ();
+
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Self, ()> {
+        let user_agent = request.headers().get_one("User-Agent");
+        let ip_address = request.remote().map(|address| address.ip().to_string());
+
+        Outcome::Success(Client(ClientInfo::new(
+            user_agent,
+            ip_address.as_ref().map(|ip_address| ip_address.as_str()),
+        )))
+    }
+}
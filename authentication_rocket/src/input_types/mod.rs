@@ -18,9 +18,37 @@
  */
 
 mod auth;
+mod bearer_token;
+mod client;
+mod client_authorization;
 mod create_permission;
+mod email_change;
+mod introspection_request;
+mod mfa_log_in;
+mod passkey_log_in;
+mod passkey_registration;
+mod password_change;
+mod password_reset;
+mod rate_limited;
 mod renewal_token;
+mod totp_confirmation;
+mod user_token;
+mod username_change;
 
 pub use self::auth::Auth;
+pub use self::bearer_token::BearerToken;
+pub use self::client::Client;
+pub use self::client_authorization::ClientAuthorization;
 pub use self::create_permission::CreatePermission;
+pub use self::email_change::EmailChange;
+pub use self::introspection_request::IntrospectionRequest;
+pub use self::mfa_log_in::MfaLogIn;
+pub use self::passkey_log_in::{PasskeyLogIn, PasskeyLogInRequest};
+pub use self::passkey_registration::PasskeyRegistration;
+pub use self::password_change::PasswordChange;
+pub use self::password_reset::{PasswordResetConfirmation, PasswordResetRequest};
+pub use self::rate_limited::{LogInLimit, RateLimited, RenewTokenLimit, SignUpLimit, VerifyLimit};
 pub use self::renewal_token::RenewalToken;
+pub use self::totp_confirmation::TotpConfirmation;
+pub use self::user_token::UserToken;
+pub use self::username_change::UsernameChange;
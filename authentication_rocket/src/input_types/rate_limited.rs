@@ -0,0 +1,113 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::marker::PhantomData;
+use authentication_backend::{AuthService, RateLimit};
+use authentication_backend::Error as BackendError;
+use rocket::{Outcome, State};
+use rocket::http::Status;
+use rocket::request::{self, FromRequest, Request};
+use error::Error;
+
+pub trait Limited {
+    fn limit(service: &AuthService) -> &RateLimit;
+}
+
+pub struct LogInLimit;
+pub struct SignUpLimit;
+pub struct RenewTokenLimit;
+pub struct VerifyLimit;
+
+impl Limited for LogInLimit {
+    fn limit(service: &AuthService) -> &RateLimit {
+        service.rate_limits().log_in()
+    }
+}
+
+impl Limited for SignUpLimit {
+    fn limit(service: &AuthService) -> &RateLimit {
+        service.rate_limits().sign_up()
+    }
+}
+
+impl Limited for RenewTokenLimit {
+    fn limit(service: &AuthService) -> &RateLimit {
+        service.rate_limits().renew_token()
+    }
+}
+
+impl Limited for VerifyLimit {
+    fn limit(service: &AuthService) -> &RateLimit {
+        service.rate_limits().verify()
+    }
+}
+
+pub struct RateLimited<L> {
+    address: Result<(), BackendError>,
+    limited: PhantomData<L>,
+}
+
+impl<L> RateLimited<L>
+where
+    L: Limited,
+{
+    pub fn check(self) -> Result<(), Error> {
+        self.address.map_err(Error::from)
+    }
+
+    pub fn check_username(
+        self,
+        service: &AuthService,
+        username: Option<&str>,
+    ) -> Result<(), Error> {
+        self.address?;
+
+        if let Some(username) = username {
+            service.check_rate_limit(L::limit(service), &format!("user:{}", username))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a, 'r, L> FromRequest<'a, 'r> for RateLimited<L>
+where
+    L: Limited,
+{
+    type Error = ();
+
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Self, ()> {
+        let service = match request.guard::<State<AuthService>>() {
+            Outcome::Success(service) => service,
+            _ => return Outcome::Failure((Status::InternalServerError, ())),
+        };
+
+        let address = match request.remote() {
+            Some(address) => {
+                service.check_rate_limit(L::limit(&service), &format!("ip:{}", address.ip()))
+            }
+            None => Ok(()),
+        };
+
+        Outcome::Success(RateLimited {
+            address: address,
+            limited: PhantomData,
+        })
+    }
+}
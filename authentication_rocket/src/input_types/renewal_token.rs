@@ -20,4 +20,5 @@
 #[derive(Deserialize)]
 pub struct RenewalToken {
     pub renewal_token: String,
+    pub audience: Option<String>,
 }
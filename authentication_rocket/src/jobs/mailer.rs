@@ -18,13 +18,21 @@
  */
 
 use super::{Result, Error, Handler};
-use authentication_backend::{VerificationCode, UserTrait, User};
+use authentication_backend::{AuthService, VerificationCode, UserTrait, User};
 
-pub struct Mailer;
+pub struct Mailer {
+    service: AuthService,
+}
+
+impl Mailer {
+    pub fn new(service: AuthService) -> Self {
+        Mailer { service: service }
+    }
+}
 
 impl Handler<i32> for Mailer {
     fn handle_present(&self, user_id: &i32) -> Result {
-        let vc = match VerificationCode::find_by_user_id(*user_id) {
+        let vc = match VerificationCode::find_by_user_id(&self.service, *user_id) {
             Ok(vc) => vc,
             Err(_) => {
                 return Err(Error::ProcessingError(
@@ -33,13 +41,19 @@ impl Handler<i32> for Mailer {
             }
         };
 
-        let user = match User::find_by_id(*user_id) {
+        let user = match User::find_by_id(&self.service, *user_id) {
             Ok(user) => user,
             Err(_) => return Err(Error::ProcessingError("Could not find user".to_owned())),
         };
 
+        let email = match vc.email().or(user.email()) {
+            Some(email) => email,
+            None => return Err(Error::ProcessingError("User has no email address".to_owned())),
+        };
+
         println!(
-            "Sending email to user '{}' with verification code '{}'",
+            "Sending email to '{}' for user '{}' with verification code '{}'",
+            email,
             user.username(),
             vc.code()
         );
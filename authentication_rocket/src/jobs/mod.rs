@@ -18,15 +18,47 @@
  */
 
 use authentication_background::Config;
+use authentication_backend::AuthService;
 
 pub use authentication_background::{Result, Error, Handler};
 
 mod mailer;
+mod password_reset_mailer;
+mod security_alert;
 
 use self::mailer::Mailer;
+use self::password_reset_mailer::PasswordResetMailer;
+use self::security_alert::SecurityAlert;
 
-static MAILER: Mailer = Mailer {};
-
-pub fn register_jobs(config: &mut Config<i32>) {
-    config.register_handler("mail", &MAILER).unwrap()
+pub fn register_jobs(config: &mut Config<i32>, service: &AuthService) {
+    config
+        .register_handler("mail", Mailer::new(service.clone()))
+        .unwrap();
+    config
+        .register_handler("password_reset", PasswordResetMailer::new(service.clone()))
+        .unwrap();
+    config
+        .register_handler(
+            "token_reuse",
+            SecurityAlert::new(service.clone(), "Renewal token reused, token family revoked"),
+        )
+        .unwrap();
+    config
+        .register_handler(
+            "recovery_code_used",
+            SecurityAlert::new(service.clone(), "Recovery code used to log in"),
+        )
+        .unwrap();
+    config
+        .register_handler(
+            "account_locked",
+            SecurityAlert::new(service.clone(), "Account locked after too many failed log ins"),
+        )
+        .unwrap();
+    config
+        .register_handler(
+            "sign_up_existing",
+            SecurityAlert::new(service.clone(), "Someone tried to sign up with your username"),
+        )
+        .unwrap();
 }
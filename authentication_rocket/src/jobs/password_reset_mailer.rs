@@ -0,0 +1,67 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::{Result, Error, Handler};
+use authentication_backend::{AuthService, PasswordReset, UserTrait, User};
+
+pub struct PasswordResetMailer {
+    service: AuthService,
+}
+
+impl PasswordResetMailer {
+    pub fn new(service: AuthService) -> Self {
+        PasswordResetMailer { service: service }
+    }
+}
+
+impl Handler<i32> for PasswordResetMailer {
+    fn handle_present(&self, user_id: &i32) -> Result {
+        let user = match User::find_by_id(&self.service, *user_id) {
+            Ok(user) => user,
+            Err(_) => return Err(Error::ProcessingError("Could not find user".to_owned())),
+        };
+
+        let email = match user.email() {
+            Some(email) => email,
+            None => return Err(Error::ProcessingError("User has no email address".to_owned())),
+        };
+
+        let token = match PasswordReset::create(&self.service, &user) {
+            Ok(token) => token,
+            Err(_) => {
+                return Err(Error::ProcessingError(
+                    "Could not create password reset".to_owned(),
+                ))
+            }
+        };
+
+        println!(
+            "Sending email to '{}' for user '{}' with password reset token '{}'",
+            email,
+            user.username(),
+            token
+        );
+
+        Ok(())
+    }
+
+    fn handle_missing(&self) -> Result {
+        Ok(())
+    }
+}
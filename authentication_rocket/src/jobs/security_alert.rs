@@ -0,0 +1,57 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::{Result, Error, Handler};
+use authentication_backend::{AuthService, UserTrait, User};
+
+pub struct SecurityAlert {
+    service: AuthService,
+    event: &'static str,
+}
+
+impl SecurityAlert {
+    pub fn new(service: AuthService, event: &'static str) -> Self {
+        SecurityAlert {
+            service: service,
+            event: event,
+        }
+    }
+}
+
+impl Handler<i32> for SecurityAlert {
+    fn handle_present(&self, user_id: &i32) -> Result {
+        let user = match User::find_by_id(&self.service, *user_id) {
+            Ok(user) => user,
+            Err(_) => return Err(Error::ProcessingError("Could not find user".to_owned())),
+        };
+
+        println!(
+            "Security alert for user '{}' ({}): {}",
+            user.username(),
+            user_id,
+            self.event
+        );
+
+        Ok(())
+    }
+
+    fn handle_missing(&self) -> Result {
+        Ok(())
+    }
+}
@@ -37,12 +37,15 @@ mod auth_response;
 mod input_types;
 
 use std::sync::Mutex;
+use authentication_backend::AuthService;
 
 pub fn launch() -> () {
+    let service = AuthService::initialize().expect("Failed to initialize AuthService");
+
     let mut config: authentication_background::Config<i32> =
         authentication_background::Config::new();
 
-    jobs::register_jobs(&mut config);
+    jobs::register_jobs(&mut config, &service);
 
     let config = config;
 
@@ -54,16 +57,39 @@ pub fn launch() -> () {
             routes![
                 routes::users::sign_up,
                 routes::users::log_in,
+                routes::users::log_in_mfa,
+                routes::users::log_out,
+                routes::users::log_out_everywhere,
                 routes::users::is_authenticated,
+                routes::users::sessions,
+                routes::users::revoke_session,
+                routes::users::change_password,
+                routes::users::change_username,
+                routes::users::change_email,
                 routes::users::delete,
                 routes::users::grant_permission,
                 routes::users::revoke_permission,
+                routes::users::unlock,
+                routes::totp::status,
+                routes::totp::enroll,
+                routes::totp::confirm,
+                routes::totp::disable,
+                routes::totp::regenerate_recovery_codes,
+                routes::webauthn::begin_registration,
+                routes::webauthn::finish_registration,
+                routes::webauthn::begin_log_in,
+                routes::webauthn::finish_log_in,
                 routes::webtokens::renew,
+                routes::webtokens::introspect,
                 routes::verification_codes::verify,
+                routes::password_resets::request,
+                routes::password_resets::confirm,
                 routes::permissions::create,
                 routes::permissions::delete,
+                routes::keys::jwks,
             ],
         )
+        .manage(service)
         .manage(Mutex::new(hooks.hook()))
         .launch();
 
@@ -26,3 +26,7 @@ pub mod users;
 pub mod webtokens;
 pub mod verification_codes;
 pub mod permissions;
+pub mod totp;
+pub mod webauthn;
+pub mod password_resets;
+pub mod keys;
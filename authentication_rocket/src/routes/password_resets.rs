@@ -0,0 +1,57 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use authentication_backend::AuthService;
+use authentication_backend::controllers::password_resets;
+use authentication_backend::Error as BackendError;
+use authentication_background::MsgSender;
+use rocket_contrib::Json;
+use rocket::State;
+use std::sync::Mutex;
+use input_types::{PasswordResetConfirmation, PasswordResetRequest};
+use super::Response;
+use auth_response::AuthResponse;
+
+#[post("/password-reset/request", format = "application/json", data = "<reset_request>")]
+pub fn request(
+    reset_request: Json<PasswordResetRequest>,
+    service: State<AuthService>,
+    sender: State<Mutex<MsgSender<i32>>>,
+) -> Response {
+    let sender = match sender.lock() {
+        Ok(sender) => sender.clone(),
+        Err(_) => return Err(BackendError::IOError.into()),
+    };
+
+    password_resets::request(&service, &reset_request.0.username, &sender)?;
+
+    Ok(AuthResponse::empty("Password reset requested"))
+}
+
+#[post("/password-reset/confirm", format = "application/json", data = "<confirmation>")]
+pub fn confirm(
+    confirmation: Json<PasswordResetConfirmation>,
+    service: State<AuthService>,
+) -> Response {
+    let confirmation = confirmation.0;
+
+    password_resets::confirm(&service, &confirmation.token, &confirmation.new_password)?;
+
+    Ok(AuthResponse::empty("Password reset"))
+}
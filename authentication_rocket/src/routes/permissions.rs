@@ -17,7 +17,9 @@
  * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
  */
 
+use authentication_backend::AuthService;
 use authentication_backend::controllers::permissions;
+use rocket::State;
 use rocket_contrib::Json;
 use input_types::Auth;
 use input_types::CreatePermission;
@@ -25,15 +27,20 @@ use super::Response;
 use auth_response::AuthResponse;
 
 #[post("/permissions", format = "application/json", data = "<new_permission>")]
-pub fn create(new_permission: Json<CreatePermission>) -> Response {
-    let permission = permissions::create(new_permission.0.permission(), &new_permission.0)?;
+pub fn create(new_permission: Json<CreatePermission>, service: State<AuthService>) -> Response {
+    let permission =
+        permissions::create(&service, new_permission.0.permission(), &new_permission.0)?;
 
     Ok(AuthResponse::new("Permission created", permission))
 }
 
 #[post("/permissions/<permission_name>/delete", format = "application/json", data = "<payload>")]
-pub fn delete(permission_name: String, payload: Json<Auth>) -> Response {
-    permissions::delete(&permission_name, &payload.0)?;
+pub fn delete(
+    permission_name: String,
+    payload: Json<Auth>,
+    service: State<AuthService>,
+) -> Response {
+    permissions::delete(&service, &permission_name, &payload.0)?;
 
     Ok(AuthResponse::empty("Permission deleted"))
 }
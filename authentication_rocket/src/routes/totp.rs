@@ -0,0 +1,88 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use authentication_backend::controllers::totp;
+use authentication_backend::{AuthService, Authenticatable};
+use rocket_contrib::Json;
+use rocket::State;
+use input_types::{Auth, BearerToken, TotpConfirmation};
+use super::Response;
+use auth_response::AuthResponse;
+
+// STATUS
+
+#[get("/users/<target_user>/totp")]
+pub fn status(target_user: String, token: BearerToken, service: State<AuthService>) -> Response {
+    let auth = Authenticatable::UserToken { user_token: &token.0 };
+
+    let status = totp::status(&service, &target_user, &auth)?;
+
+    Ok(AuthResponse::new("Two-factor authentication status", status))
+}
+
+// ENROLL
+
+#[post("/users/<target_user>/totp", format = "application/json", data = "<payload>")]
+pub fn enroll(target_user: String, payload: Json<Auth>, service: State<AuthService>) -> Response {
+    let enrollment = totp::enroll(&service, &target_user, &payload.0)?;
+
+    Ok(AuthResponse::new("Enrolled, confirm with a code", enrollment))
+}
+
+// CONFIRM
+
+#[post("/users/<target_user>/totp/confirm", format = "application/json", data = "<payload>")]
+pub fn confirm(
+    target_user: String,
+    payload: Json<TotpConfirmation>,
+    service: State<AuthService>,
+) -> Response {
+    let payload = payload.0;
+
+    totp::confirm(&service, &target_user, &payload.code, &payload.auth)?;
+
+    Ok(AuthResponse::empty("Two-factor authentication enabled"))
+}
+
+// DISABLE
+
+#[post("/users/<target_user>/totp/disable", format = "application/json", data = "<payload>")]
+pub fn disable(target_user: String, payload: Json<Auth>, service: State<AuthService>) -> Response {
+    totp::disable(&service, &target_user, payload.0.code(), &payload.0)?;
+
+    Ok(AuthResponse::empty("Two-factor authentication disabled"))
+}
+
+// RECOVERY CODES
+
+#[post("/users/<target_user>/recovery-codes", format = "application/json", data = "<payload>")]
+pub fn regenerate_recovery_codes(
+    target_user: String,
+    payload: Json<Auth>,
+    service: State<AuthService>,
+) -> Response {
+    let recovery_codes = totp::regenerate_recovery_codes(
+        &service,
+        &target_user,
+        payload.0.code(),
+        &payload.0,
+    )?;
+
+    Ok(AuthResponse::new("Recovery codes generated", recovery_codes))
+}
@@ -18,52 +18,223 @@
  */
 
 use authentication_backend::controllers::users;
+use authentication_backend::{AuthService, Authenticatable, LogIn};
 use authentication_backend::Error as BackendError;
 use authentication_background::MsgSender;
 use rocket_contrib::Json;
 use rocket::State;
 use std::sync::Mutex;
-use input_types::Auth;
+use input_types::{Auth, BearerToken, Client, EmailChange, LogInLimit, MfaLogIn, PasswordChange,
+                  RateLimited, SignUpLimit, UserToken, UsernameChange};
 use super::Response;
 use auth_response::AuthResponse;
 
 // SIGN UP
 
 #[post("/sign-up", format = "application/json", data = "<create_user>")]
-pub fn sign_up(create_user: Json<Auth>, sender: State<Mutex<MsgSender<i32>>>) -> Response {
+pub fn sign_up(
+    create_user: Json<Auth>,
+    limit: RateLimited<SignUpLimit>,
+    service: State<AuthService>,
+    sender: State<Mutex<MsgSender<i32>>>,
+) -> Response {
+    limit.check_username(&service, create_user.0.username())?;
+
     let sender = match sender.lock() {
         Ok(sender) => sender.clone(),
         Err(_) => return Err(BackendError::IOError.into()),
     };
 
-    let user = users::sign_up(&create_user.0, &sender)?;
+    users::sign_up(&service, &create_user.0, create_user.0.email(), &sender)?;
 
-    Ok(AuthResponse::new("User created", Some(user)))
+    Ok(AuthResponse::empty("Check your email to finish signing up"))
 }
 
 // LOG IN
 
 #[post("/log-in", format = "application/json", data = "<create_user>")]
-pub fn log_in(create_user: Json<Auth>) -> Response {
-    let token = users::log_in(&create_user.0)?;
+pub fn log_in(
+    create_user: Json<Auth>,
+    limit: RateLimited<LogInLimit>,
+    client: Client,
+    service: State<AuthService>,
+) -> Response {
+    limit.check_username(&service, create_user.0.username())?;
+
+    let log_in = users::log_in(&service, &create_user.0, create_user.0.audience(), &client.0)?;
+
+    let message = match log_in {
+        LogIn::Authenticated(_) => "Authenticated",
+        LogIn::MfaRequired(_) => "Two-factor code required",
+    };
+
+    Ok(AuthResponse::new(message, log_in))
+}
+
+#[post("/log-in/mfa", format = "application/json", data = "<mfa_log_in>")]
+pub fn log_in_mfa(
+    mfa_log_in: Json<MfaLogIn>,
+    limit: RateLimited<LogInLimit>,
+    client: Client,
+    service: State<AuthService>,
+    sender: State<Mutex<MsgSender<i32>>>,
+) -> Response {
+    limit.check()?;
+
+    let sender = match sender.lock() {
+        Ok(sender) => sender.clone(),
+        Err(_) => return Err(BackendError::IOError.into()),
+    };
+
+    let mfa_log_in = mfa_log_in.0;
+
+    let token = users::log_in_mfa(
+        &service,
+        &mfa_log_in.mfa_token,
+        &mfa_log_in.code,
+        &client.0,
+        &sender,
+    )?;
 
     Ok(AuthResponse::new("Authenticated", token))
 }
 
+// LOG OUT
+
+#[post("/log-out", format = "application/json", data = "<user_token>")]
+pub fn log_out(user_token: Json<UserToken>, service: State<AuthService>) -> Response {
+    users::log_out(&service, &user_token.0.user_token)?;
+
+    Ok(AuthResponse::empty("Logged out"))
+}
+
+#[post("/log-out-everywhere", format = "application/json", data = "<token>")]
+pub fn log_out_everywhere(token: Json<Auth>, service: State<AuthService>) -> Response {
+    users::log_out_everywhere(&service, token.0.code(), &token.0)?;
+
+    Ok(AuthResponse::empty("Logged out everywhere"))
+}
+
 // CHECK AUTHENTICATION
 
 #[post("/is-authenticated", format = "application/json", data = "<token>")]
-pub fn is_authenticated(token: Json<Auth>) -> Response {
-    users::is_authenticated(&token.0)?;
+pub fn is_authenticated(token: Json<Auth>, service: State<AuthService>) -> Response {
+    users::is_authenticated(&service, &token.0, token.0.audience())?;
 
     Ok(AuthResponse::empty("Authenticated"))
 }
 
+// SESSIONS
+
+#[get("/users/<target_user>/sessions")]
+pub fn sessions(target_user: String, token: BearerToken, service: State<AuthService>) -> Response {
+    let auth = Authenticatable::UserToken { user_token: &token.0 };
+
+    let sessions = users::sessions(&service, &target_user, &auth)?;
+
+    Ok(AuthResponse::new("Sessions", sessions))
+}
+
+#[post("/users/<target_user>/sessions/<session_id>/revoke", format = "application/json",
+       data = "<payload>")]
+pub fn revoke_session(
+    target_user: String,
+    session_id: i32,
+    payload: Json<Auth>,
+    service: State<AuthService>,
+) -> Response {
+    users::revoke_session(&service, &target_user, session_id, &payload.0)?;
+
+    Ok(AuthResponse::empty("Session revoked"))
+}
+
+// CHANGE PASSWORD AND USERNAME
+
+#[post("/users/<target_user>/password", format = "application/json", data = "<payload>")]
+pub fn change_password(
+    target_user: String,
+    payload: Json<PasswordChange>,
+    client: Client,
+    service: State<AuthService>,
+) -> Response {
+    let payload = payload.0;
+
+    let log_in = users::change_password(
+        &service,
+        &target_user,
+        &payload.new_password,
+        payload.auth.code(),
+        &payload.auth,
+        payload.auth.audience(),
+        &client.0,
+    )?;
+
+    let message = match log_in {
+        LogIn::Authenticated(_) => "Password changed",
+        LogIn::MfaRequired(_) => "Password changed, two-factor code required",
+    };
+
+    Ok(AuthResponse::new(message, log_in))
+}
+
+#[post("/users/<target_user>/username", format = "application/json", data = "<payload>")]
+pub fn change_username(
+    target_user: String,
+    payload: Json<UsernameChange>,
+    client: Client,
+    service: State<AuthService>,
+) -> Response {
+    let payload = payload.0;
+
+    let log_in = users::change_username(
+        &service,
+        &target_user,
+        &payload.new_username,
+        payload.auth.code(),
+        &payload.auth,
+        payload.auth.audience(),
+        &client.0,
+    )?;
+
+    let message = match log_in {
+        LogIn::Authenticated(_) => "Username changed",
+        LogIn::MfaRequired(_) => "Username changed, two-factor code required",
+    };
+
+    Ok(AuthResponse::new(message, log_in))
+}
+
+#[post("/users/<target_user>/email", format = "application/json", data = "<payload>")]
+pub fn change_email(
+    target_user: String,
+    payload: Json<EmailChange>,
+    service: State<AuthService>,
+    sender: State<Mutex<MsgSender<i32>>>,
+) -> Response {
+    let sender = match sender.lock() {
+        Ok(sender) => sender.clone(),
+        Err(_) => return Err(BackendError::IOError.into()),
+    };
+
+    let payload = payload.0;
+
+    users::change_email(
+        &service,
+        &target_user,
+        &payload.new_email,
+        payload.auth.code(),
+        &payload.auth,
+        &sender,
+    )?;
+
+    Ok(AuthResponse::empty("Check your email to confirm the new address"))
+}
+
 // DELETE
 
 #[post("/users/<target_user>/delete", format = "application/json", data = "<payload>")]
-pub fn delete(target_user: String, payload: Json<Auth>) -> Response {
-    users::delete(&target_user, &payload.0)?;
+pub fn delete(target_user: String, payload: Json<Auth>, service: State<AuthService>) -> Response {
+    users::delete(&service, &target_user, payload.0.code(), &payload.0)?;
 
     Ok(AuthResponse::empty("Deleted"))
 }
@@ -71,17 +242,36 @@ pub fn delete(target_user: String, payload: Json<Auth>) -> Response {
 // GRANT PERMISSION
 
 #[post("/users/<target_user>/grant/<permission>", format = "application/json", data = "<payload>")]
-pub fn grant_permission(target_user: String, permission: String, payload: Json<Auth>) -> Response {
-    users::grant_permission(&target_user, &permission, &payload.0)?;
+pub fn grant_permission(
+    target_user: String,
+    permission: String,
+    payload: Json<Auth>,
+    service: State<AuthService>,
+) -> Response {
+    users::grant_permission(&service, &target_user, &permission, &payload.0)?;
 
     Ok(AuthResponse::empty("Permission granted"))
 }
 
+// UNLOCK
+
+#[post("/users/<target_user>/unlock", format = "application/json", data = "<payload>")]
+pub fn unlock(target_user: String, payload: Json<Auth>, service: State<AuthService>) -> Response {
+    users::unlock(&service, &target_user, &payload.0)?;
+
+    Ok(AuthResponse::empty("User unlocked"))
+}
+
 // REVOKE PERMISSION
 
 #[post("/users/<target_user>/revoke/<permission>", format = "application/json", data = "<payload>")]
-pub fn revoke_permission(target_user: String, permission: String, payload: Json<Auth>) -> Response {
-    users::revoke_permission(&target_user, &permission, &payload.0)?;
+pub fn revoke_permission(
+    target_user: String,
+    permission: String,
+    payload: Json<Auth>,
+    service: State<AuthService>,
+) -> Response {
+    users::revoke_permission(&service, &target_user, &permission, &payload.0)?;
 
     Ok(AuthResponse::empty("Permission revoked"))
 }
@@ -17,13 +17,22 @@
  * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
  */
 
+use authentication_backend::AuthService;
 use authentication_backend::controllers::verification_codes;
+use rocket::State;
+use input_types::{RateLimited, VerifyLimit};
 use super::Response;
 use auth_response::AuthResponse;
 
 #[get("/verify/<code>")]
-pub fn verify(code: String) -> Response {
-    verification_codes::verify(&code)?;
+pub fn verify(
+    code: String,
+    limit: RateLimited<VerifyLimit>,
+    service: State<AuthService>,
+) -> Response {
+    limit.check()?;
+
+    verification_codes::verify(&service, &code)?;
 
     Ok(AuthResponse::empty("User verified"))
 }
@@ -0,0 +1,92 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use authentication_backend::controllers::webauthn;
+use authentication_backend::{Assertion, Attestation, AuthService};
+use rocket_contrib::Json;
+use rocket::State;
+use input_types::{Auth, Client, PasskeyLogIn, PasskeyLogInRequest, PasskeyRegistration};
+use super::Response;
+use auth_response::AuthResponse;
+
+// REGISTER
+
+#[post("/users/<target_user>/webauthn/register/begin", format = "application/json",
+       data = "<payload>")]
+pub fn begin_registration(
+    target_user: String,
+    payload: Json<Auth>,
+    service: State<AuthService>,
+) -> Response {
+    let options = webauthn::registration_options(&service, &target_user, &payload.0)?;
+
+    Ok(AuthResponse::new("Create a credential with these options", options))
+}
+
+#[post("/users/<target_user>/webauthn/register/finish", format = "application/json",
+       data = "<payload>")]
+pub fn finish_registration(
+    target_user: String,
+    payload: Json<PasskeyRegistration>,
+    service: State<AuthService>,
+) -> Response {
+    let payload = payload.0;
+
+    let attestation = Attestation::new(
+        &payload.challenge_token,
+        &payload.client_data_json,
+        &payload.attestation_object,
+    );
+
+    webauthn::register(&service, &target_user, &attestation, &payload.auth)?;
+
+    Ok(AuthResponse::empty("Passkey registered"))
+}
+
+// LOG IN
+
+#[post("/log-in/webauthn/begin", format = "application/json", data = "<payload>")]
+pub fn begin_log_in(payload: Json<PasskeyLogInRequest>, service: State<AuthService>) -> Response {
+    let audience = payload.0.audience.as_ref().map(|audience| audience.as_str());
+
+    let options = webauthn::authentication_options(&service, audience)?;
+
+    Ok(AuthResponse::new("Sign the challenge with a passkey", options))
+}
+
+#[post("/log-in/webauthn/finish", format = "application/json", data = "<payload>")]
+pub fn finish_log_in(
+    payload: Json<PasskeyLogIn>,
+    client: Client,
+    service: State<AuthService>,
+) -> Response {
+    let payload = payload.0;
+
+    let assertion = Assertion::new(
+        &payload.challenge_token,
+        &payload.credential_id,
+        &payload.client_data_json,
+        &payload.authenticator_data,
+        &payload.signature,
+    );
+
+    let token = webauthn::log_in(&service, &assertion, &client.0)?;
+
+    Ok(AuthResponse::new("Authenticated", token))
+}
@@ -17,15 +17,53 @@
  * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
  */
 
+use authentication_backend::{AuthService, Introspection};
+use authentication_backend::Error as BackendError;
 use authentication_backend::controllers::webtokens;
+use authentication_background::MsgSender;
+use rocket::State;
+use rocket::request::Form;
 use rocket_contrib::Json;
-use input_types::RenewalToken;
+use std::sync::Mutex;
+use input_types::{ClientAuthorization, IntrospectionRequest, RateLimited, RenewTokenLimit,
+                  RenewalToken};
 use auth_response::AuthResponse;
+use error::Error;
 use super::Response;
 
 #[post("/renew-token", format = "application/json", data = "<renewal_token>")]
-pub fn renew(renewal_token: Json<RenewalToken>) -> Response {
-    let webtoken = webtokens::renew(&renewal_token.0.renewal_token)?;
+pub fn renew(
+    renewal_token: Json<RenewalToken>,
+    limit: RateLimited<RenewTokenLimit>,
+    service: State<AuthService>,
+    sender: State<Mutex<MsgSender<i32>>>,
+) -> Response {
+    limit.check()?;
+
+    let sender = match sender.lock() {
+        Ok(sender) => sender.clone(),
+        Err(_) => return Err(BackendError::IOError.into()),
+    };
+
+    let renewal_token = renewal_token.0;
+
+    let webtoken = webtokens::renew(
+        &service,
+        &renewal_token.renewal_token,
+        renewal_token.audience.as_ref().map(|audience| audience.as_str()),
+        &sender,
+    )?;
 
     Ok(AuthResponse::new("Renewed", webtoken))
 }
+
+#[post("/introspect", format = "application/x-www-form-urlencoded", data = "<request>")]
+pub fn introspect(
+    request: Form<IntrospectionRequest>,
+    authorization: ClientAuthorization,
+    service: State<AuthService>,
+) -> Result<Json<Introspection>, Error> {
+    let introspection = webtokens::introspect(&service, &authorization.0, &request.get().token)?;
+
+    Ok(Json(introspection))
+}
@@ -0,0 +1,69 @@
+/*
+ * This file is part of Authentication.
+ *
+ * Copyright © 2017 Riley Trautman
+ *
+ * Authentication is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Authentication is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Authentication.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+extern crate authentication_backend;
+
+use std::env;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use authentication_backend::BreachFilter;
+
+const DEFAULT_FALSE_POSITIVE_RATE: f64 = 0.001;
+
+fn main() {
+    let mut args = env::args();
+
+    if args.len() != 3 && args.len() != 4 {
+        panic!("Usage: build_breach_filter <download> <filter> [false_positive_rate]");
+    }
+
+    let _executable: Option<String> = args.next();
+
+    let download: String = args.next().expect("Failed to get download path from arguments");
+    let output: String = args.next().expect("Failed to get filter path from arguments");
+
+    let false_positive_rate = match args.next() {
+        Some(rate) => {
+            match rate.parse::<f64>() {
+                Ok(rate) if rate > 0.0 && rate < 1.0 => rate,
+                _ => panic!("False positive rate must be between 0 and 1"),
+            }
+        }
+        None => DEFAULT_FALSE_POSITIVE_RATE,
+    };
+
+    let filter = match BreachFilter::build(Path::new(&download), false_positive_rate) {
+        Ok(filter) => filter,
+        Err(error) => panic!("Failed to read '{}': '{}'", download, error),
+    };
+
+    let mut file = BufWriter::new(File::create(&output).expect(&format!(
+        "Failed to create '{}'",
+        output
+    )));
+
+    filter.write_to(&mut file).expect(&format!(
+        "Failed to write '{}'",
+        output
+    ));
+    file.flush().expect(&format!("Failed to write '{}'", output));
+
+    println!("Wrote breach filter to '{}'", output);
+}
@@ -20,6 +20,7 @@
 extern crate authentication_backend;
 
 use std::env;
+use authentication_backend::AuthService;
 use authentication_backend::Authenticatable;
 use authentication_backend::User;
 use authentication_backend::UserTrait;
@@ -41,7 +42,9 @@ fn main() {
         password: &password, // "ThisIsAP4ssw0rt$.",
     };
 
-    let user = User::create(&auth);
+    let service = AuthService::initialize().expect("Failed to initialize AuthService");
+
+    let user = User::create(&service, &auth);
 
     let user = match user {
         Ok(user) => user,
@@ -20,6 +20,7 @@
 extern crate authentication_backend;
 
 use std::env;
+use authentication_backend::AuthService;
 use authentication_backend::User;
 use authentication_backend::Permission;
 use authentication_backend::UserPermission;
@@ -35,14 +36,16 @@ fn main() {
 
     let uname: String = args.next().expect("Failed to get username from arguments");
 
-    let user: User = User::find_by_name(&uname).expect(&format!(
+    let service = AuthService::initialize().expect("Failed to initialize AuthService");
+
+    let user: User = User::find_by_name(&service, &uname).expect(&format!(
         "Unable to find user with username '{}'",
         &uname
     ));
     let permission: Permission =
-        Permission::find("admin").expect("Failed to find admin permission");
+        Permission::find(&service, "admin").expect("Failed to find admin permission");
 
-    let _user_permission = UserPermission::create(&user, &permission).expect(&format!(
+    let _user_permission = UserPermission::create(&service, &user, &permission).expect(&format!(
         "Failed to make '{}' an admin",
         &uname
     ));
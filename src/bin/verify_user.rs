@@ -20,7 +20,7 @@
 extern crate authentication_backend;
 
 use std::env;
-use authentication_backend::{User, UserTrait, VerificationCode};
+use authentication_backend::{AuthService, User, UserTrait, VerificationCode};
 
 fn main() {
     let mut args = env::args();
@@ -33,14 +33,16 @@ fn main() {
 
     let uname: String = args.next().expect("Failed to get username from arguments");
 
-    let mut user: User = User::find_by_name(&uname).expect(&format!(
+    let service = AuthService::initialize().expect("Failed to initialize AuthService");
+
+    let mut user: User = User::find_by_name(&service, &uname).expect(&format!(
         "Unable to find user with username '{}'",
         &uname
     ));
 
     if !user.is_verified() {
-        if user.verify() {
-            VerificationCode::delete_by_user_id(user.id()).expect(&format!(
+        if user.verify(&service) {
+            VerificationCode::delete_by_user_id(&service, user.id()).expect(&format!(
                 "Failed to delete verification_code for user '{}'",
                 user.username(),
                 ));